@@ -0,0 +1,88 @@
+//! Times rearranging many tiled clients headless, against `MockBackend`,
+//! and counts the configure requests each pass sends.
+//!
+//! Run with `cargo bench --bench layout`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use oxwm::backend::mock::MockBackend;
+use oxwm::client::{Client, Geometry};
+use oxwm::layout::{Arrangement, GapConfig, LayoutType};
+use x11rb::protocol::xproto::Window;
+
+const ITERATIONS: u32 = 200;
+
+/// Arranges `clients` tiled on a 1920x1080 monitor and configures them,
+/// returning how many configure requests went out.
+fn relayout(backend: &MockBackend, windows: &[Window], clients: &mut [Client], master_factor: f32) -> usize {
+    let arrangement = Arrangement {
+        x: 0,
+        y: 0,
+        width: 1920,
+        height: 1080,
+        gaps: GapConfig {
+            inner_horizontal: 5,
+            inner_vertical: 5,
+            outer_horizontal: 5,
+            outer_vertical: 5,
+        },
+        master_factor,
+        num_master: 1,
+        smartgaps_enabled: false,
+        border_width: 2,
+    };
+    let layout = LayoutType::Tiling.new();
+    let slots = arrangement.slots(layout.as_ref(), windows);
+
+    let mut configured = 0;
+    for (client, slot) in clients.iter_mut().zip(slots) {
+        let geometry = Geometry {
+            x: slot.x_coordinate as i16,
+            y: slot.y_coordinate as i16,
+            width: slot.width as u16,
+            height: slot.height as u16,
+            border_width: 2,
+        };
+        if client.configure(backend, geometry).unwrap() {
+            configured += 1;
+        }
+    }
+    configured
+}
+
+fn report(count: usize, pass: &str, elapsed: Duration, configured: usize) {
+    println!(
+        "{:>4} clients  {:<10} {:>10.2?}/pass  {:>4} configures/pass",
+        count,
+        pass,
+        elapsed / ITERATIONS,
+        configured
+    );
+}
+
+fn main() {
+    for count in [50, 100, 200] {
+        let windows: Vec<Window> = (1..=count as Window).collect();
+        let mut clients: Vec<Client> = windows.iter().map(|&window| Client::new(window, 0, 1)).collect();
+        let backend = MockBackend::default();
+
+        // Every client moves: the master factor flips between passes.
+        let start = Instant::now();
+        let mut configured = 0;
+        for iteration in 0..ITERATIONS {
+            let master_factor = if iteration % 2 == 0 { 0.55 } else { 0.5 };
+            configured = relayout(&backend, &windows, &mut clients, master_factor);
+            black_box(backend.take_requests());
+        }
+        report(count, "moved", start.elapsed(), configured);
+
+        // Nothing moves, as when focus changes or a bar redraw relayouts.
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            configured = relayout(&backend, &windows, &mut clients, 0.5);
+            black_box(backend.take_requests());
+        }
+        report(count, "unchanged", start.elapsed(), configured);
+    }
+}
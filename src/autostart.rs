@@ -0,0 +1,239 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::AutostartCommand;
+
+/// Keeps `oxwm.autostart_once` commands running exactly once per session.
+///
+/// The pids of started commands are written to a state file so that a
+/// restarted oxwm adopts the processes it started before instead of
+/// spawning duplicates. Each pid is saved with its start time, so a pid
+/// that has since been reused by an unrelated process is not adopted.
+pub struct Supervisor {
+    processes: Vec<SupervisedProcess>,
+    state_path: Option<PathBuf>,
+}
+
+struct SupervisedProcess {
+    command: AutostartCommand,
+    pid: i32,
+    /// Start time from `/proc/<pid>/stat`, in clock ticks after boot.
+    started: Option<u64>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        let state_path = dirs::runtime_dir()
+            .or_else(dirs::cache_dir)
+            .map(|dir| dir.join("oxwm").join(format!("autostart{}.state", display_suffix())));
+
+        let mut supervisor = Self {
+            processes: Vec::new(),
+            state_path,
+        };
+        supervisor.load_state();
+        supervisor
+    }
+
+    /// Starts every command that is not already running and updates the
+    /// restart policy of the ones that are.
+    pub fn ensure_running(&mut self, commands: &[AutostartCommand]) {
+        for command in commands {
+            if let Some(process) = self
+                .processes
+                .iter_mut()
+                .find(|process| process.command.command == command.command)
+            {
+                process.command.restart = command.restart;
+                continue;
+            }
+
+            if let Some(pid) = spawn(&command.command) {
+                self.processes.push(SupervisedProcess {
+                    command: command.clone(),
+                    pid,
+                    started: start_time(pid),
+                });
+            }
+        }
+        self.save_state();
+    }
+
+    /// Reaps exited processes and restarts those that asked for it.
+    pub fn poll(&mut self) {
+        let mut changed = false;
+
+        self.processes.retain_mut(|process| {
+            if is_alive(process.pid) {
+                return true;
+            }
+            changed = true;
+
+            if !process.command.restart {
+                log::info!("'{}' exited", process.command.command);
+                return false;
+            }
+
+            log::warn!("'{}' exited, restarting", process.command.command);
+            match spawn(&process.command.command) {
+                Some(pid) => {
+                    process.pid = pid;
+                    process.started = start_time(pid);
+                    true
+                }
+                None => false,
+            }
+        });
+
+        if changed {
+            self.save_state();
+        }
+    }
+
+    /// Terminates all supervised processes and forgets them.
+    pub fn shutdown(&mut self) {
+        for process in self.processes.drain(..) {
+            if start_time(process.pid) != process.started {
+                continue;
+            }
+            unsafe {
+                libc::kill(process.pid, libc::SIGTERM);
+            }
+        }
+        if let Some(path) = &self.state_path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    fn load_state(&mut self) {
+        let Some(contents) = self
+            .state_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+        else {
+            return;
+        };
+
+        for line in contents.lines() {
+            let Some((pid, started, restart, command)) = parse_state_line(line) else {
+                continue;
+            };
+            if is_alive(pid) && start_time(pid) == Some(started) {
+                self.processes.push(SupervisedProcess {
+                    command: AutostartCommand { command, restart },
+                    pid,
+                    started: Some(started),
+                });
+            }
+        }
+    }
+
+    fn save_state(&self) {
+        let Some(path) = &self.state_path else {
+            return;
+        };
+
+        let contents: String = self
+            .processes
+            .iter()
+            .filter_map(|process| {
+                Some(format!(
+                    "{}\t{}\t{}\t{}\n",
+                    process.pid,
+                    process.started?,
+                    process.command.restart,
+                    process.command.command
+                ))
+            })
+            .collect();
+
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Err(error) = std::fs::write(path, contents) {
+            log::error!("Failed to write {}: {}", path.display(), error);
+        }
+    }
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn parse_state_line(line: &str) -> Option<(i32, u64, bool, String)> {
+    let mut fields = line.splitn(4, '\t');
+    let pid = fields.next()?.parse().ok()?;
+    let started = fields.next()?.parse().ok()?;
+    let restart = fields.next()?.parse().ok()?;
+    let command = fields.next()?.to_string();
+    Some((pid, started, restart, command))
+}
+
+/// Field 22 of `/proc/<pid>/stat`, which together with the pid identifies a
+/// process across pid reuse. The command name in field 2 may contain spaces
+/// and parentheses, so fields are counted from its closing `)`.
+fn start_time(pid: i32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// Separate state per X display so nested sessions do not adopt each
+/// other's processes.
+fn display_suffix() -> String {
+    std::env::var("DISPLAY")
+        .map(|display| format!("-{}", display.replace(['/', ':'], "_")))
+        .unwrap_or_default()
+}
+
+fn spawn(command: &str) -> Option<i32> {
+    match Command::new("sh").arg("-c").arg(command).spawn() {
+        Ok(child) => {
+            log::info!("Spawned once: {}", command);
+            Some(child.id() as i32)
+        }
+        Err(error) => {
+            log::error!("Failed to spawn '{}': {}", command, error);
+            None
+        }
+    }
+}
+
+/// Reaps `pid` if it is our child and has exited. Processes adopted across
+/// an exec restart are still our children, so `waitpid` covers them too;
+/// `kill(pid, 0)` is the fallback for anything that is not.
+fn is_alive(pid: i32) -> bool {
+    let mut status = 0;
+    match unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) } {
+        0 => true,
+        -1 => unsafe { libc::kill(pid, 0) == 0 },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_lines_are_parsed() {
+        assert_eq!(
+            parse_state_line("42\t1234\ttrue\tpicom --config a\tb"),
+            Some((42, 1234, true, "picom --config a\tb".to_string()))
+        );
+    }
+
+    #[test]
+    fn state_lines_without_a_start_time_are_ignored() {
+        assert_eq!(parse_state_line("42\ttrue\tpicom"), None);
+    }
+
+    #[test]
+    fn start_time_identifies_a_running_process() {
+        let pid = std::process::id() as i32;
+        assert!(start_time(pid).is_some());
+        assert_eq!(start_time(pid), start_time(pid));
+    }
+}
@@ -0,0 +1,186 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use super::{Backend, InputOps, Output, OutputOps, PropertyOps, WindowId, WindowOps};
+use crate::errors::X11Error;
+
+/// A request made through a backend trait, as recorded by `MockBackend`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    Configure {
+        window: WindowId,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        border_width: u32,
+    },
+    Map(WindowId),
+    Unmap(WindowId),
+    Focus(WindowId),
+    Kill(WindowId),
+    MovePointer(WindowId, i16, i16),
+    GrabKey(u16, u8),
+    UngrabAllKeys,
+    GrabPointer(WindowId),
+    ReleaseGrabs,
+    GrabClientButtons(WindowId),
+    SetProperty(WindowId, u32),
+    RemoveProperty(WindowId, u32),
+    Commit,
+}
+
+/// A backend without an X server. Requests are recorded in order, and
+/// properties are kept so what is written can be read back. Outputs and
+/// the pointer position are whatever the caller sets.
+#[derive(Default)]
+pub struct MockBackend {
+    pub outputs: Vec<Output>,
+    pub pointer: Cell<(i32, i32)>,
+    /// Set to make pointer grabs fail, as when another client holds it.
+    pub pointer_grabbed_elsewhere: bool,
+    requests: RefCell<Vec<Request>>,
+    properties: RefCell<HashMap<(WindowId, u32), Vec<u32>>>,
+}
+
+impl MockBackend {
+    pub fn with_outputs(outputs: Vec<Output>) -> Self {
+        Self {
+            outputs,
+            ..Self::default()
+        }
+    }
+
+    /// Everything requested since the last call, oldest first.
+    pub fn take_requests(&self) -> Vec<Request> {
+        self.requests.take()
+    }
+
+    fn record(&self, request: Request) {
+        self.requests.borrow_mut().push(request);
+    }
+}
+
+impl Backend for MockBackend {
+    type Error = X11Error;
+
+    fn commit(&self) -> Result<(), X11Error> {
+        self.record(Request::Commit);
+        Ok(())
+    }
+}
+
+impl WindowOps for MockBackend {
+    fn configure(
+        &self,
+        window: WindowId,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        border_width: u32,
+    ) -> Result<(), X11Error> {
+        self.record(Request::Configure {
+            window,
+            x,
+            y,
+            width,
+            height,
+            border_width,
+        });
+        Ok(())
+    }
+
+    fn map(&self, window: WindowId) -> Result<(), X11Error> {
+        self.record(Request::Map(window));
+        Ok(())
+    }
+
+    fn unmap(&self, window: WindowId) -> Result<(), X11Error> {
+        self.record(Request::Unmap(window));
+        Ok(())
+    }
+
+    fn focus(&self, window: WindowId) -> Result<(), X11Error> {
+        self.record(Request::Focus(window));
+        Ok(())
+    }
+
+    fn kill(&self, window: WindowId) -> Result<(), X11Error> {
+        self.record(Request::Kill(window));
+        self.properties.borrow_mut().retain(|(owner, _), _| *owner != window);
+        Ok(())
+    }
+}
+
+impl InputOps for MockBackend {
+    fn pointer_position(&self, _root: WindowId) -> Result<(i32, i32), X11Error> {
+        Ok(self.pointer.get())
+    }
+
+    fn move_pointer(&self, window: WindowId, x: i16, y: i16) -> Result<(), X11Error> {
+        self.record(Request::MovePointer(window, x, y));
+        self.pointer.set((x as i32, y as i32));
+        Ok(())
+    }
+
+    fn grab_key_combination(&self, _root: WindowId, modifiers: u16, keycode: u8) -> Result<(), X11Error> {
+        self.record(Request::GrabKey(modifiers, keycode));
+        Ok(())
+    }
+
+    fn ungrab_all_keys(&self, _root: WindowId) -> Result<(), X11Error> {
+        self.record(Request::UngrabAllKeys);
+        Ok(())
+    }
+
+    fn grab_pointer_motion(&self, window: WindowId) -> Result<bool, X11Error> {
+        self.record(Request::GrabPointer(window));
+        Ok(!self.pointer_grabbed_elsewhere)
+    }
+
+    fn release_grabs(&self) -> Result<(), X11Error> {
+        self.record(Request::ReleaseGrabs);
+        Ok(())
+    }
+
+    fn grab_client_buttons(&self, window: WindowId) -> Result<(), X11Error> {
+        self.record(Request::GrabClientButtons(window));
+        Ok(())
+    }
+}
+
+impl OutputOps for MockBackend {
+    fn outputs(&self, _root: WindowId) -> Result<Vec<Output>, X11Error> {
+        Ok(self.outputs.clone())
+    }
+}
+
+impl PropertyOps for MockBackend {
+    fn property32(&self, window: WindowId, property: u32, _type: u32) -> Result<Vec<u32>, X11Error> {
+        Ok(self
+            .properties
+            .borrow()
+            .get(&(window, property))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn set_property32(
+        &self,
+        window: WindowId,
+        property: u32,
+        _type: u32,
+        values: &[u32],
+    ) -> Result<(), X11Error> {
+        self.record(Request::SetProperty(window, property));
+        self.properties.borrow_mut().insert((window, property), values.to_vec());
+        Ok(())
+    }
+
+    fn remove_property(&self, window: WindowId, property: u32) -> Result<(), X11Error> {
+        self.record(Request::RemoveProperty(window, property));
+        self.properties.borrow_mut().remove(&(window, property));
+        Ok(())
+    }
+}
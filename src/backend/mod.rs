@@ -0,0 +1,79 @@
+pub mod mock;
+pub mod x11;
+
+pub type WindowId = u32;
+
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+pub trait Backend {
+    type Error;
+
+    fn commit(&self) -> Result<(), Self::Error>;
+}
+
+pub trait WindowOps: Backend {
+    fn configure(
+        &self,
+        window: WindowId,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        border_width: u32,
+    ) -> Result<(), Self::Error>;
+
+    fn map(&self, window: WindowId) -> Result<(), Self::Error>;
+
+    fn unmap(&self, window: WindowId) -> Result<(), Self::Error>;
+
+    fn focus(&self, window: WindowId) -> Result<(), Self::Error>;
+
+    fn kill(&self, window: WindowId) -> Result<(), Self::Error>;
+}
+
+pub trait InputOps: Backend {
+    fn pointer_position(&self, root: WindowId) -> Result<(i32, i32), Self::Error>;
+
+    fn move_pointer(&self, window: WindowId, x: i16, y: i16) -> Result<(), Self::Error>;
+
+    fn grab_key_combination(&self, root: WindowId, modifiers: u16, keycode: u8) -> Result<(), Self::Error>;
+
+    fn ungrab_all_keys(&self, root: WindowId) -> Result<(), Self::Error>;
+
+    /// Sends pointer motion and button events to `window` until the grab
+    /// is released, for interactive moves and resizes. Returns false when
+    /// another client holds the pointer.
+    fn grab_pointer_motion(&self, window: WindowId) -> Result<bool, Self::Error>;
+
+    fn release_grabs(&self) -> Result<(), Self::Error>;
+
+    /// Grabs clicks on an unfocused client so the first click can focus it.
+    fn grab_client_buttons(&self, window: WindowId) -> Result<(), Self::Error>;
+}
+
+pub trait PropertyOps: Backend {
+    /// Reads a 32 bit property, empty when the window doesn't have it or
+    /// is already gone.
+    fn property32(&self, window: WindowId, property: u32, type_: u32) -> Result<Vec<u32>, Self::Error>;
+
+    fn set_property32(
+        &self,
+        window: WindowId,
+        property: u32,
+        type_: u32,
+        values: &[u32],
+    ) -> Result<(), Self::Error>;
+
+    fn remove_property(&self, window: WindowId, property: u32) -> Result<(), Self::Error>;
+}
+
+pub trait OutputOps: Backend {
+    fn outputs(&self, root: WindowId) -> Result<Vec<Output>, Self::Error>;
+}
@@ -0,0 +1,254 @@
+use x11rb::connection::Connection;
+use x11rb::protocol::randr::{self, ConnectionExt as _};
+use x11rb::protocol::xinerama::ConnectionExt as _;
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+
+use super::{Backend, InputOps, Output, OutputOps, PropertyOps, WindowId, WindowOps};
+use crate::errors::X11Error;
+
+impl Backend for RustConnection {
+    type Error = X11Error;
+
+    fn commit(&self) -> Result<(), X11Error> {
+        self.flush()?;
+        Ok(())
+    }
+}
+
+impl WindowOps for RustConnection {
+    fn configure(
+        &self,
+        window: WindowId,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        border_width: u32,
+    ) -> Result<(), X11Error> {
+        self.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(x)
+                .y(y)
+                .width(width)
+                .height(height)
+                .border_width(border_width),
+        )?;
+        Ok(())
+    }
+
+    fn map(&self, window: WindowId) -> Result<(), X11Error> {
+        self.map_window(window)?;
+        Ok(())
+    }
+
+    fn unmap(&self, window: WindowId) -> Result<(), X11Error> {
+        self.unmap_window(window)?;
+        Ok(())
+    }
+
+    fn focus(&self, window: WindowId) -> Result<(), X11Error> {
+        self.set_input_focus(InputFocus::POINTER_ROOT, window, x11rb::CURRENT_TIME)?;
+        Ok(())
+    }
+
+    fn kill(&self, window: WindowId) -> Result<(), X11Error> {
+        self.kill_client(window)?;
+        Ok(())
+    }
+}
+
+impl InputOps for RustConnection {
+    fn pointer_position(&self, root: WindowId) -> Result<(i32, i32), X11Error> {
+        let pointer = self.query_pointer(root)?.reply()?;
+        Ok((pointer.root_x as i32, pointer.root_y as i32))
+    }
+
+    fn move_pointer(&self, window: WindowId, x: i16, y: i16) -> Result<(), X11Error> {
+        self.warp_pointer(x11rb::NONE, window, 0, 0, 0, 0, x, y)?;
+        Ok(())
+    }
+
+    fn grab_key_combination(&self, root: WindowId, modifiers: u16, keycode: u8) -> Result<(), X11Error> {
+        self.grab_key(true, root, modifiers.into(), keycode, GrabMode::ASYNC, GrabMode::ASYNC)?;
+        Ok(())
+    }
+
+    fn ungrab_all_keys(&self, root: WindowId) -> Result<(), X11Error> {
+        self.ungrab_key(Grab::ANY, root, ModMask::ANY)?;
+        Ok(())
+    }
+
+    fn grab_pointer_motion(&self, window: WindowId) -> Result<bool, X11Error> {
+        let reply = self
+            .grab_pointer(
+                false,
+                window,
+                EventMask::POINTER_MOTION | EventMask::BUTTON_RELEASE | EventMask::BUTTON_PRESS,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+                x11rb::NONE,
+                x11rb::NONE,
+                x11rb::CURRENT_TIME,
+            )?
+            .reply()?;
+        Ok(reply.status == GrabStatus::SUCCESS)
+    }
+
+    fn release_grabs(&self) -> Result<(), X11Error> {
+        self.ungrab_pointer(x11rb::CURRENT_TIME)?;
+        self.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+        Ok(())
+    }
+
+    fn grab_client_buttons(&self, window: WindowId) -> Result<(), X11Error> {
+        self.grab_button(
+            false,
+            window,
+            EventMask::BUTTON_PRESS,
+            GrabMode::SYNC,
+            GrabMode::ASYNC,
+            x11rb::NONE,
+            x11rb::NONE,
+            ButtonIndex::ANY,
+            ModMask::ANY,
+        )?;
+        Ok(())
+    }
+}
+
+impl PropertyOps for RustConnection {
+    fn property32(&self, window: WindowId, property: u32, type_: u32) -> Result<Vec<u32>, X11Error> {
+        let reply = self
+            .get_property(false, window, property, type_, 0, u32::MAX / 4)?
+            .reply();
+        Ok(reply
+            .ok()
+            .and_then(|reply| reply.value32().map(|values| values.collect()))
+            .unwrap_or_default())
+    }
+
+    fn set_property32(
+        &self,
+        window: WindowId,
+        property: u32,
+        type_: u32,
+        values: &[u32],
+    ) -> Result<(), X11Error> {
+        let bytes: Vec<u8> = values.iter().flat_map(|value| value.to_ne_bytes()).collect();
+        self.change_property(
+            PropMode::REPLACE,
+            window,
+            property,
+            type_,
+            32,
+            values.len() as u32,
+            &bytes,
+        )?;
+        Ok(())
+    }
+
+    fn remove_property(&self, window: WindowId, property: u32) -> Result<(), X11Error> {
+        self.delete_property(window, property)?;
+        Ok(())
+    }
+}
+
+impl OutputOps for RustConnection {
+    fn outputs(&self, root: WindowId) -> Result<Vec<Output>, X11Error> {
+        let mut outputs = Vec::<Output>::new();
+
+        let xinerama_active = self
+            .xinerama_is_active()
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .is_some_and(|reply| reply.state != 0);
+
+        if !xinerama_active {
+            return Ok(outputs);
+        }
+
+        let Some(xinerama_reply) = self
+            .xinerama_query_screens()
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+        else {
+            return Ok(outputs);
+        };
+
+        for screen_info in &xinerama_reply.screen_info {
+            let has_valid_dimensions = screen_info.width > 0 && screen_info.height > 0;
+            if !has_valid_dimensions {
+                continue;
+            }
+
+            let output = Output {
+                name: None,
+                x: screen_info.x_org as i32,
+                y: screen_info.y_org as i32,
+                width: screen_info.width as u32,
+                height: screen_info.height as u32,
+            };
+
+            let is_duplicate_output = outputs.iter().any(|existing| {
+                existing.x == output.x
+                    && existing.y == output.y
+                    && existing.width == output.width
+                    && existing.height == output.height
+            });
+
+            if !is_duplicate_output {
+                outputs.push(output);
+            }
+        }
+
+        assign_output_names(self, root, &mut outputs);
+
+        Ok(outputs)
+    }
+}
+
+fn assign_output_names(connection: &RustConnection, root: Window, outputs: &mut [Output]) {
+    let Some(resources) = connection
+        .randr_get_screen_resources_current(root)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+    else {
+        return;
+    };
+
+    for randr_output in &resources.outputs {
+        let Some(output_info) = connection
+            .randr_get_output_info(*randr_output, resources.config_timestamp)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+        else {
+            continue;
+        };
+
+        if output_info.connection != randr::Connection::CONNECTED || output_info.crtc == x11rb::NONE {
+            continue;
+        }
+
+        let Some(crtc_info) = connection
+            .randr_get_crtc_info(output_info.crtc, resources.config_timestamp)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+        else {
+            continue;
+        };
+
+        let output_name = String::from_utf8_lossy(&output_info.name).into_owned();
+
+        if let Some(output) = outputs.iter_mut().find(|output| {
+            output.name.is_none()
+                && output.x == crtc_info.x as i32
+                && output.y == crtc_info.y as i32
+                && output.width == crtc_info.width as u32
+                && output.height == crtc_info.height as u32
+        }) {
+            output.name = Some(output_name);
+        }
+    }
+}
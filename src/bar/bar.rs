@@ -1,6 +1,7 @@
 use super::blocks::Block;
 use super::font::{Font, FontDraw};
-use crate::Config;
+use super::markup;
+use crate::{Config, TagCountStyle};
 use crate::errors::X11Error;
 use std::time::Instant;
 use x11rb::COPY_DEPTH_FROM_PARENT;
@@ -8,6 +9,14 @@ use x11rb::connection::Connection;
 use x11rb::protocol::xproto::*;
 use x11rb::rust_connection::RustConnection;
 
+pub enum BarClick {
+    Tag(usize),
+    LayoutSymbol,
+    /// A status block, by its index in `Config::status_blocks`, and how far
+    /// into the block the click landed.
+    Block(usize, i16),
+}
+
 pub struct Bar {
     window: Window,
     width: u16,
@@ -15,21 +24,48 @@ pub struct Bar {
     graphics_context: Gcontext,
     pixmap: x11::xlib::Pixmap,
     display: *mut x11::xlib::Display,
+    /// Colormap of the 32-bit visual when the bar is translucent, whose
+    /// pixels then carry an alpha channel.
+    argb_colormap: Option<Colormap>,
+    background_alpha: f32,
 
     font_draw: FontDraw,
 
     tag_widths: Vec<u16>,
+    tag_padding: Option<u32>,
+    /// Clients on each tag, shown in the cell unless `tag_count_style` is
+    /// `None`.
+    tag_counts: Vec<usize>,
+    tag_count_style: TagCountStyle,
+    /// Icon replacing each tag's name, from the class of a window on it.
+    tag_icons: Vec<Option<String>>,
+    hovered_tag: Option<usize>,
+    floating_count: usize,
+    urgent_tags: u32,
+    layout_symbol_x: i16,
+    layout_symbol_width: u16,
     needs_redraw: bool,
 
     blocks: Vec<Box<dyn Block>>,
     block_last_updates: Vec<Instant>,
     block_underlines: Vec<bool>,
+    /// Index in `Config::status_blocks` of each shown block.
+    block_indices: Vec<usize>,
+    /// Horizontal position and width of each block as last drawn.
+    block_areas: Vec<(i16, u16)>,
     status_text: String,
 
+    text_top_padding: i16,
+    separator: String,
+    block_padding: u16,
+    underline_thickness: Option<u16>,
+
+    monitor_index: usize,
     tags: Vec<String>,
     scheme_normal: crate::ColorScheme,
     scheme_occupied: crate::ColorScheme,
     scheme_selected: crate::ColorScheme,
+    urgent_color: u32,
 }
 
 impl Bar {
@@ -40,6 +76,7 @@ impl Bar {
         config: &Config,
         display: *mut x11::xlib::Display,
         font: &Font,
+        monitor_index: usize,
         x: i16,
         y: i16,
         width: u16,
@@ -47,10 +84,51 @@ impl Bar {
         let window = connection.generate_id()?;
         let graphics_context = connection.generate_id()?;
 
-        let height = (font.height() as f32 * 1.4) as u16;
+        let height = config
+            .bar_height
+            .map(|height| height as u16)
+            .unwrap_or((font.height() as f32 * 1.4) as u16);
+        let text_top_padding = if config.bar_height.is_some() {
+            (height as i16 - font.height() as i16) / 2
+        } else {
+            4
+        };
+
+        let argb_visual = if config.bar_alpha < 1.0 {
+            find_argb_visual(connection, display, screen_num)?
+        } else {
+            None
+        };
+
+        let mut window_aux = CreateWindowAux::new()
+            .event_mask(EventMask::EXPOSURE | EventMask::BUTTON_PRESS)
+            .override_redirect(1);
+        let (depth, visual, colormap, argb_colormap) = match argb_visual {
+            Some(visual_info) => {
+                let colormap = connection.generate_id()?;
+                connection.create_colormap(
+                    ColormapAlloc::NONE,
+                    colormap,
+                    screen.root,
+                    visual_info.visualid as Visualid,
+                )?;
+                window_aux = window_aux
+                    .background_pixel(background_pixel(config.scheme_normal.background, config.bar_alpha))
+                    .border_pixel(0)
+                    .colormap(colormap);
+                (32, visual_info.visual, colormap as x11::xlib::Colormap, Some(colormap))
+            }
+            None => {
+                window_aux = window_aux.background_pixel(config.scheme_normal.background);
+                let visual = unsafe { x11::xlib::XDefaultVisual(display, screen_num as i32) };
+                let colormap = unsafe { x11::xlib::XDefaultColormap(display, screen_num as i32) };
+                let depth = unsafe { x11::xlib::XDefaultDepth(display, screen_num as i32) };
+                (depth, visual, colormap, None)
+            }
+        };
 
         connection.create_window(
-            COPY_DEPTH_FROM_PARENT,
+            argb_visual.map_or(COPY_DEPTH_FROM_PARENT, |_| 32),
             window,
             screen.root,
             x,
@@ -59,13 +137,14 @@ impl Bar {
             height,
             0,
             WindowClass::INPUT_OUTPUT,
-            screen.root_visual,
-            &CreateWindowAux::new()
-                .background_pixel(config.scheme_normal.background)
-                .event_mask(EventMask::EXPOSURE | EventMask::BUTTON_PRESS)
-                .override_redirect(1),
+            argb_visual.map_or(screen.root_visual, |visual_info| visual_info.visualid as Visualid),
+            &window_aux,
         )?;
 
+        if config.bar_radius > 0 && crate::shape::is_supported(connection) {
+            crate::shape::round_corners(connection, window, (0, 0), (width, height), config.bar_radius as u16)?;
+        }
+
         connection.create_gc(
             graphics_context,
             window,
@@ -77,10 +156,6 @@ impl Bar {
         connection.map_window(window)?;
         connection.flush()?;
 
-        let visual = unsafe { x11::xlib::XDefaultVisual(display, screen_num as i32) };
-        let colormap = unsafe { x11::xlib::XDefaultColormap(display, screen_num as i32) };
-        let depth = unsafe { x11::xlib::XDefaultDepth(display, screen_num as i32) };
-
         let pixmap = unsafe {
             x11::xlib::XCreatePixmap(
                 display,
@@ -93,30 +168,25 @@ impl Bar {
 
         let font_draw = FontDraw::new(display, pixmap, visual, colormap)?;
 
-        let horizontal_padding = (font.height() as f32 * 0.4) as u16;
-
-        let tag_widths = config
-            .tags
-            .iter()
-            .map(|tag| {
-                let text_width = font.text_width(tag);
-                text_width + (horizontal_padding * 2)
-            })
-            .collect();
+        let tag_widths = compute_tag_widths(&config.tags, font, config.bar_tag_padding);
 
         let blocks: Vec<Box<dyn Block>> = config
             .status_blocks
             .iter()
+            .filter(|block_config| block_config.shows_on(monitor_index))
             .map(|block_config| block_config.to_block())
             .collect();
 
         let block_underlines: Vec<bool> = config
             .status_blocks
             .iter()
+            .filter(|block_config| block_config.shows_on(monitor_index))
             .map(|block_config| block_config.underline)
             .collect();
 
+        let block_indices = shown_block_indices(config, monitor_index);
         let block_last_updates = vec![Instant::now(); blocks.len()];
+        let block_areas = vec![(0, 0); blocks.len()];
 
         Ok(Bar {
             window,
@@ -125,17 +195,36 @@ impl Bar {
             graphics_context,
             pixmap,
             display,
+            argb_colormap,
+            background_alpha: config.bar_alpha,
             font_draw,
             tag_widths,
+            tag_padding: config.bar_tag_padding,
+            tag_counts: Vec::new(),
+            tag_count_style: config.bar_tag_counts,
+            tag_icons: Vec::new(),
+            hovered_tag: None,
+            floating_count: 0,
+            urgent_tags: 0,
+            layout_symbol_x: 0,
+            layout_symbol_width: 0,
             needs_redraw: true,
             blocks,
             block_last_updates,
             block_underlines,
+            block_indices,
+            block_areas,
             status_text: String::new(),
+            text_top_padding,
+            separator: config.bar_separator.clone(),
+            block_padding: config.bar_block_padding as u16,
+            underline_thickness: config.bar_underline_thickness.map(|thickness| thickness as u16),
+            monitor_index,
             tags: config.tags.clone(),
             scheme_normal: config.scheme_normal,
             scheme_occupied: config.scheme_occupied,
             scheme_selected: config.scheme_selected,
+            urgent_color: urgent_color(config),
         })
     }
 
@@ -156,6 +245,10 @@ impl Bar {
         let mut changed = false;
 
         for (i, block) in self.blocks.iter_mut().enumerate() {
+            if block.poll() {
+                changed = true;
+            }
+
             let elapsed = now.duration_since(self.block_last_updates[i]);
 
             if elapsed >= block.interval() {
@@ -188,6 +281,7 @@ impl Bar {
         draw_blocks: bool,
         layout_symbol: &str,
         keychord_indicator: Option<&str>,
+        mode_indicators: &[String],
     ) -> Result<(), X11Error> {
         if !self.needs_redraw {
             return Ok(());
@@ -199,31 +293,24 @@ impl Bar {
         )?;
         connection.flush()?;
 
-        unsafe {
-            let gc = x11::xlib::XCreateGC(display, self.pixmap, 0, std::ptr::null_mut());
-            x11::xlib::XSetForeground(display, gc, self.scheme_normal.background as u64);
-            x11::xlib::XFillRectangle(
-                display,
-                self.pixmap,
-                gc,
-                0,
-                0,
-                self.width as u32,
-                self.height as u32,
-            );
-            x11::xlib::XFreeGC(display, gc);
-        }
+        let background = match self.argb_colormap {
+            Some(_) => background_pixel(self.scheme_normal.background, self.background_alpha),
+            None => self.scheme_normal.background,
+        };
+        fill_rectangle(display, self.pixmap, background, (0, 0), (self.width, self.height));
 
         let mut x_position: i16 = 0;
 
-        for (tag_index, tag) in self.tags.iter().enumerate() {
+        for (tag_index, tag) in self.tag_labels().iter().enumerate() {
             let tag_mask = 1 << tag_index;
             let is_selected = (current_tags & tag_mask) != 0;
             let is_occupied = (occupied_tags & tag_mask) != 0;
+            let is_urgent = (self.urgent_tags & tag_mask) != 0 && !is_selected;
 
             let tag_width = self.tag_widths[tag_index];
+            let is_hovered = self.hovered_tag == Some(tag_index);
 
-            let scheme = if is_selected {
+            let scheme = if is_selected || is_hovered {
                 &self.scheme_selected
             } else if is_occupied {
                 &self.scheme_occupied
@@ -231,18 +318,14 @@ impl Bar {
                 &self.scheme_normal
             };
 
-            let text_width = font.text_width(tag);
-            let text_x = x_position + ((tag_width - text_width) / 2) as i16;
-
-            let top_padding = 4;
-            let text_y = top_padding + font.ascent();
-
-            self.font_draw
-                .draw_text(font, scheme.foreground, text_x, text_y, tag);
-
-            if is_selected {
-                let font_height = font.height();
-                let underline_height = font_height / 8;
+            // A window dragged over a tag or an urgent window on it fills the
+            // whole cell, otherwise the selected tag gets its usual underline.
+            let highlight = if is_hovered {
+                Some((scheme.underline, x_position, 0, tag_width, self.height))
+            } else if is_urgent {
+                Some((self.urgent_color, x_position, 0, tag_width, self.height))
+            } else if is_selected {
+                let underline_height = self.underline_height(font);
                 let bottom_gap = 3;
                 let underline_y = self.height as i16 - underline_height as i16 - bottom_gap;
 
@@ -250,30 +333,41 @@ impl Bar {
                 let underline_width = tag_width - underline_padding;
                 let underline_x = x_position + (underline_padding / 2) as i16;
 
-                unsafe {
-                    let gc = x11::xlib::XCreateGC(display, self.pixmap, 0, std::ptr::null_mut());
-                    x11::xlib::XSetForeground(display, gc, scheme.underline as u64);
-                    x11::xlib::XFillRectangle(
-                        display,
-                        self.pixmap,
-                        gc,
-                        underline_x as i32,
-                        underline_y as i32,
-                        underline_width as u32,
-                        underline_height as u32,
-                    );
-                    x11::xlib::XFreeGC(display, gc);
-                }
+                Some((scheme.underline, underline_x, underline_y, underline_width, underline_height))
+            } else {
+                None
+            };
+
+            if let Some((color, rect_x, rect_y, rect_width, rect_height)) = highlight {
+                fill_rectangle(
+                    display,
+                    self.pixmap,
+                    opaque_pixel(color, self.argb_colormap.is_some()),
+                    (rect_x, rect_y),
+                    (rect_width, rect_height),
+                );
             }
 
+            let text_width = font.text_width(tag);
+            let text_x = x_position + ((tag_width - text_width) / 2) as i16;
+
+            let text_y = self.text_top_padding + font.ascent();
+
+            let text_color = if is_hovered || is_urgent {
+                scheme.background
+            } else {
+                scheme.foreground
+            };
+            self.font_draw
+                .draw_text(font, text_color, text_x, text_y, tag);
+
             x_position += tag_width as i16;
         }
 
         x_position += 10;
 
         let text_x = x_position;
-        let top_padding = 4;
-        let text_y = top_padding + font.ascent();
+        let text_y = self.text_top_padding + font.ascent();
 
         self.font_draw.draw_text(
             font,
@@ -283,13 +377,45 @@ impl Bar {
             layout_symbol,
         );
 
-        x_position += font.text_width(layout_symbol) as i16;
+        self.layout_symbol_x = text_x;
+        self.layout_symbol_width = font.text_width(layout_symbol);
+        x_position += self.layout_symbol_width as i16;
+
+        if self.floating_count > 0 {
+            x_position += 4;
+
+            let badge = format!("{}{}", crate::tab_bar::FLOATING_INDICATOR, self.floating_count);
+            self.font_draw.draw_text(
+                font,
+                self.scheme_selected.foreground,
+                x_position,
+                text_y,
+                &badge,
+            );
+
+            x_position += font.text_width(&badge) as i16;
+        }
+
+        for indicator in mode_indicators {
+            x_position += 10;
+
+            let text_y = self.text_top_padding + font.ascent();
+            self.font_draw.draw_text(
+                font,
+                self.scheme_selected.foreground,
+                x_position,
+                text_y,
+                indicator,
+            );
+
+            x_position += font.text_width(indicator) as i16;
+        }
 
         if let Some(indicator) = keychord_indicator {
             x_position += 10;
 
             let text_x = x_position;
-            let text_y = top_padding + font.ascent();
+            let text_y = self.text_top_padding + font.ascent();
 
             self.font_draw.draw_text(
                 font,
@@ -300,24 +426,44 @@ impl Bar {
             );
         }
 
+        self.block_areas.iter_mut().for_each(|area| *area = (0, 0));
         if draw_blocks && !self.status_text.is_empty() {
             let padding = 10;
             let mut x_position = self.width as i16 - padding;
+            let text_y = self.text_top_padding + font.ascent();
+            let underline_height = self.underline_height(font);
+            let separator_width = font.text_width(&self.separator) as i16;
 
             for (i, block) in self.blocks.iter_mut().enumerate().rev() {
                 if let Ok(text) = block.content() {
-                    let text_width = font.text_width(&text);
+                    let segments = markup::parse(&text);
+                    let text_width: u16 = segments
+                        .iter()
+                        .map(|segment| font.text_width(&segment.text))
+                        .sum();
+                    x_position -= self.block_padding as i16;
                     x_position -= text_width as i16;
 
-                    let top_padding = 4;
-                    let text_y = top_padding + font.ascent();
-
-                    self.font_draw
-                        .draw_text(font, block.color(), x_position, text_y, &text);
+                    let mut segment_x = x_position;
+                    for segment in &segments {
+                        let segment_width = font.text_width(&segment.text);
+                        if let Some(background) = segment.background {
+                            fill_rectangle(
+                                display,
+                                self.pixmap,
+                                opaque_pixel(background, self.argb_colormap.is_some()),
+                                (segment_x, 0),
+                                (segment_width, self.height),
+                            );
+                        }
+                        let color = segment.foreground.unwrap_or(block.color());
+                        self.font_draw
+                            .draw_text(font, color, segment_x, text_y, &segment.text);
+                        segment_x += segment_width as i16;
+                    }
+                    self.block_areas[i] = (x_position, text_width);
 
                     if self.block_underlines[i] {
-                        let font_height = font.height();
-                        let underline_height = font_height / 8;
                         let bottom_gap = 3;
                         let underline_y = self.height as i16 - underline_height as i16 - bottom_gap;
 
@@ -325,20 +471,27 @@ impl Bar {
                         let underline_width = text_width + underline_padding;
                         let underline_x = x_position - (underline_padding / 2) as i16;
 
-                        unsafe {
-                            let gc = x11::xlib::XCreateGC(display, self.pixmap, 0, std::ptr::null_mut());
-                            x11::xlib::XSetForeground(display, gc, block.color() as u64);
-                            x11::xlib::XFillRectangle(
-                                display,
-                                self.pixmap,
-                                gc,
-                                underline_x as i32,
-                                underline_y as i32,
-                                underline_width as u32,
-                                underline_height as u32,
-                            );
-                            x11::xlib::XFreeGC(display, gc);
-                        }
+                        fill_rectangle(
+                            display,
+                            self.pixmap,
+                            opaque_pixel(block.color(), self.argb_colormap.is_some()),
+                            (underline_x, underline_y),
+                            (underline_width, underline_height),
+                        );
+                    }
+
+                    x_position -= self.block_padding as i16;
+
+                    let is_first_block = i == 0;
+                    if !is_first_block && !self.separator.is_empty() {
+                        x_position -= separator_width;
+                        self.font_draw.draw_text(
+                            font,
+                            self.scheme_normal.foreground,
+                            x_position,
+                            text_y,
+                            &self.separator,
+                        );
                     }
                 }
             }
@@ -367,16 +520,118 @@ impl Bar {
         Ok(())
     }
 
-    pub fn handle_click(&self, click_x: i16) -> Option<usize> {
+    fn underline_height(&self, font: &Font) -> u16 {
+        self.underline_thickness.unwrap_or(font.height() / 8)
+    }
+
+    pub fn handle_click(&self, click_x: i16) -> Option<BarClick> {
         let mut current_x_position = 0;
 
         for (tag_index, &tag_width) in self.tag_widths.iter().enumerate() {
             if click_x >= current_x_position && click_x < current_x_position + tag_width as i16 {
-                return Some(tag_index);
+                return Some(BarClick::Tag(tag_index));
             }
             current_x_position += tag_width as i16;
         }
-        None
+
+        let (symbol_x, symbol_width) = self.layout_symbol_area();
+        if click_x >= symbol_x && click_x < symbol_x + symbol_width as i16 {
+            return Some(BarClick::LayoutSymbol);
+        }
+
+        self.block_areas
+            .iter()
+            .zip(&self.block_indices)
+            .find(|((block_x, block_width), _)| {
+                click_x >= *block_x && click_x < block_x + *block_width as i16
+            })
+            .map(|((block_x, _), &index)| BarClick::Block(index, click_x - block_x))
+    }
+
+    /// Horizontal position and width of the layout symbol as last drawn.
+    pub fn layout_symbol_area(&self) -> (i16, u16) {
+        (self.layout_symbol_x, self.layout_symbol_width)
+    }
+
+    /// Replaces the tag labels, e.g. after a tag was renamed, and recomputes
+    /// the width of every tag cell.
+    pub fn set_tags(&mut self, tags: &[String], font: &Font, tag_padding: Option<u32>) {
+        self.tags = tags.to_vec();
+        self.tag_padding = tag_padding;
+        self.tag_widths = compute_tag_widths(&self.tag_labels(), font, tag_padding);
+        self.needs_redraw = true;
+    }
+
+    /// Number of clients on each tag, resizing the tag cells when the
+    /// counts are shown and have changed.
+    pub fn set_tag_counts(&mut self, counts: Vec<usize>, font: &Font) {
+        if self.tag_counts == counts {
+            return;
+        }
+        self.tag_counts = counts;
+        if self.tag_count_style != TagCountStyle::None {
+            self.tag_widths = compute_tag_widths(&self.tag_labels(), font, self.tag_padding);
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Icons to show instead of the tag names, None for tags keeping their
+    /// name, resizing the tag cells when they changed.
+    pub fn set_tag_icons(&mut self, icons: Vec<Option<String>>, font: &Font) {
+        if self.tag_icons == icons {
+            return;
+        }
+        self.tag_icons = icons;
+        self.tag_widths = compute_tag_widths(&self.tag_labels(), font, self.tag_padding);
+        self.needs_redraw = true;
+    }
+
+    /// Tag names, or their icons, with client counts attached as configured.
+    fn tag_labels(&self) -> Vec<String> {
+        self.tags
+            .iter()
+            .enumerate()
+            .map(|(tag_index, tag)| {
+                let tag = self
+                    .tag_icons
+                    .get(tag_index)
+                    .and_then(Option::as_ref)
+                    .unwrap_or(tag);
+                let count = self.tag_counts.get(tag_index).copied().unwrap_or(0);
+                match self.tag_count_style {
+                    _ if count == 0 => tag.clone(),
+                    TagCountStyle::None => tag.clone(),
+                    TagCountStyle::Suffix => format!("{} {}", tag, count),
+                    TagCountStyle::Superscript => format!("{}{}", tag, superscript(count)),
+                }
+            })
+            .collect()
+    }
+
+    /// Highlights the tag a window is being dragged onto.
+    pub fn set_hovered_tag(&mut self, tag_index: Option<usize>) {
+        if self.hovered_tag != tag_index {
+            self.hovered_tag = tag_index;
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Number of visible floating windows, shown as a badge next to the
+    /// layout symbol.
+    pub fn set_floating_count(&mut self, count: usize) {
+        if self.floating_count != count {
+            self.floating_count = count;
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Tags holding a window that wants attention, drawn filled with the
+    /// urgent color.
+    pub fn set_urgent_tags(&mut self, tags: u32) {
+        if self.urgent_tags != tags {
+            self.urgent_tags = tags;
+            self.needs_redraw = true;
+        }
     }
 
     pub fn needs_redraw(&self) -> bool {
@@ -387,31 +642,136 @@ impl Bar {
         self.blocks = config
             .status_blocks
             .iter()
+            .filter(|block_config| block_config.shows_on(self.monitor_index))
             .map(|block_config| block_config.to_block())
             .collect();
 
         self.block_underlines = config
             .status_blocks
             .iter()
+            .filter(|block_config| block_config.shows_on(self.monitor_index))
             .map(|block_config| block_config.underline)
             .collect();
 
+        self.block_indices = shown_block_indices(config, self.monitor_index);
         self.block_last_updates = vec![Instant::now(); self.blocks.len()];
+        self.block_areas = vec![(0, 0); self.blocks.len()];
+
+        self.separator = config.bar_separator.clone();
+        self.block_padding = config.bar_block_padding as u16;
+        self.underline_thickness = config.bar_underline_thickness.map(|thickness| thickness as u16);
 
         self.tags = config.tags.clone();
+        self.tag_count_style = config.bar_tag_counts;
+        self.update_colors(config);
+
+        self.status_text.clear();
+    }
+
+    pub fn update_colors(&mut self, config: &Config) {
         self.scheme_normal = config.scheme_normal;
         self.scheme_occupied = config.scheme_occupied;
         self.scheme_selected = config.scheme_selected;
-
-        self.status_text.clear();
+        self.urgent_color = urgent_color(config);
+        self.background_alpha = config.bar_alpha;
         self.needs_redraw = true;
     }
 }
 
+fn shown_block_indices(config: &Config, monitor_index: usize) -> Vec<usize> {
+    config
+        .status_blocks
+        .iter()
+        .enumerate()
+        .filter(|(_, block_config)| block_config.shows_on(monitor_index))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn urgent_color(config: &Config) -> u32 {
+    config.border_urgent.unwrap_or(config.scheme_selected.underline)
+}
+
+fn superscript(number: usize) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    number
+        .to_string()
+        .chars()
+        .filter_map(|digit| digit.to_digit(10))
+        .map(|digit| DIGITS[digit as usize])
+        .collect()
+}
+
+/// A 32-bit TrueColor visual, if a compositor is running to blend windows
+/// drawn with it. Without one the bar keeps the screen's default visual.
+fn find_argb_visual(
+    connection: &RustConnection,
+    display: *mut x11::xlib::Display,
+    screen_num: usize,
+) -> Result<Option<x11::xlib::XVisualInfo>, X11Error> {
+    let selection = format!("_NET_WM_CM_S{}", screen_num);
+    let atom = connection.intern_atom(false, selection.as_bytes())?.reply()?.atom;
+    if connection.get_selection_owner(atom)?.reply()?.owner == x11rb::NONE {
+        log::info!("No compositor running, drawing the bar opaque");
+        return Ok(None);
+    }
+
+    let mut visual_info: x11::xlib::XVisualInfo = unsafe { std::mem::zeroed() };
+    let found = unsafe {
+        x11::xlib::XMatchVisualInfo(display, screen_num as i32, 32, x11::xlib::TrueColor, &mut visual_info)
+    };
+    if found == 0 {
+        log::warn!("No 32-bit visual available, drawing the bar opaque");
+        return Ok(None);
+    }
+    Ok(Some(visual_info))
+}
+
+/// `color` with the bar's opacity, premultiplied as compositors expect.
+fn background_pixel(color: u32, alpha: f32) -> u32 {
+    let scale = |channel: u32| ((channel & 0xFF) as f32 * alpha).round() as u32;
+    (scale(0xFF) << 24) | (scale(color >> 16) << 16) | (scale(color >> 8) << 8) | scale(color)
+}
+
+fn opaque_pixel(color: u32, argb: bool) -> u32 {
+    if argb { 0xFF00_0000 | color } else { color }
+}
+
+fn fill_rectangle(
+    display: *mut x11::xlib::Display,
+    pixmap: x11::xlib::Pixmap,
+    color: u32,
+    (x, y): (i16, i16),
+    (width, height): (u16, u16),
+) {
+    unsafe {
+        let gc = x11::xlib::XCreateGC(display, pixmap, 0, std::ptr::null_mut());
+        x11::xlib::XSetForeground(display, gc, color as u64);
+        x11::xlib::XFillRectangle(display, pixmap, gc, x as i32, y as i32, width as u32, height as u32);
+        x11::xlib::XFreeGC(display, gc);
+    }
+}
+
+fn compute_tag_widths(tags: &[String], font: &Font, tag_padding: Option<u32>) -> Vec<u16> {
+    let horizontal_padding = tag_padding
+        .map(|padding| padding as u16)
+        .unwrap_or((font.height() as f32 * 0.4) as u16);
+
+    tags.iter()
+        .map(|tag| {
+            let text_width = font.text_width(tag);
+            text_width + (horizontal_padding * 2)
+        })
+        .collect()
+}
+
 impl Drop for Bar {
     fn drop(&mut self) {
         unsafe {
             x11::xlib::XFreePixmap(self.display, self.pixmap);
+            if let Some(colormap) = self.argb_colormap {
+                x11::xlib::XFreeColormap(self.display, colormap as x11::xlib::Colormap);
+            }
         }
     }
 }
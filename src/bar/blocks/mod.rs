@@ -1,20 +1,37 @@
 use crate::errors::BlockError;
+use crate::keyboard::{Arg, KeyAction};
 use std::time::Duration;
 
 mod battery;
 mod datetime;
+#[cfg(feature = "mpris")]
+mod now_playing;
 mod ram;
 mod shell;
+mod temperature;
+#[cfg(feature = "net-blocks")]
+mod weather;
 
 use battery::Battery;
 use datetime::DateTime;
+#[cfg(feature = "mpris")]
+use now_playing::NowPlaying;
 use ram::Ram;
 use shell::ShellBlock;
+use temperature::Temperature;
+#[cfg(feature = "net-blocks")]
+use weather::Weather;
 
 pub trait Block {
     fn content(&mut self) -> Result<String, BlockError>;
     fn interval(&self) -> Duration;
     fn color(&self) -> u32;
+
+    /// Collects work a block does in the background, returning true when
+    /// its content changed since the last call.
+    fn poll(&mut self) -> bool {
+        false
+    }
 }
 
 #[derive(Clone)]
@@ -24,11 +41,60 @@ pub struct BlockConfig {
     pub interval_secs: u64,
     pub color: u32,
     pub underline: bool,
+    pub monitor: Option<usize>,
+    /// Passed to click callbacks to tell blocks apart.
+    pub name: Option<String>,
+    pub click: Option<BlockClick>,
+}
+
+/// What clicking a status block does.
+#[derive(Clone)]
+pub enum BlockClick {
+    /// One action per button, see `button_name`.
+    Actions(Vec<(String, KeyAction, Arg)>),
+    /// A Lua function called with the click, which may return an action.
+    /// The Lua state is kept alive with it.
+    Callback(mlua::Lua, mlua::Function),
+}
+
+/// Names of the mouse buttons as used in block click handlers.
+pub const CLICK_BUTTONS: [&str; 5] = ["left", "middle", "right", "scroll_up", "scroll_down"];
+
+pub fn button_name(button: u8) -> Option<&'static str> {
+    CLICK_BUTTONS.get((button as usize).checked_sub(1)?).copied()
+}
+
+impl BlockClick {
+    /// The action for a click `x` pixels into the block named `name`.
+    pub fn action(&self, name: &str, button: &str, x: i16) -> mlua::Result<Option<(KeyAction, Arg)>> {
+        match self {
+            BlockClick::Actions(actions) => Ok(actions
+                .iter()
+                .find(|(action_button, _, _)| action_button == button)
+                .map(|(_, action, arg)| (*action, arg.clone()))),
+            BlockClick::Callback(lua, function) => {
+                let click = lua.create_table()?;
+                click.set("name", name)?;
+                click.set("button", button)?;
+                click.set("x", x)?;
+                match function.call::<mlua::Value>(click)? {
+                    mlua::Value::Nil => Ok(None),
+                    value => crate::config::parse_action_value(lua, value).map(Some),
+                }
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
 pub enum BlockCommand {
-    Shell(String),
+    Shell {
+        command: String,
+        /// Shown while the command runs past its interval.
+        busy_text: String,
+        /// Color of the block after the command exited non-zero.
+        error_color: u32,
+    },
     DateTime(String),
     Battery {
         format_charging: String,
@@ -36,17 +102,45 @@ pub enum BlockCommand {
         format_full: String,
     },
     Ram,
+    Temperature {
+        sensor: Option<String>,
+        critical: Option<f32>,
+        critical_color: u32,
+    },
     Static(String),
+    /// Current weather at `location` from wttr.in, in its `format` syntax
+    /// like "%c %t".
+    /// Artist and title of the active media player, drawn in
+    /// `paused_color` while it is paused.
+    #[cfg(feature = "mpris")]
+    NowPlaying {
+        paused_color: u32,
+    },
+    #[cfg(feature = "net-blocks")]
+    Weather {
+        location: String,
+        format: String,
+    },
 }
 
 impl BlockConfig {
+    pub fn shows_on(&self, monitor_index: usize) -> bool {
+        self.monitor.is_none_or(|monitor| monitor == monitor_index)
+    }
+
     pub fn to_block(&self) -> Box<dyn Block> {
         match &self.command {
-            BlockCommand::Shell(cmd) => Box::new(ShellBlock::new(
+            BlockCommand::Shell {
+                command,
+                busy_text,
+                error_color,
+            } => Box::new(ShellBlock::new(
                 &self.format,
-                cmd,
+                command,
                 self.interval_secs,
                 self.color,
+                busy_text,
+                *error_color,
             )),
             BlockCommand::DateTime(fmt) => Box::new(DateTime::new(
                 &self.format,
@@ -66,10 +160,34 @@ impl BlockConfig {
                 self.color,
             )),
             BlockCommand::Ram => Box::new(Ram::new(&self.format, self.interval_secs, self.color)),
+            BlockCommand::Temperature {
+                sensor,
+                critical,
+                critical_color,
+            } => Box::new(Temperature::new(
+                &self.format,
+                sensor.as_deref(),
+                *critical,
+                *critical_color,
+                self.interval_secs,
+                self.color,
+            )),
             BlockCommand::Static(text) => Box::new(StaticBlock::new(
                 &format!("{}{}", self.format, text),
                 self.color,
             )),
+            #[cfg(feature = "mpris")]
+            BlockCommand::NowPlaying { paused_color } => {
+                Box::new(NowPlaying::new(&self.format, self.color, *paused_color))
+            }
+            #[cfg(feature = "net-blocks")]
+            BlockCommand::Weather { location, format } => Box::new(Weather::new(
+                &self.format,
+                location,
+                format,
+                self.interval_secs,
+                self.color,
+            )),
         }
     }
 }
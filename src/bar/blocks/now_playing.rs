@@ -0,0 +1,72 @@
+use super::Block;
+use crate::errors::BlockError;
+use crate::media::mpris::{self, Track};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+/// Artist and title of the active MPRIS player. A background thread
+/// listens for track changes on the session bus, so the block updates as
+/// soon as the player changes instead of on an interval. Hidden while no
+/// player is running.
+pub struct NowPlaying {
+    format: String,
+    color: u32,
+    paused_color: u32,
+    track: Option<Track>,
+    updates: Receiver<Option<Track>>,
+}
+
+impl NowPlaying {
+    pub fn new(format: &str, color: u32, paused_color: u32) -> Self {
+        let (sender, updates) = mpsc::channel();
+        std::thread::spawn(move || {
+            if let Err(error) = mpris::watch(sender) {
+                log::warn!("Failed to watch media players: {}", error);
+            }
+        });
+
+        Self {
+            format: format.to_string(),
+            color,
+            paused_color,
+            track: None,
+            updates,
+        }
+    }
+}
+
+impl Block for NowPlaying {
+    fn content(&mut self) -> Result<String, BlockError> {
+        self.poll();
+
+        let Some(track) = &self.track else {
+            return Err(BlockError::CommandFailed("no media player".to_string()));
+        };
+        let text = match (track.artist.is_empty(), track.title.is_empty()) {
+            (_, true) => return Err(BlockError::CommandFailed("nothing playing".to_string())),
+            (true, false) => track.title.clone(),
+            (false, false) => format!("{} - {}", track.artist, track.title),
+        };
+        Ok(self.format.replace("{}", &text))
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(u64::MAX)
+    }
+
+    fn color(&self) -> u32 {
+        match &self.track {
+            Some(track) if !track.playing => self.paused_color,
+            _ => self.color,
+        }
+    }
+
+    fn poll(&mut self) -> bool {
+        let Some(track) = self.updates.try_iter().last() else {
+            return false;
+        };
+        let changed = self.track != track;
+        self.track = track;
+        changed
+    }
+}
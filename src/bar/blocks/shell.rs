@@ -1,43 +1,95 @@
 use super::Block;
 use crate::errors::BlockError;
 use std::process::Command;
-use std::time::Duration;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
 
+/// Runs a command through `sh` every interval and shows what it printed.
+///
+/// Commands run on a background thread so a slow one never stalls the
+/// bar. While a run takes longer than the interval the block shows
+/// `busy_text` instead of the previous output, and after a run exits
+/// non-zero it is drawn in `error_color`.
 pub struct ShellBlock {
     format: String,
     command: String,
     interval: Duration,
     color: u32,
+    busy_text: String,
+    error_color: u32,
+    text: Option<String>,
+    failed: bool,
+    last_started: Option<Instant>,
+    /// Output and whether the command succeeded, sent once it exits.
+    running: Option<Receiver<(String, bool)>>,
 }
 
 impl ShellBlock {
-    pub fn new(format: &str, command: &str, interval_secs: u64, color: u32) -> Self {
+    pub fn new(
+        format: &str,
+        command: &str,
+        interval_secs: u64,
+        color: u32,
+        busy_text: &str,
+        error_color: u32,
+    ) -> Self {
         Self {
             format: format.to_string(),
             command: command.to_string(),
             interval: Duration::from_secs(interval_secs),
             color,
+            busy_text: busy_text.to_string(),
+            error_color,
+            text: None,
+            failed: false,
+            last_started: None,
+            running: None,
         }
     }
+
+    fn start(&mut self) {
+        let (sender, receiver) = mpsc::channel();
+        let command = self.command.clone();
+        std::thread::spawn(move || {
+            let result = match Command::new("sh").arg("-c").arg(&command).output() {
+                Ok(output) => (
+                    String::from_utf8_lossy(&output.stdout).trim().to_string(),
+                    output.status.success(),
+                ),
+                Err(error) => {
+                    log::warn!("Failed to run block command '{}': {}", command, error);
+                    (String::new(), false)
+                }
+            };
+            let _ = sender.send(result);
+        });
+        self.running = Some(receiver);
+        self.last_started = Some(Instant::now());
+    }
+
+    fn is_overdue(&self) -> bool {
+        self.running.is_some()
+            && self
+                .last_started
+                .is_some_and(|started| started.elapsed() >= self.interval)
+    }
 }
 
 impl Block for ShellBlock {
     fn content(&mut self) -> Result<String, BlockError> {
-        let output = Command::new("sh")
-            .arg("-c")
-            .arg(&self.command)
-            .output()
-            .map_err(|e| BlockError::CommandFailed(format!("Failed to execute command: {}", e)))?;
-
-        if !output.status.success() {
-            return Err(BlockError::CommandFailed(format!(
-                "Command exited with status: {}",
-                output.status
-            )));
+        self.poll();
+
+        let due = self
+            .last_started
+            .is_none_or(|started| started.elapsed() >= self.interval);
+        if self.running.is_none() && due {
+            self.start();
         }
 
-        let result = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        Ok(self.format.replace("{}", &result))
+        match &self.text {
+            Some(text) if !self.is_overdue() => Ok(self.format.replace("{}", text)),
+            _ => Ok(self.busy_text.clone()),
+        }
     }
 
     fn interval(&self) -> Duration {
@@ -45,6 +97,30 @@ impl Block for ShellBlock {
     }
 
     fn color(&self) -> u32 {
-        self.color
+        if self.failed {
+            self.error_color
+        } else {
+            self.color
+        }
+    }
+
+    fn poll(&mut self) -> bool {
+        let Some(receiver) = &self.running else {
+            return false;
+        };
+        match receiver.try_recv() {
+            Ok((text, success)) => {
+                self.text = Some(text);
+                self.failed = !success;
+                self.running = None;
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+            Err(TryRecvError::Disconnected) => {
+                self.failed = true;
+                self.running = None;
+                true
+            }
+        }
     }
 }
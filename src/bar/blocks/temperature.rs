@@ -0,0 +1,99 @@
+use super::Block;
+use crate::errors::BlockError;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub struct Temperature {
+    format: String,
+    sensor: Option<String>,
+    critical: Option<f32>,
+    critical_color: u32,
+    interval: Duration,
+    color: u32,
+    last_celsius: f32,
+}
+
+impl Temperature {
+    pub fn new(
+        format: &str,
+        sensor: Option<&str>,
+        critical: Option<f32>,
+        critical_color: u32,
+        interval_secs: u64,
+        color: u32,
+    ) -> Self {
+        Self {
+            format: format.to_string(),
+            sensor: sensor.map(|s| s.to_string()),
+            critical,
+            critical_color,
+            interval: Duration::from_secs(interval_secs),
+            color,
+            last_celsius: 0.0,
+        }
+    }
+
+    fn find_sensor_path(&self) -> Result<PathBuf, BlockError> {
+        let mut hwmon_dirs: Vec<PathBuf> = fs::read_dir("/sys/class/hwmon")?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect();
+        hwmon_dirs.sort();
+
+        for dir in hwmon_dirs {
+            let input_path = dir.join("temp1_input");
+            if !input_path.exists() {
+                continue;
+            }
+
+            match &self.sensor {
+                Some(sensor) => {
+                    let name = fs::read_to_string(dir.join("name")).unwrap_or_default();
+                    if name.trim() == sensor {
+                        return Ok(input_path);
+                    }
+                }
+                None => return Ok(input_path),
+            }
+        }
+
+        Err(BlockError::MissingFile(match &self.sensor {
+            Some(sensor) => format!("/sys/class/hwmon/*/temp1_input for sensor '{}'", sensor),
+            None => "/sys/class/hwmon/*/temp1_input".to_string(),
+        }))
+    }
+
+    fn read_celsius(&self) -> Result<f32, BlockError> {
+        let path = self.find_sensor_path()?;
+        let millidegrees: i64 = fs::read_to_string(path)?.trim().parse()?;
+        Ok(millidegrees as f32 / 1000.0)
+    }
+}
+
+impl Block for Temperature {
+    fn content(&mut self) -> Result<String, BlockError> {
+        let celsius = self.read_celsius()?;
+        self.last_celsius = celsius;
+
+        let fahrenheit = celsius * 9.0 / 5.0 + 32.0;
+
+        let result = self
+            .format
+            .replace("{celsius}", &format!("{:.0}", celsius))
+            .replace("{fahrenheit}", &format!("{:.0}", fahrenheit))
+            .replace("{}", &format!("{:.0}", celsius));
+
+        Ok(result)
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        match self.critical {
+            Some(critical) if self.last_celsius >= critical => self.critical_color,
+            _ => self.color,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use super::Block;
+use crate::errors::BlockError;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Current weather from wttr.in, fetched on a background thread every
+/// interval. A failed fetch keeps showing the last successful result.
+pub struct Weather {
+    format: String,
+    location: String,
+    weather_format: String,
+    interval: Duration,
+    color: u32,
+    text: Option<String>,
+    last_started: Option<Instant>,
+    running: Option<Receiver<Result<String, String>>>,
+}
+
+impl Weather {
+    pub fn new(format: &str, location: &str, weather_format: &str, interval_secs: u64, color: u32) -> Self {
+        Self {
+            format: format.to_string(),
+            location: location.to_string(),
+            weather_format: weather_format.to_string(),
+            interval: Duration::from_secs(interval_secs),
+            color,
+            text: None,
+            last_started: None,
+            running: None,
+        }
+    }
+
+    fn start(&mut self) {
+        let (sender, receiver) = mpsc::channel();
+        let url = format!("https://wttr.in/{}", encode_location(&self.location));
+        let weather_format = self.weather_format.clone();
+        std::thread::spawn(move || {
+            let result = ureq::get(&url)
+                .timeout(TIMEOUT)
+                .query("format", &weather_format)
+                .call()
+                .map_err(|e| e.to_string())
+                .and_then(|response| response.into_string().map_err(|e| e.to_string()));
+            let _ = sender.send(result.map(|text| text.trim().to_string()));
+        });
+        self.running = Some(receiver);
+        self.last_started = Some(Instant::now());
+    }
+}
+
+/// `location` as a URL path segment: spaces become `+` as wttr.in writes
+/// them, and anything else outside the characters wttr.in uses in
+/// locations (`~` for landmarks, `@` for domains, `,` between coordinates)
+/// is percent-encoded byte by byte.
+fn encode_location(location: &str) -> String {
+    let mut encoded = String::with_capacity(location.len());
+    for byte in location.bytes() {
+        match byte {
+            b' ' => encoded.push('+'),
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b',' | b'@' | b':' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+impl Block for Weather {
+    fn content(&mut self) -> Result<String, BlockError> {
+        self.poll();
+
+        let due = self
+            .last_started
+            .is_none_or(|started| started.elapsed() >= self.interval);
+        if self.running.is_none() && due {
+            self.start();
+        }
+
+        match &self.text {
+            Some(text) => Ok(self.format.replace("{}", text)),
+            None => Err(BlockError::CommandFailed("weather not fetched yet".to_string())),
+        }
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+
+    fn poll(&mut self) -> bool {
+        let Some(receiver) = &self.running else {
+            return false;
+        };
+        match receiver.try_recv() {
+            Ok(Ok(text)) => {
+                self.running = None;
+                self.text = Some(text);
+                true
+            }
+            Ok(Err(error)) => {
+                log::warn!("Failed to fetch weather for '{}': {}", self.location, error);
+                self.running = None;
+                false
+            }
+            Err(TryRecvError::Empty) => false,
+            Err(TryRecvError::Disconnected) => {
+                self.running = None;
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_locations_are_kept() {
+        assert_eq!(encode_location("Berlin"), "Berlin");
+        assert_eq!(encode_location("~Eiffel tower"), "~Eiffel+tower");
+        assert_eq!(encode_location("-78.46,106.79"), "-78.46,106.79");
+        assert_eq!(encode_location("@github.com"), "@github.com");
+    }
+
+    #[test]
+    fn url_delimiters_are_encoded() {
+        assert_eq!(encode_location("a&b?c#d/e"), "a%26b%3Fc%23d%2Fe");
+        assert_eq!(encode_location("100%"), "100%25");
+        assert_eq!(encode_location("x+y"), "x%2By");
+    }
+
+    #[test]
+    fn non_ascii_is_encoded_as_utf8() {
+        assert_eq!(encode_location("Zürich"), "Z%C3%BCrich");
+        assert_eq!(encode_location("東京"), "%E6%9D%B1%E4%BA%AC");
+    }
+}
@@ -5,59 +5,126 @@ use x11::xrender::XRenderColor;
 
 use crate::errors::X11Error;
 
+/// A primary Xft font and the fallbacks tried, in order, for characters
+/// it has no glyph for. Text is measured and drawn in runs of characters
+/// that share a font, and the line metrics cover every loaded font so
+/// glyphs from a taller fallback are not clipped.
 pub struct Font {
-    xft_font: *mut XftFont,
+    xft_fonts: Vec<*mut XftFont>,
     display: *mut Display,
 }
 
-impl Font {
-    pub fn new(display: *mut Display, screen: i32, font_name: &str) -> Result<Self, X11Error> {
-        let font_name_cstr =
-            CString::new(font_name).map_err(|_| X11Error::FontLoadFailed(font_name.to_string()))?;
+fn open_font(display: *mut Display, screen: i32, font_name: &str) -> Result<*mut XftFont, X11Error> {
+    let font_name_cstr =
+        CString::new(font_name).map_err(|_| X11Error::FontLoadFailed(font_name.to_string()))?;
+
+    let xft_font = unsafe { XftFontOpenName(display, screen, font_name_cstr.as_ptr()) };
 
-        let xft_font = unsafe { XftFontOpenName(display, screen, font_name_cstr.as_ptr()) };
+    if xft_font.is_null() {
+        return Err(X11Error::FontLoadFailed(font_name.to_string()));
+    }
 
-        if xft_font.is_null() {
-            return Err(X11Error::FontLoadFailed(font_name.to_string()));
+    Ok(xft_font)
+}
+
+impl Font {
+    /// Opens `font_name` and each of `fallbacks`. Only the primary font has
+    /// to load; a fallback that fails is logged and left out.
+    pub fn new(
+        display: *mut Display,
+        screen: i32,
+        font_name: &str,
+        fallbacks: &[String],
+    ) -> Result<Self, X11Error> {
+        let mut xft_fonts = vec![open_font(display, screen, font_name)?];
+        for fallback in fallbacks {
+            match open_font(display, screen, fallback) {
+                Ok(xft_font) => xft_fonts.push(xft_font),
+                Err(error) => log::warn!("Skipping fallback font: {}", error),
+            }
         }
 
-        Ok(Font { xft_font, display })
+        Ok(Font { xft_fonts, display })
     }
 
     pub fn height(&self) -> u16 {
-        unsafe {
-            let font = &*self.xft_font;
-            font.height as u16
-        }
+        let (height, descent) = self
+            .xft_fonts
+            .iter()
+            .map(|&xft_font| unsafe { ((*xft_font).height, (*xft_font).descent) })
+            .fold((0, 0), |(height, descent), (font_height, font_descent)| {
+                (height.max(font_height), descent.max(font_descent))
+            });
+        height.max(self.ascent() as i32 + descent) as u16
     }
 
     pub fn ascent(&self) -> i16 {
-        unsafe {
-            let font = &*self.xft_font;
-            font.ascent as i16
-        }
+        self.xft_fonts
+            .iter()
+            .map(|&xft_font| unsafe { (*xft_font).ascent })
+            .max()
+            .unwrap_or(0) as i16
     }
 
     pub fn text_width(&self, text: &str) -> u16 {
+        self.runs(text)
+            .into_iter()
+            .map(|(xft_font, run)| self.run_width(xft_font, run))
+            .sum()
+    }
+
+    fn run_width(&self, xft_font: *mut XftFont, run: &str) -> u16 {
         unsafe {
             let mut extents = std::mem::zeroed();
             x11::xft::XftTextExtentsUtf8(
                 self.display,
-                self.xft_font,
-                text.as_ptr(),
-                text.len() as i32,
+                xft_font,
+                run.as_ptr(),
+                run.len() as i32,
                 &mut extents,
             );
-            extents.width
+            extents.xOff as u16
         }
     }
+
+    /// The first font with a glyph for `character`, or the primary font
+    /// when none has one so it draws its missing-glyph box.
+    fn font_for(&self, character: char) -> *mut XftFont {
+        self.xft_fonts
+            .iter()
+            .copied()
+            .find(|&xft_font| unsafe { x11::xft::XftCharExists(self.display, xft_font, character as u32) != 0 })
+            .unwrap_or(self.xft_fonts[0])
+    }
+
+    /// Splits `text` into the longest runs that draw with a single font.
+    fn runs<'a>(&self, text: &'a str) -> Vec<(*mut XftFont, &'a str)> {
+        let mut runs: Vec<(*mut XftFont, &'a str)> = Vec::new();
+        let mut run_start = 0;
+        let mut run_font = None;
+
+        for (index, character) in text.char_indices() {
+            let xft_font = self.font_for(character);
+            if let Some(current) = run_font
+                && current != xft_font
+            {
+                runs.push((current, &text[run_start..index]));
+                run_start = index;
+            }
+            run_font = Some(xft_font);
+        }
+        if let Some(current) = run_font {
+            runs.push((current, &text[run_start..]));
+        }
+        runs
+    }
 }
 
 impl Drop for Font {
     fn drop(&mut self) {
-        unsafe {
-            if !self.xft_font.is_null() {
-                x11::xft::XftFontClose(self.display, self.xft_font);
+        for &xft_font in &self.xft_fonts {
+            unsafe {
+                x11::xft::XftFontClose(self.display, xft_font);
             }
         }
     }
@@ -106,15 +173,19 @@ impl FontDraw {
                 &mut xft_color,
             );
 
-            XftDrawStringUtf8(
-                self.xft_draw,
-                &xft_color,
-                font.xft_font,
-                x as i32,
-                y as i32,
-                text.as_ptr(),
-                text.len() as i32,
-            );
+            let mut run_x = x as i32;
+            for (xft_font, run) in font.runs(text) {
+                XftDrawStringUtf8(
+                    self.xft_draw,
+                    &xft_color,
+                    xft_font,
+                    run_x,
+                    y as i32,
+                    run.as_ptr(),
+                    run.len() as i32,
+                );
+                run_x += font.run_width(xft_font, run) as i32;
+            }
 
             x11::xft::XftColorFree(
                 x11::xft::XftDrawDisplay(self.xft_draw),
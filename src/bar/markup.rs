@@ -0,0 +1,160 @@
+/// A run of block text drawn with the same colors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub text: String,
+    /// Text color, the block's color when None.
+    pub foreground: Option<u32>,
+    /// Color filled behind the text, none when None.
+    pub background: Option<u32>,
+}
+
+/// Splits block output into colored segments.
+///
+/// `^fg(#rrggbb)` and `^bg(#rrggbb)` change the text and background color
+/// of what follows, `^fg()` and `^bg()` go back to the block's own, and
+/// `^^` is a literal caret. Anything else starting with a caret is kept as
+/// text, so output that was never meant as markup draws unchanged.
+pub fn parse(text: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut foreground = None;
+    let mut background = None;
+    let mut rest = text;
+
+    while let Some(caret) = rest.find('^') {
+        current.push_str(&rest[..caret]);
+        rest = &rest[caret..];
+
+        if let Some(after) = rest.strip_prefix("^^") {
+            current.push('^');
+            rest = after;
+            continue;
+        }
+
+        let Some((command, color, after)) = parse_command(rest) else {
+            current.push('^');
+            rest = &rest[1..];
+            continue;
+        };
+
+        if !current.is_empty() {
+            segments.push(Segment {
+                text: std::mem::take(&mut current),
+                foreground,
+                background,
+            });
+        }
+        match command {
+            "fg" => foreground = color,
+            _ => background = color,
+        }
+        rest = after;
+    }
+
+    current.push_str(rest);
+    if !current.is_empty() {
+        segments.push(Segment {
+            text: current,
+            foreground,
+            background,
+        });
+    }
+    segments
+}
+
+/// Reads `^fg(...)` or `^bg(...)` at the start of `text`, returning the
+/// command, its color (None to reset) and the text after it.
+fn parse_command(text: &str) -> Option<(&str, Option<u32>, &str)> {
+    let command = ["fg", "bg"]
+        .into_iter()
+        .find(|command| text[1..].starts_with(&format!("{}(", command)))?;
+    let start = 1 + command.len() + 1;
+    let end = start + text[start..].find(')')?;
+
+    let argument = text[start..end].trim();
+    let color = if argument.is_empty() {
+        None
+    } else {
+        Some(u32::from_str_radix(argument.trim_start_matches('#'), 16).ok()?)
+    };
+    Some((command, color, &text[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, foreground: Option<u32>, background: Option<u32>) -> Segment {
+        Segment {
+            text: text.to_string(),
+            foreground,
+            background,
+        }
+    }
+
+    #[test]
+    fn plain_text_is_one_segment() {
+        assert_eq!(parse("cpu 12%"), [segment("cpu 12%", None, None)]);
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn colors_apply_to_what_follows() {
+        assert_eq!(
+            parse("a ^fg(#ff0000)b^fg() c"),
+            [
+                segment("a ", None, None),
+                segment("b", Some(0xff0000), None),
+                segment(" c", None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_colors_combine_and_reset_separately() {
+        assert_eq!(
+            parse("^bg(#000000)x^fg(#00ff00)y^bg()z^fg()w"),
+            [
+                segment("x", None, Some(0x000000)),
+                segment("y", Some(0x00ff00), Some(0x000000)),
+                segment("z", Some(0x00ff00), None),
+                segment("w", None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn inner_color_replaces_outer_until_reset() {
+        assert_eq!(
+            parse("^fg(#111111)a^fg(#222222)b^fg()c"),
+            [
+                segment("a", Some(0x111111), None),
+                segment("b", Some(0x222222), None),
+                segment("c", None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_commands_stay_text() {
+        assert_eq!(parse("^fg(#ff0000 text"), [segment("^fg(#ff0000 text", None, None)]);
+        assert_eq!(parse("ends with ^"), [segment("ends with ^", None, None)]);
+    }
+
+    #[test]
+    fn invalid_colors_and_unknown_commands_stay_text() {
+        assert_eq!(parse("^fg(#zzzzzz)a"), [segment("^fg(#zzzzzz)a", None, None)]);
+        assert_eq!(parse("^ul(1)a"), [segment("^ul(1)a", None, None)]);
+    }
+
+    #[test]
+    fn doubled_caret_is_a_literal_caret() {
+        assert_eq!(parse("2^^8"), [segment("2^8", None, None)]);
+        assert_eq!(parse("^^fg(#ff0000)"), [segment("^fg(#ff0000)", None, None)]);
+    }
+
+    #[test]
+    fn angle_brackets_are_not_markup() {
+        assert_eq!(parse("<b>a</b> < 5"), [segment("<b>a</b> < 5", None, None)]);
+    }
+}
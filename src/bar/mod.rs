@@ -1,12 +1,12 @@
 mod bar;
 mod blocks;
 pub mod font;
+pub mod markup;
 
-pub use bar::Bar;
-pub use blocks::{BlockCommand, BlockConfig};
+pub use bar::{Bar, BarClick};
+pub use blocks::{BlockClick, BlockCommand, BlockConfig, CLICK_BUTTONS, button_name};
 
-// Bar position (for future use)
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BarPosition {
     Top,
     Bottom,
@@ -2,8 +2,11 @@ use std::path::PathBuf;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let arguments: Vec<String> = std::env::args().collect();
+    oxwm::logging::init();
 
     let mut custom_config_path: Option<PathBuf> = None;
+    let mut test_display: Option<String> = None;
+    let mut test_mode = false;
 
     match arguments.get(1).map(|string| string.as_str()) {
         Some("--version") => {
@@ -18,6 +21,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             init_config()?;
             return Ok(());
         }
+        Some("--doctor") => {
+            let config_path = match arguments.get(2).map(|string| string.as_str()) {
+                Some("--config") => match arguments.get(3) {
+                    Some(path) => PathBuf::from(path),
+                    None => {
+                        eprintln!("Error: --config requires a path argument");
+                        std::process::exit(1);
+                    }
+                },
+                _ => get_config_path().join("config.lua"),
+            };
+            if !oxwm::doctor::run(&config_path) {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
         Some("--config") => {
             if let Some(path) = arguments.get(2) {
                 custom_config_path = Some(PathBuf::from(path));
@@ -26,15 +45,42 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 std::process::exit(1);
             }
         }
+        Some("--test") => {
+            test_mode = true;
+            let mut options = arguments[2..].iter();
+            while let Some(option) = options.next() {
+                let Some(value) = options.next() else {
+                    eprintln!("Error: {} requires an argument", option);
+                    std::process::exit(1);
+                };
+                match option.as_str() {
+                    "--display" => test_display = Some(value.clone()),
+                    "--config" => custom_config_path = Some(PathBuf::from(value)),
+                    _ => {
+                        eprintln!("Error: unknown option {} for --test", option);
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
         _ => {}
     }
 
-    let (config, had_broken_config) = load_config(custom_config_path)?;
+    if let Some(display) = &test_display {
+        // Programs spawned from the config should open on the test display
+        // too. Nothing else is running yet.
+        unsafe { std::env::set_var("DISPLAY", display) };
+    }
+    oxwm::reaper::adopt();
+
+    let (config, config_error) = load_config(custom_config_path)?;
+    oxwm::logging::set_file(config.log_file);
 
-    let mut window_manager = oxwm::window_manager::WindowManager::new(config)?;
+    let mut window_manager =
+        oxwm::window_manager::WindowManager::with_display(config, test_display.as_deref(), test_mode)?;
 
-    if had_broken_config {
-        window_manager.show_migration_overlay();
+    if let Some(error) = config_error {
+        window_manager.show_migration_overlay(&error);
     }
 
     let should_restart = window_manager.run()?;
@@ -43,14 +89,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     if should_restart {
         use std::os::unix::process::CommandExt;
-        let error = std::process::Command::new(&arguments[0]).args(&arguments[1..]).exec();
-        eprintln!("Failed to restart: {}", error);
+        let mut command = std::process::Command::new(&arguments[0]);
+        oxwm::reaper::hand_over(&mut command);
+        let error = command.args(&arguments[1..]).exec();
+        log::error!("Failed to restart: {}", error);
     }
 
     Ok(())
 }
 
-fn load_config(custom_path: Option<PathBuf>) -> Result<(oxwm::Config, bool), Box<dyn std::error::Error>> {
+fn load_config(custom_path: Option<PathBuf>) -> Result<(oxwm::Config, Option<String>), Box<dyn std::error::Error>> {
     let config_path = if let Some(path) = custom_path {
         path
     } else {
@@ -80,15 +128,15 @@ fn load_config(custom_path: Option<PathBuf>) -> Result<(oxwm::Config, bool), Box
     let config_string = std::fs::read_to_string(&config_path)
         .map_err(|error| format!("Failed to read config file: {}", error))?;
 
-    let config_directory = config_path.parent();
-
-    match oxwm::config::parse_lua_config(&config_string, config_directory) {
-        Ok(config) => Ok((config, false)),
-        Err(_error) => {
+    match oxwm::config::parse_lua_config(&config_string, Some(&config_path), Default::default()) {
+        Ok(config) => Ok((config, None)),
+        Err(error) => {
             let template = include_str!("../../templates/config.lua");
-            let config = oxwm::config::parse_lua_config(template, None)
+            let mut config = oxwm::config::parse_lua_config(template, None, Default::default())
                 .map_err(|error| format!("Failed to parse default template config: {}", error))?;
-            Ok((config, true))
+            // Keep reloading the user's file once they have fixed it.
+            config.path = Some(config_path);
+            Ok((config, Some(error.to_string())))
         }
     }
 }
@@ -121,6 +169,10 @@ fn print_help() {
     println!("OPTIONS:");
     println!("    --init              Create default config in ~/.config/oxwm/config.lua");
     println!("    --config <PATH>     Use custom config file");
+    println!("    --doctor            Check the environment and config for common problems");
+    println!("    --test [--display <DISPLAY>] [--config <PATH>]");
+    println!("                        Run on another display, e.g. a nested Xephyr, even if");
+    println!("                        a window manager is already running there");
     println!("    --version           Print version information");
     println!("    --help              Print this help message\n");
     println!("CONFIG:");
@@ -128,6 +180,9 @@ fn print_help() {
     println!("    Edit the config file and use Mod+Shift+R to reload");
     println!("    No compilation needed - instant hot-reload!");
     println!("    LSP support included with oxwm.lua type definitions\n");
+    println!("TESTING:");
+    println!("    Xephyr -br -ac -noreset -screen 1280x720 :1 &");
+    println!("    oxwm --test --display :1 --config ./config.lua\n");
     println!("FIRST RUN:");
     println!("    Run 'oxwm --init' to create a config file");
     println!("    Or just start oxwm and it will create one automatically\n");
@@ -0,0 +1,281 @@
+use x11rb::protocol::xproto::Window;
+
+/// The clients of a monitor, kept both in tiling order (the order layouts
+/// arrange them in) and in focus order (most recently focused first).
+#[derive(Debug, Clone, Default)]
+pub struct ClientList {
+    tile_order: Vec<Window>,
+    focus_order: Vec<Window>,
+}
+
+impl ClientList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tile_order.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tile_order.len()
+    }
+
+    pub fn contains(&self, window: Window) -> bool {
+        self.tile_order.contains(&window)
+    }
+
+    /// Clients in tiling order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Window> + '_ {
+        self.tile_order.iter().copied()
+    }
+
+    /// Clients in focus order, most recently focused first.
+    pub fn stack(&self) -> impl DoubleEndedIterator<Item = Window> + '_ {
+        self.focus_order.iter().copied()
+    }
+
+    pub fn attach(&mut self, window: Window) {
+        self.detach(window);
+        self.tile_order.insert(0, window);
+    }
+
+    /// Inserts `window` right after `after` in tiling order, or at the front
+    /// if `after` is not in the list.
+    pub fn attach_after(&mut self, window: Window, after: Window) {
+        self.detach(window);
+        match self.tile_order.iter().position(|&w| w == after) {
+            Some(index) => self.tile_order.insert(index + 1, window),
+            None => self.tile_order.insert(0, window),
+        }
+    }
+
+    /// Inserts `window` right before `before` in tiling order, or at the
+    /// front if `before` is not in the list.
+    pub fn attach_before(&mut self, window: Window, before: Window) {
+        self.detach(window);
+        let index = self.tile_order.iter().position(|&w| w == before).unwrap_or(0);
+        self.tile_order.insert(index, window);
+    }
+
+    pub fn attach_bottom(&mut self, window: Window) {
+        self.detach(window);
+        self.tile_order.push(window);
+    }
+
+    pub fn detach(&mut self, window: Window) {
+        self.tile_order.retain(|&w| w != window);
+    }
+
+    pub fn attach_stack(&mut self, window: Window) {
+        self.detach_stack(window);
+        self.focus_order.insert(0, window);
+    }
+
+    pub fn detach_stack(&mut self, window: Window) {
+        self.focus_order.retain(|&w| w != window);
+    }
+
+    /// Swaps the tiling positions of two clients. Does nothing if either is
+    /// not in the list.
+    pub fn swap(&mut self, first: Window, second: Window) {
+        let first_index = self.tile_order.iter().position(|&w| w == first);
+        let second_index = self.tile_order.iter().position(|&w| w == second);
+        if let (Some(first_index), Some(second_index)) = (first_index, second_index) {
+            self.tile_order.swap(first_index, second_index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A list whose tiling order is `windows`, attached bottom to top.
+    fn list_of(windows: &[Window]) -> ClientList {
+        let mut list = ClientList::new();
+        for &window in windows {
+            list.attach_bottom(window);
+        }
+        list
+    }
+
+    fn tiled(list: &ClientList) -> Vec<Window> {
+        list.iter().collect()
+    }
+
+    fn stacked(list: &ClientList) -> Vec<Window> {
+        list.stack().collect()
+    }
+
+    #[test]
+    fn attach_puts_the_window_first() {
+        let mut list = list_of(&[1, 2]);
+        list.attach(3);
+        assert_eq!(tiled(&list), [3, 1, 2]);
+    }
+
+    #[test]
+    fn attach_moves_a_window_already_in_the_list() {
+        let mut list = list_of(&[1, 2, 3]);
+        list.attach(3);
+        assert_eq!(tiled(&list), [3, 1, 2]);
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn attach_after_inserts_behind_the_given_window() {
+        let mut list = list_of(&[1, 2, 3]);
+        list.attach_after(4, 2);
+        assert_eq!(tiled(&list), [1, 2, 4, 3]);
+
+        list.attach_after(5, 3);
+        assert_eq!(tiled(&list), [1, 2, 4, 3, 5]);
+    }
+
+    #[test]
+    fn attach_after_a_missing_window_attaches_first() {
+        let mut list = list_of(&[1, 2]);
+        list.attach_after(3, 9);
+        assert_eq!(tiled(&list), [3, 1, 2]);
+    }
+
+    #[test]
+    fn attach_before_inserts_in_front_of_the_given_window() {
+        let mut list = list_of(&[1, 2, 3]);
+        list.attach_before(4, 3);
+        assert_eq!(tiled(&list), [1, 2, 4, 3]);
+
+        list.attach_before(5, 1);
+        assert_eq!(tiled(&list), [5, 1, 2, 4, 3]);
+    }
+
+    #[test]
+    fn attach_before_a_missing_window_attaches_first() {
+        let mut list = list_of(&[1, 2]);
+        list.attach_before(3, 9);
+        assert_eq!(tiled(&list), [3, 1, 2]);
+    }
+
+    #[test]
+    fn attach_before_moves_a_window_already_in_the_list() {
+        let mut list = list_of(&[1, 2, 3, 4]);
+        list.attach_before(4, 2);
+        assert_eq!(tiled(&list), [1, 4, 2, 3]);
+
+        list.attach_before(1, 3);
+        assert_eq!(tiled(&list), [4, 2, 1, 3]);
+        assert_eq!(list.len(), 4);
+    }
+
+    #[test]
+    fn attach_before_a_missing_window_moves_a_listed_window_first() {
+        let mut list = list_of(&[1, 2, 3]);
+        list.attach_before(3, 9);
+        assert_eq!(tiled(&list), [3, 1, 2]);
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn attach_after_moves_a_window_already_in_the_list() {
+        let mut list = list_of(&[1, 2, 3, 4]);
+        list.attach_after(1, 3);
+        assert_eq!(tiled(&list), [2, 3, 1, 4]);
+        assert_eq!(list.len(), 4);
+    }
+
+    #[test]
+    fn attach_bottom_appends() {
+        let mut list = list_of(&[1, 2]);
+        list.attach_bottom(3);
+        assert_eq!(tiled(&list), [1, 2, 3]);
+    }
+
+    #[test]
+    fn attach_bottom_moves_a_window_already_in_the_list() {
+        let mut list = list_of(&[1, 2, 3]);
+        list.attach_stack(1);
+        list.attach_bottom(1);
+        assert_eq!(tiled(&list), [2, 3, 1]);
+        assert_eq!(list.len(), 3);
+        assert_eq!(stacked(&list), [1]);
+    }
+
+    #[test]
+    fn detach_removes_head_middle_and_tail() {
+        let mut list = list_of(&[1, 2, 3, 4, 5]);
+
+        list.detach(1);
+        assert_eq!(tiled(&list), [2, 3, 4, 5]);
+
+        list.detach(3);
+        assert_eq!(tiled(&list), [2, 4, 5]);
+
+        list.detach(5);
+        assert_eq!(tiled(&list), [2, 4]);
+        assert!(!list.contains(5));
+    }
+
+    #[test]
+    fn detach_of_a_missing_window_changes_nothing() {
+        let mut list = list_of(&[1, 2]);
+        list.detach(9);
+        assert_eq!(tiled(&list), [1, 2]);
+    }
+
+    #[test]
+    fn detach_leaves_the_focus_order_alone() {
+        let mut list = list_of(&[1, 2]);
+        list.attach_stack(1);
+        list.detach(1);
+        assert_eq!(tiled(&list), [2]);
+        assert_eq!(stacked(&list), [1]);
+    }
+
+    #[test]
+    fn attach_stack_keeps_most_recently_focused_first() {
+        let mut list = list_of(&[1, 2, 3]);
+        list.attach_stack(1);
+        list.attach_stack(2);
+        list.attach_stack(3);
+        assert_eq!(stacked(&list), [3, 2, 1]);
+
+        list.attach_stack(1);
+        assert_eq!(stacked(&list), [1, 3, 2]);
+        assert_eq!(tiled(&list), [1, 2, 3]);
+    }
+
+    #[test]
+    fn detach_stack_removes_from_the_focus_order_only() {
+        let mut list = list_of(&[1, 2, 3]);
+        for window in [1, 2, 3] {
+            list.attach_stack(window);
+        }
+
+        list.detach_stack(2);
+        assert_eq!(stacked(&list), [3, 1]);
+        assert_eq!(tiled(&list), [1, 2, 3]);
+    }
+
+    #[test]
+    fn swap_neighbours() {
+        let mut list = list_of(&[1, 2, 3]);
+        list.swap(1, 2);
+        assert_eq!(tiled(&list), [2, 1, 3]);
+    }
+
+    #[test]
+    fn swap_non_neighbours() {
+        let mut list = list_of(&[1, 2, 3, 4]);
+        list.swap(4, 1);
+        assert_eq!(tiled(&list), [4, 2, 3, 1]);
+    }
+
+    #[test]
+    fn swap_with_a_missing_window_does_nothing() {
+        let mut list = list_of(&[1, 2, 3]);
+        list.swap(1, 9);
+        list.swap(9, 3);
+        assert_eq!(tiled(&list), [1, 2, 3]);
+    }
+}
@@ -1,20 +1,57 @@
-use x11rb::protocol::xproto::Window;
+mod list;
+
+pub use list::ClientList;
+
+use crate::backend::WindowOps;
+use x11rb::protocol::xproto::{Colormap, Window};
 
 pub type TagMask = u32;
 
+/// Hidden tag that stashed windows are moved to. It is never viewed.
+pub const STASH_TAG: TagMask = 1 << (TagMask::BITS - 1);
+
+/// Number of tags that fit in a `TagMask` next to the stash tag.
+pub const MAX_TAGS: usize = TagMask::BITS as usize - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowType {
+    Normal,
+    Dialog,
+    Utility,
+    Toolbar,
+    Splash,
+    Notification,
+}
+
+impl WindowType {
+    /// Splash screens and notifications are shown but never take focus.
+    pub fn takes_focus(&self) -> bool {
+        !matches!(self, WindowType::Splash | WindowType::Notification)
+    }
+}
+
+/// Position, size and border width of a client, as last configured.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Geometry {
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+    pub border_width: u16,
+}
+
 #[derive(Debug, Clone)]
 pub struct Client {
     pub name: String,
+    /// WM_CLASS class and instance, read when the window is managed.
+    pub class: String,
+    pub instance: String,
     pub min_aspect: f32,
     pub max_aspect: f32,
     pub x_position: i16,
     pub y_position: i16,
     pub width: u16,
     pub height: u16,
-    pub old_x_position: i16,
-    pub old_y_position: i16,
-    pub old_width: u16,
-    pub old_height: u16,
     pub base_width: i32,
     pub base_height: i32,
     pub increment_width: i32,
@@ -25,16 +62,29 @@ pub struct Client {
     pub min_height: i32,
     pub hints_valid: bool,
     pub border_width: u16,
-    pub old_border_width: u16,
     pub tags: TagMask,
     pub is_fixed: bool,
     pub is_floating: bool,
     pub is_urgent: bool,
     pub never_focus: bool,
-    pub old_state: bool,
+    /// Leader of the application's window group, from WM_HINTS.
+    pub window_group: Option<Window>,
+    /// Where a window rule wants the client attached, instead of the
+    /// configured default.
+    pub attach_position: Option<crate::AttachPosition>,
+    /// Geometry and floating state from before fullscreen, put back by
+    /// `restore_geometry`.
+    saved_geometry: Option<(Geometry, bool)>,
     pub is_fullscreen: bool,
-    pub next: Option<Window>,
-    pub stack_next: Option<Window>,
+    /// Whether we set _NET_WM_BYPASS_COMPOSITOR on it while fullscreen and
+    /// have to remove it again.
+    pub bypassing_compositor: bool,
+    /// Geometry to restore when a maximized client is unmaximized.
+    pub pre_maximize_geometry: Option<Geometry>,
+    pub opacity: f32,
+    pub window_type: WindowType,
+    pub colormap: Colormap,
+    pub depth: u8,
     pub monitor_index: usize,
     pub window: Window,
 }
@@ -43,16 +93,14 @@ impl Client {
     pub fn new(window: Window, monitor_index: usize, tags: TagMask) -> Self {
         Self {
             name: String::new(),
+            class: String::new(),
+            instance: String::new(),
             min_aspect: 0.0,
             max_aspect: 0.0,
             x_position: 0,
             y_position: 0,
             width: 0,
             height: 0,
-            old_x_position: 0,
-            old_y_position: 0,
-            old_width: 0,
-            old_height: 0,
             base_width: 0,
             base_height: 0,
             increment_width: 0,
@@ -63,16 +111,21 @@ impl Client {
             min_height: 0,
             hints_valid: false,
             border_width: 0,
-            old_border_width: 0,
             tags,
             is_fixed: false,
             is_floating: false,
             is_urgent: false,
             never_focus: false,
-            old_state: false,
+            window_group: None,
+            attach_position: None,
+            saved_geometry: None,
             is_fullscreen: false,
-            next: None,
-            stack_next: None,
+            bypassing_compositor: false,
+            pre_maximize_geometry: None,
+            opacity: 1.0,
+            window_type: WindowType::Normal,
+            colormap: x11rb::NONE,
+            depth: 0,
             monitor_index,
             window,
         }
@@ -85,4 +138,117 @@ impl Client {
     pub fn height_with_border(&self) -> u16 {
         self.height.saturating_add(2 * self.border_width)
     }
+
+    pub fn geometry(&self) -> Geometry {
+        Geometry {
+            x: self.x_position,
+            y: self.y_position,
+            width: self.width,
+            height: self.height,
+            border_width: self.border_width,
+        }
+    }
+
+    pub fn set_geometry(&mut self, geometry: Geometry) {
+        self.x_position = geometry.x;
+        self.y_position = geometry.y;
+        self.width = geometry.width;
+        self.height = geometry.height;
+        self.border_width = geometry.border_width;
+    }
+
+    /// Moves and resizes the client to `geometry`, skipping the request
+    /// when it is there already so rearranging only touches clients that
+    /// move. Returns whether a request was sent.
+    pub fn configure<B: WindowOps>(&mut self, backend: &B, geometry: Geometry) -> Result<bool, B::Error> {
+        if self.geometry() == geometry {
+            return Ok(false);
+        }
+
+        self.set_geometry(geometry);
+        backend.configure(
+            self.window,
+            geometry.x as i32,
+            geometry.y as i32,
+            geometry.width as u32,
+            geometry.height as u32,
+            geometry.border_width as u32,
+        )?;
+        Ok(true)
+    }
+
+    /// Remembers the current geometry and floating state, replacing
+    /// anything saved before.
+    pub fn save_geometry(&mut self) {
+        self.saved_geometry = Some((self.geometry(), self.is_floating));
+    }
+
+    /// Puts back what `save_geometry` remembered and returns the geometry,
+    /// or None with the client left as it is if nothing was saved.
+    pub fn restore_geometry(&mut self) -> Option<Geometry> {
+        let (geometry, was_floating) = self.saved_geometry.take()?;
+        self.set_geometry(geometry);
+        self.is_floating = was_floating;
+        Some(geometry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::{MockBackend, Request};
+
+    const GEOMETRY: Geometry = Geometry {
+        x: 10,
+        y: 20,
+        width: 300,
+        height: 200,
+        border_width: 2,
+    };
+
+    #[test]
+    fn configure_sends_new_geometry() {
+        let backend = MockBackend::default();
+        let mut client = Client::new(7, 0, 1);
+
+        assert!(client.configure(&backend, GEOMETRY).unwrap());
+        assert_eq!(client.geometry(), GEOMETRY);
+        assert_eq!(
+            backend.take_requests(),
+            vec![Request::Configure {
+                window: 7,
+                x: 10,
+                y: 20,
+                width: 300,
+                height: 200,
+                border_width: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn configure_skips_unchanged_geometry() {
+        let backend = MockBackend::default();
+        let mut client = Client::new(7, 0, 1);
+        client.configure(&backend, GEOMETRY).unwrap();
+        backend.take_requests();
+
+        assert!(!client.configure(&backend, GEOMETRY).unwrap());
+        assert!(backend.take_requests().is_empty());
+    }
+
+    #[test]
+    fn configure_sends_border_width_change() {
+        let backend = MockBackend::default();
+        let mut client = Client::new(7, 0, 1);
+        client.configure(&backend, GEOMETRY).unwrap();
+        backend.take_requests();
+
+        let borderless = Geometry {
+            border_width: 0,
+            ..GEOMETRY
+        };
+        assert!(client.configure(&backend, borderless).unwrap());
+        assert_eq!(backend.take_requests().len(), 1);
+    }
 }
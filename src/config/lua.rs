@@ -1,52 +1,133 @@
 use crate::errors::ConfigError;
-use mlua::Lua;
+use mlua::{Lua, Table};
+use std::path::Path;
 
-use super::lua_api;
+use super::{RuntimeState, lua_api};
 
+/// Parses a Lua config. `config_path` is the file `input` was read from; it
+/// names the chunk in error messages and lets the config `require` modules
+/// next to it, e.g. `require("keys")` for `keys.lua` or `lib/keys.lua`.
+/// `state` is what `oxwm.state` reports, empty unless reloading.
 pub fn parse_lua_config(
     input: &str,
-    config_dir: Option<&std::path::Path>,
+    config_path: Option<&Path>,
+    state: RuntimeState,
 ) -> Result<crate::Config, ConfigError> {
     let lua = Lua::new();
 
-    if let Some(dir) = config_dir {
-        if let Some(dir_str) = dir.to_str() {
-            let setup_code = format!("package.path = '{}/?.lua;' .. package.path", dir_str);
-            lua.load(&setup_code)
-                .exec()
-                .map_err(|e| ConfigError::LuaError(format!("Failed to set package.path: {}", e)))?;
-        }
+    if let Some(dir) = config_path.and_then(Path::parent) {
+        add_module_dir(&lua, dir)
+            .map_err(|e| ConfigError::LuaError(format!("Failed to set package.path: {}", e)))?;
     }
 
-    let builder = lua_api::register_api(&lua)?;
+    let builder = lua_api::register_api(&lua, state)?;
 
+    let chunk_name = match config_path {
+        Some(path) => format!("@{}", path.display()),
+        None => "=config.lua".to_string(),
+    };
     lua.load(input)
+        .set_name(chunk_name)
         .exec()
         .map_err(|e| ConfigError::LuaError(format!("{}", e)))?;
 
     let builder_data = builder.borrow().clone();
 
-    return Ok(crate::Config {
+    let mut config = crate::Config {
         border_width: builder_data.border_width,
         border_focused: builder_data.border_focused,
         border_unfocused: builder_data.border_unfocused,
+        border_urgent: builder_data.border_urgent,
+        border_style: builder_data.border_style,
+        smart_borders: builder_data.smart_borders,
+        border_radius: builder_data.border_radius,
         font: builder_data.font,
+        font_fallbacks: builder_data.font_fallbacks,
+        floating_placement: builder_data.floating_placement,
+        attach_position: builder_data.attach_position,
+        snap_distance: builder_data.snap_distance,
+        constrain_drag: builder_data.constrain_drag,
         gaps_enabled: builder_data.gaps_enabled,
         smartgaps_enabled: builder_data.smartgaps_enabled,
         gap_inner_horizontal: builder_data.gap_inner_horizontal,
         gap_inner_vertical: builder_data.gap_inner_vertical,
         gap_outer_horizontal: builder_data.gap_outer_horizontal,
         gap_outer_vertical: builder_data.gap_outer_vertical,
+        tab_bar_position: builder_data.tab_bar_position,
+        tab_bar_height: builder_data.tab_bar_height,
+        tab_bar_gap: builder_data.tab_bar_gap,
+        tab_bar_show_floating: builder_data.tab_bar_show_floating,
+        tab_bar_max_tab_width: builder_data.tab_bar_max_tab_width,
+        tab_bar_separator: builder_data.tab_bar_separator,
+        tab_bar_label: builder_data.tab_bar_label,
+        tab_bar_scheme_active: builder_data.tab_bar_scheme_active,
+        tab_bar_scheme_inactive: builder_data.tab_bar_scheme_inactive,
+        tab_bar_urgent: builder_data.tab_bar_urgent,
+        monitor_order: builder_data.monitor_order,
+        monitor_scales: builder_data.monitor_scales,
+        monitor_padding: builder_data.monitor_padding,
+        monitor_keep_tags: builder_data.monitor_keep_tags,
+        master_factor: builder_data.master_factor,
+        num_master: builder_data.num_master,
+        resize_hints: builder_data.resize_hints,
+        layout_resize_hints: builder_data.layout_resize_hints,
+        monitor_masters: builder_data.monitor_masters,
+        tag_overrides: builder_data.tag_overrides,
+        tag_icons: builder_data.tag_icons,
         terminal: builder_data.terminal,
         modkey: builder_data.modkey,
+        quit_grace_period: builder_data.quit_grace_period,
+        kill_timeout: builder_data.kill_timeout,
+        auto_force_kill: builder_data.auto_force_kill,
+        osd_timeout: builder_data.osd_timeout,
+        log_file: builder_data.log_file,
+        allow_activation: builder_data.allow_activation,
+        bypass_compositor: builder_data.bypass_compositor,
         tags: builder_data.tags,
         layout_symbols: builder_data.layout_symbols,
+        layout_cycle_skip: builder_data.layout_cycle_skip,
         keybindings: builder_data.keybindings,
+        modes: builder_data.modes,
         window_rules: builder_data.window_rules,
         status_blocks: builder_data.status_blocks,
+        bar_height: builder_data.bar_height,
+        bar_separator: builder_data.bar_separator,
+        bar_block_padding: builder_data.bar_block_padding,
+        bar_tag_padding: builder_data.bar_tag_padding,
+        bar_tag_counts: builder_data.bar_tag_counts,
+        bar_underline_thickness: builder_data.bar_underline_thickness,
+        bar_scroll_skip_empty: builder_data.bar_scroll_skip_empty,
+        bar_alpha: builder_data.bar_alpha,
+        bar_radius: builder_data.bar_radius,
         scheme_normal: builder_data.scheme_normal,
         scheme_occupied: builder_data.scheme_occupied,
         scheme_selected: builder_data.scheme_selected,
+        theme_file: builder_data.theme_file,
+        theme_roles: builder_data.theme_roles,
+        screenshot_dir: builder_data.screenshot_dir,
+        screenshot_save: builder_data.screenshot_save,
+        screenshot_clipboard: builder_data.screenshot_clipboard,
         autostart: builder_data.autostart,
-    })
+        autostart_once: builder_data.autostart_once,
+        path: config_path.map(Path::to_path_buf),
+    };
+
+    if let Err(error) = super::apply_theme(&mut config) {
+        log::warn!("{}", error);
+    }
+
+    Ok(config)
+}
+
+/// Puts the config directory and its `lib/` subdirectory in front of the
+/// module search path.
+fn add_module_dir(lua: &Lua, dir: &Path) -> mlua::Result<()> {
+    let package: Table = lua.globals().get("package")?;
+    let current: String = package.get("path")?;
+
+    let dir = dir.display();
+    let path = format!(
+        "{dir}/?.lua;{dir}/?/init.lua;{dir}/lib/?.lua;{dir}/lib/?/init.lua;{current}"
+    );
+    package.set("path", path)
 }
@@ -1,37 +1,100 @@
-use mlua::{Lua, Table, Value};
+use mlua::{Function, Lua, Table, Value};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
-use crate::bar::BlockConfig;
+use crate::bar::{BarPosition, BlockConfig};
+use super::RuntimeState;
+use super::theme::THEME_ROLES;
+use super::xresources::Xresources;
 use crate::errors::ConfigError;
-use crate::keyboard::handlers::{Arg, KeyAction, KeyBinding, KeyPress};
+use crate::keyboard::handlers::{Arg, KeyAction, KeyBinding, KeyPress, KeyTrigger, SpawnCommand};
 use crate::keyboard::keysyms::{self, Keysym};
 use crate::ColorScheme;
 use x11rb::protocol::xproto::KeyButMask;
 
+/// What a shell block shows while its command runs past the interval.
+const DEFAULT_BUSY_TEXT: &str = "…";
+/// Color of a shell block whose command last exited non-zero.
+const DEFAULT_ERROR_COLOR: u32 = 0xff5555;
+
 #[derive(Clone)]
 pub struct ConfigBuilder {
     pub border_width: u32,
     pub border_focused: u32,
     pub border_unfocused: u32,
+    pub border_urgent: Option<u32>,
+    pub border_style: crate::BorderStyle,
+    pub smart_borders: bool,
+    pub border_radius: u32,
     pub font: String,
+    pub font_fallbacks: Vec<String>,
+    pub floating_placement: crate::FloatingPlacement,
+    pub attach_position: crate::AttachPosition,
+    pub snap_distance: u32,
+    pub constrain_drag: bool,
     pub gaps_enabled: bool,
     pub smartgaps_enabled: bool,
     pub gap_inner_horizontal: u32,
     pub gap_inner_vertical: u32,
     pub gap_outer_horizontal: u32,
     pub gap_outer_vertical: u32,
+    pub tab_bar_position: BarPosition,
+    pub tab_bar_height: u32,
+    pub tab_bar_gap: u32,
+    pub tab_bar_show_floating: bool,
+    pub tab_bar_max_tab_width: Option<u32>,
+    pub tab_bar_separator: String,
+    pub tab_bar_label: crate::TabLabel,
+    pub tab_bar_scheme_active: Option<ColorScheme>,
+    pub tab_bar_scheme_inactive: Option<ColorScheme>,
+    pub tab_bar_urgent: Option<u32>,
+    pub monitor_order: Vec<String>,
+    pub monitor_scales: Vec<crate::MonitorScale>,
+    pub monitor_padding: Vec<crate::MonitorPadding>,
+    pub monitor_keep_tags: bool,
+    pub master_factor: f32,
+    pub num_master: i32,
+    pub monitor_masters: Vec<crate::MonitorMaster>,
+    pub tag_overrides: Vec<crate::TagOverride>,
+    pub tag_icons: HashMap<String, String>,
+    pub resize_hints: bool,
+    pub layout_resize_hints: HashMap<String, bool>,
     pub terminal: String,
     pub modkey: KeyButMask,
+    pub quit_grace_period: u64,
+    pub kill_timeout: u64,
+    pub auto_force_kill: bool,
+    pub osd_timeout: u64,
+    pub log_file: bool,
+    pub allow_activation: bool,
+    pub bypass_compositor: bool,
     pub tags: Vec<String>,
     pub layout_symbols: Vec<crate::LayoutSymbolOverride>,
+    pub layout_cycle_skip: Vec<String>,
     pub keybindings: Vec<KeyBinding>,
+    pub modes: HashMap<String, Vec<KeyBinding>>,
     pub window_rules: Vec<crate::WindowRule>,
     pub status_blocks: Vec<BlockConfig>,
+    pub bar_height: Option<u32>,
+    pub bar_separator: String,
+    pub bar_block_padding: u32,
+    pub bar_tag_padding: Option<u32>,
+    pub bar_tag_counts: crate::TagCountStyle,
+    pub bar_underline_thickness: Option<u32>,
+    pub bar_scroll_skip_empty: bool,
+    pub bar_alpha: f32,
+    pub bar_radius: u32,
     pub scheme_normal: ColorScheme,
     pub scheme_occupied: ColorScheme,
     pub scheme_selected: ColorScheme,
+    pub theme_file: Option<String>,
+    pub theme_roles: Vec<(String, String)>,
+    pub screenshot_dir: Option<String>,
+    pub screenshot_save: bool,
+    pub screenshot_clipboard: bool,
     pub autostart: Vec<String>,
+    pub autostart_once: Vec<crate::AutostartCommand>,
 }
 
 impl Default for ConfigBuilder {
@@ -40,20 +103,68 @@ impl Default for ConfigBuilder {
             border_width: 2,
             border_focused: 0x6dade3,
             border_unfocused: 0xbbbbbb,
+            border_urgent: None,
+            border_style: crate::BorderStyle::default(),
+            smart_borders: false,
+            border_radius: 0,
             font: "monospace:style=Bold:size=10".to_string(),
+            font_fallbacks: Vec::new(),
+            floating_placement: crate::FloatingPlacement::Requested,
+            attach_position: crate::AttachPosition::Aside,
+            snap_distance: 32,
+            constrain_drag: false,
             gaps_enabled: true,
             smartgaps_enabled: true,
             gap_inner_horizontal: 5,
             gap_inner_vertical: 5,
             gap_outer_horizontal: 5,
             gap_outer_vertical: 5,
+            tab_bar_position: BarPosition::Top,
+            tab_bar_height: crate::layout::tabbed::TAB_BAR_HEIGHT,
+            tab_bar_gap: 0,
+            tab_bar_show_floating: false,
+            tab_bar_max_tab_width: None,
+            tab_bar_separator: String::new(),
+            tab_bar_label: crate::TabLabel::Title,
+            tab_bar_scheme_active: None,
+            tab_bar_scheme_inactive: None,
+            tab_bar_urgent: None,
+            monitor_order: Vec::new(),
+            monitor_scales: Vec::new(),
+            monitor_padding: Vec::new(),
+            monitor_keep_tags: false,
+            master_factor: crate::monitor::DEFAULT_MASTER_FACTOR,
+            num_master: crate::monitor::DEFAULT_NUM_MASTER,
+            resize_hints: false,
+            layout_resize_hints: HashMap::new(),
+            monitor_masters: Vec::new(),
+            tag_overrides: Vec::new(),
+            tag_icons: HashMap::new(),
             terminal: "st".to_string(),
             modkey: KeyButMask::MOD4,
+            quit_grace_period: 5000,
+            kill_timeout: 5000,
+            auto_force_kill: false,
+            osd_timeout: 1000,
+            log_file: false,
+            allow_activation: false,
+            bypass_compositor: false,
             tags: vec!["1".into(), "2".into(), "3".into()],
             layout_symbols: Vec::new(),
+            layout_cycle_skip: Vec::new(),
             keybindings: Vec::new(),
+            modes: HashMap::new(),
             window_rules: Vec::new(),
             status_blocks: Vec::new(),
+            bar_height: None,
+            bar_separator: String::new(),
+            bar_block_padding: 0,
+            bar_tag_padding: None,
+            bar_tag_counts: crate::TagCountStyle::None,
+            bar_underline_thickness: None,
+            bar_scroll_skip_empty: true,
+            bar_alpha: 1.0,
+            bar_radius: 0,
             scheme_normal: ColorScheme {
                 foreground: 0xffffff,
                 background: 0x000000,
@@ -69,29 +180,42 @@ impl Default for ConfigBuilder {
                 background: 0x000000,
                 underline: 0x444444,
             },
+            theme_file: None,
+            theme_roles: Vec::new(),
+            screenshot_dir: None,
+            screenshot_save: true,
+            screenshot_clipboard: false,
             autostart: Vec::new(),
+            autostart_once: Vec::new(),
         }
     }
 }
 
 type SharedBuilder = Rc<RefCell<ConfigBuilder>>;
 
-pub fn register_api(lua: &Lua) -> Result<SharedBuilder, ConfigError> {
+pub fn register_api(lua: &Lua, state: RuntimeState) -> Result<SharedBuilder, ConfigError> {
     let builder = Rc::new(RefCell::new(ConfigBuilder::default()));
 
     let oxwm_table = lua.create_table()?;
 
     register_spawn(&lua, &oxwm_table, builder.clone())?;
     register_key_module(&lua, &oxwm_table, builder.clone())?;
+    register_mode_module(lua, &oxwm_table, builder.clone())?;
     register_gaps_module(&lua, &oxwm_table, builder.clone())?;
     register_border_module(&lua, &oxwm_table, builder.clone())?;
-    register_client_module(&lua, &oxwm_table)?;
-    register_layout_module(&lua, &oxwm_table)?;
-    register_tag_module(&lua, &oxwm_table)?;
-    register_monitor_module(&lua, &oxwm_table)?;
+    register_tab_bar_module(&lua, &oxwm_table, builder.clone())?;
+    register_client_module(&lua, &oxwm_table, builder.clone())?;
+    register_layout_module(&lua, &oxwm_table, builder.clone())?;
+    register_tag_module(&lua, &oxwm_table, builder.clone())?;
+    register_monitor_module(&lua, &oxwm_table, builder.clone())?;
     register_rule_module(&lua, &oxwm_table, builder.clone())?;
     register_bar_module(&lua, &oxwm_table, builder.clone())?;
+    register_theme_module(lua, &oxwm_table, builder.clone())?;
+    register_screenshot_module(lua, &oxwm_table, builder.clone())?;
+    register_media_module(lua, &oxwm_table)?;
     register_misc(&lua, &oxwm_table, builder.clone())?;
+    register_environment(lua, &oxwm_table)?;
+    register_state_module(lua, &oxwm_table, state)?;
 
     lua.globals().set("oxwm", oxwm_table)?;
 
@@ -113,46 +237,114 @@ fn register_spawn(lua: &Lua, parent: &Table, _builder: SharedBuilder) -> Result<
 fn register_key_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<(), ConfigError> {
     let key_table = lua.create_table()?;
 
+    let bind = create_bind_function(lua, builder.clone(), KeyTrigger::Press)?;
+    let bind_release = create_bind_function(lua, builder.clone(), KeyTrigger::Release)?;
+    let hold = create_bind_function(lua, builder.clone(), KeyTrigger::Hold)?;
+
     let builder_clone = builder.clone();
-    let bind = lua.create_function(move |lua, (mods, key, action): (Value, String, Value)| {
-        let modifiers = parse_modifiers_value(lua, mods)?;
-        let keysym = parse_keysym(&key)?;
-        let (key_action, arg) = parse_action_value(lua, action)?;
+    let chord = lua.create_function(
+        move |lua, (keys, action, description): (Table, Value, Option<String>)| {
+            let mut key_presses = Vec::new();
 
-        let binding = KeyBinding::single_key(modifiers, keysym, key_action, arg);
-        builder_clone.borrow_mut().keybindings.push(binding);
+            for i in 1..=keys.len()? {
+                let key_spec: Table = keys.get(i)?;
+                let mods: Value = key_spec.get(1)?;
+                let key: String = key_spec.get(2)?;
 
-        Ok(())
-    })?;
+                let modifiers = parse_modifiers_value(lua, mods)?;
+                let keysym = parse_keysym(&key)?;
+
+                key_presses.push(KeyPress { modifiers, keysym });
+            }
+
+            let (key_action, arg) = parse_action_value(lua, action)?;
+            let binding =
+                KeyBinding::new(key_presses, key_action, arg).with_description(description);
+            builder_clone.borrow_mut().keybindings.push(binding);
+
+            Ok(())
+        },
+    )?;
+
+    key_table.set("bind", bind)?;
+    key_table.set("bind_release", bind_release)?;
+    key_table.set("hold", hold)?;
+    key_table.set("chord", chord)?;
+    parent.set("key", key_table)?;
+    Ok(())
+}
+
+fn register_mode_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<(), ConfigError> {
+    let mode_table = lua.create_table()?;
 
     let builder_clone = builder.clone();
-    let chord = lua.create_function(move |lua, (keys, action): (Table, Value)| {
-        let mut key_presses = Vec::new();
+    let define = lua.create_function(move |lua, (name, bindings): (String, Table)| {
+        let mut keybindings = Vec::new();
 
-        for i in 1..=keys.len()? {
-            let key_spec: Table = keys.get(i)?;
-            let mods: Value = key_spec.get(1)?;
-            let key: String = key_spec.get(2)?;
+        for i in 1..=bindings.len()? {
+            let spec: Table = bindings.get(i)?;
+            let mods: Value = spec.get(1)?;
+            let key: String = spec.get(2)?;
+            let action: Value = spec.get(3)?;
+            let description: Option<String> = spec.get(4)?;
 
             let modifiers = parse_modifiers_value(lua, mods)?;
             let keysym = parse_keysym(&key)?;
+            let (key_action, arg) = parse_action_value(lua, action)?;
 
-            key_presses.push(KeyPress { modifiers, keysym });
+            keybindings.push(
+                KeyBinding::single_key(modifiers, keysym, key_action, arg)
+                    .with_description(description),
+            );
         }
 
-        let (key_action, arg) = parse_action_value(lua, action)?;
-        let binding = KeyBinding::new(key_presses, key_action, arg);
-        builder_clone.borrow_mut().keybindings.push(binding);
+        if keybindings.is_empty() {
+            return Err(mlua::Error::RuntimeError(format!(
+                "oxwm.mode.define: mode '{}' has no bindings",
+                name
+            )));
+        }
 
+        builder_clone.borrow_mut().modes.insert(name, keybindings);
         Ok(())
     })?;
 
-    key_table.set("bind", bind)?;
-    key_table.set("chord", chord)?;
-    parent.set("key", key_table)?;
+    let enter = lua.create_function(|lua, name: String| {
+        create_action_table(lua, "EnterMode", Value::String(lua.create_string(&name)?))
+    })?;
+
+    let exit = lua.create_function(|lua, ()| {
+        create_action_table(lua, "ExitMode", Value::Nil)
+    })?;
+
+    mode_table.set("define", define)?;
+    mode_table.set("enter", enter)?;
+    mode_table.set("exit", exit)?;
+    parent.set("mode", mode_table)?;
     Ok(())
 }
 
+fn create_bind_function(
+    lua: &Lua,
+    builder: SharedBuilder,
+    trigger: KeyTrigger,
+) -> mlua::Result<Function> {
+    lua.create_function(
+        move |lua, (mods, key, action, description): (Value, String, Value, Option<String>)| {
+            let modifiers = parse_modifiers_value(lua, mods)?;
+            let keysym = parse_keysym(&key)?;
+            let (key_action, arg) = parse_action_value(lua, action)?;
+
+            let binding = KeyBinding::single_key(modifiers, keysym, key_action, arg)
+                .with_description(description)
+                .with_trigger(trigger);
+            builder.borrow_mut().keybindings.push(binding);
+
+            Ok(())
+        },
+    )
+}
+
 fn register_gaps_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<(), ConfigError> {
     let gaps_table = lua.create_table()?;
 
@@ -196,12 +388,27 @@ fn register_gaps_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Re
         Ok(())
     })?;
 
+    let adjust = lua.create_function(|lua, delta: i32| {
+        create_action_table(lua, "AdjustGaps", Value::Integer(delta as i64))
+    })?;
+
+    let adjust_inner = lua.create_function(|lua, delta: i32| {
+        create_action_table(lua, "AdjustInnerGaps", Value::Integer(delta as i64))
+    })?;
+
+    let adjust_outer = lua.create_function(|lua, delta: i32| {
+        create_action_table(lua, "AdjustOuterGaps", Value::Integer(delta as i64))
+    })?;
+
     gaps_table.set("set_enabled", set_enabled)?;
     gaps_table.set("enable", enable)?;
     gaps_table.set("disable", disable)?;
     gaps_table.set("set_inner", set_inner)?;
     gaps_table.set("set_outer", set_outer)?;
     gaps_table.set("set_smart", set_smart)?;
+    gaps_table.set("adjust", adjust)?;
+    gaps_table.set("adjust_inner", adjust_inner)?;
+    gaps_table.set("adjust_outer", adjust_outer)?;
     parent.set("gaps", gaps_table)?;
     Ok(())
 }
@@ -229,24 +436,206 @@ fn register_border_module(lua: &Lua, parent: &Table, builder: SharedBuilder) ->
         Ok(())
     })?;
 
+    let builder_clone = builder.clone();
+    let set_urgent_color = lua.create_function(move |_, color: Value| {
+        let color_u32 = parse_color_value(color)?;
+        builder_clone.borrow_mut().border_urgent = Some(color_u32);
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_smart = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().smart_borders = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_radius = lua.create_function(move |_, radius: u32| {
+        builder_clone.borrow_mut().border_radius = radius;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_style = lua.create_function(move |_, style: Table| {
+        let mut b = builder_clone.borrow_mut();
+
+        b.border_style.inner_width = style.get::<Option<u32>>("inner_width")?.unwrap_or(0);
+        b.border_style.inner_color = match style.get::<Value>("inner_color")? {
+            Value::Nil => 0,
+            value => parse_color_value(value)?,
+        };
+
+        let (color, gradient) = parse_border_colors(style.get("focused")?)?;
+        if let Some(color) = color {
+            b.border_focused = color;
+        }
+        b.border_style.focused = gradient;
+
+        let (color, gradient) = parse_border_colors(style.get("unfocused")?)?;
+        if let Some(color) = color {
+            b.border_unfocused = color;
+        }
+        b.border_style.unfocused = gradient;
+
+        let (color, gradient) = parse_border_colors(style.get("urgent")?)?;
+        if color.is_some() {
+            b.border_urgent = color;
+        }
+        b.border_style.urgent = gradient;
+
+        Ok(())
+    })?;
+
     border_table.set("set_width", set_width)?;
     border_table.set("set_focused_color", set_focused_color)?;
     border_table.set("set_unfocused_color", set_unfocused_color)?;
+    border_table.set("set_urgent_color", set_urgent_color)?;
+    border_table.set("set_smart", set_smart)?;
+    border_table.set("set_radius", set_radius)?;
+    border_table.set("set_style", set_style)?;
     parent.set("border", border_table)?;
     Ok(())
 }
 
-fn register_client_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError> {
+fn register_tab_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<(), ConfigError> {
+    let tab_bar_table = lua.create_table()?;
+
+    let builder_clone = builder.clone();
+    let set_position = lua.create_function(move |_, position: String| {
+        let position = match position.to_lowercase().as_str() {
+            "top" => BarPosition::Top,
+            "bottom" => BarPosition::Bottom,
+            _ => {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "oxwm.tab_bar.set_position: unknown position '{}' (expected 'top' or 'bottom')",
+                    position
+                )));
+            }
+        };
+        builder_clone.borrow_mut().tab_bar_position = position;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_height = lua.create_function(move |_, height: u32| {
+        if height == 0 {
+            return Err(mlua::Error::RuntimeError("oxwm.tab_bar.set_height: height must be greater than 0".into()));
+        }
+        builder_clone.borrow_mut().tab_bar_height = height;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_gap = lua.create_function(move |_, gap: u32| {
+        builder_clone.borrow_mut().tab_bar_gap = gap;
+        Ok(())
+    })?;
+
+    tab_bar_table.set("set_position", set_position)?;
+    tab_bar_table.set("set_height", set_height)?;
+    let builder_clone = builder.clone();
+    let set_show_floating = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().tab_bar_show_floating = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_max_tab_width = lua.create_function(move |_, width: u32| {
+        builder_clone.borrow_mut().tab_bar_max_tab_width = (width > 0).then_some(width);
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_separator = lua.create_function(move |_, separator: String| {
+        builder_clone.borrow_mut().tab_bar_separator = separator;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_label = lua.create_function(move |_, label: String| {
+        let label = crate::TabLabel::parse(&label)
+            .map_err(|e| mlua::Error::RuntimeError(format!("oxwm.tab_bar.set_label: {}", e)))?;
+        builder_clone.borrow_mut().tab_bar_label = label;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_scheme_active = lua.create_function(move |_, (fg, bg, ul): (Value, Value, Value)| {
+        builder_clone.borrow_mut().tab_bar_scheme_active = Some(ColorScheme {
+            foreground: parse_color_value(fg)?,
+            background: parse_color_value(bg)?,
+            underline: parse_color_value(ul)?,
+        });
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_scheme_inactive = lua.create_function(move |_, (fg, bg, ul): (Value, Value, Value)| {
+        builder_clone.borrow_mut().tab_bar_scheme_inactive = Some(ColorScheme {
+            foreground: parse_color_value(fg)?,
+            background: parse_color_value(bg)?,
+            underline: parse_color_value(ul)?,
+        });
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_urgent_color = lua.create_function(move |_, color: Value| {
+        builder_clone.borrow_mut().tab_bar_urgent = Some(parse_color_value(color)?);
+        Ok(())
+    })?;
+
+    tab_bar_table.set("set_gap", set_gap)?;
+    tab_bar_table.set("set_show_floating", set_show_floating)?;
+    tab_bar_table.set("set_max_tab_width", set_max_tab_width)?;
+    tab_bar_table.set("set_separator", set_separator)?;
+    tab_bar_table.set("set_label", set_label)?;
+    tab_bar_table.set("set_scheme_active", set_scheme_active)?;
+    tab_bar_table.set("set_scheme_inactive", set_scheme_inactive)?;
+    tab_bar_table.set("set_urgent_color", set_urgent_color)?;
+    parent.set("tab_bar", tab_bar_table)?;
+    Ok(())
+}
+
+fn register_client_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<(), ConfigError> {
     let client_table = lua.create_table()?;
 
     let kill = lua.create_function(|lua, ()| {
         create_action_table(lua, "KillClient", Value::Nil)
     })?;
 
+    let force_kill = lua.create_function(|lua, ()| {
+        create_action_table(lua, "ForceKill", Value::Nil)
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_kill_timeout = lua.create_function(move |_, (milliseconds, auto): (u64, Option<bool>)| {
+        let mut b = builder_clone.borrow_mut();
+        b.kill_timeout = milliseconds;
+        b.auto_force_kill = auto.unwrap_or(false);
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_allow_activation = lua.create_function(move |_, allow: bool| {
+        builder_clone.borrow_mut().allow_activation = allow;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_bypass_compositor = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().bypass_compositor = enabled;
+        Ok(())
+    })?;
+
     let toggle_fullscreen = lua.create_function(|lua, ()| {
         create_action_table(lua, "ToggleFullScreen", Value::Nil)
     })?;
 
+    let toggle_fullscreen_span = lua.create_function(|lua, ()| {
+        create_action_table(lua, "FullscreenSpan", Value::Nil)
+    })?;
+
     let toggle_floating = lua.create_function(|lua, ()| {
         create_action_table(lua, "ToggleFloating", Value::Nil)
     })?;
@@ -259,17 +648,134 @@ fn register_client_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError>
         create_action_table(lua, "MoveStack", Value::Integer(dir as i64))
     })?;
 
+    let zoom = lua.create_function(|lua, ()| {
+        create_action_table(lua, "Zoom", Value::Nil)
+    })?;
+
+    let switcher = lua.create_function(|lua, dir: Option<i32>| {
+        create_action_table(lua, "WindowSwitcher", Value::Integer(dir.unwrap_or(1) as i64))
+    })?;
+
+    let set_opacity = lua.create_function(|lua, delta: i32| {
+        create_action_table(lua, "SetOpacity", Value::Integer(delta as i64))
+    })?;
+
     client_table.set("kill", kill)?;
     client_table.set("toggle_fullscreen", toggle_fullscreen)?;
+    client_table.set("toggle_fullscreen_span", toggle_fullscreen_span)?;
     client_table.set("toggle_floating", toggle_floating)?;
     client_table.set("focus_stack", focus_stack)?;
     client_table.set("move_stack", move_stack)?;
+    client_table.set("zoom", zoom)?;
+    client_table.set("switcher", switcher)?;
+    let toggle_maximize = lua.create_function(|lua, ()| {
+        create_action_table(lua, "ToggleMaximize", Value::Nil)
+    })?;
+
+    let center = lua.create_function(|lua, ()| {
+        create_action_table(lua, "CenterWindow", Value::Nil)
+    })?;
+
+    let snap = lua.create_function(|lua, region: String| {
+        const REGIONS: [&str; 8] = [
+            "left-half",
+            "right-half",
+            "top-half",
+            "bottom-half",
+            "top-left",
+            "top-right",
+            "bottom-left",
+            "bottom-right",
+        ];
+        if !REGIONS.contains(&region.as_str()) {
+            return Err(mlua::Error::RuntimeError(format!(
+                "oxwm.client.snap: invalid region '{}'. Valid options: {}",
+                region,
+                REGIONS.join(", ")
+            )));
+        }
+        create_action_table(lua, "SnapWindow", Value::String(lua.create_string(&region)?))
+    })?;
+
+    let minimize = lua.create_function(|lua, ()| {
+        create_action_table(lua, "MinimizeClient", Value::Nil)
+    })?;
+
+    let restore_last = lua.create_function(|lua, ()| {
+        create_action_table(lua, "RestoreLast", Value::Nil)
+    })?;
+
+    let stash = lua.create_function(|lua, ()| {
+        create_action_table(lua, "StashWindow", Value::Nil)
+    })?;
+
+    let unstash = lua.create_function(|lua, ()| {
+        create_action_table(lua, "UnstashWindow", Value::Nil)
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_placement = lua.create_function(move |_, placement: String| {
+        use crate::FloatingPlacement;
+
+        let placement = match placement.to_lowercase().as_str() {
+            "requested" => FloatingPlacement::Requested,
+            "smart" => FloatingPlacement::Smart,
+            "cascade" => FloatingPlacement::Cascade,
+            "center" => FloatingPlacement::Center,
+            "under_pointer" => FloatingPlacement::UnderPointer,
+            _ => {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "oxwm.client.set_placement: unknown placement '{}' (expected 'requested', 'smart', 'cascade', 'center' or 'under_pointer')",
+                    placement
+                )));
+            }
+        };
+        builder_clone.borrow_mut().floating_placement = placement;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_attach = lua.create_function(move |_, position: String| {
+        let position = crate::AttachPosition::parse(&position)
+            .map_err(|e| mlua::Error::RuntimeError(format!("oxwm.client.set_attach: {}", e)))?;
+        builder_clone.borrow_mut().attach_position = position;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_snap = lua.create_function(move |_, distance: u32| {
+        builder_clone.borrow_mut().snap_distance = distance;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_constrain_drag = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().constrain_drag = enabled;
+        Ok(())
+    })?;
+
+    client_table.set("set_opacity", set_opacity)?;
+    client_table.set("set_placement", set_placement)?;
+    client_table.set("set_attach", set_attach)?;
+    client_table.set("set_snap", set_snap)?;
+    client_table.set("set_constrain_drag", set_constrain_drag)?;
+    client_table.set("toggle_maximize", toggle_maximize)?;
+    client_table.set("center", center)?;
+    client_table.set("snap", snap)?;
+    client_table.set("minimize", minimize)?;
+    client_table.set("restore_last", restore_last)?;
+    client_table.set("force_kill", force_kill)?;
+    client_table.set("set_kill_timeout", set_kill_timeout)?;
+    client_table.set("set_allow_activation", set_allow_activation)?;
+    client_table.set("set_bypass_compositor", set_bypass_compositor)?;
+    client_table.set("stash", stash)?;
+    client_table.set("unstash", unstash)?;
 
     parent.set("client", client_table)?;
     Ok(())
 }
 
-fn register_layout_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError> {
+fn register_layout_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<(), ConfigError> {
     let layout_table = lua.create_table()?;
 
     let cycle = lua.create_function(|lua, ()| {
@@ -280,13 +786,181 @@ fn register_layout_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError>
         create_action_table(lua, "ChangeLayout", Value::String(lua.create_string(&name)?))
     })?;
 
+    let builder_clone = builder.clone();
+    let set_master_factor = lua.create_function(move |_, (factor, monitor): (f32, Option<usize>)| {
+        if !(0.05..=0.95).contains(&factor) {
+            return Err(mlua::Error::RuntimeError(
+                "oxwm.layout.set_master_factor: factor must be between 0.05 and 0.95".into(),
+            ));
+        }
+        let mut b = builder_clone.borrow_mut();
+        match monitor {
+            Some(monitor) => monitor_master_entry(&mut b.monitor_masters, monitor).master_factor = Some(factor),
+            None => b.master_factor = factor,
+        }
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_num_master = lua.create_function(move |_, (count, monitor): (i32, Option<usize>)| {
+        if count < 0 {
+            return Err(mlua::Error::RuntimeError(
+                "oxwm.layout.set_num_master: count must not be negative".into(),
+            ));
+        }
+        let mut b = builder_clone.borrow_mut();
+        match monitor {
+            Some(monitor) => monitor_master_entry(&mut b.monitor_masters, monitor).num_master = Some(count),
+            None => b.num_master = count,
+        }
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_resize_hints = lua.create_function(move |_, (enabled, layout): (bool, Option<String>)| {
+        let mut b = builder_clone.borrow_mut();
+        match layout {
+            Some(layout) => {
+                crate::layout::LayoutType::from_str(&layout).map_err(|e| {
+                    mlua::Error::RuntimeError(format!("oxwm.layout.set_resize_hints: {}", e))
+                })?;
+                b.layout_resize_hints.insert(layout, enabled);
+            }
+            None => b.resize_hints = enabled,
+        }
+        Ok(())
+    })?;
+
+    let inc_master_factor = lua.create_function(|lua, delta: Option<i32>| {
+        create_action_table(lua, "SetMasterFactor", Value::Integer(delta.unwrap_or(5) as i64))
+    })?;
+
+    let resize_master = lua.create_function(|lua, (direction, amount): (String, Option<i32>)| {
+        if !matches!(direction.as_str(), "left" | "right" | "up" | "down") {
+            return Err(mlua::Error::RuntimeError(format!(
+                "oxwm.layout.resize_master: invalid direction '{}', expected left, right, up or down",
+                direction
+            )));
+        }
+        let arg = lua.create_sequence_from([direction, amount.unwrap_or(5).to_string()])?;
+        create_action_table(lua, "ResizeMaster", Value::Table(arg))
+    })?;
+
+    let inc_num_master = lua.create_function(|lua, delta: Option<i32>| {
+        create_action_table(lua, "IncNumMaster", Value::Integer(delta.unwrap_or(1) as i64))
+    })?;
+
+    let tab_next = lua.create_function(|lua, ()| {
+        create_action_table(lua, "FocusTabNext", Value::Nil)
+    })?;
+
+    let tab_prev = lua.create_function(|lua, ()| {
+        create_action_table(lua, "FocusTabPrev", Value::Nil)
+    })?;
+
     layout_table.set("cycle", cycle)?;
     layout_table.set("set", set)?;
+    layout_table.set("inc_master_factor", inc_master_factor)?;
+    layout_table.set("resize_master", resize_master)?;
+    layout_table.set("inc_num_master", inc_num_master)?;
+    layout_table.set("set_master_factor", set_master_factor)?;
+    layout_table.set("set_num_master", set_num_master)?;
+    layout_table.set("set_resize_hints", set_resize_hints)?;
+    let builder_clone = builder.clone();
+    let register = lua.create_function(move |lua, (name, options): (String, Option<Table>)| {
+        let name = crate::layout::LayoutType::from_str(&name)
+            .map_err(|_| {
+                mlua::Error::RuntimeError(format!(
+                    "oxwm.layout.register: '{}' is not a built-in layout, expected one of {}",
+                    name,
+                    crate::layout::LayoutType::ALL.map(|layout| layout.as_str()).join(", ")
+                ))
+            })?
+            .as_str()
+            .to_string();
+        if let Some(options) = options {
+            let symbol: Option<String> = options.get("symbol")?;
+            let cycle: Option<bool> = options.get("cycle")?;
+            let mut b = builder_clone.borrow_mut();
+            if let Some(symbol) = symbol {
+                set_layout_symbol_override(&mut b, name.clone(), symbol);
+            }
+            if let Some(cycle) = cycle {
+                set_layout_cycle(&mut b, &name, cycle);
+            }
+        }
+        create_layout_handle(lua, &name, builder_clone.clone())
+    })?;
+
+    layout_table.set("tab_next", tab_next)?;
+    layout_table.set("tab_prev", tab_prev)?;
+    layout_table.set("register", register)?;
     parent.set("layout", layout_table)?;
     Ok(())
 }
 
-fn register_tag_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError> {
+/// Table returned by `oxwm.layout.register`, for switching to the layout
+/// and changing how it shows up later on. `set` makes an action, so it
+/// works from bindings and click handlers, while `set_symbol` and
+/// `set_cycle` change the config being built and only count while it
+/// loads.
+fn create_layout_handle(lua: &Lua, name: &str, builder: SharedBuilder) -> mlua::Result<Table> {
+    let handle = lua.create_table()?;
+
+    let layout_name = name.to_string();
+    let set = lua.create_function(move |lua, ()| {
+        create_action_table(lua, "ChangeLayout", Value::String(lua.create_string(&layout_name)?))
+    })?;
+
+    let builder_clone = builder.clone();
+    let layout_name = name.to_string();
+    let set_symbol = lua.create_function(move |_, symbol: String| {
+        set_layout_symbol_override(&mut builder_clone.borrow_mut(), layout_name.clone(), symbol);
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let layout_name = name.to_string();
+    let set_cycle = lua.create_function(move |_, cycle: bool| {
+        set_layout_cycle(&mut builder_clone.borrow_mut(), &layout_name, cycle);
+        Ok(())
+    })?;
+
+    handle.set("name", name)?;
+    handle.set("set", set)?;
+    handle.set("set_symbol", set_symbol)?;
+    handle.set("set_cycle", set_cycle)?;
+    Ok(handle)
+}
+
+fn set_layout_symbol_override(builder: &mut ConfigBuilder, name: String, symbol: String) {
+    builder.layout_symbols.retain(|existing| existing.name != name);
+    builder.layout_symbols.push(crate::LayoutSymbolOverride { name, symbol });
+}
+
+fn set_layout_cycle(builder: &mut ConfigBuilder, name: &str, cycle: bool) {
+    builder.layout_cycle_skip.retain(|skipped| skipped != name);
+    if !cycle {
+        builder.layout_cycle_skip.push(name.to_string());
+    }
+}
+
+fn monitor_master_entry(masters: &mut Vec<crate::MonitorMaster>, monitor: usize) -> &mut crate::MonitorMaster {
+    let index = match masters.iter().position(|master| master.monitor == monitor) {
+        Some(index) => index,
+        None => {
+            masters.push(crate::MonitorMaster {
+                monitor,
+                master_factor: None,
+                num_master: None,
+            });
+            masters.len() - 1
+        }
+    };
+    &mut masters[index]
+}
+
+fn register_tag_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<(), ConfigError> {
     let tag_table = lua.create_table()?;
 
     let view = lua.create_function(|lua, idx: i32| {
@@ -305,15 +979,59 @@ fn register_tag_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError> {
         create_action_table(lua, "ToggleTag", Value::Integer(idx as i64))
     })?;
 
+    let rename = lua.create_function(|lua, name: Option<String>| {
+        let arg = match name {
+            Some(name) => Value::String(lua.create_string(&name)?),
+            None => Value::Nil,
+        };
+        create_action_table(lua, "RenameTag", arg)
+    })?;
+
+    let add = lua.create_function(|lua, name: Option<String>| {
+        let arg = match name {
+            Some(name) => Value::String(lua.create_string(&name)?),
+            None => Value::Nil,
+        };
+        create_action_table(lua, "AddTag", arg)
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_overrides = lua.create_function(move |_, (tag, overrides): (usize, Table)| {
+        let gaps: Option<bool> = overrides.get("gaps")?;
+        let border_width: Option<u32> = overrides.get("border_width")?;
+
+        let mut b = builder_clone.borrow_mut();
+        b.tag_overrides.retain(|existing| existing.tag != tag);
+        b.tag_overrides.push(crate::TagOverride {
+            tag,
+            gaps,
+            border_width,
+        });
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_icons = lua.create_function(move |_, icons: HashMap<String, String>| {
+        builder_clone.borrow_mut().tag_icons = icons
+            .into_iter()
+            .map(|(class, icon)| (class.to_lowercase(), icon))
+            .collect();
+        Ok(())
+    })?;
+
     tag_table.set("view", view)?;
+    tag_table.set("set_overrides", set_overrides)?;
+    tag_table.set("set_icons", set_icons)?;
     tag_table.set("toggleview", toggleview)?;
     tag_table.set("move_to", move_to)?;
     tag_table.set("toggletag", toggletag)?;
+    tag_table.set("rename", rename)?;
+    tag_table.set("add", add)?;
     parent.set("tag", tag_table)?;
     Ok(())
 }
 
-fn register_monitor_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError> {
+fn register_monitor_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<(), ConfigError> {
     let monitor_table = lua.create_table()?;
 
     let focus = lua.create_function(|lua, direction: i64| {
@@ -324,8 +1042,56 @@ fn register_monitor_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError>
         create_action_table(lua, "TagMonitor", Value::Integer(direction))
     })?;
 
+    let tag_follow = lua.create_function(|lua, direction: i64| {
+        create_action_table(lua, "TagMonitorFollow", Value::Integer(direction))
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_order = lua.create_function(move |_, outputs: Vec<String>| {
+        builder_clone.borrow_mut().monitor_order = outputs;
+        Ok(())
+    })?;
+
     monitor_table.set("focus", focus)?;
     monitor_table.set("tag", tag)?;
+    monitor_table.set("tag_follow", tag_follow)?;
+    let builder_clone = builder.clone();
+    let set_scale = lua.create_function(move |_, (monitor, scale): (usize, f32)| {
+        if scale <= 0.0 {
+            return Err(mlua::Error::RuntimeError("oxwm.monitor.set_scale: scale must be greater than 0".into()));
+        }
+        let mut b = builder_clone.borrow_mut();
+        b.monitor_scales.retain(|monitor_scale| monitor_scale.monitor != monitor);
+        b.monitor_scales.push(crate::MonitorScale { monitor, scale });
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_padding = lua.create_function(
+        move |_, (monitor, top, bottom, left, right): (usize, u32, u32, u32, u32)| {
+            let mut b = builder_clone.borrow_mut();
+            b.monitor_padding.retain(|padding| padding.monitor != monitor);
+            b.monitor_padding.push(crate::MonitorPadding {
+                monitor,
+                top,
+                bottom,
+                left,
+                right,
+            });
+            Ok(())
+        },
+    )?;
+
+    let builder_clone = builder.clone();
+    let set_keep_tags = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().monitor_keep_tags = enabled;
+        Ok(())
+    })?;
+
+    monitor_table.set("set_order", set_order)?;
+    monitor_table.set("set_keep_tags", set_keep_tags)?;
+    monitor_table.set("set_scale", set_scale)?;
+    monitor_table.set("set_padding", set_padding)?;
     parent.set("monitor", monitor_table)?;
     Ok(())
 }
@@ -340,6 +1106,18 @@ fn register_rule_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Re
         let title: Option<String> = config.get("title").ok();
         let is_floating: Option<bool> = config.get("floating").ok();
         let monitor: Option<usize> = config.get("monitor").ok();
+        let opacity: Option<f32> = config.get("opacity").ok();
+        let on_title_change: bool = config.get::<Option<bool>>("on_title_change")?.unwrap_or(false);
+        let attach = config
+            .get::<Option<String>>("attach")?
+            .map(|position| crate::AttachPosition::parse(&position))
+            .transpose()
+            .map_err(|e| mlua::Error::RuntimeError(format!("oxwm.rule.add: {}", e)))?;
+        let layout = config.get::<Option<String>>("layout")?;
+        if let Some(layout) = &layout {
+            crate::layout::LayoutType::from_str(layout)
+                .map_err(|e| mlua::Error::RuntimeError(format!("oxwm.rule.add: {}", e)))?;
+        }
 
         let tags: Option<u32> = if let Ok(tag_index) = config.get::<i32>("tag") {
             if tag_index > 0 {
@@ -358,6 +1136,10 @@ fn register_rule_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Re
             tags,
             is_floating,
             monitor,
+            opacity,
+            attach,
+            layout,
+            on_title_change,
         };
 
         builder_clone.borrow_mut().window_rules.push(rule);
@@ -372,9 +1154,78 @@ fn register_rule_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Re
 fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<(), ConfigError> {
     let bar_table = lua.create_table()?;
 
+    let toggle = lua.create_function(|lua, ()| {
+        create_action_table(lua, "ToggleBar", Value::Nil)
+    })?;
+    bar_table.set("toggle", toggle)?;
+
+    let builder_clone = builder.clone();
+    let set_font = lua.create_function(move |_, (font, fallbacks): (String, Option<Vec<String>>)| {
+        let mut builder = builder_clone.borrow_mut();
+        builder.font = font;
+        builder.font_fallbacks = fallbacks.unwrap_or_default();
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_height = lua.create_function(move |_, height: u32| {
+        if height == 0 {
+            return Err(mlua::Error::RuntimeError("oxwm.bar.set_height: height must be greater than 0".into()));
+        }
+        builder_clone.borrow_mut().bar_height = Some(height);
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_separator = lua.create_function(move |_, separator: String| {
+        builder_clone.borrow_mut().bar_separator = separator;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_block_padding = lua.create_function(move |_, padding: u32| {
+        builder_clone.borrow_mut().bar_block_padding = padding;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_tag_padding = lua.create_function(move |_, padding: u32| {
+        builder_clone.borrow_mut().bar_tag_padding = Some(padding);
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_tag_counts = lua.create_function(move |_, style: String| {
+        let style = crate::TagCountStyle::parse(&style)
+            .map_err(|e| mlua::Error::RuntimeError(format!("oxwm.bar.set_tag_counts: {}", e)))?;
+        builder_clone.borrow_mut().bar_tag_counts = style;
+        Ok(())
+    })?;
+
     let builder_clone = builder.clone();
-    let set_font = lua.create_function(move |_, font: String| {
-        builder_clone.borrow_mut().font = font;
+    let set_alpha = lua.create_function(move |_, alpha: f32| {
+        if !(0.0..=1.0).contains(&alpha) {
+            return Err(mlua::Error::RuntimeError("oxwm.bar.set_alpha: alpha must be between 0 and 1".into()));
+        }
+        builder_clone.borrow_mut().bar_alpha = alpha;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_radius = lua.create_function(move |_, radius: u32| {
+        builder_clone.borrow_mut().bar_radius = radius;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_underline_thickness = lua.create_function(move |_, thickness: u32| {
+        builder_clone.borrow_mut().bar_underline_thickness = Some(thickness);
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_scroll_skip_empty = lua.create_function(move |_, skip: bool| {
+        builder_clone.borrow_mut().bar_scroll_skip_empty = skip;
         Ok(())
     })?;
 
@@ -393,7 +1244,15 @@ fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Res
     let shell = lua.create_function(|lua, config: Table| {
         let command: String = config.get("command")
             .map_err(|_| mlua::Error::RuntimeError("oxwm.bar.block.shell: 'command' field is required".into()))?;
-        create_block_config(lua, config, "Shell", Some(Value::String(lua.create_string(&command)?)))
+        let busy: Option<String> = config.get("busy").ok();
+        let error_color: Value = config.get("error_color").unwrap_or(Value::Nil);
+
+        let options_table = lua.create_table()?;
+        options_table.set("command", command)?;
+        options_table.set("busy", busy)?;
+        options_table.set("error_color", error_color)?;
+
+        create_block_config(lua, config, "Shell", Some(Value::Table(options_table)))
     })?;
 
     let static_block = lua.create_function(|lua, config: Table| {
@@ -402,6 +1261,44 @@ fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Res
         create_block_config(lua, config, "Static", Some(Value::String(lua.create_string(&text)?)))
     })?;
 
+    let temperature = lua.create_function(|lua, config: Table| {
+        let sensor: Option<String> = config.get("sensor").ok();
+        let critical: Option<f32> = config.get("critical").ok();
+        let critical_color: Value = config.get("critical_color").unwrap_or(Value::Nil);
+
+        let options_table = lua.create_table()?;
+        options_table.set("sensor", sensor)?;
+        options_table.set("critical", critical)?;
+        options_table.set("critical_color", critical_color)?;
+
+        create_block_config(lua, config, "Temperature", Some(Value::Table(options_table)))
+    })?;
+
+    let weather = lua.create_function(|lua, config: Table| {
+        let location: String = config.get("location")
+            .map_err(|_| mlua::Error::RuntimeError("oxwm.bar.block.weather: 'location' field is required".into()))?;
+        let weather_format: Option<String> = config.get("weather_format").ok();
+
+        let options_table = lua.create_table()?;
+        options_table.set("location", location)?;
+        options_table.set("weather_format", weather_format)?;
+
+        create_block_config(lua, config, "Weather", Some(Value::Table(options_table)))
+    })?;
+
+    let now_playing = lua.create_function(|lua, config: Table| {
+        // Updates come from the player, the interval is never used.
+        if config.get::<Option<u64>>("interval")?.is_none() {
+            config.set("interval", 0)?;
+        }
+        let paused_color: Value = config.get("paused_color").unwrap_or(Value::Nil);
+
+        let options_table = lua.create_table()?;
+        options_table.set("paused_color", paused_color)?;
+
+        create_block_config(lua, config, "NowPlaying", Some(Value::Table(options_table)))
+    })?;
+
     let battery = lua.create_function(|lua, config: Table| {
         let charging: String = config.get("charging")
             .map_err(|_| mlua::Error::RuntimeError("oxwm.bar.block.battery: 'charging' field is required".into()))?;
@@ -421,15 +1318,18 @@ fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Res
     block_table.set("ram", ram)?;
     block_table.set("datetime", datetime)?;
     block_table.set("shell", shell)?;
+    block_table.set("weather", weather)?;
+    block_table.set("now_playing", now_playing)?;
     block_table.set("static", static_block)?;
     block_table.set("battery", battery)?;
+    block_table.set("temperature", temperature)?;
 
     // Deprecated add_block() function for backwards compatibility
     // This allows old configs to still work, but users should migrate to set_blocks()
     let builder_clone = builder.clone();
     let add_block = lua.create_function(move |_, (format, block_type, arg, interval, color, underline): (String, String, Value, u64, Value, Option<bool>)| -> mlua::Result<()> {
-        eprintln!("WARNING: oxwm.bar.add_block() is deprecated. Please migrate to oxwm.bar.set_blocks() with block constructors.");
-        eprintln!("See the migration guide for details.");
+        log::warn!("oxwm.bar.add_block() is deprecated. Please migrate to oxwm.bar.set_blocks() with block constructors.");
+        log::warn!("See the migration guide for details.");
 
         let cmd = match block_type.as_str() {
             "DateTime" => {
@@ -446,7 +1346,11 @@ fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Res
                 } else {
                     return Err(mlua::Error::RuntimeError("Shell block requires command string as third argument".into()));
                 };
-                crate::bar::BlockCommand::Shell(cmd_str)
+                crate::bar::BlockCommand::Shell {
+                    command: cmd_str,
+                    busy_text: DEFAULT_BUSY_TEXT.to_string(),
+                    error_color: DEFAULT_ERROR_COLOR,
+                }
             }
             "Ram" => crate::bar::BlockCommand::Ram,
             "Static" => {
@@ -473,6 +1377,9 @@ fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Res
             interval_secs: interval,
             color: color_u32,
             underline: underline.unwrap_or(false),
+            monitor: None,
+            name: None,
+            click: None,
         };
 
         builder_clone.borrow_mut().status_blocks.push(block);
@@ -480,7 +1387,7 @@ fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Res
     })?;
 
     let builder_clone = builder.clone();
-    let set_blocks = lua.create_function(move |_, blocks: Table| {
+    let set_blocks = lua.create_function(move |lua, blocks: Table| {
         use crate::bar::BlockCommand;
 
         let mut block_configs = Vec::new();
@@ -492,6 +1399,9 @@ fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Res
             let interval: u64 = block_table.get("interval")?;
             let color_val: Value = block_table.get("color")?;
             let underline: bool = block_table.get("underline").unwrap_or(false);
+            let monitor: Option<usize> = block_table.get("monitor")?;
+            let name: Option<String> = block_table.get("name")?;
+            let click = parse_block_click(lua, block_table.get("on_click")?)?;
             let arg: Option<Value> = block_table.get("__arg").ok();
 
             let cmd = match block_type.as_str() {
@@ -506,16 +1416,52 @@ fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Res
                     BlockCommand::DateTime(fmt)
                 }
                 "Shell" => {
-                    let cmd_str = arg.and_then(|v| {
-                        if let Value::String(s) = v {
-                            s.to_str().ok().map(|s| s.to_string())
+                    let options = arg.and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
                         } else {
                             None
                         }
                     }).ok_or_else(|| mlua::Error::RuntimeError("Shell block missing command".into()))?;
-                    BlockCommand::Shell(cmd_str)
+
+                    let command: String = options.get("command")?;
+                    let busy_text = options
+                        .get::<Option<String>>("busy")?
+                        .unwrap_or_else(|| DEFAULT_BUSY_TEXT.to_string());
+                    let error_color = match options.get::<Value>("error_color")? {
+                        Value::Nil => DEFAULT_ERROR_COLOR,
+                        value => parse_color_value(value)?,
+                    };
+
+                    BlockCommand::Shell {
+                        command,
+                        busy_text,
+                        error_color,
+                    }
                 }
                 "Ram" => BlockCommand::Ram,
+                "Temperature" => {
+                    let options = arg.and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    }).ok_or_else(|| mlua::Error::RuntimeError("Temperature block missing options".into()))?;
+
+                    let sensor: Option<String> = options.get("sensor")?;
+                    let critical: Option<f32> = options.get("critical")?;
+                    let critical_color = match options.get::<Value>("critical_color")? {
+                        Value::Nil => 0xff5555,
+                        value => parse_color_value(value)?,
+                    };
+
+                    BlockCommand::Temperature {
+                        sensor,
+                        critical,
+                        critical_color,
+                    }
+                }
                 "Static" => {
                     let text = arg.and_then(|v| {
                         if let Value::String(s) = v {
@@ -545,6 +1491,46 @@ fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Res
                         format_full: full,
                     }
                 }
+                #[cfg(feature = "mpris")]
+                "NowPlaying" => {
+                    let paused_color = match arg {
+                        Some(Value::Table(options)) => options.get::<Value>("paused_color")?,
+                        _ => Value::Nil,
+                    };
+                    BlockCommand::NowPlaying {
+                        paused_color: match paused_color {
+                            Value::Nil => parse_color_value(color_val.clone())?,
+                            value => parse_color_value(value)?,
+                        },
+                    }
+                }
+                #[cfg(not(feature = "mpris"))]
+                "NowPlaying" => {
+                    log::warn!("Skipping now playing block: oxwm was built without the mpris feature");
+                    continue;
+                }
+                #[cfg(feature = "net-blocks")]
+                "Weather" => {
+                    let options = arg.and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    }).ok_or_else(|| mlua::Error::RuntimeError("Weather block missing location".into()))?;
+
+                    BlockCommand::Weather {
+                        location: options.get("location")?,
+                        format: options
+                            .get::<Option<String>>("weather_format")?
+                            .unwrap_or_else(|| "%c %t".to_string()),
+                    }
+                }
+                #[cfg(not(feature = "net-blocks"))]
+                "Weather" => {
+                    log::warn!("Skipping weather block: oxwm was built without the net-blocks feature");
+                    continue;
+                }
                 _ => return Err(mlua::Error::RuntimeError(format!("Unknown block type '{}'", block_type))),
             };
 
@@ -556,6 +1542,9 @@ fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Res
                 interval_secs: interval,
                 color: color_u32,
                 underline,
+                monitor,
+                name,
+                click,
             };
 
             block_configs.push(block);
@@ -608,6 +1597,15 @@ fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Res
     })?;
 
     bar_table.set("set_font", set_font)?;
+    bar_table.set("set_height", set_height)?;
+    bar_table.set("set_separator", set_separator)?;
+    bar_table.set("set_block_padding", set_block_padding)?;
+    bar_table.set("set_tag_padding", set_tag_padding)?;
+    bar_table.set("set_tag_counts", set_tag_counts)?;
+    bar_table.set("set_alpha", set_alpha)?;
+    bar_table.set("set_radius", set_radius)?;
+    bar_table.set("set_underline_thickness", set_underline_thickness)?;
+    bar_table.set("set_scroll_skip_empty", set_scroll_skip_empty)?;
     bar_table.set("block", block_table)?;
     bar_table.set("add_block", add_block)?;  // Deprecated, for backwards compatibility
     bar_table.set("set_blocks", set_blocks)?;
@@ -618,6 +1616,94 @@ fn register_bar_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Res
     Ok(())
 }
 
+fn register_theme_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<(), ConfigError> {
+    let theme_table = lua.create_table()?;
+
+    let builder_clone = builder.clone();
+    let set_file = lua.create_function(move |_, (path, roles): (String, Option<Table>)| {
+        let mut theme_roles = Vec::new();
+        if let Some(roles) = roles {
+            for pair in roles.pairs::<String, String>() {
+                let (role, name) = pair?;
+                if !THEME_ROLES.iter().any(|(known, _)| *known == role) {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "oxwm.theme.set_file: unknown role '{}'",
+                        role
+                    )));
+                }
+                theme_roles.push((role, name));
+            }
+        }
+
+        let mut b = builder_clone.borrow_mut();
+        b.theme_file = Some(path);
+        b.theme_roles = theme_roles;
+        Ok(())
+    })?;
+
+    let reload = lua.create_function(|lua, ()| {
+        create_action_table(lua, "ReloadTheme", Value::Nil)
+    })?;
+
+    theme_table.set("set_file", set_file)?;
+    theme_table.set("reload", reload)?;
+    parent.set("theme", theme_table)?;
+    Ok(())
+}
+
+fn register_screenshot_module(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<(), ConfigError> {
+    let screenshot_table = lua.create_table()?;
+
+    for mode in ["full", "window", "selection"] {
+        let action = lua.create_function(move |lua, ()| {
+            create_action_table(lua, "Screenshot", Value::String(lua.create_string(mode)?))
+        })?;
+        screenshot_table.set(mode, action)?;
+    }
+
+    let builder_clone = builder.clone();
+    let set_dir = lua.create_function(move |_, dir: String| {
+        builder_clone.borrow_mut().screenshot_dir = Some(dir);
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_save = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().screenshot_save = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_clipboard = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().screenshot_clipboard = enabled;
+        Ok(())
+    })?;
+
+    screenshot_table.set("set_dir", set_dir)?;
+    screenshot_table.set("set_save", set_save)?;
+    screenshot_table.set("set_clipboard", set_clipboard)?;
+    parent.set("screenshot", screenshot_table)?;
+    Ok(())
+}
+
+fn register_media_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError> {
+    let media_table = lua.create_table()?;
+
+    for (name, action) in [
+        ("play_pause", "MediaPlayPause"),
+        ("next", "MediaNext"),
+        ("previous", "MediaPrevious"),
+    ] {
+        let function = lua.create_function(move |lua, ()| {
+            create_action_table(lua, action, Value::Nil)
+        })?;
+        media_table.set(name, function)?;
+    }
+
+    parent.set("media", media_table)?;
+    Ok(())
+}
+
 fn register_misc(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<(), ConfigError> {
     let builder_clone = builder.clone();
     let set_terminal = lua.create_function(move |_, term: String| {
@@ -635,6 +1721,13 @@ fn register_misc(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<()
 
     let builder_clone = builder.clone();
     let set_tags = lua.create_function(move |_, tags: Vec<String>| {
+        if tags.len() > crate::client::MAX_TAGS {
+            return Err(mlua::Error::RuntimeError(format!(
+                "oxwm.set_tags: at most {} tags are supported, got {}",
+                crate::client::MAX_TAGS,
+                tags.len()
+            )));
+        }
         builder_clone.borrow_mut().tags = tags;
         Ok(())
     })?;
@@ -643,8 +1736,39 @@ fn register_misc(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<()
         create_action_table(lua, "Quit", Value::Nil)
     })?;
 
-    let restart = lua.create_function(|lua, ()| {
-        create_action_table(lua, "Restart", Value::Nil)
+    let force_quit = lua.create_function(|lua, ()| {
+        create_action_table(lua, "ForceQuit", Value::Nil)
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_quit_grace_period = lua.create_function(move |_, milliseconds: u64| {
+        builder_clone.borrow_mut().quit_grace_period = milliseconds;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_osd_timeout = lua.create_function(move |_, milliseconds: u64| {
+        builder_clone.borrow_mut().osd_timeout = milliseconds;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_log_file = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().log_file = enabled;
+        Ok(())
+    })?;
+
+    let restart = lua.create_function(|lua, options: Option<Table>| {
+        let exec = match options {
+            Some(options) => options.get::<Option<bool>>("exec")?.unwrap_or(false),
+            None => false,
+        };
+        let arg = if exec {
+            Value::String(lua.create_string("exec")?)
+        } else {
+            Value::Nil
+        };
+        create_action_table(lua, "Restart", arg)
     })?;
 
     let recompile = lua.create_function(|lua, ()| {
@@ -655,6 +1779,10 @@ fn register_misc(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<()
         create_action_table(lua, "ToggleGaps", Value::Nil)
     })?;
 
+    let toggle_focus_lock = lua.create_function(|lua, ()| {
+        create_action_table(lua, "ToggleFocusLock", Value::Nil)
+    })?;
+
     let set_master_factor = lua.create_function(|lua, delta: i32| {
         create_action_table(lua, "SetMasterFactor", Value::Integer(delta as i64))
     })?;
@@ -673,10 +1801,7 @@ fn register_misc(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<()
 
     let builder_clone = builder.clone();
     let set_layout_symbol = lua.create_function(move |_, (name, symbol): (String, String)| {
-        builder_clone.borrow_mut().layout_symbols.push(crate::LayoutSymbolOverride {
-            name,
-            symbol,
-        });
+        set_layout_symbol_override(&mut builder_clone.borrow_mut(), name, symbol);
         Ok(())
     })?;
 
@@ -686,15 +1811,34 @@ fn register_misc(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<()
         Ok(())
     })?;
 
+    let builder_clone = builder.clone();
+    let autostart_once = lua.create_function(move |_, (cmd, options): (String, Option<Table>)| {
+        let restart = match options {
+            Some(options) => options.get::<Option<bool>>("restart")?.unwrap_or(false),
+            None => false,
+        };
+        builder_clone
+            .borrow_mut()
+            .autostart_once
+            .push(crate::AutostartCommand { command: cmd, restart });
+        Ok(())
+    })?;
+
     parent.set("set_terminal", set_terminal)?;
     parent.set("set_modkey", set_modkey)?;
     parent.set("set_tags", set_tags)?;
     parent.set("set_layout_symbol", set_layout_symbol)?;
     parent.set("autostart", autostart)?;
+    parent.set("autostart_once", autostart_once)?;
     parent.set("quit", quit)?;
+    parent.set("force_quit", force_quit)?;
+    parent.set("set_quit_grace_period", set_quit_grace_period)?;
+    parent.set("set_osd_timeout", set_osd_timeout)?;
+    parent.set("set_log_file", set_log_file)?;
     parent.set("restart", restart)?;
     parent.set("recompile", recompile)?;
     parent.set("toggle_gaps", toggle_gaps)?;
+    parent.set("toggle_focus_lock", toggle_focus_lock)?;
     parent.set("set_master_factor", set_master_factor)?;
     parent.set("inc_num_master", inc_num_master)?;
     parent.set("show_keybinds", show_keybinds)?;
@@ -702,6 +1846,90 @@ fn register_misc(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<()
     Ok(())
 }
 
+/// Helpers for branching one config between machines.
+fn register_environment(lua: &Lua, parent: &Table) -> Result<(), ConfigError> {
+    let env = lua.create_function(|_, (name, default): (String, Option<String>)| {
+        Ok(std::env::var(&name).ok().or(default))
+    })?;
+
+    let hostname = lua.create_function(|_, ()| Ok(hostname()))?;
+
+    let monitor_count = lua.create_function(|_, ()| Ok(monitor_count()))?;
+
+    // Loaded on first use, so configs that never ask don't touch the display.
+    let resources: std::cell::OnceCell<Xresources> = std::cell::OnceCell::new();
+    let xresources = lua.create_function(move |_, (name, default): (String, Option<String>)| {
+        let value = resources.get_or_init(Xresources::load).get(&name);
+        Ok(value.map(str::to_string).or(default))
+    })?;
+
+    parent.set("env", env)?;
+    parent.set("hostname", hostname)?;
+    parent.set("monitor_count", monitor_count)?;
+    parent.set("xresources", xresources)?;
+    Ok(())
+}
+
+/// Read-only view of the running window manager. Empty on first start and
+/// in `oxwm --doctor`, filled in when the config is reloaded.
+fn register_state_module(lua: &Lua, parent: &Table, state: RuntimeState) -> Result<(), ConfigError> {
+    let state_table = lua.create_table()?;
+    let state = Rc::new(state);
+
+    let state_clone = state.clone();
+    let clients = lua.create_function(move |lua, ()| {
+        let table = lua.create_table()?;
+        for client in &state_clone.clients {
+            table.push(client.to_table(lua)?)?;
+        }
+        Ok(table)
+    })?;
+
+    let state_clone = state.clone();
+    let monitors = lua.create_function(move |lua, ()| {
+        let table = lua.create_table()?;
+        for (index, monitor) in state_clone.monitors.iter().enumerate() {
+            let focused = index == state_clone.selected_monitor;
+            table.push(monitor.to_table(lua, index, focused)?)?;
+        }
+        Ok(table)
+    })?;
+
+    let current_tag = lua.create_function(move |_, ()| Ok(state.current_tag()))?;
+
+    state_table.set("clients", clients)?;
+    state_table.set("monitors", monitors)?;
+    state_table.set("current_tag", current_tag)?;
+    parent.set("state", state_table)?;
+    Ok(())
+}
+
+fn hostname() -> String {
+    let mut buffer = [0u8; 256];
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if result != 0 {
+        return String::new();
+    }
+    let length = buffer.iter().position(|&byte| byte == 0).unwrap_or(buffer.len());
+    String::from_utf8_lossy(&buffer[..length]).into_owned()
+}
+
+/// Number of connected monitors on `$DISPLAY`, or 1 if there is no display
+/// to ask (e.g. when running `oxwm --doctor` over ssh).
+fn monitor_count() -> usize {
+    use crate::backend::OutputOps;
+    use x11rb::connection::Connection;
+
+    let Ok((connection, screen_number)) = x11rb::connect(None) else {
+        return 1;
+    };
+    let root = connection.setup().roots[screen_number].root;
+    connection
+        .outputs(root)
+        .map(|outputs| outputs.len().max(1))
+        .unwrap_or(1)
+}
+
 fn parse_modifiers_value(_lua: &Lua, value: Value) -> mlua::Result<Vec<KeyButMask>> {
     match value {
         Value::Table(t) => {
@@ -741,10 +1969,10 @@ fn parse_modkey_string(s: &str) -> Result<KeyButMask, ConfigError> {
 
 fn parse_keysym(key: &str) -> mlua::Result<Keysym> {
     keysyms::keysym_from_str(key)
-        .ok_or_else(|| mlua::Error::RuntimeError(format!("unknown key '{}'. valid keys include: Return, Space, A-Z, 0-9, F1-F12, Left, Right, Up, Down, etc. check oxwm.lua type definitions for the complete list", key)))
+        .ok_or_else(|| mlua::Error::RuntimeError(format!("unknown key '{}'. valid keys include: Return, Space, A-Z, 0-9, F1-F12, Left, Right, Up, Down and any X keysym name such as XF86AudioPlay or Print", key)))
 }
 
-fn parse_action_value(_lua: &Lua, value: Value) -> mlua::Result<(KeyAction, Arg)> {
+pub(crate) fn parse_action_value(_lua: &Lua, value: Value) -> mlua::Result<(KeyAction, Arg)> {
     match value {
         Value::Function(_) => {
             Err(mlua::Error::RuntimeError(
@@ -777,9 +2005,13 @@ fn string_to_action(s: &str) -> mlua::Result<KeyAction> {
         "Spawn" => Ok(KeyAction::Spawn),
         "SpawnTerminal" => Ok(KeyAction::SpawnTerminal),
         "KillClient" => Ok(KeyAction::KillClient),
+        "ForceKill" => Ok(KeyAction::ForceKill),
         "FocusStack" => Ok(KeyAction::FocusStack),
         "MoveStack" => Ok(KeyAction::MoveStack),
+        "Zoom" => Ok(KeyAction::Zoom),
+        "WindowSwitcher" => Ok(KeyAction::WindowSwitcher),
         "Quit" => Ok(KeyAction::Quit),
+        "ForceQuit" => Ok(KeyAction::ForceQuit),
         "Restart" => Ok(KeyAction::Restart),
         "Recompile" => Ok(KeyAction::Recompile),
         "ViewTag" => Ok(KeyAction::ViewTag),
@@ -787,14 +2019,41 @@ fn string_to_action(s: &str) -> mlua::Result<KeyAction> {
         "MoveToTag" => Ok(KeyAction::MoveToTag),
         "ToggleTag" => Ok(KeyAction::ToggleTag),
         "ToggleGaps" => Ok(KeyAction::ToggleGaps),
+        "AdjustGaps" => Ok(KeyAction::AdjustGaps),
+        "AdjustInnerGaps" => Ok(KeyAction::AdjustInnerGaps),
+        "AdjustOuterGaps" => Ok(KeyAction::AdjustOuterGaps),
         "SetMasterFactor" => Ok(KeyAction::SetMasterFactor),
+        "ResizeMaster" => Ok(KeyAction::ResizeMaster),
         "IncNumMaster" => Ok(KeyAction::IncNumMaster),
+        "FocusTabNext" => Ok(KeyAction::FocusTabNext),
+        "FocusTabPrev" => Ok(KeyAction::FocusTabPrev),
+        "SetOpacity" => Ok(KeyAction::SetOpacity),
         "ToggleFullScreen" => Ok(KeyAction::ToggleFullScreen),
+        "FullscreenSpan" => Ok(KeyAction::FullscreenSpan),
         "ToggleFloating" => Ok(KeyAction::ToggleFloating),
+        "ToggleFocusLock" => Ok(KeyAction::ToggleFocusLock),
+        "ToggleBar" => Ok(KeyAction::ToggleBar),
+        "ToggleMaximize" => Ok(KeyAction::ToggleMaximize),
+        "CenterWindow" => Ok(KeyAction::CenterWindow),
+        "SnapWindow" => Ok(KeyAction::SnapWindow),
+        "MediaPlayPause" => Ok(KeyAction::MediaPlayPause),
+        "MediaNext" => Ok(KeyAction::MediaNext),
+        "MediaPrevious" => Ok(KeyAction::MediaPrevious),
+        "RenameTag" => Ok(KeyAction::RenameTag),
+        "AddTag" => Ok(KeyAction::AddTag),
+        "MinimizeClient" => Ok(KeyAction::MinimizeClient),
+        "RestoreLast" => Ok(KeyAction::RestoreLast),
+        "StashWindow" => Ok(KeyAction::StashWindow),
+        "UnstashWindow" => Ok(KeyAction::UnstashWindow),
+        "EnterMode" => Ok(KeyAction::EnterMode),
+        "ExitMode" => Ok(KeyAction::ExitMode),
+        "ReloadTheme" => Ok(KeyAction::ReloadTheme),
+        "Screenshot" => Ok(KeyAction::Screenshot),
         "ChangeLayout" => Ok(KeyAction::ChangeLayout),
         "CycleLayout" => Ok(KeyAction::CycleLayout),
         "FocusMonitor" => Ok(KeyAction::FocusMonitor),
         "TagMonitor" => Ok(KeyAction::TagMonitor),
+        "TagMonitorFollow" => Ok(KeyAction::TagMonitorFollow),
         "ShowKeybindOverlay" => Ok(KeyAction::ShowKeybindOverlay),
         _ => Err(mlua::Error::RuntimeError(format!("unknown action '{}'. this is an internal error, please report it", s))),
     }
@@ -806,6 +2065,7 @@ fn value_to_arg(value: Value) -> mlua::Result<Arg> {
         Value::String(s) => Ok(Arg::Str(s.to_str()?.to_string())),
         Value::Integer(i) => Ok(Arg::Int(i as i32)),
         Value::Number(n) => Ok(Arg::Int(n as i32)),
+        Value::Table(t) if t.contains_key("cmd")? => Ok(Arg::Spawn(table_to_spawn_command(&t)?)),
         Value::Table(t) => {
             let mut arr = Vec::new();
             for i in 1..=t.len()? {
@@ -818,6 +2078,35 @@ fn value_to_arg(value: Value) -> mlua::Result<Arg> {
     }
 }
 
+fn table_to_spawn_command(table: &Table) -> mlua::Result<SpawnCommand> {
+    let argv = match table.get::<Value>("cmd")? {
+        Value::String(s) => vec![s.to_str()?.to_string()],
+        Value::Table(t) => t.sequence_values::<String>().collect::<mlua::Result<_>>()?,
+        _ => {
+            return Err(mlua::Error::RuntimeError(
+                "oxwm.spawn: cmd must be a string or an array of strings".into(),
+            ));
+        }
+    };
+    if argv.is_empty() {
+        return Err(mlua::Error::RuntimeError("oxwm.spawn: cmd must not be empty".into()));
+    }
+
+    let mut env = Vec::new();
+    if let Some(env_table) = table.get::<Option<Table>>("env")? {
+        for pair in env_table.pairs::<String, String>() {
+            env.push(pair?);
+        }
+    }
+
+    Ok(SpawnCommand {
+        argv,
+        cwd: table.get("cwd")?,
+        env,
+        shell: table.get::<Option<bool>>("shell")?.unwrap_or(false),
+    })
+}
+
 fn create_action_table(lua: &Lua, action_name: &str, arg: Value) -> mlua::Result<Table> {
     let table = lua.create_table()?;
     table.set("__action", action_name)?;
@@ -848,6 +2137,50 @@ fn parse_color_value(value: Value) -> mlua::Result<u32> {
     }
 }
 
+/// Parses a border color entry of oxwm.border.set_style: either a single
+/// color or an `{inner, outer}` pair for a gradient.
+fn parse_border_colors(value: Value) -> mlua::Result<(Option<u32>, Option<crate::BorderGradient>)> {
+    match value {
+        Value::Nil => Ok((None, None)),
+        Value::Table(colors) => {
+            let inner = parse_color_value(colors.get(1)?)?;
+            let outer = parse_color_value(colors.get(2)?)?;
+            Ok((Some(inner), Some(crate::BorderGradient { inner, outer })))
+        }
+        value => Ok((Some(parse_color_value(value)?), None)),
+    }
+}
+
+/// Reads a block's `on_click`: either a function receiving the click or a
+/// table of actions keyed by button name.
+fn parse_block_click(lua: &Lua, value: Value) -> mlua::Result<Option<crate::bar::BlockClick>> {
+    use crate::bar::{BlockClick, CLICK_BUTTONS};
+
+    match value {
+        Value::Nil => Ok(None),
+        Value::Function(function) => Ok(Some(BlockClick::Callback(lua.clone(), function))),
+        Value::Table(table) => {
+            let mut actions = Vec::new();
+            for pair in table.pairs::<String, Value>() {
+                let (button, action) = pair?;
+                if !CLICK_BUTTONS.contains(&button.as_str()) {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "oxwm.bar.block: unknown button '{}' in 'on_click'. valid buttons: {}",
+                        button,
+                        CLICK_BUTTONS.join(", ")
+                    )));
+                }
+                let (action, arg) = parse_action_value(lua, action)?;
+                actions.push((button, action, arg));
+            }
+            Ok(Some(BlockClick::Actions(actions)))
+        }
+        _ => Err(mlua::Error::RuntimeError(
+            "oxwm.bar.block: 'on_click' must be a function or a table of actions".into(),
+        )),
+    }
+}
+
 fn create_block_config(lua: &Lua, config: Table, block_type: &str, arg: Option<Value>) -> mlua::Result<Table> {
     let table = lua.create_table()?;
     table.set("__block_type", block_type)?;
@@ -856,11 +2189,17 @@ fn create_block_config(lua: &Lua, config: Table, block_type: &str, arg: Option<V
     let interval: u64 = config.get("interval")?;
     let color: Value = config.get("color")?;
     let underline: bool = config.get("underline").unwrap_or(false);
+    let monitor: Option<usize> = config.get("monitor")?;
+    let name: Option<String> = config.get("name")?;
+    let on_click: Value = config.get("on_click")?;
 
     table.set("format", format)?;
     table.set("interval", interval)?;
     table.set("color", color)?;
     table.set("underline", underline)?;
+    table.set("monitor", monitor)?;
+    table.set("name", name)?;
+    table.set("on_click", on_click)?;
 
     if let Some(arg_val) = arg {
         table.set("__arg", arg_val)?;
@@ -1,5 +1,11 @@
 mod lua;
 mod lua_api;
+mod state;
+mod theme;
+mod xresources;
 
 pub use lua::parse_lua_config;
+pub(crate) use lua_api::parse_action_value;
+pub use state::{ClientState, MonitorState, RuntimeState};
+pub use theme::apply_theme;
 
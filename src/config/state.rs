@@ -0,0 +1,71 @@
+use mlua::{Lua, Table};
+
+/// Snapshot of the running window manager handed to the config on reload,
+/// read through `oxwm.state`.
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeState {
+    pub clients: Vec<ClientState>,
+    pub monitors: Vec<MonitorState>,
+    pub selected_monitor: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientState {
+    pub window: u32,
+    pub class: String,
+    pub instance: String,
+    pub title: String,
+    /// 0-based indices of the tags the client is on.
+    pub tags: Vec<usize>,
+    pub floating: bool,
+    pub monitor: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct MonitorState {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub name: Option<String>,
+    /// 0-based indices of the tags being viewed.
+    pub tags: Vec<usize>,
+}
+
+impl RuntimeState {
+    /// The first tag viewed on the selected monitor.
+    pub fn current_tag(&self) -> Option<usize> {
+        self.monitors
+            .get(self.selected_monitor)
+            .and_then(|monitor| monitor.tags.first().copied())
+    }
+}
+
+impl ClientState {
+    pub(super) fn to_table(&self, lua: &Lua) -> mlua::Result<Table> {
+        let table = lua.create_table()?;
+        table.set("window", self.window)?;
+        table.set("class", self.class.as_str())?;
+        table.set("instance", self.instance.as_str())?;
+        table.set("title", self.title.as_str())?;
+        table.set("tags", self.tags.clone())?;
+        table.set("floating", self.floating)?;
+        table.set("monitor", self.monitor)?;
+        Ok(table)
+    }
+}
+
+impl MonitorState {
+    pub(super) fn to_table(&self, lua: &Lua, index: usize, focused: bool) -> mlua::Result<Table> {
+        let table = lua.create_table()?;
+        table.set("index", index)?;
+        table.set("x", self.x)?;
+        table.set("y", self.y)?;
+        table.set("width", self.width)?;
+        table.set("height", self.height)?;
+        table.set("name", self.name.as_deref())?;
+        table.set("tags", self.tags.clone())?;
+        table.set("focused", focused)?;
+        Ok(table)
+    }
+}
@@ -0,0 +1,76 @@
+use crate::Config;
+
+use super::xresources::Xresources;
+
+/// Every color a theme file can set, with the pywal color it is read from
+/// unless the config maps the role to another name.
+pub const THEME_ROLES: [(&str, &str); 12] = [
+    ("border_focused", "color4"),
+    ("border_unfocused", "color0"),
+    ("border_urgent", "color1"),
+    ("normal_fg", "foreground"),
+    ("normal_bg", "background"),
+    ("normal_underline", "color0"),
+    ("occupied_fg", "color4"),
+    ("occupied_bg", "background"),
+    ("occupied_underline", "color4"),
+    ("selected_fg", "color6"),
+    ("selected_bg", "background"),
+    ("selected_underline", "color6"),
+];
+
+/// Reads `config.theme_file` and overwrites the border and bar colors with
+/// the ones it defines. Roles the file has no color for are left alone.
+pub fn apply_theme(config: &mut Config) -> Result<(), String> {
+    let Some(path) = &config.theme_file else {
+        return Ok(());
+    };
+    let path = crate::keyboard::handlers::expand_home(path);
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|error| format!("Failed to read theme {}: {}", path.display(), error))?;
+
+    let colors = if path.extension().is_some_and(|extension| extension == "json") {
+        Xresources::parse_json(&contents)
+    } else {
+        Xresources::parse(&contents)
+    };
+
+    for (role, default_name) in THEME_ROLES {
+        let name = config
+            .theme_roles
+            .iter()
+            .find(|(mapped_role, _)| mapped_role == role)
+            .map(|(_, name)| name.as_str())
+            .unwrap_or(default_name);
+        let Some(color) = colors.get(name).and_then(parse_hex_color) else {
+            continue;
+        };
+
+        match role {
+            "border_focused" => config.border_focused = color,
+            "border_unfocused" => config.border_unfocused = color,
+            "border_urgent" => config.border_urgent = Some(color),
+            "normal_fg" => config.scheme_normal.foreground = color,
+            "normal_bg" => config.scheme_normal.background = color,
+            "normal_underline" => config.scheme_normal.underline = color,
+            "occupied_fg" => config.scheme_occupied.foreground = color,
+            "occupied_bg" => config.scheme_occupied.background = color,
+            "occupied_underline" => config.scheme_occupied.underline = color,
+            "selected_fg" => config.scheme_selected.foreground = color,
+            "selected_bg" => config.scheme_selected.background = color,
+            "selected_underline" => config.scheme_selected.underline = color,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_hex_color(value: &str) -> Option<u32> {
+    // Xresources colors may carry an alpha prefix, e.g. [90]#1a1b26.
+    let hex = value.trim().rsplit('#').next()?;
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
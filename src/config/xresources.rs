@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+
+/// X resources as loaded by `xrdb`, read from the RESOURCE_MANAGER property
+/// of the root window. Falls back to parsing `~/.Xresources` directly when
+/// there is no display or nothing was loaded.
+pub struct Xresources {
+    entries: HashMap<String, String>,
+}
+
+impl Xresources {
+    pub fn load() -> Self {
+        let contents = resource_manager()
+            .or_else(|| {
+                let path = dirs::home_dir()?.join(".Xresources");
+                std::fs::read_to_string(path).ok()
+            })
+            .unwrap_or_default();
+
+        Self::parse(&contents)
+    }
+
+    /// Parses `name: value` lines, skipping comments and preprocessor lines.
+    pub fn parse(contents: &str) -> Self {
+        let entries = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('!') && !line.starts_with('#'))
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Self { entries }
+    }
+
+    /// Collects every `"name": "value"` string pair of a JSON document,
+    /// ignoring nesting. Enough for pywal's colors.json, where names like
+    /// `color4` and `background` are unique.
+    pub fn parse_json(contents: &str) -> Self {
+        let mut entries = HashMap::new();
+        let mut name: Option<&str> = None;
+        let mut rest = contents;
+
+        while let Some(start) = rest.find('"') {
+            let before = &rest[..start];
+            let after = &rest[start + 1..];
+            let Some(end) = after.find('"') else {
+                break;
+            };
+            let string = &after[..end];
+
+            // A string directly after `"name":` is that name's value,
+            // anything else starts a new name.
+            match name.take() {
+                Some(name) if before.trim() == ":" => {
+                    entries.insert(name.to_string(), string.to_string());
+                }
+                _ => name = Some(string),
+            }
+            rest = &after[end + 1..];
+        }
+
+        Self { entries }
+    }
+
+    /// Looks up `name`, matching it exactly first and then against wildcard
+    /// entries, so `color4` finds `*color4` and `*.color4`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        if let Some(value) = self.entries.get(name) {
+            return Some(value);
+        }
+        ["*", "*."]
+            .iter()
+            .find_map(|prefix| self.entries.get(&format!("{}{}", prefix, name)))
+            .map(String::as_str)
+    }
+}
+
+fn resource_manager() -> Option<String> {
+    let (connection, screen_number) = x11rb::connect(None).ok()?;
+    let root = connection.setup().roots[screen_number].root;
+    let reply = connection
+        .get_property(
+            false,
+            root,
+            AtomEnum::RESOURCE_MANAGER,
+            AtomEnum::STRING,
+            0,
+            u32::MAX / 4,
+        )
+        .ok()?
+        .reply()
+        .ok()?;
+
+    if reply.value.is_empty() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&reply.value).into_owned())
+}
@@ -0,0 +1,237 @@
+use std::path::Path;
+use std::process::Command;
+
+use x11rb::connection::Connection;
+use x11rb::protocol::randr::ConnectionExt as _;
+use x11rb::protocol::xproto::{ChangeWindowAttributesAux, ConnectionExt as _, EventMask};
+
+use crate::keyboard::{Arg, KeyAction};
+
+struct Report {
+    problems: usize,
+}
+
+impl Report {
+    fn ok(&self, message: &str) {
+        println!("  ✓ {}", message);
+    }
+
+    fn problem(&mut self, message: &str, hint: &str) {
+        self.problems += 1;
+        println!("  ✗ {}", message);
+        println!("      hint: {}", hint);
+    }
+}
+
+pub fn run(config_path: &Path) -> bool {
+    let mut report = Report { problems: 0 };
+
+    println!("oxwm {} doctor\n", env!("CARGO_PKG_VERSION"));
+
+    println!("Display:");
+    check_display(&mut report);
+
+    println!("\nConfig:");
+    let config = check_config(&mut report, config_path);
+
+    if let Some(config) = &config {
+        println!("\nFont:");
+        check_font(&mut report, &config.font);
+        for fallback in &config.font_fallbacks {
+            check_font(&mut report, fallback);
+        }
+
+        println!("\nPrograms:");
+        check_programs(&mut report, config);
+    }
+
+    println!();
+    if report.problems == 0 {
+        println!("No problems found.");
+    } else {
+        println!("{} problem(s) found.", report.problems);
+    }
+
+    report.problems == 0
+}
+
+fn check_display(report: &mut Report) {
+    match std::env::var("DISPLAY") {
+        Ok(display) if !display.is_empty() => report.ok(&format!("DISPLAY is set to {}", display)),
+        _ => {
+            report.problem(
+                "DISPLAY is not set",
+                "start oxwm from your .xinitrc (startx) or a display manager session",
+            );
+            return;
+        }
+    }
+
+    let (connection, screen_number) = match x11rb::connect(None) {
+        Ok(connection) => connection,
+        Err(error) => {
+            report.problem(
+                &format!("Cannot connect to the X server: {}", error),
+                "check that the X server is running and that XAUTHORITY points to a valid cookie (try `xauth list`)",
+            );
+            return;
+        }
+    };
+    report.ok("Connected to the X server");
+
+    let root = connection.setup().roots[screen_number].root;
+    let another_wm_running = connection
+        .change_window_attributes(
+            root,
+            &ChangeWindowAttributesAux::new().event_mask(EventMask::SUBSTRUCTURE_REDIRECT),
+        )
+        .ok()
+        .is_none_or(|cookie| cookie.check().is_err());
+
+    if another_wm_running {
+        report.problem(
+            "Another window manager is running",
+            "stop it before starting oxwm (this is expected if you run --doctor from inside oxwm)",
+        );
+    } else {
+        report.ok("No other window manager is running");
+    }
+
+    let randr_version = connection
+        .randr_query_version(1, 2)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok());
+
+    match randr_version {
+        Some(version) => report.ok(&format!(
+            "RandR {}.{} is available",
+            version.major_version, version.minor_version
+        )),
+        None => report.problem(
+            "RandR is not available",
+            "monitor names for oxwm.monitor.set_order will not be detected; check your X server/driver",
+        ),
+    }
+}
+
+fn check_config(report: &mut Report, config_path: &Path) -> Option<crate::Config> {
+    let config_string = match std::fs::read_to_string(config_path) {
+        Ok(config_string) => config_string,
+        Err(error) => {
+            report.problem(
+                &format!("Cannot read {:?}: {}", config_path, error),
+                "run `oxwm --init` to create a default config",
+            );
+            return None;
+        }
+    };
+
+    match crate::config::parse_lua_config(&config_string, Some(config_path), Default::default()) {
+        Ok(config) => {
+            report.ok(&format!("{:?} parsed successfully", config_path));
+            Some(config)
+        }
+        Err(error) => {
+            report.problem(
+                &format!("{:?} failed to parse: {}", config_path, error),
+                "fix the error above; oxwm will fall back to the default config until then",
+            );
+            None
+        }
+    }
+}
+
+fn check_font(report: &mut Report, font: &str) {
+    let display = unsafe { x11::xlib::XOpenDisplay(std::ptr::null()) };
+    if display.is_null() {
+        report.problem(
+            &format!("Cannot load font '{}' without a display", font),
+            "fix the display problems above first",
+        );
+        return;
+    }
+
+    let screen = unsafe { x11::xlib::XDefaultScreen(display) };
+    match crate::bar::font::Font::new(display, screen, font, &[]) {
+        Ok(_) => report.ok(&format!("Font '{}' loaded", font)),
+        Err(error) => report.problem(
+            &format!("{}", error),
+            "install the font or change oxwm.bar.set_font in your config",
+        ),
+    }
+    unsafe { x11::xlib::XCloseDisplay(display) };
+
+    let requested_family = font.split(':').next().unwrap_or_default().trim();
+    let matched_family = Command::new("fc-match")
+        .args(["-f", "%{family}", font])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned());
+
+    if let Some(matched_family) = matched_family {
+        let is_generic_family = matches!(requested_family, "monospace" | "sans" | "sans-serif" | "serif");
+        if !is_generic_family
+            && !matched_family
+                .to_lowercase()
+                .contains(&requested_family.to_lowercase())
+        {
+            report.problem(
+                &format!("Font family '{}' is not installed, falling back to '{}'", requested_family, matched_family),
+                "install the font (check with `fc-list`) or change oxwm.bar.set_font in your config",
+            );
+        }
+    }
+}
+
+fn check_programs(report: &mut Report, config: &crate::Config) {
+    let mut programs: Vec<String> = vec![config.terminal.clone()];
+
+    for keybinding in &config.keybindings {
+        if keybinding.func != KeyAction::Spawn {
+            continue;
+        }
+        let program = match &keybinding.arg {
+            Arg::Str(command) => command.split_whitespace().next().map(str::to_string),
+            Arg::Array(command) => command.first().cloned(),
+            Arg::Spawn(spawn) if !spawn.shell => spawn.argv.first().cloned(),
+            _ => None,
+        };
+        if let Some(program) = program.filter(|program| !programs.contains(program)) {
+            programs.push(program);
+        }
+    }
+
+    for program in programs {
+        let program_name = program.split_whitespace().next().unwrap_or_default();
+        if program_name.is_empty() {
+            continue;
+        }
+        if is_executable_in_path(program_name) {
+            report.ok(&format!("{} found", program_name));
+        } else {
+            report.problem(
+                &format!("{} is referenced in your config but was not found in PATH", program_name),
+                "install it or update the keybinding/terminal in your config",
+            );
+        }
+    }
+}
+
+fn is_executable_in_path(program: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    let is_executable = |path: &Path| {
+        path.metadata()
+            .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    };
+
+    if program.contains('/') {
+        return is_executable(Path::new(program));
+    }
+
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).any(|directory| is_executable(&directory.join(program))))
+        .unwrap_or(false)
+}
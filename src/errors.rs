@@ -16,6 +16,7 @@ pub enum X11Error {
     ReplyError(x11rb::errors::ReplyError),
     ReplyOrIdError(x11rb::errors::ReplyOrIdError),
     DisplayOpenFailed,
+    AnotherWmRunning,
     FontLoadFailed(String),
     DrawCreateFailed,
 }
@@ -62,6 +63,10 @@ impl std::fmt::Display for X11Error {
             Self::ReplyError(err) => write!(f, "{}", err),
             Self::ReplyOrIdError(err) => write!(f, "{}", err),
             Self::DisplayOpenFailed => write!(f, "failed to open X11 display"),
+            Self::AnotherWmRunning => write!(
+                f,
+                "another window manager is already running (use oxwm --test to try a config inside Xephyr)"
+            ),
             Self::FontLoadFailed(font_name) => write!(f, "failed to load Xft font: {}", font_name),
             Self::DrawCreateFailed => write!(f, "failed to create XftDraw"),
         }
@@ -1,10 +1,13 @@
+use std::collections::HashMap;
 use std::io::{ErrorKind, Result};
 use std::process::Command;
 
 use serde::Deserialize;
 use x11rb::connection::Connection;
+use x11rb::protocol::xkb::{self, ConnectionExt as _};
 use x11rb::protocol::xproto::*;
 
+use crate::backend::InputOps;
 use crate::errors::X11Error;
 use crate::keyboard::keysyms::{self, Keysym};
 
@@ -13,9 +16,13 @@ pub enum KeyAction {
     Spawn,
     SpawnTerminal,
     KillClient,
+    ForceKill,
     FocusStack,
     MoveStack,
+    Zoom,
+    WindowSwitcher,
     Quit,
+    ForceQuit,
     Restart,
     Recompile,
     ViewTag,
@@ -23,15 +30,42 @@ pub enum KeyAction {
     MoveToTag,
     ToggleTag,
     ToggleGaps,
+    AdjustGaps,
+    AdjustInnerGaps,
+    AdjustOuterGaps,
     ToggleFullScreen,
+    FullscreenSpan,
     ToggleFloating,
+    ToggleMaximize,
+    CenterWindow,
+    SnapWindow,
+    MediaPlayPause,
+    MediaNext,
+    MediaPrevious,
+    RenameTag,
+    AddTag,
+    ToggleFocusLock,
+    ToggleBar,
+    MinimizeClient,
+    RestoreLast,
+    StashWindow,
+    UnstashWindow,
+    EnterMode,
+    ExitMode,
+    ReloadTheme,
+    Screenshot,
     ChangeLayout,
     CycleLayout,
     FocusMonitor,
     TagMonitor,
+    TagMonitorFollow,
     ShowKeybindOverlay,
     SetMasterFactor,
+    ResizeMaster,
     IncNumMaster,
+    FocusTabNext,
+    FocusTabPrev,
+    SetOpacity,
     None,
 }
 
@@ -41,6 +75,46 @@ pub enum Arg {
     Int(i32),
     Str(String),
     Array(Vec<String>),
+    Spawn(SpawnCommand),
+}
+
+/// A structured spawn from `oxwm.spawn{ cmd = ..., cwd = ..., env = ..., shell = ... }`.
+#[derive(Debug, Clone, Default)]
+pub struct SpawnCommand {
+    pub argv: Vec<String>,
+    pub cwd: Option<String>,
+    pub env: Vec<(String, String)>,
+    pub shell: bool,
+}
+
+impl SpawnCommand {
+    fn to_command(&self) -> Option<Command> {
+        let mut command = if self.shell {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(self.argv.join(" "));
+            command
+        } else {
+            let (program, args) = self.argv.split_first()?;
+            let mut command = Command::new(program);
+            command.args(args);
+            command
+        };
+
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(expand_home(cwd));
+        }
+        command.envs(self.env.iter().map(|(key, value)| (key, value)));
+        Some(command)
+    }
+}
+
+pub(crate) fn expand_home(path: &str) -> std::path::PathBuf {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => std::path::PathBuf::from(path),
+    }
 }
 
 impl Arg {
@@ -55,16 +129,36 @@ pub struct KeyPress {
     pub(crate) keysym: Keysym,
 }
 
+/// When a binding fires.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyTrigger {
+    #[default]
+    Press,
+    Release,
+    /// Fires on press and again on release, so toggles like the keybind
+    /// overlay stay active only while the key is held.
+    Hold,
+}
+
 #[derive(Clone)]
 pub struct KeyBinding {
     pub(crate) keys: Vec<KeyPress>,
     pub(crate) func: KeyAction,
     pub(crate) arg: Arg,
+    /// Shown in the keybind overlay in place of the action name.
+    pub(crate) description: Option<String>,
+    pub(crate) trigger: KeyTrigger,
 }
 
 impl KeyBinding {
     pub fn new(keys: Vec<KeyPress>, func: KeyAction, arg: Arg) -> Self {
-        Self { keys, func, arg }
+        Self {
+            keys,
+            func,
+            arg,
+            description: None,
+            trigger: KeyTrigger::Press,
+        }
     }
 
     pub fn single_key(
@@ -77,8 +171,20 @@ impl KeyBinding {
             keys: vec![KeyPress { modifiers, keysym }],
             func,
             arg,
+            description: None,
+            trigger: KeyTrigger::Press,
         }
     }
+
+    pub fn with_description(mut self, description: Option<String>) -> Self {
+        self.description = description;
+        self
+    }
+
+    pub fn with_trigger(mut self, trigger: KeyTrigger) -> Self {
+        self.trigger = trigger;
+        self
+    }
 }
 
 pub type Key = KeyBinding;
@@ -94,6 +200,8 @@ pub enum KeychordState {
 
 pub enum KeychordResult {
     Completed(KeyAction, Arg),
+    /// A hold binding was pressed; its action runs again on release.
+    Held(KeyAction, Arg),
     InProgress(Vec<usize>),
     None,
     Cancelled,
@@ -109,9 +217,46 @@ pub struct KeyboardMapping {
     pub syms: Vec<Keysym>,
     pub keysyms_per_keycode: u8,
     pub min_keycode: Keycode,
+    /// Modifier bit NumLock is mapped to, usually Mod2.
+    pub numlock_mask: u16,
+    keycodes: HashMap<Keysym, Vec<Keycode>>,
+    /// Modifier bits set by each modifier key, e.g. Super_L to Mod4.
+    modifier_keys: HashMap<Keycode, u16>,
 }
 
 impl KeyboardMapping {
+    /// Caps Lock and NumLock combinations that are grabbed alongside every
+    /// binding so they work regardless of lock state.
+    pub fn lock_masks(&self) -> [u16; 4] {
+        let caps_lock = u16::from(ModMask::LOCK);
+        [0, caps_lock, self.numlock_mask, caps_lock | self.numlock_mask]
+    }
+
+    /// Strips Caps Lock and NumLock from an event state.
+    pub fn clean_state(&self, state: u16) -> u16 {
+        state & !(u16::from(ModMask::LOCK) | self.numlock_mask)
+    }
+
+    /// Like `clean_state`, but also drops the modifier the released key
+    /// itself sets, which is still part of the state of its release event.
+    pub fn release_state(&self, keycode: Keycode, state: u16) -> u16 {
+        let own_modifier = self.modifier_keys.get(&keycode).copied().unwrap_or(0);
+        self.clean_state(state) & !own_modifier
+    }
+
+    /// The keysym a key press produces. Keypad keys give their NumLock
+    /// symbol, e.g. KP_1 instead of KP_End, while NumLock is on.
+    pub fn event_keysym(&self, keycode: Keycode, state: u16) -> Keysym {
+        let keysym = self.keycode_to_keysym(keycode);
+        if state & self.numlock_mask == 0 {
+            return keysym;
+        }
+        match self.keycode_to_shifted_keysym(keycode) {
+            shifted if keysyms::is_keypad(shifted) => shifted,
+            _ => keysym,
+        }
+    }
+
     pub fn keycode_to_keysym(&self, keycode: Keycode) -> Keysym {
         if keycode < self.min_keycode {
             return 0;
@@ -120,16 +265,21 @@ impl KeyboardMapping {
         self.syms.get(index).copied().unwrap_or(0)
     }
 
-    pub fn find_keycode(&self, keysym: Keysym, min_keycode: Keycode, max_keycode: Keycode) -> Option<Keycode> {
-        for keycode in min_keycode..=max_keycode {
-            let index = (keycode - self.min_keycode) as usize * self.keysyms_per_keycode as usize;
-            if let Some(&sym) = self.syms.get(index) {
-                if sym == keysym {
-                    return Some(keycode);
-                }
-            }
+    /// The keysym produced by `keycode` while Shift is held, falling back to
+    /// the unshifted one for keys without a shifted symbol.
+    pub fn keycode_to_shifted_keysym(&self, keycode: Keycode) -> Keysym {
+        if keycode < self.min_keycode || self.keysyms_per_keycode < 2 {
+            return self.keycode_to_keysym(keycode);
+        }
+        let index = (keycode - self.min_keycode) as usize * self.keysyms_per_keycode as usize + 1;
+        match self.syms.get(index).copied() {
+            Some(keysym) if keysym != 0 => keysym,
+            _ => self.keycode_to_keysym(keycode),
         }
-        None
+    }
+
+    pub fn keysym_to_keycodes(&self, keysym: Keysym) -> &[Keycode] {
+        self.keycodes.get(&keysym).map(Vec::as_slice).unwrap_or_default()
     }
 }
 
@@ -144,72 +294,132 @@ pub fn get_keyboard_mapping(
         .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)?
         .reply()?;
 
+    let mut keycodes: HashMap<Keysym, Vec<Keycode>> = HashMap::new();
+    if mapping.keysyms_per_keycode > 0 {
+        for (offset, syms) in mapping
+            .keysyms
+            .chunks(mapping.keysyms_per_keycode as usize)
+            .enumerate()
+        {
+            let keycode = min_keycode + offset as Keycode;
+            if syms[0] != 0 {
+                keycodes.entry(syms[0]).or_default().push(keycode);
+            }
+            // Keypad digits live in the NumLock column.
+            let numlock_sym = syms
+                .get(1)
+                .copied()
+                .filter(|&sym| keysyms::is_keypad(sym) && sym != syms[0]);
+            if let Some(numlock_sym) = numlock_sym {
+                keycodes.entry(numlock_sym).or_default().push(keycode);
+            }
+        }
+    }
+
+    let modifier_keys = get_modifier_keys(connection)?;
+    let numlock_mask = keycodes
+        .get(&keysyms::XK_NUM_LOCK)
+        .and_then(|numlock_keycodes| {
+            numlock_keycodes
+                .iter()
+                .find_map(|keycode| modifier_keys.get(keycode).copied())
+        })
+        .unwrap_or(u16::from(ModMask::M2));
+
     Ok(KeyboardMapping {
         syms: mapping.keysyms,
         keysyms_per_keycode: mapping.keysyms_per_keycode,
         min_keycode,
+        numlock_mask,
+        keycodes,
+        modifier_keys,
     })
 }
 
-pub fn grab_keys(
+/// Maps every keycode in the modifier mapping to the modifier bits it sets.
+fn get_modifier_keys(
     connection: &impl Connection,
+) -> std::result::Result<HashMap<Keycode, u16>, X11Error> {
+    let modifiers = connection.get_modifier_mapping()?.reply()?;
+    let per_modifier = modifiers.keycodes_per_modifier() as usize;
+    let mut modifier_keys: HashMap<Keycode, u16> = HashMap::new();
+    if per_modifier == 0 {
+        return Ok(modifier_keys);
+    }
+
+    for (index, &keycode) in modifiers.keycodes.iter().enumerate() {
+        if keycode != 0 {
+            *modifier_keys.entry(keycode).or_default() |= 1u16 << (index / per_modifier);
+        }
+    }
+    Ok(modifier_keys)
+}
+
+/// Asks the server for XKB notifications about keyboard layout changes and
+/// newly plugged in keyboards. Returns false if XKB is not available, in
+/// which case only core MappingNotify events are delivered.
+pub fn select_keyboard_events(connection: &impl Connection) -> std::result::Result<bool, X11Error> {
+    let Ok(cookie) = connection.xkb_use_extension(1, 0) else {
+        return Ok(false);
+    };
+    if !cookie.reply().is_ok_and(|reply| reply.supported) {
+        return Ok(false);
+    }
+
+    connection.xkb_select_events(
+        xkb::ID::USE_CORE_KBD.into(),
+        xkb::EventType::from(0u16),
+        xkb::EventType::NEW_KEYBOARD_NOTIFY | xkb::EventType::MAP_NOTIFY,
+        xkb::MapPart::from(0u16),
+        xkb::MapPart::from(0u16),
+        &xkb::SelectEventsAux::new(),
+    )?;
+
+    // Without this, a held key sends a release before every repeated press
+    // and hold bindings would flicker.
+    connection.xkb_per_client_flags(
+        xkb::ID::USE_CORE_KBD.into(),
+        xkb::PerClientFlag::DETECTABLE_AUTO_REPEAT,
+        xkb::PerClientFlag::DETECTABLE_AUTO_REPEAT,
+        xkb::BoolCtrl::from(0u32),
+        xkb::BoolCtrl::from(0u32),
+        xkb::BoolCtrl::from(0u32),
+    )?;
+    Ok(true)
+}
+
+pub fn grab_keys(
+    connection: &(impl Connection + InputOps<Error = X11Error>),
     root: Window,
     keybindings: &[KeyBinding],
     current_key: usize,
-) -> std::result::Result<KeyboardMapping, X11Error> {
-    let setup = connection.setup();
-    let min_keycode = setup.min_keycode;
-    let max_keycode = setup.max_keycode;
-
-    let mapping = get_keyboard_mapping(connection)?;
-
-    connection.ungrab_key(x11rb::protocol::xproto::Grab::ANY, root, ModMask::ANY)?;
+    mapping: &KeyboardMapping,
+) -> std::result::Result<(), X11Error> {
+    connection.ungrab_all_keys(root)?;
 
-    let modifiers = [
-        0u16,
-        u16::from(ModMask::LOCK),
-        u16::from(ModMask::M2),
-        u16::from(ModMask::LOCK | ModMask::M2),
-    ];
+    let modifiers = mapping.lock_masks();
 
-    for keycode in min_keycode..=max_keycode {
-        for keybinding in keybindings {
-            if current_key >= keybinding.keys.len() {
-                continue;
-            }
+    for keybinding in keybindings {
+        let Some(key) = keybinding.keys.get(current_key) else {
+            continue;
+        };
 
-            let key = &keybinding.keys[current_key];
-            if key.keysym == mapping.keycode_to_keysym(keycode) {
-                let modifier_mask = modifiers_to_mask(&key.modifiers);
-                for &ignore_mask in &modifiers {
-                    connection.grab_key(
-                        true,
-                        root,
-                        (modifier_mask | ignore_mask).into(),
-                        keycode,
-                        GrabMode::ASYNC,
-                        GrabMode::ASYNC,
-                    )?;
-                }
+        let modifier_mask = modifiers_to_mask(&key.modifiers);
+        for &keycode in mapping.keysym_to_keycodes(key.keysym) {
+            for &ignore_mask in &modifiers {
+                connection.grab_key_combination(root, modifier_mask | ignore_mask, keycode)?;
             }
         }
     }
 
     if current_key > 0 {
-        if let Some(escape_keycode) = mapping.find_keycode(keysyms::XK_ESCAPE, min_keycode, max_keycode) {
-            connection.grab_key(
-                true,
-                root,
-                ModMask::ANY,
-                escape_keycode,
-                GrabMode::ASYNC,
-                GrabMode::ASYNC,
-            )?;
+        for &escape_keycode in mapping.keysym_to_keycodes(keysyms::XK_ESCAPE) {
+            connection.grab_key_combination(root, u16::from(ModMask::ANY), escape_keycode)?;
         }
     }
 
     connection.flush()?;
-    Ok(mapping)
+    Ok(())
 }
 
 pub fn handle_key_press(
@@ -218,7 +428,8 @@ pub fn handle_key_press(
     keychord_state: &KeychordState,
     mapping: &KeyboardMapping,
 ) -> KeychordResult {
-    let keysym = mapping.keycode_to_keysym(event.detail);
+    let keysym = mapping.event_keysym(event.detail, u16::from(event.state));
+    let clean_state = mapping.clean_state(u16::from(event.state));
 
     if keysym == keysyms::XK_ESCAPE {
         return match keychord_state {
@@ -228,23 +439,41 @@ pub fn handle_key_press(
     }
 
     match keychord_state {
-        KeychordState::Idle => handle_first_key(event, keysym, keybindings),
+        KeychordState::Idle => handle_first_key(clean_state, keysym, keybindings),
         KeychordState::InProgress {
             candidates,
             keys_pressed,
-        } => handle_next_key(event, keysym, keybindings, candidates, *keys_pressed),
+        } => handle_next_key(clean_state, keysym, keybindings, candidates, *keys_pressed),
     }
 }
 
+/// Finds the release binding matching a key release. Chords only ever
+/// trigger on press.
+pub fn handle_key_release(
+    event: KeyReleaseEvent,
+    keybindings: &[KeyBinding],
+    mapping: &KeyboardMapping,
+) -> Option<(KeyAction, Arg)> {
+    let keysym = mapping.event_keysym(event.detail, u16::from(event.state));
+    let state = mapping.release_state(event.detail, u16::from(event.state));
+
+    keybindings
+        .iter()
+        .filter(|keybinding| keybinding.trigger == KeyTrigger::Release)
+        .find(|keybinding| match keybinding.keys.as_slice() {
+            [key] => key.keysym == keysym && modifiers_to_mask(&key.modifiers) == state,
+            _ => false,
+        })
+        .map(|keybinding| (keybinding.func, keybinding.arg.clone()))
+}
+
 fn handle_first_key(
-    event: KeyPressEvent,
+    clean_state: u16,
     event_keysym: Keysym,
     keybindings: &[KeyBinding],
 ) -> KeychordResult {
     let mut candidates = Vec::new();
 
-    let clean_state = event.state & !(u16::from(ModMask::LOCK) | u16::from(ModMask::M2));
-
     for (keybinding_index, keybinding) in keybindings.iter().enumerate() {
         if keybinding.keys.is_empty() {
             continue;
@@ -253,9 +482,16 @@ fn handle_first_key(
         let first_key = &keybinding.keys[0];
         let modifier_mask = modifiers_to_mask(&first_key.modifiers);
 
-        if event_keysym == first_key.keysym && clean_state == modifier_mask.into() {
+        if keybinding.trigger == KeyTrigger::Release {
+            continue;
+        }
+
+        if event_keysym == first_key.keysym && clean_state == modifier_mask {
             if keybinding.keys.len() == 1 {
-                return KeychordResult::Completed(keybinding.func, keybinding.arg.clone());
+                return match keybinding.trigger {
+                    KeyTrigger::Hold => KeychordResult::Held(keybinding.func, keybinding.arg.clone()),
+                    _ => KeychordResult::Completed(keybinding.func, keybinding.arg.clone()),
+                };
             } else {
                 candidates.push(keybinding_index);
             }
@@ -270,7 +506,7 @@ fn handle_first_key(
 }
 
 fn handle_next_key(
-    event: KeyPressEvent,
+    clean_state: u16,
     event_keysym: Keysym,
     keybindings: &[KeyBinding],
     candidates: &[usize],
@@ -278,8 +514,6 @@ fn handle_next_key(
 ) -> KeychordResult {
     let mut new_candidates = Vec::new();
 
-    let clean_state = event.state & !(u16::from(ModMask::LOCK) | u16::from(ModMask::M2));
-
     for &candidate_index in candidates {
         let keybinding = &keybindings[candidate_index];
 
@@ -293,7 +527,7 @@ fn handle_next_key(
         let modifiers_match = if next_key.modifiers.is_empty() {
             true
         } else {
-            (clean_state & required_mask) == required_mask.into()
+            (clean_state & required_mask) == required_mask
         };
 
         if event_keysym == next_key.keysym && modifiers_match {
@@ -312,22 +546,32 @@ fn handle_next_key(
     }
 }
 
-pub fn handle_spawn_action(action: KeyAction, arg: &Arg, selected_monitor: usize) -> Result<()> {
+/// Spawns the command in `arg`, exporting `startup_id` as
+/// `DESKTOP_STARTUP_ID`. Returns the pid of the spawned process.
+pub fn handle_spawn_action(
+    action: KeyAction,
+    arg: &Arg,
+    selected_monitor: usize,
+    startup_id: &str,
+) -> Result<Option<u32>> {
     if let KeyAction::Spawn = action {
         match arg {
-            Arg::Str(command) => match Command::new(command.as_str()).spawn() {
+            Arg::Str(command) => match Command::new(command.as_str())
+                .env("DESKTOP_STARTUP_ID", startup_id)
+                .spawn()
+            {
                 Err(error) if error.kind() == ErrorKind::NotFound => {
-                    eprintln!(
+                    log::error!(
                         "KeyAction::Spawn failed: could not spawn \"{}\", command not found",
                         command
                     );
                 }
                 Err(error) => Err(error)?,
-                _ => (),
+                Ok(child) => return Ok(Some(crate::reaper::track(child))),
             },
             Arg::Array(command) => {
                 let Some((cmd, args)) = command.split_first() else {
-                    return Ok(());
+                    return Ok(None);
                 };
 
                 let mut args_vec: Vec<String> = args.to_vec();
@@ -341,20 +585,40 @@ pub fn handle_spawn_action(action: KeyAction, arg: &Arg, selected_monitor: usize
                 }
 
                 let args_str: Vec<&str> = args_vec.iter().map(|s| s.as_str()).collect();
-                match Command::new(cmd.as_str()).args(&args_str).spawn() {
+                match Command::new(cmd.as_str())
+                    .args(&args_str)
+                    .env("DESKTOP_STARTUP_ID", startup_id)
+                    .spawn()
+                {
                     Err(error) if error.kind() == ErrorKind::NotFound => {
-                        eprintln!(
+                        log::error!(
                             "KeyAction::Spawn failed: could not spawn \"{}\", command not found",
                             cmd
                         );
                     }
                     Err(error) => Err(error)?,
-                    _ => (),
+                    Ok(child) => return Ok(Some(crate::reaper::track(child))),
+                }
+            }
+            Arg::Spawn(spawn) => {
+                let Some(mut command) = spawn.to_command() else {
+                    return Ok(None);
+                };
+
+                match command.env("DESKTOP_STARTUP_ID", startup_id).spawn() {
+                    Err(error) if error.kind() == ErrorKind::NotFound => {
+                        log::error!(
+                            "KeyAction::Spawn failed: could not spawn \"{}\", command not found",
+                            spawn.argv.join(" ")
+                        );
+                    }
+                    Err(error) => Err(error)?,
+                    Ok(child) => return Ok(Some(crate::reaper::track(child))),
                 }
             }
             _ => {}
         }
     }
 
-    Ok(())
+    Ok(None)
 }
@@ -64,6 +64,8 @@ pub const XK_END: Keysym = 0xff57;
 pub const XK_PAGE_UP: Keysym = 0xff55;
 pub const XK_PAGE_DOWN: Keysym = 0xff56;
 pub const XK_INSERT: Keysym = 0xff63;
+pub const XK_SHIFT_L: Keysym = 0xffe1;
+pub const XK_SHIFT_R: Keysym = 0xffe2;
 pub const XK_MINUS: Keysym = 0x002d;
 pub const XK_EQUAL: Keysym = 0x003d;
 pub const XK_LEFT_BRACKET: Keysym = 0x005b;
@@ -76,6 +78,24 @@ pub const XK_COMMA: Keysym = 0x002c;
 pub const XK_PERIOD: Keysym = 0x002e;
 pub const XK_SLASH: Keysym = 0x002f;
 pub const XK_PRINT: Keysym = 0xff61;
+pub const XK_NUM_LOCK: Keysym = 0xff7f;
+
+pub const XK_KP_ENTER: Keysym = 0xff8d;
+pub const XK_KP_MULTIPLY: Keysym = 0xffaa;
+pub const XK_KP_ADD: Keysym = 0xffab;
+pub const XK_KP_SUBTRACT: Keysym = 0xffad;
+pub const XK_KP_DECIMAL: Keysym = 0xffae;
+pub const XK_KP_DIVIDE: Keysym = 0xffaf;
+pub const XK_KP_0: Keysym = 0xffb0;
+pub const XK_KP_1: Keysym = 0xffb1;
+pub const XK_KP_2: Keysym = 0xffb2;
+pub const XK_KP_3: Keysym = 0xffb3;
+pub const XK_KP_4: Keysym = 0xffb4;
+pub const XK_KP_5: Keysym = 0xffb5;
+pub const XK_KP_6: Keysym = 0xffb6;
+pub const XK_KP_7: Keysym = 0xffb7;
+pub const XK_KP_8: Keysym = 0xffb8;
+pub const XK_KP_9: Keysym = 0xffb9;
 
 pub const XF86_AUDIO_RAISE_VOLUME: Keysym = 0x1008ff13;
 pub const XF86_AUDIO_LOWER_VOLUME: Keysym = 0x1008ff11;
@@ -83,7 +103,29 @@ pub const XF86_AUDIO_MUTE: Keysym = 0x1008ff12;
 pub const XF86_MON_BRIGHTNESS_UP: Keysym = 0x1008ff02;
 pub const XF86_MON_BRIGHTNESS_DOWN: Keysym = 0x1008ff03;
 
+/// Resolves a key name from a config. The short names below are tried
+/// first, then every X keysym name such as "XF86AudioPlay", "Print" or
+/// "odiaeresis".
 pub fn keysym_from_str(s: &str) -> Option<Keysym> {
+    short_keysym_name(s).or_else(|| x_keysym_from_name(s))
+}
+
+fn x_keysym_from_name(name: &str) -> Option<Keysym> {
+    let name = std::ffi::CString::new(name).ok()?;
+    let keysym = unsafe { x11::xlib::XStringToKeysym(name.as_ptr()) };
+    (keysym != 0).then_some(keysym as Keysym)
+}
+
+fn x_keysym_name(keysym: Keysym) -> Option<String> {
+    let name = unsafe { x11::xlib::XKeysymToString(keysym as x11::xlib::KeySym) };
+    if name.is_null() {
+        return None;
+    }
+    let name = unsafe { std::ffi::CStr::from_ptr(name) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+fn short_keysym_name(s: &str) -> Option<Keysym> {
     match s {
         "Return" => Some(XK_RETURN),
         "Escape" => Some(XK_ESCAPE),
@@ -159,6 +201,22 @@ pub fn keysym_from_str(s: &str) -> Option<Keysym> {
         "Comma" => Some(XK_COMMA),
         "Period" => Some(XK_PERIOD),
         "Slash" => Some(XK_SLASH),
+        "KP_0" => Some(XK_KP_0),
+        "KP_1" => Some(XK_KP_1),
+        "KP_2" => Some(XK_KP_2),
+        "KP_3" => Some(XK_KP_3),
+        "KP_4" => Some(XK_KP_4),
+        "KP_5" => Some(XK_KP_5),
+        "KP_6" => Some(XK_KP_6),
+        "KP_7" => Some(XK_KP_7),
+        "KP_8" => Some(XK_KP_8),
+        "KP_9" => Some(XK_KP_9),
+        "KP_Enter" => Some(XK_KP_ENTER),
+        "KP_Add" => Some(XK_KP_ADD),
+        "KP_Subtract" => Some(XK_KP_SUBTRACT),
+        "KP_Multiply" => Some(XK_KP_MULTIPLY),
+        "KP_Divide" => Some(XK_KP_DIVIDE),
+        "KP_Decimal" => Some(XK_KP_DECIMAL),
         "AudioRaiseVolume" => Some(XF86_AUDIO_RAISE_VOLUME),
         "AudioLowerVolume" => Some(XF86_AUDIO_LOWER_VOLUME),
         "AudioMute" => Some(XF86_AUDIO_MUTE),
@@ -168,6 +226,26 @@ pub fn keysym_from_str(s: &str) -> Option<Keysym> {
     }
 }
 
+/// Keypad keysyms, which depend on the NumLock state.
+pub fn is_keypad(keysym: Keysym) -> bool {
+    (0xff80..=0xffbd).contains(&keysym)
+}
+
+/// Shift, Control, Caps Lock, Meta, Alt, Super, Hyper and AltGr.
+pub fn is_modifier(keysym: Keysym) -> bool {
+    (0xffe1..=0xffee).contains(&keysym) || keysym == 0xfe03
+}
+
+/// The character typed by a Latin-1 or Unicode keysym, if it is printable.
+pub fn keysym_to_char(keysym: Keysym) -> Option<char> {
+    let code_point = match keysym {
+        0x0020..=0x007e | 0x00a0..=0x00ff => keysym,
+        0x0100_00a0..=0x0110_ffff => keysym - 0x0100_0000,
+        _ => return None,
+    };
+    char::from_u32(code_point).filter(|character| !character.is_control())
+}
+
 pub fn format_keysym(keysym: Keysym) -> String {
     match keysym {
         XK_RETURN => "Return".to_string(),
@@ -209,6 +287,13 @@ pub fn format_keysym(keysym: Keysym) -> String {
         XK_APOSTROPHE => "'".to_string(),
         XK_BACKSLASH => "\\".to_string(),
         XK_PRINT => "Print".to_string(),
+        XK_KP_ENTER => "NumEnter".to_string(),
+        XK_KP_ADD => "Num+".to_string(),
+        XK_KP_SUBTRACT => "Num-".to_string(),
+        XK_KP_MULTIPLY => "Num*".to_string(),
+        XK_KP_DIVIDE => "Num/".to_string(),
+        XK_KP_DECIMAL => "Num.".to_string(),
+        XK_KP_0..=XK_KP_9 => format!("Num{}", keysym - XK_KP_0),
         XF86_AUDIO_RAISE_VOLUME => "Vol+".to_string(),
         XF86_AUDIO_LOWER_VOLUME => "Vol-".to_string(),
         XF86_AUDIO_MUTE => "Mute".to_string(),
@@ -222,6 +307,6 @@ pub fn format_keysym(keysym: Keysym) -> String {
             let ch = (keysym - XK_0 + b'0' as u32) as u8 as char;
             ch.to_string()
         }
-        _ => format!("0x{:x}", keysym),
+        _ => x_keysym_name(keysym).unwrap_or_else(|| format!("0x{:x}", keysym)),
     }
 }
@@ -0,0 +1,112 @@
+use super::{GapConfig, Layout, MasterAxis, WindowGeometry};
+use x11rb::protocol::xproto::Window;
+
+/// Master windows side by side across the top, the stack side by side
+/// below them.
+pub struct BstackLayout;
+
+impl BstackLayout {
+    /// Lays `count` windows out left to right across `width`, handing the
+    /// pixels lost to rounding to the first windows.
+    fn row(count: usize, x: i32, y: i32, width: i32, height: i32, gap: i32) -> Vec<WindowGeometry> {
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let available = width - gap * (count as i32 - 1);
+        let base_width = available / count as i32;
+        let remainder = available - base_width * count as i32;
+
+        let mut next_x = x;
+        (0..count)
+            .map(|i| {
+                let window_width = base_width + if (i as i32) < remainder { 1 } else { 0 };
+                let geometry = WindowGeometry {
+                    x_coordinate: next_x,
+                    y_coordinate: y,
+                    width: window_width.max(1) as u32,
+                    height: height.max(1) as u32,
+                };
+                next_x += window_width + gap;
+                geometry
+            })
+            .collect()
+    }
+}
+
+impl Layout for BstackLayout {
+    fn name(&self) -> &'static str {
+        super::LayoutType::Bstack.as_str()
+    }
+
+    fn symbol(&self) -> &'static str {
+        "TTT"
+    }
+
+    fn master_axis(&self) -> Option<MasterAxis> {
+        Some(MasterAxis::Vertical)
+    }
+
+    fn arrange(
+        &self,
+        windows: &[Window],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        master_factor: f32,
+        num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        let window_count = windows.len();
+        if window_count == 0 {
+            return Vec::new();
+        }
+
+        let outer_enabled = !(smartgaps_enabled && window_count == 1);
+        let outer_horizontal = if outer_enabled { gaps.outer_horizontal as i32 } else { 0 };
+        let outer_vertical = if outer_enabled { gaps.outer_vertical as i32 } else { 0 };
+        let inner_horizontal = gaps.inner_horizontal as i32;
+        let inner_vertical = gaps.inner_vertical as i32;
+
+        let num_master = num_master.max(0) as usize;
+        let master_count = window_count.min(num_master);
+        let stack_count = window_count - master_count;
+
+        let width = screen_width as i32 - 2 * outer_vertical;
+        let height = screen_height as i32 - 2 * outer_horizontal;
+
+        let (master_height, stack_height) = if master_count > 0 && stack_count > 0 {
+            let master_height = ((height - inner_horizontal) as f32 * master_factor) as i32;
+            (master_height, height - inner_horizontal - master_height)
+        } else if master_count > 0 {
+            (height, 0)
+        } else {
+            (0, height)
+        };
+
+        let master_y = outer_horizontal;
+        let stack_y = if master_count > 0 {
+            master_y + master_height + inner_horizontal
+        } else {
+            master_y
+        };
+
+        let mut geometries = Self::row(
+            master_count,
+            outer_vertical,
+            master_y,
+            width,
+            master_height,
+            inner_vertical,
+        );
+        geometries.extend(Self::row(
+            stack_count,
+            outer_vertical,
+            stack_y,
+            width,
+            stack_height,
+            inner_vertical,
+        ));
+        geometries
+    }
+}
@@ -0,0 +1,351 @@
+use super::{Arrangement, GapConfig, LayoutType, MasterAxis, WindowGeometry};
+use x11rb::protocol::xproto::Window;
+
+/// A monitor to arrange synthetic windows on, for checking layout math
+/// without an X server.
+pub struct Scenario {
+    pub width: u32,
+    pub height: u32,
+    pub gaps: GapConfig,
+    pub master_factor: f32,
+    pub num_master: i32,
+    pub smartgaps_enabled: bool,
+    pub border_width: u32,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            gaps: GapConfig {
+                inner_horizontal: 5,
+                inner_vertical: 5,
+                outer_horizontal: 5,
+                outer_vertical: 5,
+            },
+            master_factor: 0.55,
+            num_master: 1,
+            smartgaps_enabled: false,
+            border_width: 2,
+        }
+    }
+}
+
+impl Scenario {
+    fn arrangement(&self) -> Arrangement {
+        Arrangement {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+            gaps: self.gaps,
+            master_factor: self.master_factor,
+            num_master: self.num_master,
+            smartgaps_enabled: self.smartgaps_enabled,
+            border_width: self.border_width,
+        }
+    }
+
+    /// Geometries `layout_type` gives `count` windows, including borders.
+    pub fn arrange(&self, layout_type: &LayoutType, count: usize) -> Vec<WindowGeometry> {
+        let layout = layout_type.new();
+        let border = 2 * self.border_width;
+        self.arrangement()
+            .slots(layout.as_ref(), &windows(count))
+            .into_iter()
+            .map(|slot| WindowGeometry {
+                width: slot.width + border,
+                height: slot.height + border,
+                ..slot
+            })
+            .collect()
+    }
+
+    /// Arranges `count` windows and checks what should hold for any layout:
+    /// one geometry per window, all of it on screen and clear of the outer
+    /// gaps. Tiling layouts must not overlap and must honor the number of
+    /// masters and the master factor.
+    pub fn validate(&self, layout_type: &LayoutType, count: usize) -> Result<(), String> {
+        let layout = layout_type.new();
+        let name = layout.name();
+        let geometries = self.arrange(layout_type, count);
+
+        if *layout_type == LayoutType::Normie {
+            return match geometries.is_empty() {
+                true => Ok(()),
+                false => Err(format!("{}: floating layout placed windows", name)),
+            };
+        }
+        if geometries.len() != count {
+            return Err(format!("{}: {} geometries for {} windows", name, geometries.len(), count));
+        }
+
+        let outer_gaps = !(self.smartgaps_enabled && count == 1);
+        let margin = if outer_gaps {
+            self.gaps.outer_horizontal.min(self.gaps.outer_vertical) as i32
+        } else {
+            0
+        };
+        for (index, geometry) in geometries.iter().enumerate() {
+            let right = geometry.x_coordinate + geometry.width as i32;
+            let bottom = geometry.y_coordinate + geometry.height as i32;
+            if geometry.width == 0 || geometry.height == 0 {
+                return Err(format!("{}: window {} of {} has no size", name, index, count));
+            }
+            if geometry.x_coordinate < margin
+                || geometry.y_coordinate < margin
+                || right > self.width as i32 - margin
+                || bottom > self.height as i32 - margin
+            {
+                return Err(format!(
+                    "{}: window {} of {} at {:?} leaves the {}x{} area inside the outer gaps",
+                    name, index, count, geometry, self.width, self.height
+                ));
+            }
+        }
+
+        if matches!(layout_type, LayoutType::Monocle | LayoutType::Tabbed) {
+            return Ok(());
+        }
+
+        for (index, geometry) in geometries.iter().enumerate() {
+            if let Some(other) = geometries[index + 1..].iter().position(|other| overlaps(geometry, other)) {
+                return Err(format!(
+                    "{}: windows {} and {} of {} overlap",
+                    name,
+                    index,
+                    index + 1 + other,
+                    count
+                ));
+            }
+        }
+
+        if let Some(axis) = layout.master_axis() {
+            self.validate_master(name, axis, &geometries)?;
+        }
+        Ok(())
+    }
+
+    fn validate_master(&self, name: &str, axis: MasterAxis, geometries: &[WindowGeometry]) -> Result<(), String> {
+        let count = geometries.len();
+        let num_master = (self.num_master.max(0) as usize).min(count);
+        if num_master == 0 || num_master == count {
+            return Ok(());
+        }
+
+        let (master, stack) = geometries.split_at(num_master);
+        let (master_size, total, gap) = match axis {
+            MasterAxis::Horizontal => (
+                master[0].width,
+                self.width - 2 * self.gaps.outer_vertical,
+                self.gaps.inner_vertical,
+            ),
+            MasterAxis::Vertical => (
+                master[0].height,
+                self.height - 2 * self.gaps.outer_horizontal,
+                self.gaps.inner_horizontal,
+            ),
+        };
+
+        let expected = (total - gap) as f32 * self.master_factor;
+        if (master_size as f32 - expected).abs() > 1.0 {
+            return Err(format!(
+                "{}: master is {}px for a factor of {}, expected {}px",
+                name, master_size, self.master_factor, expected
+            ));
+        }
+
+        let beside = |a: &WindowGeometry, b: &WindowGeometry| match axis {
+            MasterAxis::Horizontal => a.x_coordinate + (a.width as i32) <= b.x_coordinate,
+            MasterAxis::Vertical => a.y_coordinate + (a.height as i32) <= b.y_coordinate,
+        };
+        if !master.iter().all(|m| stack.iter().all(|s| beside(m, s))) {
+            return Err(format!("{}: {} masters are not all before the stack", name, num_master));
+        }
+        Ok(())
+    }
+}
+
+/// `count` made up window ids.
+pub fn windows(count: usize) -> Vec<Window> {
+    (1..=count as Window).collect()
+}
+
+/// Runs `Scenario::validate` for every layout over a spread of window
+/// counts, master counts, factors and gap settings, returning every
+/// failure.
+pub fn validate_all() -> Vec<String> {
+    let mut failures = Vec::new();
+
+    for layout_type in LayoutType::ALL {
+        for num_master in 0..=3 {
+            for master_factor in [0.05, 0.3, 0.55, 0.95] {
+                for (gaps, smartgaps_enabled) in [(0, false), (10, false), (10, true)] {
+                    let scenario = Scenario {
+                        gaps: GapConfig {
+                            inner_horizontal: gaps,
+                            inner_vertical: gaps,
+                            outer_horizontal: gaps,
+                            outer_vertical: gaps,
+                        },
+                        master_factor,
+                        num_master,
+                        smartgaps_enabled,
+                        ..Scenario::default()
+                    };
+                    for count in 0..=9 {
+                        if let Err(failure) = scenario.validate(&layout_type, count) {
+                            failures.push(failure);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    failures
+}
+
+fn overlaps(a: &WindowGeometry, b: &WindowGeometry) -> bool {
+    a.x_coordinate < b.x_coordinate + b.width as i32
+        && b.x_coordinate < a.x_coordinate + a.width as i32
+        && a.y_coordinate < b.y_coordinate + b.height as i32
+        && b.y_coordinate < a.y_coordinate + a.height as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geometry(x_coordinate: i32, y_coordinate: i32, width: u32, height: u32) -> WindowGeometry {
+        WindowGeometry {
+            x_coordinate,
+            y_coordinate,
+            width,
+            height,
+        }
+    }
+
+    /// Even gaps of `gaps` pixels and no borders, so the arranged
+    /// geometries are exactly what the layout computed.
+    fn scenario(gaps: u32, master_factor: f32, num_master: i32) -> Scenario {
+        Scenario {
+            gaps: GapConfig {
+                inner_horizontal: gaps,
+                inner_vertical: gaps,
+                outer_horizontal: gaps,
+                outer_vertical: gaps,
+            },
+            master_factor,
+            num_master,
+            border_width: 0,
+            ..Scenario::default()
+        }
+    }
+
+    #[test]
+    fn every_layout_passes_validation() {
+        let failures = validate_all();
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn tiling_splits_master_and_stack_by_factor() {
+        let geometries = scenario(10, 0.5, 1).arrange(&LayoutType::Tiling, 3);
+        assert_eq!(
+            geometries,
+            vec![
+                geometry(10, 10, 945, 1060),
+                geometry(965, 10, 945, 525),
+                geometry(965, 545, 945, 525),
+            ]
+        );
+    }
+
+    #[test]
+    fn tiling_stacks_several_masters() {
+        let geometries = scenario(10, 0.5, 2).arrange(&LayoutType::Tiling, 3);
+        assert_eq!(
+            geometries,
+            vec![
+                geometry(10, 10, 945, 525),
+                geometry(10, 545, 945, 525),
+                geometry(965, 10, 945, 1060),
+            ]
+        );
+    }
+
+    #[test]
+    fn tiling_without_masters_fills_width_with_stack() {
+        let geometries = scenario(0, 0.5, 0).arrange(&LayoutType::Tiling, 2);
+        assert_eq!(geometries, vec![geometry(0, 0, 1920, 540), geometry(0, 540, 1920, 540)]);
+    }
+
+    #[test]
+    fn smartgaps_drop_outer_gaps_for_a_single_window() {
+        let scenario = Scenario {
+            smartgaps_enabled: true,
+            ..scenario(10, 0.5, 1)
+        };
+        assert_eq!(scenario.arrange(&LayoutType::Tiling, 1), vec![geometry(0, 0, 1920, 1080)]);
+        assert_eq!(scenario.arrange(&LayoutType::Bstack, 1), vec![geometry(0, 0, 1920, 1080)]);
+    }
+
+    #[test]
+    fn bstack_puts_master_row_above_stack_row() {
+        let geometries = scenario(10, 0.5, 1).arrange(&LayoutType::Bstack, 3);
+        assert_eq!(
+            geometries,
+            vec![
+                geometry(10, 10, 1900, 525),
+                geometry(10, 545, 945, 525),
+                geometry(965, 545, 945, 525),
+            ]
+        );
+    }
+
+    #[test]
+    fn bstack_master_height_follows_factor() {
+        let geometries = scenario(0, 0.25, 1).arrange(&LayoutType::Bstack, 2);
+        assert_eq!(geometries, vec![geometry(0, 0, 1920, 270), geometry(0, 270, 1920, 810)]);
+    }
+
+    #[test]
+    fn grid_leaves_inner_gaps_between_cells() {
+        let geometries = scenario(10, 0.5, 1).arrange(&LayoutType::Grid, 4);
+        assert_eq!(
+            geometries,
+            vec![
+                geometry(10, 10, 945, 525),
+                geometry(965, 10, 945, 525),
+                geometry(10, 545, 945, 525),
+                geometry(965, 545, 945, 525),
+            ]
+        );
+    }
+
+    #[test]
+    fn monocle_and_tabbed_fill_the_area_inside_outer_gaps() {
+        for layout_type in [LayoutType::Monocle, LayoutType::Tabbed] {
+            let geometries = scenario(10, 0.5, 1).arrange(&layout_type, 3);
+            assert_eq!(geometries, vec![geometry(10, 10, 1900, 1060); 3]);
+        }
+    }
+
+    #[test]
+    fn borders_are_taken_out_of_the_slot() {
+        let scenario = Scenario {
+            border_width: 3,
+            ..scenario(0, 0.5, 1)
+        };
+        let layout = LayoutType::Monocle.new();
+        let slots = scenario.arrangement().slots(layout.as_ref(), &windows(1));
+        assert_eq!(slots, vec![geometry(0, 0, 1914, 1074)]);
+    }
+
+    #[test]
+    fn normie_places_nothing() {
+        assert!(scenario(10, 0.5, 1).arrange(&LayoutType::Normie, 3).is_empty());
+    }
+}
@@ -1,4 +1,7 @@
+pub mod bstack;
 pub mod grid;
+#[cfg(test)]
+mod harness;
 pub mod monocle;
 pub mod normie;
 pub mod tabbed;
@@ -8,6 +11,7 @@ use x11rb::protocol::xproto::Window;
 
 pub type LayoutBox = Box<dyn Layout>;
 
+#[derive(Clone, Copy, Default)]
 pub struct GapConfig {
     pub inner_horizontal: u32,
     pub inner_vertical: u32,
@@ -15,8 +19,10 @@ pub struct GapConfig {
     pub outer_vertical: u32,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LayoutType {
     Tiling,
+    Bstack,
     Normie,
     Grid,
     Monocle,
@@ -24,9 +30,19 @@ pub enum LayoutType {
 }
 
 impl LayoutType {
+    pub const ALL: [Self; 6] = [
+        Self::Tiling,
+        Self::Bstack,
+        Self::Normie,
+        Self::Grid,
+        Self::Monocle,
+        Self::Tabbed,
+    ];
+
     pub fn new(&self) -> LayoutBox {
         match self {
             Self::Tiling => Box::new(tiling::TilingLayout),
+            Self::Bstack => Box::new(bstack::BstackLayout),
             Self::Normie => Box::new(normie::NormieLayout),
             Self::Grid => Box::new(grid::GridLayout),
             Self::Monocle => Box::new(monocle::MonocleLayout),
@@ -36,7 +52,8 @@ impl LayoutType {
 
     pub fn next(&self) -> Self {
         match self {
-            Self::Tiling => Self::Normie,
+            Self::Tiling => Self::Bstack,
+            Self::Bstack => Self::Normie,
             Self::Normie => Self::Grid,
             Self::Grid => Self::Monocle,
             Self::Monocle => Self::Tabbed,
@@ -44,9 +61,21 @@ impl LayoutType {
         }
     }
 
+    pub fn prev(&self) -> Self {
+        match self {
+            Self::Tiling => Self::Tabbed,
+            Self::Bstack => Self::Tiling,
+            Self::Normie => Self::Bstack,
+            Self::Grid => Self::Normie,
+            Self::Monocle => Self::Grid,
+            Self::Tabbed => Self::Monocle,
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Tiling => "tiling",
+            Self::Bstack => "bstack",
             Self::Normie => "normie",
             Self::Grid => "grid",
             Self::Monocle => "monocle",
@@ -57,6 +86,7 @@ impl LayoutType {
     pub fn from_str(s: &str) -> Result<Self, String> {
         match s.to_lowercase().as_str() {
             "tiling" => Ok(Self::Tiling),
+            "bstack" => Ok(Self::Bstack),
             "normie" | "floating" => Ok(Self::Normie),
             "grid" => Ok(Self::Grid),
             "monocle" => Ok(Self::Monocle),
@@ -71,12 +101,35 @@ pub fn layout_from_str(s: &str) -> Result<LayoutBox, String> {
     Ok(layout_type.new())
 }
 
-pub fn next_layout(current_name: &str) -> &'static str {
-    LayoutType::from_str(current_name)
-        .ok()
-        .map(|layout_type| layout_type.next())
-        .unwrap_or(LayoutType::Tiling)
-        .as_str()
+pub fn next_layout(current_name: &str, skip: &[String]) -> &'static str {
+    step_layout(current_name, skip, LayoutType::next)
+}
+
+pub fn prev_layout(current_name: &str, skip: &[String]) -> &'static str {
+    step_layout(current_name, skip, LayoutType::prev)
+}
+
+/// Steps from the current layout until one not in `skip` comes up, staying
+/// put when every other layout is skipped.
+fn step_layout(current_name: &str, skip: &[String], step: fn(&LayoutType) -> LayoutType) -> &'static str {
+    let Ok(mut layout_type) = LayoutType::from_str(current_name) else {
+        return LayoutType::Tiling.as_str();
+    };
+    for _ in 1..LayoutType::ALL.len() {
+        layout_type = step(&layout_type);
+        if !skip.iter().any(|name| name == layout_type.as_str()) {
+            return layout_type.as_str();
+        }
+    }
+    step(&layout_type).as_str()
+}
+
+/// Which way a layout's master area grows when the master factor does:
+/// sideways for a master column, downwards for a master row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MasterAxis {
+    Horizontal,
+    Vertical,
 }
 
 pub trait Layout {
@@ -92,9 +145,60 @@ pub trait Layout {
     ) -> Vec<WindowGeometry>;
     fn name(&self) -> &'static str;
     fn symbol(&self) -> &'static str;
+
+    /// The axis the master factor resizes along, or `None` for layouts
+    /// without a master area.
+    fn master_axis(&self) -> Option<MasterAxis> {
+        None
+    }
+
+    /// Whether windows are shown as tabs, so the tab bar is shown and its
+    /// strip kept out of the area the layout arranges in.
+    fn uses_tab_bar(&self) -> bool {
+        false
+    }
+}
+
+/// The area a monitor's tiled windows go in and everything else a layout
+/// is arranged with. Kept free of X so results can be checked headless.
+pub struct Arrangement {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub gaps: GapConfig,
+    pub master_factor: f32,
+    pub num_master: i32,
+    pub smartgaps_enabled: bool,
+    pub border_width: u32,
+}
+
+impl Arrangement {
+    /// Runs `layout` and turns the cells it returns into window geometries:
+    /// moved to the area's origin and shrunk by the border on every side.
+    pub fn slots(&self, layout: &dyn Layout, windows: &[Window]) -> Vec<WindowGeometry> {
+        layout
+            .arrange(
+                windows,
+                self.width,
+                self.height,
+                &self.gaps,
+                self.master_factor,
+                self.num_master,
+                self.smartgaps_enabled,
+            )
+            .into_iter()
+            .map(|cell| WindowGeometry {
+                x_coordinate: cell.x_coordinate + self.x,
+                y_coordinate: cell.y_coordinate + self.y,
+                width: cell.width.saturating_sub(2 * self.border_width),
+                height: cell.height.saturating_sub(2 * self.border_width),
+            })
+            .collect()
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct WindowGeometry {
     pub x_coordinate: i32,
     pub y_coordinate: i32,
@@ -14,6 +14,10 @@ impl Layout for TabbedLayout {
         "[=]"
     }
 
+    fn uses_tab_bar(&self) -> bool {
+        true
+    }
+
     fn arrange(
         &self,
         windows: &[Window],
@@ -30,11 +34,9 @@ impl Layout for TabbedLayout {
         }
 
         let x = gaps.outer_horizontal as i32;
-        let y = (gaps.outer_vertical + TAB_BAR_HEIGHT) as i32;
+        let y = gaps.outer_vertical as i32;
         let width = screen_width.saturating_sub(2 * gaps.outer_horizontal);
-        let height = screen_height
-            .saturating_sub(2 * gaps.outer_vertical)
-            .saturating_sub(TAB_BAR_HEIGHT);
+        let height = screen_height.saturating_sub(2 * gaps.outer_vertical);
 
         let geometry = WindowGeometry {
             x_coordinate: x,
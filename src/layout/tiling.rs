@@ -1,4 +1,4 @@
-use super::{GapConfig, Layout, WindowGeometry};
+use super::{GapConfig, Layout, MasterAxis, WindowGeometry};
 use x11rb::protocol::xproto::Window;
 
 pub struct TilingLayout;
@@ -79,6 +79,10 @@ impl Layout for TilingLayout {
         "[]="
     }
 
+    fn master_axis(&self) -> Option<MasterAxis> {
+        Some(MasterAxis::Horizontal)
+    }
+
     fn arrange(
         &self,
         windows: &[Window],
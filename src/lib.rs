@@ -1,30 +1,200 @@
+pub mod autostart;
+pub mod backend;
 pub mod bar;
 pub mod client;
 pub mod config;
+pub mod doctor;
 pub mod errors;
 pub mod keyboard;
 pub mod layout;
+pub mod logging;
+pub mod media;
 pub mod monitor;
 pub mod overlay;
+pub mod reaper;
+pub mod screenshot;
+pub mod selection;
+pub mod shape;
+pub mod shutdown;
 pub mod size_hints;
 pub mod tab_bar;
 pub mod window_manager;
 
 pub mod prelude {
+    pub use crate::AttachPosition;
+    pub use crate::AutostartCommand;
+    pub use crate::BorderGradient;
+    pub use crate::BorderStyle;
     pub use crate::ColorScheme;
+    pub use crate::FloatingPlacement;
     pub use crate::LayoutSymbolOverride;
+    pub use crate::MonitorMaster;
+    pub use crate::MonitorPadding;
+    pub use crate::MonitorScale;
+    pub use crate::TagOverride;
     pub use crate::WindowRule;
     pub use crate::bar::{BlockCommand, BlockConfig};
     pub use crate::keyboard::{Arg, KeyAction, handlers::KeyBinding, keysyms};
     pub use x11rb::protocol::xproto::KeyButMask;
 }
 
+/// A command started by `oxwm.autostart_once`, at most once per session.
+#[derive(Debug, Clone)]
+pub struct AutostartCommand {
+    pub command: String,
+    /// Start the command again if it exits.
+    pub restart: bool,
+}
+
 #[derive(Clone)]
 pub struct LayoutSymbolOverride {
     pub name: String,
     pub symbol: String,
 }
 
+/// Border colors from the window edge (`inner`) to the outside (`outer`).
+#[derive(Clone, Copy)]
+pub struct BorderGradient {
+    pub inner: u32,
+    pub outer: u32,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct BorderStyle {
+    pub inner_width: u32,
+    pub inner_color: u32,
+    pub focused: Option<BorderGradient>,
+    pub unfocused: Option<BorderGradient>,
+    pub urgent: Option<BorderGradient>,
+}
+
+impl BorderStyle {
+    pub fn is_flat(&self) -> bool {
+        self.inner_width == 0
+            && self.focused.is_none()
+            && self.unfocused.is_none()
+            && self.urgent.is_none()
+    }
+}
+
+/// Where newly managed floating windows are placed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloatingPlacement {
+    /// Keep the position the client asked for.
+    Requested,
+    /// Find the spot overlapping the fewest other floating windows.
+    Smart,
+    /// Offset each new window diagonally from the previous one.
+    Cascade,
+    Center,
+    UnderPointer,
+}
+
+/// Where newly managed windows enter the tiling order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttachPosition {
+    /// Become the new master.
+    Master,
+    /// Right after the first master.
+    Aside,
+    /// At the end of the stack.
+    Bottom,
+    AboveFocused,
+    AfterFocused,
+}
+
+impl AttachPosition {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "master" => Ok(Self::Master),
+            "aside" => Ok(Self::Aside),
+            "bottom" => Ok(Self::Bottom),
+            "above_focused" => Ok(Self::AboveFocused),
+            "after_focused" => Ok(Self::AfterFocused),
+            _ => Err(format!(
+                "unknown attach position '{}' (expected 'master', 'aside', 'bottom', 'above_focused' or 'after_focused')",
+                s
+            )),
+        }
+    }
+}
+
+/// How the number of clients on each tag is shown in its bar cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TagCountStyle {
+    None,
+    /// After the tag name, separated by a space.
+    Suffix,
+    /// As superscript digits right after the tag name.
+    Superscript,
+}
+
+impl TagCountStyle {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "suffix" => Ok(Self::Suffix),
+            "superscript" => Ok(Self::Superscript),
+            _ => Err(format!(
+                "unknown tag count style '{}' (expected 'none', 'suffix' or 'superscript')",
+                s
+            )),
+        }
+    }
+}
+
+/// What a tab in the tab bar is labelled with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TabLabel {
+    Title,
+    /// The WM_CLASS class, e.g. "firefox".
+    Class,
+}
+
+impl TabLabel {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "title" => Ok(Self::Title),
+            "class" => Ok(Self::Class),
+            _ => Err(format!("unknown tab label '{}' (expected 'title' or 'class')", s)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MonitorScale {
+    pub monitor: usize,
+    pub scale: f32,
+}
+
+/// Space kept free along the edges of a monitor, on top of what docks
+/// reserve, e.g. for a desktop widget like conky.
+#[derive(Clone)]
+pub struct MonitorPadding {
+    pub monitor: usize,
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32,
+}
+
+/// Master area settings for a single monitor, overriding the defaults.
+#[derive(Clone)]
+pub struct MonitorMaster {
+    pub monitor: usize,
+    pub master_factor: Option<f32>,
+    pub num_master: Option<i32>,
+}
+
+/// Gap and border settings for a single tag, overriding the defaults
+/// while it is the first tag viewed on a monitor.
+#[derive(Clone)]
+pub struct TagOverride {
+    pub tag: usize,
+    pub gaps: Option<bool>,
+    pub border_width: Option<u32>,
+}
+
 #[derive(Clone)]
 pub struct WindowRule {
     pub class: Option<String>,
@@ -33,6 +203,13 @@ pub struct WindowRule {
     pub tags: Option<u32>,
     pub is_floating: Option<bool>,
     pub monitor: Option<usize>,
+    pub opacity: Option<f32>,
+    pub attach: Option<AttachPosition>,
+    /// Layout to switch the client's tag to while a matching client is
+    /// open on it.
+    pub layout: Option<String>,
+    /// Also apply the rule when the client's title changes to match it.
+    pub on_title_change: bool,
 }
 
 impl WindowRule {
@@ -50,7 +227,23 @@ pub struct Config {
     pub border_width: u32,
     pub border_focused: u32,
     pub border_unfocused: u32,
+    pub border_urgent: Option<u32>,
+    pub border_style: BorderStyle,
+    pub smart_borders: bool,
+    /// Radius windows are rounded to with the shape extension, 0 for square.
+    pub border_radius: u32,
     pub font: String,
+    /// Fonts tried in order for characters the main font has no glyph for.
+    pub font_fallbacks: Vec<String>,
+
+    // Floating windows
+    pub floating_placement: FloatingPlacement,
+    pub attach_position: AttachPosition,
+    /// Distance in pixels at which dragged windows snap to monitor edges and
+    /// other floating windows. 0 disables snapping.
+    pub snap_distance: u32,
+    /// Keep dragged windows inside their monitor unless Shift is held.
+    pub constrain_drag: bool,
 
     // Gaps
     pub gaps_enabled: bool,
@@ -60,32 +253,124 @@ pub struct Config {
     pub gap_outer_horizontal: u32,
     pub gap_outer_vertical: u32,
 
+    // Tab bar
+    pub tab_bar_position: crate::bar::BarPosition,
+    pub tab_bar_height: u32,
+    pub tab_bar_gap: u32,
+    pub tab_bar_show_floating: bool,
+    /// Widest a tab gets, None to share the whole bar between tabs.
+    pub tab_bar_max_tab_width: Option<u32>,
+    pub tab_bar_separator: String,
+    pub tab_bar_label: TabLabel,
+    /// Colors of the focused tab, the bar's selected scheme when None.
+    pub tab_bar_scheme_active: Option<ColorScheme>,
+    /// Colors of the other tabs, the bar's occupied scheme when None.
+    pub tab_bar_scheme_inactive: Option<ColorScheme>,
+    /// Fill behind urgent tabs, the urgent border color when None.
+    pub tab_bar_urgent: Option<u32>,
+
+    // Monitors
+    pub monitor_order: Vec<String>,
+    pub monitor_scales: Vec<MonitorScale>,
+    pub monitor_padding: Vec<MonitorPadding>,
+    /// Keep a client's tags when it moves to another monitor instead of
+    /// taking over the target monitor's selected tags.
+    pub monitor_keep_tags: bool,
+
+    // Master area
+    pub master_factor: f32,
+    pub num_master: i32,
+    pub monitor_masters: Vec<MonitorMaster>,
+    pub tag_overrides: Vec<TagOverride>,
+    /// Icon shown in place of a tag's name while a window of the class,
+    /// keyed in lowercase, is on it.
+    pub tag_icons: std::collections::HashMap<String, String>,
+
+    /// Whether tiled clients keep their size hints, e.g. terminal cell
+    /// increments. Floating clients and the normie layout always do.
+    pub resize_hints: bool,
+    /// Per layout overrides of `resize_hints`, keyed by layout name.
+    pub layout_resize_hints: std::collections::HashMap<String, bool>,
+
     // Basics
     pub terminal: String,
     pub modkey: x11rb::protocol::xproto::KeyButMask,
+    /// How long Quit waits for clients to close, in milliseconds.
+    pub quit_grace_period: u64,
+    /// How long a client may ignore a close request before closing it again
+    /// kills it, in milliseconds.
+    pub kill_timeout: u64,
+    /// Kill clients that ignore a close request for `kill_timeout` without
+    /// waiting for a second close.
+    pub auto_force_kill: bool,
+    /// How long the on screen display for master factor and gap changes
+    /// stays up, in milliseconds. 0 disables it.
+    pub osd_timeout: u64,
+    /// Also log to `~/.local/share/oxwm/oxwm.log`, rotated at 1 MiB.
+    pub log_file: bool,
+    /// Let applications activate their windows through _NET_ACTIVE_WINDOW,
+    /// switching to the window's tag and focusing it. When false such
+    /// requests only mark the window urgent. Requests from
+    /// pagers and taskbars are always honored.
+    pub allow_activation: bool,
+    /// Set _NET_WM_BYPASS_COMPOSITOR on fullscreen windows so compositors
+    /// like picom unredirect them, cutting latency in games.
+    pub bypass_compositor: bool,
 
     // Tags
     pub tags: Vec<String>,
 
     // Layout symbol overrides
     pub layout_symbols: Vec<LayoutSymbolOverride>,
+    /// Layouts skipped when cycling through layouts.
+    pub layout_cycle_skip: Vec<String>,
 
     // Keybindings
     pub keybindings: Vec<crate::keyboard::handlers::Key>,
+    /// Named modes from `oxwm.mode.define`, whose bindings replace the
+    /// regular ones while the mode is active.
+    pub modes: std::collections::HashMap<String, Vec<crate::keyboard::handlers::Key>>,
 
     // Window rules
     pub window_rules: Vec<WindowRule>,
 
     // Status bar
     pub status_blocks: Vec<crate::bar::BlockConfig>,
+    pub bar_height: Option<u32>,
+    pub bar_separator: String,
+    pub bar_block_padding: u32,
+    pub bar_tag_padding: Option<u32>,
+    pub bar_tag_counts: TagCountStyle,
+    pub bar_underline_thickness: Option<u32>,
+    pub bar_scroll_skip_empty: bool,
+    /// Opacity of the bar background, applied only while a compositor runs.
+    pub bar_alpha: f32,
+    pub bar_radius: u32,
 
     // Bar color schemes
     pub scheme_normal: ColorScheme,
     pub scheme_occupied: ColorScheme,
     pub scheme_selected: ColorScheme,
 
+    /// Colors file (pywal JSON or Xresources) read on load and by
+    /// `oxwm.theme.reload()`, overriding the colors above.
+    pub theme_file: Option<String>,
+    /// `(role, color name)` pairs replacing the default theme mapping.
+    pub theme_roles: Vec<(String, String)>,
+
+    // Screenshots
+    /// Where screenshots are saved, the XDG pictures directory if unset.
+    pub screenshot_dir: Option<String>,
+    pub screenshot_save: bool,
+    pub screenshot_clipboard: bool,
+
     // Autostart commands
     pub autostart: Vec<String>,
+    pub autostart_once: Vec<AutostartCommand>,
+
+    /// File the config was loaded from, reloaded on restart and passed on
+    /// with `--config` when re-executing.
+    pub path: Option<std::path::PathBuf>,
 }
 
 #[derive(Clone, Copy)]
@@ -110,20 +395,58 @@ impl Default for Config {
             border_width: 2,
             border_focused: 0x6dade3,
             border_unfocused: 0xbbbbbb,
+            border_urgent: None,
+            border_style: BorderStyle::default(),
+            smart_borders: false,
+            border_radius: 0,
             font: "monospace:size=10".to_string(),
+            font_fallbacks: Vec::new(),
+            floating_placement: FloatingPlacement::Requested,
+            attach_position: AttachPosition::Aside,
+            snap_distance: 32,
+            constrain_drag: false,
             gaps_enabled: false,
             smartgaps_enabled: true,
             gap_inner_horizontal: 0,
             gap_inner_vertical: 0,
             gap_outer_horizontal: 0,
             gap_outer_vertical: 0,
+            tab_bar_position: crate::bar::BarPosition::Top,
+            tab_bar_height: crate::layout::tabbed::TAB_BAR_HEIGHT,
+            tab_bar_gap: 0,
+            tab_bar_show_floating: false,
+            tab_bar_max_tab_width: None,
+            tab_bar_separator: String::new(),
+            tab_bar_label: TabLabel::Title,
+            tab_bar_scheme_active: None,
+            tab_bar_scheme_inactive: None,
+            tab_bar_urgent: None,
+            monitor_order: Vec::new(),
+            monitor_scales: Vec::new(),
+            monitor_padding: Vec::new(),
+            monitor_keep_tags: false,
+            master_factor: crate::monitor::DEFAULT_MASTER_FACTOR,
+            num_master: crate::monitor::DEFAULT_NUM_MASTER,
+            resize_hints: false,
+            layout_resize_hints: std::collections::HashMap::new(),
+            monitor_masters: Vec::new(),
+            tag_overrides: Vec::new(),
+            tag_icons: std::collections::HashMap::new(),
             terminal: TERMINAL.to_string(),
             modkey: MODKEY,
+            quit_grace_period: 5000,
+            kill_timeout: 5000,
+            auto_force_kill: false,
+            osd_timeout: 1000,
+            log_file: false,
+            allow_activation: false,
+            bypass_compositor: false,
             tags: vec!["1", "2", "3", "4", "5", "6", "7", "8", "9"]
                 .into_iter()
                 .map(String::from)
                 .collect(),
             layout_symbols: vec![],
+            layout_cycle_skip: vec![],
             keybindings: vec![
                 KeyBinding::single_key(
                     vec![MODKEY],
@@ -284,6 +607,7 @@ impl Default for Config {
                     Arg::Int(8),
                 ),
             ],
+            modes: std::collections::HashMap::new(),
             window_rules: vec![],
             status_blocks: vec![crate::bar::BlockConfig {
                 format: "{}".to_string(),
@@ -291,7 +615,19 @@ impl Default for Config {
                 interval_secs: 1,
                 color: 0x0db9d7,
                 underline: true,
+                monitor: None,
+                name: None,
+                click: None,
             }],
+            bar_height: None,
+            bar_separator: String::new(),
+            bar_block_padding: 0,
+            bar_tag_padding: None,
+            bar_tag_counts: TagCountStyle::None,
+            bar_underline_thickness: None,
+            bar_scroll_skip_empty: true,
+            bar_alpha: 1.0,
+            bar_radius: 0,
             scheme_normal: ColorScheme {
                 foreground: 0xbbbbbb,
                 background: 0x1a1b26,
@@ -307,7 +643,14 @@ impl Default for Config {
                 background: 0x1a1b26,
                 underline: 0xad8ee6,
             },
+            theme_file: None,
+            theme_roles: Vec::new(),
+            screenshot_dir: None,
+            screenshot_save: true,
+            screenshot_clipboard: false,
             autostart: vec![],
+            autostart_once: vec![],
+            path: None,
         }
     }
 }
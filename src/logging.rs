@@ -0,0 +1,160 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Log files are moved to `oxwm.log.1` once they grow past this.
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+static LOGGER: Logger = Logger {
+    directives: OnceLock::new(),
+    file: Mutex::new(None),
+};
+
+/// Writes `log` records to stderr and, when enabled, to a log file.
+///
+/// Levels come from `OXWM_LOG`, a comma separated list of levels that may
+/// be prefixed with a module path, e.g. `OXWM_LOG=info,oxwm::window_manager=debug`.
+/// The most specific matching prefix wins. Without it only `info` and
+/// above are logged.
+struct Logger {
+    directives: OnceLock<Vec<(Option<String>, LevelFilter)>>,
+    file: Mutex<Option<LogFile>>,
+}
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+/// Installs the logger. Safe to call more than once.
+pub fn init() {
+    let directives = parse_directives(&std::env::var("OXWM_LOG").unwrap_or_default());
+    let max_level = directives
+        .iter()
+        .map(|(_, level)| *level)
+        .max()
+        .unwrap_or(LevelFilter::Info);
+
+    if LOGGER.directives.set(directives).is_ok() && log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Starts or stops also writing to `~/.local/share/oxwm/oxwm.log`.
+pub fn set_file(enabled: bool) {
+    let Ok(mut file) = LOGGER.file.lock() else {
+        return;
+    };
+    if !enabled {
+        *file = None;
+        return;
+    }
+    if file.is_some() {
+        return;
+    }
+
+    let Some(path) = dirs::data_local_dir().map(|dir| dir.join("oxwm").join("oxwm.log")) else {
+        return;
+    };
+    match LogFile::open(path) {
+        Ok(log_file) => *file = Some(log_file),
+        Err(error) => eprintln!("Failed to open log file: {}", error),
+    }
+}
+
+fn parse_directives(spec: &str) -> Vec<(Option<String>, LevelFilter)> {
+    let mut directives: Vec<(Option<String>, LevelFilter)> = spec
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .filter_map(|directive| match directive.split_once('=') {
+            Some((target, level)) => Some((Some(target.to_string()), level.parse().ok()?)),
+            None => Some((None, directive.parse().ok()?)),
+        })
+        .collect();
+
+    if !directives.iter().any(|(target, _)| target.is_none()) {
+        directives.push((None, LevelFilter::Info));
+    }
+    directives
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let size = std::fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+        if size >= MAX_LOG_SIZE {
+            std::fs::rename(&path, path.with_extension("log.1"))?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, file, size })
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        if self.size >= MAX_LOG_SIZE {
+            *self = Self::open(self.path.clone())?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+impl Logger {
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let Some(directives) = self.directives.get() else {
+            return LevelFilter::Info;
+        };
+        directives
+            .iter()
+            .filter(|(prefix, _)| prefix.as_deref().is_none_or(|prefix| target.starts_with(prefix)))
+            .max_by_key(|(prefix, _)| prefix.as_ref().map_or(0, String::len))
+            .map(|(_, level)| *level)
+            .unwrap_or(LevelFilter::Info)
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+
+        if let Ok(mut file) = self.file.lock()
+            && let Some(log_file) = file.as_mut()
+        {
+            let line = format!(
+                "{} {:<5} {}: {}\n",
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+            if log_file.write_line(&line).is_err() {
+                *file = None;
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock()
+            && let Some(log_file) = file.as_mut()
+        {
+            let _ = log_file.file.flush();
+        }
+    }
+}
@@ -0,0 +1,180 @@
+/// A playback command for the active media player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MediaCommand {
+    PlayPause,
+    Next,
+    Previous,
+}
+
+impl MediaCommand {
+    /// Name of the method on the MPRIS Player interface.
+    pub fn method(&self) -> &'static str {
+        match self {
+            MediaCommand::PlayPause => "PlayPause",
+            MediaCommand::Next => "Next",
+            MediaCommand::Previous => "Previous",
+        }
+    }
+
+    #[cfg(not(feature = "mpris"))]
+    fn playerctl_argument(&self) -> &'static str {
+        match self {
+            MediaCommand::PlayPause => "play-pause",
+            MediaCommand::Next => "next",
+            MediaCommand::Previous => "previous",
+        }
+    }
+}
+
+/// Sends `command` to the active player without waiting for it. With the
+/// `mpris` feature this talks to the player over the session bus,
+/// otherwise it runs `playerctl`.
+pub fn control(command: MediaCommand) {
+    #[cfg(feature = "mpris")]
+    std::thread::spawn(move || {
+        if let Err(error) = mpris::control(command) {
+            log::warn!("Failed to send {} to the media player: {}", command.method(), error);
+        }
+    });
+
+    #[cfg(not(feature = "mpris"))]
+    match std::process::Command::new("playerctl")
+        .arg(command.playerctl_argument())
+        .spawn()
+    {
+        Ok(child) => {
+            crate::reaper::track(child);
+        }
+        Err(error) => log::warn!("Failed to run playerctl: {}", error),
+    }
+}
+
+#[cfg(feature = "mpris")]
+pub mod mpris {
+    use std::collections::HashMap;
+    use std::sync::mpsc::Sender;
+
+    use zbus::blocking::{Connection, MessageIterator, Proxy};
+    use zbus::message::Type;
+    use zbus::zvariant::OwnedValue;
+    use zbus::MatchRule;
+
+    use super::MediaCommand;
+
+    const PLAYER_PREFIX: &str = "org.mpris.MediaPlayer2.";
+    const PLAYER_PATH: &str = "/org/mpris/MediaPlayer2";
+    const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+    /// What the active player is playing.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Track {
+        pub artist: String,
+        pub title: String,
+        pub playing: bool,
+    }
+
+    /// The player that is playing, or else the first one on the bus.
+    fn active_player(connection: &Connection) -> zbus::Result<Option<String>> {
+        let bus = Proxy::new(
+            connection,
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+        )?;
+        let names: Vec<String> = bus.call("ListNames", &())?;
+        let players: Vec<String> = names
+            .into_iter()
+            .filter(|name| name.starts_with(PLAYER_PREFIX))
+            .collect();
+
+        let playing = players
+            .iter()
+            .find(|player| status(connection, player).is_ok_and(|status| status == "Playing"));
+        Ok(playing.or(players.first()).cloned())
+    }
+
+    fn property(connection: &Connection, player: &str, name: &str) -> zbus::Result<OwnedValue> {
+        let properties = Proxy::new(connection, player, PLAYER_PATH, "org.freedesktop.DBus.Properties")?;
+        properties.call("Get", &(PLAYER_INTERFACE, name))
+    }
+
+    fn status(connection: &Connection, player: &str) -> zbus::Result<String> {
+        Ok(String::try_from(property(connection, player, "PlaybackStatus")?)?)
+    }
+
+    /// The track of the active player, None when no player is running.
+    pub fn current_track(connection: &Connection) -> zbus::Result<Option<Track>> {
+        let Some(player) = active_player(connection)? else {
+            return Ok(None);
+        };
+
+        let metadata: HashMap<String, OwnedValue> =
+            HashMap::try_from(property(connection, &player, "Metadata")?)?;
+        let title = metadata
+            .get("xesam:title")
+            .and_then(|title| String::try_from(title.try_clone().ok()?).ok())
+            .unwrap_or_default();
+        let artist = metadata
+            .get("xesam:artist")
+            .and_then(|artists| Vec::<String>::try_from(artists.try_clone().ok()?).ok())
+            .map(|artists| artists.join(", "))
+            .unwrap_or_default();
+
+        Ok(Some(Track {
+            artist,
+            title,
+            playing: status(connection, &player).is_ok_and(|status| status == "Playing"),
+        }))
+    }
+
+    pub fn control(command: MediaCommand) -> zbus::Result<()> {
+        let connection = Connection::session()?;
+        let Some(player) = active_player(&connection)? else {
+            return Ok(());
+        };
+        let proxy = Proxy::new(&connection, player.as_str(), PLAYER_PATH, PLAYER_INTERFACE)?;
+        proxy.call_method(command.method(), &())?;
+        Ok(())
+    }
+
+    /// Sends the current track, and again whenever a player changes track
+    /// or state or a player starts or quits, until `sender` is dropped.
+    pub fn watch(sender: Sender<Option<Track>>) -> zbus::Result<()> {
+        let connection = Connection::session()?;
+        sender.send(current_track(&connection)?).ok();
+
+        let players_changed = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .sender("org.freedesktop.DBus")?
+            .member("NameOwnerChanged")?
+            .arg0ns("org.mpris.MediaPlayer2")?
+            .build();
+        let player_connection = connection.clone();
+        let player_sender = sender.clone();
+        std::thread::spawn(move || {
+            let Ok(messages) = MessageIterator::for_match_rule(players_changed, &player_connection, None) else {
+                return;
+            };
+            for _ in messages {
+                let track = current_track(&player_connection).ok().flatten();
+                if player_sender.send(track).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let properties_changed = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .interface("org.freedesktop.DBus.Properties")?
+            .member("PropertiesChanged")?
+            .path(PLAYER_PATH)?
+            .build();
+        for _ in MessageIterator::for_match_rule(properties_changed, &connection, None)? {
+            let track = current_track(&connection).ok().flatten();
+            if sender.send(track).is_err() {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+}
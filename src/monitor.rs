@@ -1,16 +1,57 @@
-use crate::errors::WmError;
-use x11rb::protocol::xinerama::ConnectionExt as _;
+use crate::backend::OutputOps;
+use crate::client::ClientList;
+use crate::errors::{WmError, X11Error};
 use x11rb::protocol::xproto::{Screen, Window};
-use x11rb::rust_connection::RustConnection;
 
 type WmResult<T> = Result<T, WmError>;
 
+pub const DEFAULT_MASTER_FACTOR: f32 = 0.55;
+pub const DEFAULT_NUM_MASTER: i32 = 1;
+
+/// Space a dock reserves along the root window edges, from
+/// _NET_WM_STRUT_PARTIAL (or _NET_WM_STRUT, which spans the whole edge).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Strut {
+    pub left: i32,
+    pub right: i32,
+    pub top: i32,
+    pub bottom: i32,
+    pub left_range: (i32, i32),
+    pub right_range: (i32, i32),
+    pub top_range: (i32, i32),
+    pub bottom_range: (i32, i32),
+}
+
+impl Strut {
+    pub fn from_values(values: &[u32]) -> Option<Self> {
+        let value = |index: usize| values.get(index).map(|&v| v as i32);
+        let full = (0, i32::MAX);
+        let range = |start: usize| match (value(start), value(start + 1)) {
+            (Some(start), Some(end)) => (start, end),
+            _ => full,
+        };
+
+        Some(Self {
+            left: value(0)?,
+            right: value(1)?,
+            top: value(2)?,
+            bottom: value(3)?,
+            left_range: range(4),
+            right_range: range(6),
+            top_range: range(8),
+            bottom_range: range(10),
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Monitor {
     pub layout_symbol: String,
     pub master_factor: f32,
     pub num_master: i32,
     pub monitor_number: usize,
+    pub output_name: Option<String>,
+    pub scale: f32,
     pub bar_y_position: i32,
     pub screen_x: i32,
     pub screen_y: i32,
@@ -28,10 +69,12 @@ pub struct Monitor {
     pub selected_layout_index: usize,
     pub tagset: [u32; 2],
     pub show_bar: bool,
+    /// Whether the bar window is mapped. Differs from `show_bar` while a
+    /// fullscreen client on this monitor covers it.
+    pub bar_mapped: bool,
     pub top_bar: bool,
-    pub clients_head: Option<Window>,
+    pub clients: ClientList,
     pub selected_client: Option<Window>,
-    pub stack_head: Option<Window>,
     pub bar_window: Option<Window>,
     pub layout_indices: [usize; 2],
 }
@@ -40,9 +83,11 @@ impl Monitor {
     pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
         Self {
             layout_symbol: String::from("[]"),
-            master_factor: 0.55,
-            num_master: 1,
+            master_factor: DEFAULT_MASTER_FACTOR,
+            num_master: DEFAULT_NUM_MASTER,
             monitor_number: 0,
+            output_name: None,
+            scale: 1.0,
             bar_y_position: 0,
             screen_x: x,
             screen_y: y,
@@ -60,84 +105,145 @@ impl Monitor {
             selected_layout_index: 0,
             tagset: [1, 1],
             show_bar: true,
+            bar_mapped: true,
             top_bar: true,
-            clients_head: None,
+            clients: ClientList::new(),
             selected_client: None,
-            stack_head: None,
             bar_window: None,
             layout_indices: [0, 1],
         }
     }
 
+    pub fn scaled(&self, value: u32) -> u32 {
+        (value as f32 * self.scale).round() as u32
+    }
+
+    /// The whole monitor as `(x, y, width, height)`, what fullscreen
+    /// clients cover.
+    pub fn screen_area(&self) -> (i32, i32, i32, i32) {
+        (self.screen_x, self.screen_y, self.screen_width, self.screen_height)
+    }
+
+    /// Where clients are placed as `(x, y, width, height)`: the screen minus
+    /// the bar, dock struts and padding, as of the last `update_work_area`.
+    pub fn work_area(&self) -> (i32, i32, i32, i32) {
+        (
+            self.window_area_x,
+            self.window_area_y,
+            self.window_area_width,
+            self.window_area_height,
+        )
+    }
+
+    /// Recomputes the work area from the `(left, right, top, bottom)` space
+    /// reserved by docks and padding and the height of the bar along the
+    /// top, 0 while it is hidden.
+    pub fn update_work_area(&mut self, (left, right, top, bottom): (i32, i32, i32, i32), bar_height: i32) {
+        self.window_area_x = self.screen_x + left;
+        self.window_area_y = self.screen_y + top + bar_height;
+        self.window_area_width = (self.screen_width - left - right).max(1);
+        self.window_area_height = (self.screen_height - top - bottom - bar_height).max(1);
+    }
+
+    /// Moves a window of the given outer size at `(x, y)` as little as
+    /// possible to get it entirely inside the work area, preferring the top
+    /// left corner when it is too big to fit.
+    pub fn clamp_to_work_area(&self, x: i32, y: i32, outer_width: i32, outer_height: i32) -> (i32, i32) {
+        let (area_x, area_y, area_width, area_height) = self.work_area();
+        let x = x.min(area_x + area_width - outer_width).max(area_x);
+        let y = y.min(area_y + area_height - outer_height).max(area_y);
+        (x, y)
+    }
+
     pub fn contains_point(&self, x: i32, y: i32) -> bool {
         x >= self.screen_x
             && x < self.screen_x + self.screen_width
             && y >= self.screen_y
             && y < self.screen_y + self.screen_height
     }
-}
-
-pub fn detect_monitors(
-    connection: &RustConnection,
-    screen: &Screen,
-    _root: Window,
-) -> WmResult<Vec<Monitor>> {
-    let fallback_monitors = || {
-        vec![Monitor::new(
-            0,
-            0,
-            screen.width_in_pixels as u32,
-            screen.height_in_pixels as u32,
-        )]
-    };
 
-    let mut monitors = Vec::<Monitor>::new();
+    /// Returns the (left, right, top, bottom) space `strut` reserves on this
+    /// monitor, given the size of the root window it is relative to.
+    pub fn reserved_by(&self, strut: &Strut, root_width: i32, root_height: i32) -> (i32, i32, i32, i32) {
+        let overlaps = |(start, end): (i32, i32), from: i32, length: i32| start < from + length && end >= from;
+        let monitor_right = self.screen_x + self.screen_width;
+        let monitor_bottom = self.screen_y + self.screen_height;
 
-    let xinerama_active = connection
-        .xinerama_is_active()
-        .ok()
-        .and_then(|cookie| cookie.reply().ok())
-        .map_or(false, |reply| reply.state != 0);
-
-    if xinerama_active {
-        let Ok(xinerama_cookie) = connection.xinerama_query_screens() else {
-            return Ok(fallback_monitors());
+        let left = if strut.left > 0 && overlaps(strut.left_range, self.screen_y, self.screen_height) {
+            (strut.left - self.screen_x).clamp(0, self.screen_width)
+        } else {
+            0
+        };
+        let right = if strut.right > 0 && overlaps(strut.right_range, self.screen_y, self.screen_height) {
+            (monitor_right - (root_width - strut.right)).clamp(0, self.screen_width)
+        } else {
+            0
         };
-        let Ok(xinerama_reply) = xinerama_cookie.reply() else {
-            return Ok(fallback_monitors());
+        let top = if strut.top > 0 && overlaps(strut.top_range, self.screen_x, self.screen_width) {
+            (strut.top - self.screen_y).clamp(0, self.screen_height)
+        } else {
+            0
         };
+        let bottom = if strut.bottom > 0 && overlaps(strut.bottom_range, self.screen_x, self.screen_width) {
+            (monitor_bottom - (root_height - strut.bottom)).clamp(0, self.screen_height)
+        } else {
+            0
+        };
+
+        (left, right, top, bottom)
+    }
+}
+
+/// Applies the per-monitor settings from the config to freshly detected
+/// monitors.
+pub fn apply_monitor_config(monitors: &mut [Monitor], config: &crate::Config) {
+    for monitor in monitors.iter_mut() {
+        monitor.master_factor = config.master_factor;
+        monitor.num_master = config.num_master;
+    }
 
-        for screen_info in &xinerama_reply.screen_info {
-            let has_valid_dimensions = screen_info.width > 0 && screen_info.height > 0;
-            if !has_valid_dimensions {
-                continue;
-            }
-
-            let x_position = screen_info.x_org as i32;
-            let y_position = screen_info.y_org as i32;
-            let width_in_pixels = screen_info.width as u32;
-            let height_in_pixels = screen_info.height as u32;
-
-            let is_duplicate_monitor = monitors.iter().any(|monitor| {
-                monitor.screen_x == x_position
-                    && monitor.screen_y == y_position
-                    && monitor.screen_width == width_in_pixels as i32
-                    && monitor.screen_height == height_in_pixels as i32
-            });
-
-            if !is_duplicate_monitor {
-                monitors.push(Monitor::new(
-                    x_position,
-                    y_position,
-                    width_in_pixels,
-                    height_in_pixels,
-                ));
-            }
+    for monitor_scale in &config.monitor_scales {
+        if let Some(monitor) = monitors.get_mut(monitor_scale.monitor) {
+            monitor.scale = monitor_scale.scale;
         }
     }
 
+    for master in &config.monitor_masters {
+        let Some(monitor) = monitors.get_mut(master.monitor) else {
+            continue;
+        };
+        if let Some(master_factor) = master.master_factor {
+            monitor.master_factor = master_factor;
+        }
+        if let Some(num_master) = master.num_master {
+            monitor.num_master = num_master;
+        }
+    }
+}
+
+pub fn detect_monitors(
+    backend: &impl OutputOps<Error = X11Error>,
+    screen: &Screen,
+    root: Window,
+    monitor_order: &[String],
+) -> WmResult<Vec<Monitor>> {
+    let mut monitors: Vec<Monitor> = backend
+        .outputs(root)?
+        .into_iter()
+        .map(|output| {
+            let mut monitor = Monitor::new(output.x, output.y, output.width, output.height);
+            monitor.output_name = output.name;
+            monitor
+        })
+        .collect();
+
     if monitors.is_empty() {
-        monitors = fallback_monitors();
+        monitors.push(Monitor::new(
+            0,
+            0,
+            screen.width_in_pixels as u32,
+            screen.height_in_pixels as u32,
+        ));
     }
 
     monitors.sort_by(|a, b| match a.screen_y.cmp(&b.screen_y) {
@@ -145,5 +251,20 @@ pub fn detect_monitors(
         other => other,
     });
 
+    if !monitor_order.is_empty() {
+        let order_position = |monitor: &Monitor| {
+            monitor
+                .output_name
+                .as_ref()
+                .and_then(|name| monitor_order.iter().position(|ordered| ordered == name))
+                .unwrap_or(monitor_order.len())
+        };
+        monitors.sort_by_key(|monitor| order_position(monitor));
+    }
+
+    for (index, monitor) in monitors.iter_mut().enumerate() {
+        monitor.monitor_number = index;
+    }
+
     Ok(monitors)
 }
@@ -4,15 +4,37 @@ use crate::errors::X11Error;
 use x11rb::connection::Connection;
 use x11rb::protocol::xproto::*;
 use x11rb::rust_connection::RustConnection;
+use std::io::Write;
+use std::process::{Command, Stdio};
 
 const PADDING: i16 = 20;
 const LINE_SPACING: i16 = 5;
 const BORDER_WIDTH: u16 = 2;
 const BORDER_COLOR: u32 = 0xff5555;
+const HINT_COLOR: u32 = 0x888888;
+const SCROLL_HINT: &str = "j/k scroll  ·  y copy  ·  Esc close";
+
+/// Clipboard tools tried in order when copying the error message.
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+];
+
+pub enum ErrorScroll {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+}
 
 pub struct ErrorOverlay {
     base: OverlayBase,
+    message: String,
     lines: Vec<String>,
+    scroll_offset: usize,
+    visible_lines: usize,
 }
 
 impl ErrorOverlay {
@@ -39,7 +61,10 @@ impl ErrorOverlay {
 
         Ok(ErrorOverlay {
             base,
+            message: String::new(),
             lines: Vec::new(),
+            scroll_offset: 0,
+            visible_lines: 0,
         })
     }
 
@@ -53,11 +78,24 @@ impl ErrorOverlay {
         screen_width: u16,
         screen_height: u16,
     ) -> Result<(), X11Error> {
-        let max_line_width = (screen_width as i16 / 2 - PADDING * 4).max(300) as u16;
+        let max_line_width = (screen_width as i16 - PADDING * 6).max(300) as u16;
+        self.message = error_text.to_string();
         let error_with_instruction = format!("{}\n\nFix the config file and reload.", error_text);
         self.lines = self.wrap_text(&error_with_instruction, font, max_line_width);
+        self.scroll_offset = 0;
+
+        let line_height = font.height() + LINE_SPACING as u16;
+        let max_height = (screen_height as u32 * 3 / 4) as u16;
+        let max_lines = (max_height.saturating_sub(PADDING as u16 * 2) / line_height).max(1) as usize;
+
+        let is_scrollable = self.lines.len() > max_lines;
+        self.visible_lines = if is_scrollable {
+            max_lines - 1
+        } else {
+            self.lines.len()
+        };
 
-        let mut content_width = 0u16;
+        let mut content_width = font.text_width(SCROLL_HINT);
         for line in &self.lines {
             let line_width = font.text_width(line);
             if line_width > content_width {
@@ -66,18 +104,69 @@ impl ErrorOverlay {
         }
 
         let width = content_width + (PADDING as u16 * 2);
-        let line_height = font.height() + LINE_SPACING as u16;
-        let height = (self.lines.len() as u16 * line_height) + (PADDING as u16 * 2);
+        let hint_lines = if is_scrollable { 1 } else { 0 };
+        let height = ((self.visible_lines + hint_lines) as u16 * line_height) + (PADDING as u16 * 2);
 
-        let x = monitor_x + ((screen_width - width) / 2) as i16;
-        let y = monitor_y + ((screen_height - height) / 2) as i16;
+        let x = monitor_x + (screen_width.saturating_sub(width) / 2) as i16;
+        let y = monitor_y + (screen_height.saturating_sub(height) / 2) as i16;
 
         self.base.configure(connection, x, y, width, height)?;
         self.base.show(connection)?;
+        connection.grab_keyboard(
+            false,
+            self.base.window,
+            x11rb::CURRENT_TIME,
+            GrabMode::ASYNC,
+            GrabMode::ASYNC,
+        )?;
         self.draw(connection, font)?;
         Ok(())
     }
 
+    pub fn scroll(
+        &mut self,
+        connection: &RustConnection,
+        font: &Font,
+        direction: ErrorScroll,
+    ) -> Result<(), X11Error> {
+        let max_offset = self.lines.len().saturating_sub(self.visible_lines);
+        let page = self.visible_lines.max(1);
+        self.scroll_offset = match direction {
+            ErrorScroll::Up => self.scroll_offset.saturating_sub(1),
+            ErrorScroll::Down => self.scroll_offset + 1,
+            ErrorScroll::PageUp => self.scroll_offset.saturating_sub(page),
+            ErrorScroll::PageDown => self.scroll_offset + page,
+            ErrorScroll::Top => 0,
+            ErrorScroll::Bottom => max_offset,
+        }
+        .min(max_offset);
+        self.draw(connection, font)
+    }
+
+    /// Copies the error message to the clipboard using whichever of
+    /// xclip/xsel is installed.
+    pub fn copy_to_clipboard(&self) {
+        for command in CLIPBOARD_COMMANDS {
+            let Ok(mut child) = Command::new(command[0])
+                .args(&command[1..])
+                .stdin(Stdio::piped())
+                .spawn()
+            else {
+                continue;
+            };
+            let written = child
+                .stdin
+                .take()
+                .map(|mut stdin| stdin.write_all(self.message.as_bytes()));
+            if let Some(Err(error)) = written {
+                log::error!("Failed to copy error to clipboard: {}", error);
+            }
+            std::thread::spawn(move || child.wait());
+            return;
+        }
+        log::warn!("Failed to copy error to clipboard: neither xclip nor xsel is installed");
+    }
+
     fn wrap_text(&self, text: &str, font: &Font, max_width: u16) -> Vec<String> {
         let mut lines = Vec::new();
         for paragraph in text.lines() {
@@ -86,12 +175,28 @@ impl ErrorOverlay {
                 continue;
             }
 
+            let indent: String = paragraph.chars().take_while(|c| c.is_whitespace()).collect();
             let words: Vec<&str> = paragraph.split_whitespace().collect();
             let mut current_line = String::new();
 
             for word in words {
+                if font.text_width(word) > max_width {
+                    if !current_line.is_empty() {
+                        lines.push(std::mem::take(&mut current_line));
+                    }
+                    for character in word.chars() {
+                        current_line.push(character);
+                        if font.text_width(&current_line) > max_width {
+                            current_line.pop();
+                            lines.push(std::mem::take(&mut current_line));
+                            current_line.push(character);
+                        }
+                    }
+                    continue;
+                }
+
                 let test_line = if current_line.is_empty() {
-                    word.to_string()
+                    format!("{}{}", indent, word)
                 } else {
                     format!("{} {}", current_line, word)
                 };
@@ -122,8 +227,12 @@ impl Overlay for ErrorOverlay {
     }
 
     fn hide(&mut self, connection: &RustConnection) -> Result<(), X11Error> {
+        if self.base.is_visible {
+            connection.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+        }
         self.base.hide(connection)?;
         self.lines.clear();
+        self.message.clear();
         Ok(())
     }
 
@@ -134,12 +243,25 @@ impl Overlay for ErrorOverlay {
         self.base.draw_background(connection)?;
         let line_height = font.height() + LINE_SPACING as u16;
         let mut y = PADDING + font.ascent();
-        for line in &self.lines {
+        for line in self.lines.iter().skip(self.scroll_offset).take(self.visible_lines) {
             self.base
                 .font_draw
                 .draw_text(font, self.base.foreground_color, PADDING, y, line);
             y += line_height as i16;
         }
+        if self.lines.len() > self.visible_lines {
+            let last_line = (self.scroll_offset + self.visible_lines).min(self.lines.len());
+            let hint = format!(
+                "{}  ({}-{} of {})",
+                SCROLL_HINT,
+                self.scroll_offset + 1,
+                last_line,
+                self.lines.len()
+            );
+            self.base
+                .font_draw
+                .draw_text(font, HINT_COLOR, PADDING, y, &hint);
+        }
         connection.flush()?;
         Ok(())
     }
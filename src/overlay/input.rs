@@ -0,0 +1,141 @@
+use super::{Overlay, OverlayBase};
+use crate::bar::font::Font;
+use crate::errors::X11Error;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+
+const PADDING: i16 = 10;
+const WIDTH: u16 = 320;
+const BORDER_WIDTH: u16 = 2;
+const BORDER_COLOR: u32 = 0x7fccff;
+const PROMPT_COLOR: u32 = 0x7fccff;
+const CURSOR: &str = "_";
+
+/// Single line text prompt, used for renaming tags.
+pub struct TextInput {
+    base: OverlayBase,
+    prompt: String,
+    text: String,
+}
+
+impl TextInput {
+    pub fn new(
+        connection: &RustConnection,
+        screen: &Screen,
+        screen_num: usize,
+        display: *mut x11::xlib::Display,
+    ) -> Result<Self, X11Error> {
+        let base = OverlayBase::new(
+            connection,
+            screen,
+            screen_num,
+            display,
+            WIDTH,
+            100,
+            BORDER_WIDTH,
+            BORDER_COLOR,
+            0x1a1a1a,
+            0xffffff,
+        )?;
+
+        Ok(TextInput {
+            base,
+            prompt: String::new(),
+            text: String::new(),
+        })
+    }
+
+    /// Shows the prompt pre-filled with `initial`, centered horizontally in
+    /// the strip given as `(x, y, width)`.
+    pub fn show(
+        &mut self,
+        connection: &RustConnection,
+        font: &Font,
+        prompt: &str,
+        initial: &str,
+        (monitor_x, y, screen_width): (i16, i16, u16),
+    ) -> Result<(), X11Error> {
+        self.prompt = prompt.to_string();
+        self.text = initial.to_string();
+
+        let width = WIDTH.min(screen_width);
+        let height = font.height() + (PADDING as u16 * 2);
+        let x = monitor_x + ((screen_width - width) / 2) as i16;
+
+        self.base.configure(connection, x, y, width, height)?;
+        self.base.show(connection)?;
+        connection.grab_keyboard(
+            false,
+            self.base.window,
+            x11rb::CURRENT_TIME,
+            GrabMode::ASYNC,
+            GrabMode::ASYNC,
+        )?;
+        self.draw(connection, font)?;
+        Ok(())
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn push(
+        &mut self,
+        connection: &RustConnection,
+        font: &Font,
+        character: char,
+    ) -> Result<(), X11Error> {
+        self.text.push(character);
+        self.draw(connection, font)
+    }
+
+    pub fn pop(&mut self, connection: &RustConnection, font: &Font) -> Result<(), X11Error> {
+        self.text.pop();
+        self.draw(connection, font)
+    }
+}
+
+impl Overlay for TextInput {
+    fn window(&self) -> Window {
+        self.base.window
+    }
+
+    fn is_visible(&self) -> bool {
+        self.base.is_visible
+    }
+
+    fn hide(&mut self, connection: &RustConnection) -> Result<(), X11Error> {
+        if self.base.is_visible {
+            connection.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+        }
+        self.base.hide(connection)?;
+        self.text.clear();
+        Ok(())
+    }
+
+    fn draw(&self, connection: &RustConnection, font: &Font) -> Result<(), X11Error> {
+        if !self.base.is_visible {
+            return Ok(());
+        }
+
+        self.base.draw_background(connection)?;
+
+        let y = PADDING + font.ascent();
+        self.base
+            .font_draw
+            .draw_text(font, PROMPT_COLOR, PADDING, y, &self.prompt);
+
+        let text_x = PADDING + font.text_width(&self.prompt) as i16 + PADDING;
+        let text = format!("{}{}", self.text, CURSOR);
+        self.base
+            .font_draw
+            .draw_text(font, self.base.foreground_color, text_x, y, &text);
+
+        self.base.font_draw.flush();
+
+        connection.flush()?;
+
+        Ok(())
+    }
+}
@@ -162,15 +162,40 @@ impl KeybindOverlay {
             if let Some(binding) = binding {
                 if !binding.keys.is_empty() {
                     let key_str = self.format_key_combo(&binding.keys[0]);
-                    let action_str = self.action_description(binding);
+                    let action_str = self.binding_description(binding);
                     result.push((key_str, action_str));
                 }
             }
         }
 
+        // Anything the user described is worth listing, not just the
+        // priority actions.
+        for binding in keybindings {
+            let Some(description) = &binding.description else {
+                continue;
+            };
+            if binding.keys.is_empty() || priority_actions.contains(&binding.func) {
+                continue;
+            }
+            let key_str = binding
+                .keys
+                .iter()
+                .map(|key| self.format_key_combo(key))
+                .collect::<Vec<_>>()
+                .join(", ");
+            result.push((key_str, description.clone()));
+        }
+
         result
     }
 
+    fn binding_description(&self, binding: &KeyBinding) -> String {
+        binding
+            .description
+            .clone()
+            .unwrap_or_else(|| self.action_description(binding))
+    }
+
     fn format_key_combo(&self, key: &KeyPress) -> String {
         let mut parts = Vec::new();
 
@@ -197,17 +222,27 @@ impl KeybindOverlay {
         match binding.func {
             KeyAction::ShowKeybindOverlay => "Show This Keybind Help".to_string(),
             KeyAction::Quit => "Quit Window Manager".to_string(),
-            KeyAction::Restart => "Restart Window Manager".to_string(),
+            KeyAction::ForceQuit => "Quit Without Closing Windows".to_string(),
+            KeyAction::Restart => match &binding.arg {
+                Arg::Str(mode) if mode == "exec" => "Re-exec Window Manager".to_string(),
+                _ => "Restart Window Manager".to_string(),
+            },
             KeyAction::Recompile => "Recompile Window Manager".to_string(),
             KeyAction::KillClient => "Close Focused Window".to_string(),
+            KeyAction::ForceKill => "Kill Focused Window".to_string(),
             KeyAction::Spawn => match &binding.arg {
                 Arg::Str(cmd) => format!("Launch: {}", cmd),
                 Arg::Array(arr) if !arr.is_empty() => format!("Launch: {}", arr[0]),
+                Arg::Spawn(spawn) if !spawn.argv.is_empty() => {
+                    format!("Launch: {}", spawn.argv.join(" "))
+                }
                 _ => "Launch Program".to_string(),
             },
             KeyAction::SpawnTerminal => "Launch Terminal".to_string(),
             KeyAction::FocusStack => "Focus Next/Previous Window".to_string(),
             KeyAction::MoveStack => "Move Window Up/Down Stack".to_string(),
+            KeyAction::Zoom => "Swap Window with Master".to_string(),
+            KeyAction::WindowSwitcher => "Switch Windows".to_string(),
             KeyAction::ViewTag => match &binding.arg {
                 Arg::Int(n) => format!("View Workspace {}", n),
                 _ => "View Workspace".to_string(),
@@ -218,15 +253,66 @@ impl KeybindOverlay {
             },
             KeyAction::MoveToTag => "Move Window to Workspace".to_string(),
             KeyAction::ToggleTag => "Toggle Window on Workspace".to_string(),
+            KeyAction::AddTag => "Add New Workspace".to_string(),
+            KeyAction::RenameTag => match &binding.arg {
+                Arg::Str(name) => format!("Rename Workspace to {}", name),
+                _ => "Rename Workspace".to_string(),
+            },
             KeyAction::ToggleGaps => "Toggle Window Gaps".to_string(),
+            KeyAction::AdjustGaps => "Adjust Window Gaps".to_string(),
+            KeyAction::AdjustInnerGaps => "Adjust Inner Gaps".to_string(),
+            KeyAction::AdjustOuterGaps => "Adjust Outer Gaps".to_string(),
             KeyAction::ToggleFullScreen => "Toggle Fullscreen Mode".to_string(),
+            KeyAction::FullscreenSpan => "Toggle Fullscreen Across Monitors".to_string(),
             KeyAction::ToggleFloating => "Toggle Floating Mode".to_string(),
+            KeyAction::ToggleFocusLock => "Toggle Focus Lock (Do Not Disturb)".to_string(),
+            KeyAction::ToggleBar => "Toggle Bar".to_string(),
+            KeyAction::ToggleMaximize => "Toggle Maximize".to_string(),
+            KeyAction::CenterWindow => "Center Window".to_string(),
+            KeyAction::MediaPlayPause => "Play/Pause Media".to_string(),
+            KeyAction::MediaNext => "Next Track".to_string(),
+            KeyAction::MediaPrevious => "Previous Track".to_string(),
+            KeyAction::SnapWindow => match &binding.arg {
+                Arg::Str(region) => format!("Snap Window to {}", region.replace('-', " ")),
+                _ => "Snap Window".to_string(),
+            },
+            KeyAction::MinimizeClient => "Minimize Window".to_string(),
+            KeyAction::RestoreLast => "Restore Last Minimized Window".to_string(),
+            KeyAction::StashWindow => "Stash Window".to_string(),
+            KeyAction::UnstashWindow => "Recall Last Stashed Window".to_string(),
+            KeyAction::EnterMode => match &binding.arg {
+                Arg::Str(name) => format!("Enter {} Mode", name),
+                _ => "Enter Mode".to_string(),
+            },
+            KeyAction::ExitMode => "Leave Mode".to_string(),
+            KeyAction::ReloadTheme => "Reload Theme Colors".to_string(),
+            KeyAction::Screenshot => match &binding.arg {
+                Arg::Str(mode) if mode == "window" => "Screenshot Focused Window".to_string(),
+                Arg::Str(mode) if mode == "selection" => "Screenshot Selection".to_string(),
+                _ => "Screenshot Screen".to_string(),
+            },
             KeyAction::ChangeLayout => "Change Layout".to_string(),
             KeyAction::CycleLayout => "Cycle Through Layouts".to_string(),
             KeyAction::FocusMonitor => "Focus Next Monitor".to_string(),
             KeyAction::TagMonitor => "Send Window to Monitor".to_string(),
-            KeyAction::SetMasterFactor => "Adjust Master Area Size".to_string(),
-            KeyAction::IncNumMaster => "Adjust Number of Master Windows".to_string(),
+            KeyAction::TagMonitorFollow => "Move Window and Focus to Monitor".to_string(),
+            KeyAction::SetMasterFactor => match &binding.arg {
+                Arg::Int(delta) if *delta > 0 => "Grow Master Area".to_string(),
+                Arg::Int(delta) if *delta < 0 => "Shrink Master Area".to_string(),
+                _ => "Adjust Master Area Size".to_string(),
+            },
+            KeyAction::ResizeMaster => match &binding.arg {
+                Arg::Array(arg) if !arg.is_empty() => format!("Resize Master Area {}", arg[0]),
+                _ => "Resize Master Area".to_string(),
+            },
+            KeyAction::IncNumMaster => match &binding.arg {
+                Arg::Int(delta) if *delta > 0 => "More Master Windows".to_string(),
+                Arg::Int(delta) if *delta < 0 => "Fewer Master Windows".to_string(),
+                _ => "Adjust Number of Master Windows".to_string(),
+            },
+            KeyAction::FocusTabNext => "Focus Next Tab".to_string(),
+            KeyAction::FocusTabPrev => "Focus Previous Tab".to_string(),
+            KeyAction::SetOpacity => "Adjust Window Opacity".to_string(),
             KeyAction::None => "No Action".to_string(),
         }
     }
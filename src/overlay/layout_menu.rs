@@ -0,0 +1,178 @@
+use super::{Overlay, OverlayBase};
+use crate::bar::font::Font;
+use crate::errors::X11Error;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+
+const PADDING: i16 = 8;
+const LINE_SPACING: i16 = 6;
+const BORDER_WIDTH: u16 = 2;
+const BORDER_COLOR: u32 = 0x7fccff;
+const HIGHLIGHT_COLOR: u32 = 0x2a2a2a;
+
+/// Small popup listing every layout, opened by right clicking the layout
+/// symbol in the bar.
+pub struct LayoutMenu {
+    base: OverlayBase,
+    entries: Vec<(String, String)>,
+    selected: usize,
+    line_height: u16,
+}
+
+impl LayoutMenu {
+    pub fn new(
+        connection: &RustConnection,
+        screen: &Screen,
+        screen_num: usize,
+        display: *mut x11::xlib::Display,
+    ) -> Result<Self, X11Error> {
+        let base = OverlayBase::new(
+            connection,
+            screen,
+            screen_num,
+            display,
+            100,
+            100,
+            BORDER_WIDTH,
+            BORDER_COLOR,
+            0x1a1a1a,
+            0xffffff,
+        )?;
+
+        Ok(LayoutMenu {
+            base,
+            entries: Vec::new(),
+            selected: 0,
+            line_height: 0,
+        })
+    }
+
+    /// Shows the menu with its top left corner at `x`, `y`. `entries` are
+    /// `(layout name, label)` pairs and `current` is the active layout name.
+    pub fn show(
+        &mut self,
+        connection: &RustConnection,
+        font: &Font,
+        entries: Vec<(String, String)>,
+        current: &str,
+        x: i16,
+        y: i16,
+    ) -> Result<(), X11Error> {
+        self.selected = entries
+            .iter()
+            .position(|(name, _)| name == current)
+            .unwrap_or(0);
+        self.entries = entries;
+        self.line_height = font.height() + LINE_SPACING as u16;
+
+        let max_label_width = self
+            .entries
+            .iter()
+            .map(|(_, label)| font.text_width(label))
+            .max()
+            .unwrap_or(0);
+        let width = max_label_width + (PADDING as u16 * 2);
+        let height = self.entries.len() as u16 * self.line_height + (PADDING as u16 * 2);
+
+        self.base.configure(connection, x, y, width, height)?;
+        self.base.show(connection)?;
+        connection.grab_keyboard(
+            false,
+            self.base.window,
+            x11rb::CURRENT_TIME,
+            GrabMode::ASYNC,
+            GrabMode::ASYNC,
+        )?;
+        self.draw(connection, font)?;
+        Ok(())
+    }
+
+    /// Moves the highlighted entry by `delta`, wrapping around.
+    pub fn move_selection(
+        &mut self,
+        connection: &RustConnection,
+        font: &Font,
+        delta: i32,
+    ) -> Result<(), X11Error> {
+        if self.entries.is_empty() {
+            return Ok(());
+        }
+        let count = self.entries.len() as i32;
+        self.selected = (self.selected as i32 + delta).rem_euclid(count) as usize;
+        self.draw(connection, font)
+    }
+
+    pub fn selected_layout(&self) -> Option<String> {
+        self.entries.get(self.selected).map(|(name, _)| name.clone())
+    }
+
+    /// The layout whose entry is at `y`, relative to the menu window.
+    pub fn layout_at(&self, y: i16) -> Option<String> {
+        if y < PADDING || self.line_height == 0 {
+            return None;
+        }
+        let index = ((y - PADDING) as u16 / self.line_height) as usize;
+        self.entries.get(index).map(|(name, _)| name.clone())
+    }
+}
+
+impl Overlay for LayoutMenu {
+    fn window(&self) -> Window {
+        self.base.window
+    }
+
+    fn is_visible(&self) -> bool {
+        self.base.is_visible
+    }
+
+    fn hide(&mut self, connection: &RustConnection) -> Result<(), X11Error> {
+        if self.base.is_visible {
+            connection.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+        }
+        self.base.hide(connection)?;
+        self.entries.clear();
+        Ok(())
+    }
+
+    fn draw(&self, connection: &RustConnection, font: &Font) -> Result<(), X11Error> {
+        if !self.base.is_visible {
+            return Ok(());
+        }
+
+        self.base.draw_background(connection)?;
+
+        let mut y = PADDING;
+        for (index, (_, label)) in self.entries.iter().enumerate() {
+            if index == self.selected {
+                connection.change_gc(
+                    self.base.graphics_context,
+                    &ChangeGCAux::new().foreground(HIGHLIGHT_COLOR),
+                )?;
+                connection.poly_fill_rectangle(
+                    self.base.window,
+                    self.base.graphics_context,
+                    &[Rectangle {
+                        x: 0,
+                        y,
+                        width: self.base.width,
+                        height: self.line_height,
+                    }],
+                )?;
+            }
+
+            let text_y = y + LINE_SPACING / 2 + font.ascent();
+            self.base
+                .font_draw
+                .draw_text(font, self.base.foreground_color, PADDING, text_y, label);
+
+            y += self.line_height as i16;
+        }
+
+        self.base.font_draw.flush();
+
+        connection.flush()?;
+
+        Ok(())
+    }
+}
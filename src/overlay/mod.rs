@@ -6,10 +6,20 @@ use x11rb::protocol::xproto::*;
 use x11rb::rust_connection::RustConnection;
 
 pub mod error;
+pub mod input;
 pub mod keybind;
-
-pub use error::ErrorOverlay;
+pub mod layout_menu;
+pub mod osd;
+#[cfg(feature = "window-previews")]
+pub mod preview;
+pub mod switcher;
+
+pub use error::{ErrorOverlay, ErrorScroll};
+pub use input::TextInput;
 pub use keybind::KeybindOverlay;
+pub use layout_menu::LayoutMenu;
+pub use osd::Osd;
+pub use switcher::{SwitcherEntry, WindowSwitcher};
 
 pub trait Overlay {
     fn window(&self) -> Window;
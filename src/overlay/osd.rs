@@ -0,0 +1,136 @@
+use super::{Overlay, OverlayBase};
+use crate::bar::font::Font;
+use crate::errors::X11Error;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+
+const PADDING: i16 = 10;
+const WIDTH: u16 = 240;
+const BAR_HEIGHT: u16 = 6;
+const BOTTOM_MARGIN: i16 = 80;
+const BORDER_WIDTH: u16 = 2;
+const BORDER_COLOR: u32 = 0x7fccff;
+const TRACK_COLOR: u32 = 0x333333;
+const FILL_COLOR: u32 = 0x7fccff;
+
+/// On screen display briefly showing a value being adjusted, like the
+/// master factor or gaps, with a bar for how far along its range it is.
+pub struct Osd {
+    base: OverlayBase,
+    label: String,
+    value: String,
+    fraction: f32,
+}
+
+impl Osd {
+    pub fn new(
+        connection: &RustConnection,
+        screen: &Screen,
+        screen_num: usize,
+        display: *mut x11::xlib::Display,
+    ) -> Result<Self, X11Error> {
+        let base = OverlayBase::new(
+            connection,
+            screen,
+            screen_num,
+            display,
+            WIDTH,
+            100,
+            BORDER_WIDTH,
+            BORDER_COLOR,
+            0x1a1a1a,
+            0xffffff,
+        )?;
+
+        Ok(Osd {
+            base,
+            label: String::new(),
+            value: String::new(),
+            fraction: 0.0,
+        })
+    }
+
+    /// Shows `label` and `value` above a bar filled to `fraction`, centered
+    /// near the bottom of the monitor given as `(x, y, width, height)`.
+    pub fn show(
+        &mut self,
+        connection: &RustConnection,
+        font: &Font,
+        label: &str,
+        value: &str,
+        fraction: f32,
+        (monitor_x, monitor_y, monitor_width, monitor_height): (i16, i16, u16, u16),
+    ) -> Result<(), X11Error> {
+        self.label = label.to_string();
+        self.value = value.to_string();
+        self.fraction = fraction.clamp(0.0, 1.0);
+
+        let width = WIDTH.min(monitor_width);
+        let height = font.height() + BAR_HEIGHT + (PADDING as u16 * 3);
+        let x = monitor_x + ((monitor_width - width) / 2) as i16;
+        let y = monitor_y + monitor_height as i16 - height as i16 - BOTTOM_MARGIN;
+
+        self.base.configure(connection, x, y, width, height)?;
+        if !self.base.is_visible {
+            self.base.show(connection)?;
+        }
+        self.draw(connection, font)
+    }
+}
+
+impl Overlay for Osd {
+    fn window(&self) -> Window {
+        self.base.window
+    }
+
+    fn is_visible(&self) -> bool {
+        self.base.is_visible
+    }
+
+    fn hide(&mut self, connection: &RustConnection) -> Result<(), X11Error> {
+        self.base.hide(connection)
+    }
+
+    fn draw(&self, connection: &RustConnection, font: &Font) -> Result<(), X11Error> {
+        if !self.base.is_visible {
+            return Ok(());
+        }
+
+        self.base.draw_background(connection)?;
+
+        let text_y = PADDING + font.ascent();
+        self.base
+            .font_draw
+            .draw_text(font, self.base.foreground_color, PADDING, text_y, &self.label);
+
+        let value_x = self.base.width as i16 - PADDING - font.text_width(&self.value) as i16;
+        self.base
+            .font_draw
+            .draw_text(font, FILL_COLOR, value_x, text_y, &self.value);
+        self.base.font_draw.flush();
+
+        let track = Rectangle {
+            x: PADDING,
+            y: PADDING * 2 + font.height() as i16,
+            width: self.base.width - PADDING as u16 * 2,
+            height: BAR_HEIGHT,
+        };
+        let fill = Rectangle {
+            width: (track.width as f32 * self.fraction).round() as u16,
+            ..track
+        };
+
+        for (color, rectangle) in [(TRACK_COLOR, track), (FILL_COLOR, fill)] {
+            connection.change_gc(
+                self.base.graphics_context,
+                &ChangeGCAux::new().foreground(color),
+            )?;
+            connection.poly_fill_rectangle(self.base.window, self.base.graphics_context, &[rectangle])?;
+        }
+
+        connection.flush()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use crate::errors::X11Error;
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::composite::{self, ConnectionExt as _, Redirect};
+use x11rb::protocol::render::{self, ConnectionExt as _, CreatePictureAux, PictOp, Pictformat, Transform};
+use x11rb::protocol::xproto::{ConnectionExt as _, MapState, Visualid, Window};
+use x11rb::rust_connection::RustConnection;
+
+/// Live window contents read through the composite extension and scaled
+/// down with render.
+///
+/// Every top level window is redirected offscreen automatically, so the
+/// server keeps drawing them to the screen as usual while their contents
+/// stay readable, including windows moved off screen on hidden tags.
+pub struct Previews {
+    formats: render::QueryPictFormatsReply,
+}
+
+impl Previews {
+    /// Redirects the children of `root`. None when the server lacks the
+    /// composite or render extension.
+    pub fn new(connection: &RustConnection, root: Window) -> Result<Option<Self>, X11Error> {
+        if connection.extension_information(composite::X11_EXTENSION_NAME)?.is_none()
+            || connection.extension_information(render::X11_EXTENSION_NAME)?.is_none()
+        {
+            return Ok(None);
+        }
+
+        connection.composite_query_version(0, 4)?.reply()?;
+        connection.render_query_version(0, 11)?.reply()?;
+        connection.composite_redirect_subwindows(root, Redirect::AUTOMATIC)?;
+        let formats = connection.render_query_pict_formats()?.reply()?;
+
+        Ok(Some(Previews { formats }))
+    }
+
+    fn format_for(&self, visual: Visualid) -> Option<Pictformat> {
+        self.formats
+            .screens
+            .iter()
+            .flat_map(|screen| &screen.depths)
+            .flat_map(|depth| &depth.visuals)
+            .find(|pict_visual| pict_visual.visual == visual)
+            .map(|pict_visual| pict_visual.format)
+    }
+
+    /// Draws `source` scaled down to fit `(x, y, width, height)` of `target`
+    /// and centered in it. Returns false without drawing anything when the
+    /// window has no contents to show, like an unmapped one.
+    pub fn draw(
+        &self,
+        connection: &RustConnection,
+        source: Window,
+        target: Window,
+        target_visual: Visualid,
+        (x, y, width, height): (i16, i16, u16, u16),
+    ) -> Result<bool, X11Error> {
+        let Ok(attributes) = connection.get_window_attributes(source)?.reply() else {
+            return Ok(false);
+        };
+        if attributes.map_state != MapState::VIEWABLE {
+            return Ok(false);
+        }
+        let Ok(geometry) = connection.get_geometry(source)?.reply() else {
+            return Ok(false);
+        };
+        let (Some(source_format), Some(target_format)) =
+            (self.format_for(attributes.visual), self.format_for(target_visual))
+        else {
+            return Ok(false);
+        };
+
+        let source_width = geometry.width + geometry.border_width * 2;
+        let source_height = geometry.height + geometry.border_width * 2;
+        let scale = (width as f32 / source_width as f32)
+            .min(height as f32 / source_height as f32)
+            .min(1.0);
+        let scaled_width = ((source_width as f32 * scale) as u16).max(1);
+        let scaled_height = ((source_height as f32 * scale) as u16).max(1);
+        let scaled_x = x + ((width - scaled_width) / 2) as i16;
+        let scaled_y = y + ((height - scaled_height) / 2) as i16;
+
+        let pixmap = connection.generate_id()?;
+        let source_picture = connection.generate_id()?;
+        let target_picture = connection.generate_id()?;
+        connection.composite_name_window_pixmap(source, pixmap)?;
+        connection.render_create_picture(source_picture, pixmap, source_format, &CreatePictureAux::new())?;
+        connection.render_create_picture(target_picture, target, target_format, &CreatePictureAux::new())?;
+
+        // The transform maps destination pixels back to source pixels, so
+        // shrinking by `scale` means stretching the coordinates by its inverse.
+        let inverse = to_fixed(1.0 / scale);
+        connection.render_set_picture_transform(
+            source_picture,
+            Transform {
+                matrix11: inverse,
+                matrix12: 0,
+                matrix13: 0,
+                matrix21: 0,
+                matrix22: inverse,
+                matrix23: 0,
+                matrix31: 0,
+                matrix32: 0,
+                matrix33: to_fixed(1.0),
+            },
+        )?;
+        connection.render_set_picture_filter(source_picture, b"bilinear", &[])?;
+        connection.render_composite(
+            PictOp::SRC,
+            source_picture,
+            x11rb::NONE,
+            target_picture,
+            0,
+            0,
+            0,
+            0,
+            scaled_x,
+            scaled_y,
+            scaled_width,
+            scaled_height,
+        )?;
+
+        connection.render_free_picture(source_picture)?;
+        connection.render_free_picture(target_picture)?;
+        connection.free_pixmap(pixmap)?;
+        Ok(true)
+    }
+}
+
+/// Converts to the 16.16 fixed point numbers render uses.
+fn to_fixed(value: f32) -> render::Fixed {
+    (value * 65536.0).round() as render::Fixed
+}
@@ -0,0 +1,316 @@
+use super::{Overlay, OverlayBase};
+use crate::bar::font::Font;
+use crate::errors::X11Error;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+
+const PADDING: i16 = 12;
+const THUMBNAIL_WIDTH: u16 = 200;
+const THUMBNAIL_HEIGHT: u16 = 125;
+const LABEL_SPACING: i16 = 6;
+const MAX_COLUMNS: usize = 5;
+const BORDER_WIDTH: u16 = 2;
+const BORDER_COLOR: u32 = 0x7fccff;
+const HIGHLIGHT_COLOR: u32 = 0x2a2a2a;
+const THUMBNAIL_COLOR: u32 = 0x111111;
+const CLASS_COLOR: u32 = 0x7fccff;
+
+/// A window offered by the switcher.
+pub struct SwitcherEntry {
+    pub window: Window,
+    pub class: String,
+    pub title: String,
+}
+
+/// Alt-Tab style popup showing windows as a grid of cells, each with a
+/// miniature of the window above its title.
+///
+/// With the `window-previews` feature the miniature is the live window
+/// contents, otherwise (or when the window has nothing to show, like a
+/// minimized one) its class is written in its place.
+pub struct WindowSwitcher {
+    base: OverlayBase,
+    entries: Vec<SwitcherEntry>,
+    selected: usize,
+    columns: usize,
+    line_height: u16,
+    #[cfg(feature = "window-previews")]
+    previews: Option<super::preview::Previews>,
+    #[cfg(feature = "window-previews")]
+    visual: Visualid,
+}
+
+impl WindowSwitcher {
+    pub fn new(
+        connection: &RustConnection,
+        screen: &Screen,
+        screen_num: usize,
+        display: *mut x11::xlib::Display,
+    ) -> Result<Self, X11Error> {
+        let base = OverlayBase::new(
+            connection,
+            screen,
+            screen_num,
+            display,
+            100,
+            100,
+            BORDER_WIDTH,
+            BORDER_COLOR,
+            0x1a1a1a,
+            0xffffff,
+        )?;
+
+        #[cfg(feature = "window-previews")]
+        let previews = match super::preview::Previews::new(connection, screen.root) {
+            Ok(previews) => {
+                if previews.is_none() {
+                    log::warn!("No composite or render extension, window previews are disabled");
+                }
+                previews
+            }
+            Err(error) => {
+                log::warn!("Failed to set up window previews: {:?}", error);
+                None
+            }
+        };
+
+        Ok(WindowSwitcher {
+            base,
+            entries: Vec::new(),
+            selected: 0,
+            columns: 1,
+            line_height: 0,
+            #[cfg(feature = "window-previews")]
+            previews,
+            #[cfg(feature = "window-previews")]
+            visual: screen.root_visual,
+        })
+    }
+
+    /// Whether the cells show live window contents, which then need
+    /// redrawing every so often while the switcher is open.
+    #[cfg(feature = "window-previews")]
+    pub fn is_live(&self) -> bool {
+        self.previews.is_some()
+    }
+
+    #[cfg(not(feature = "window-previews"))]
+    pub fn is_live(&self) -> bool {
+        false
+    }
+
+    /// Shows `entries`, most recently focused first, centered on the monitor
+    /// given as `(x, y, width, height)` with `selected` highlighted.
+    pub fn show(
+        &mut self,
+        connection: &RustConnection,
+        font: &Font,
+        entries: Vec<SwitcherEntry>,
+        selected: usize,
+        (monitor_x, monitor_y, monitor_width, monitor_height): (i16, i16, u16, u16),
+    ) -> Result<(), X11Error> {
+        self.selected = selected.min(entries.len().saturating_sub(1));
+        self.entries = entries;
+        self.line_height = font.height() + LABEL_SPACING as u16;
+
+        let cell_width = THUMBNAIL_WIDTH + PADDING as u16;
+        let fitting_columns = (monitor_width.saturating_sub(PADDING as u16) / cell_width).max(1) as usize;
+        self.columns = self.entries.len().clamp(1, MAX_COLUMNS.min(fitting_columns));
+        let rows = self.entries.len().div_ceil(self.columns).max(1);
+
+        let width = self.columns as u16 * cell_width + PADDING as u16;
+        let height = rows as u16 * (self.cell_height() + PADDING as u16) + PADDING as u16;
+        let x = monitor_x + (monitor_width.saturating_sub(width) / 2) as i16;
+        let y = monitor_y + (monitor_height.saturating_sub(height) / 2) as i16;
+
+        self.base.configure(connection, x, y, width, height)?;
+        self.base.show(connection)?;
+        connection.grab_keyboard(
+            false,
+            self.base.window,
+            x11rb::CURRENT_TIME,
+            GrabMode::ASYNC,
+            GrabMode::ASYNC,
+        )?;
+        self.draw(connection, font)
+    }
+
+    /// Moves the highlighted cell by `delta`, wrapping around.
+    pub fn move_selection(
+        &mut self,
+        connection: &RustConnection,
+        font: &Font,
+        delta: i32,
+    ) -> Result<(), X11Error> {
+        if self.entries.is_empty() {
+            return Ok(());
+        }
+        let count = self.entries.len() as i32;
+        self.selected = (self.selected as i32 + delta).rem_euclid(count) as usize;
+        self.draw(connection, font)
+    }
+
+    pub fn selected_window(&self) -> Option<Window> {
+        self.entries.get(self.selected).map(|entry| entry.window)
+    }
+
+    /// The window whose cell is at `(x, y)`, relative to the switcher window.
+    pub fn window_at(&self, x: i16, y: i16) -> Option<Window> {
+        (0..self.entries.len())
+            .find(|&index| {
+                let (cell_x, cell_y) = self.cell_position(index);
+                (cell_x..cell_x + THUMBNAIL_WIDTH as i16).contains(&x)
+                    && (cell_y..cell_y + self.cell_height() as i16).contains(&y)
+            })
+            .map(|index| self.entries[index].window)
+    }
+
+    fn cell_height(&self) -> u16 {
+        THUMBNAIL_HEIGHT + self.line_height
+    }
+
+    fn cell_position(&self, index: usize) -> (i16, i16) {
+        let column = (index % self.columns) as i16;
+        let row = (index / self.columns) as i16;
+        (
+            PADDING + column * (THUMBNAIL_WIDTH as i16 + PADDING),
+            PADDING + row * (self.cell_height() as i16 + PADDING),
+        )
+    }
+
+    fn fill_rectangle(
+        &self,
+        connection: &RustConnection,
+        color: u32,
+        (x, y): (i16, i16),
+        (width, height): (u16, u16),
+    ) -> Result<(), X11Error> {
+        connection.change_gc(
+            self.base.graphics_context,
+            &ChangeGCAux::new().foreground(color),
+        )?;
+        connection.poly_fill_rectangle(
+            self.base.window,
+            self.base.graphics_context,
+            &[Rectangle { x, y, width, height }],
+        )?;
+        Ok(())
+    }
+
+    /// Draws the window contents into a thumbnail area, returning false
+    /// when there are none to draw.
+    #[cfg(feature = "window-previews")]
+    fn draw_preview(
+        &self,
+        connection: &RustConnection,
+        window: Window,
+        area: (i16, i16, u16, u16),
+    ) -> Result<bool, X11Error> {
+        match &self.previews {
+            Some(previews) => previews.draw(connection, window, self.base.window, self.visual, area),
+            None => Ok(false),
+        }
+    }
+
+    #[cfg(not(feature = "window-previews"))]
+    fn draw_preview(
+        &self,
+        _connection: &RustConnection,
+        _window: Window,
+        _area: (i16, i16, u16, u16),
+    ) -> Result<bool, X11Error> {
+        Ok(false)
+    }
+}
+
+/// `text` cut short with an ellipsis so it fits in `width`.
+fn fit_text(font: &Font, text: &str, width: u16) -> String {
+    if font.text_width(text) <= width {
+        return text.to_string();
+    }
+    let mut fitted: String = text.to_string();
+    while fitted.pop().is_some() {
+        let candidate = format!("{}…", fitted);
+        if font.text_width(&candidate) <= width {
+            return candidate;
+        }
+    }
+    String::new()
+}
+
+impl Overlay for WindowSwitcher {
+    fn window(&self) -> Window {
+        self.base.window
+    }
+
+    fn is_visible(&self) -> bool {
+        self.base.is_visible
+    }
+
+    fn hide(&mut self, connection: &RustConnection) -> Result<(), X11Error> {
+        if self.base.is_visible {
+            connection.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+        }
+        self.base.hide(connection)?;
+        self.entries.clear();
+        Ok(())
+    }
+
+    fn draw(&self, connection: &RustConnection, font: &Font) -> Result<(), X11Error> {
+        if !self.base.is_visible {
+            return Ok(());
+        }
+
+        self.base.draw_background(connection)?;
+
+        let mut fallbacks = Vec::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            let (x, y) = self.cell_position(index);
+            if index == self.selected {
+                self.fill_rectangle(
+                    connection,
+                    HIGHLIGHT_COLOR,
+                    (x - PADDING / 2, y - PADDING / 2),
+                    (THUMBNAIL_WIDTH + PADDING as u16, self.cell_height() + PADDING as u16),
+                )?;
+            }
+            self.fill_rectangle(connection, THUMBNAIL_COLOR, (x, y), (THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT))?;
+
+            let area = (x, y, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT);
+            if !self.draw_preview(connection, entry.window, area)? {
+                fallbacks.push(index);
+            }
+        }
+
+        // Text goes through Xlib on its own connection, so everything drawn
+        // above has to reach the server first or it could paint over it.
+        connection.flush()?;
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let (x, y) = self.cell_position(index);
+
+            if fallbacks.contains(&index) {
+                let class = fit_text(font, &entry.class, THUMBNAIL_WIDTH - PADDING as u16);
+                let class_x = x + ((THUMBNAIL_WIDTH - font.text_width(&class)) / 2) as i16;
+                let class_y = y + (THUMBNAIL_HEIGHT as i16 - font.height() as i16) / 2 + font.ascent();
+                self.base
+                    .font_draw
+                    .draw_text(font, CLASS_COLOR, class_x, class_y, &class);
+            }
+
+            let title = fit_text(font, &entry.title, THUMBNAIL_WIDTH);
+            let title_x = x + ((THUMBNAIL_WIDTH - font.text_width(&title)) / 2) as i16;
+            let title_y = y + THUMBNAIL_HEIGHT as i16 + LABEL_SPACING + font.ascent();
+            self.base
+                .font_draw
+                .draw_text(font, self.base.foreground_color, title_x, title_y, &title);
+        }
+
+        self.base.font_draw.flush();
+
+        connection.flush()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use std::process::{Child, Command};
+use std::sync::Mutex;
+
+/// Environment variable an exec restart passes the tracked pids in.
+const INHERITED_VAR: &str = "OXWM_CHILDREN";
+
+static CHILDREN: Mutex<Vec<Child>> = Mutex::new(Vec::new());
+
+/// Children a previous oxwm spawned before exec'ing this one. They are
+/// still our children, but only known by pid.
+static INHERITED: Mutex<Vec<i32>> = Mutex::new(Vec::new());
+
+/// Keeps `child` around to be reaped once it exits and returns its pid.
+/// Processes we spawn and never wait on would otherwise stay behind as
+/// zombies for the rest of the session.
+pub fn track(child: Child) -> u32 {
+    let pid = child.id();
+    if let Ok(mut children) = CHILDREN.lock() {
+        children.push(child);
+    }
+    pid
+}
+
+/// Passes every child still running on to the oxwm that `command` execs,
+/// which picks them up with `adopt`.
+pub fn hand_over(command: &mut Command) {
+    let mut pids: Vec<String> = Vec::new();
+    if let Ok(children) = CHILDREN.lock() {
+        pids.extend(children.iter().map(|child| child.id().to_string()));
+    }
+    if let Ok(inherited) = INHERITED.lock() {
+        pids.extend(inherited.iter().map(|pid| pid.to_string()));
+    }
+    command.env(INHERITED_VAR, pids.join(","));
+}
+
+/// Takes over the children handed over by the oxwm that exec'd us, and
+/// keeps the list out of the environment of programs we spawn. Must be
+/// called before any other thread is started.
+pub fn adopt() {
+    let Ok(pids) = std::env::var(INHERITED_VAR) else {
+        return;
+    };
+    unsafe { std::env::remove_var(INHERITED_VAR) };
+
+    if let Ok(mut inherited) = INHERITED.lock() {
+        inherited.extend(pids.split(',').filter_map(|pid| pid.parse::<i32>().ok()));
+    }
+}
+
+/// Waits on every tracked child that has exited and returns their pids.
+/// Only tracked and inherited children are reaped, so commands run with
+/// `output()` still get their status.
+pub fn reap() -> Vec<u32> {
+    let mut exited = Vec::new();
+    if let Ok(mut children) = CHILDREN.lock() {
+        children.retain_mut(|child| match child.try_wait() {
+            Ok(None) => true,
+            _ => {
+                exited.push(child.id());
+                false
+            }
+        });
+    }
+    if let Ok(mut inherited) = INHERITED.lock() {
+        inherited.retain(|&pid| {
+            let mut status = 0;
+            unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) == 0 }
+        });
+    }
+    exited
+}
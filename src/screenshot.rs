@@ -0,0 +1,216 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ConnectionExt, ImageFormat, ImageOrder, Window};
+
+use crate::errors::X11Error;
+
+/// What `oxwm.screenshot.*` captures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenshotMode {
+    Full,
+    Window,
+    Selection,
+}
+
+impl ScreenshotMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "full" => Some(Self::Full),
+            "window" => Some(Self::Window),
+            "selection" => Some(Self::Selection),
+            _ => None,
+        }
+    }
+}
+
+/// Reads a rectangle of the root window and encodes it as PNG, cut down to
+/// the part on screen. Returns `None` when none of it is, or for pixel
+/// formats other than 32 bits per pixel, which no TrueColor screen in
+/// practice uses.
+pub fn capture(
+    connection: &impl Connection,
+    root: Window,
+    area: (i16, i16, u16, u16),
+) -> Result<Option<Vec<u8>>, X11Error> {
+    let root_geometry = connection.get_geometry(root)?.reply()?;
+    let Some((x, y, width, height)) = clamp_area(area, root_geometry.width, root_geometry.height) else {
+        return Ok(None);
+    };
+
+    let setup = connection.setup();
+    let byte_order = setup.image_byte_order;
+    let image = connection
+        .get_image(ImageFormat::Z_PIXMAP, root, x, y, width, height, !0)?
+        .reply()?;
+
+    let bits_per_pixel = setup
+        .pixmap_formats
+        .iter()
+        .find(|format| format.depth == image.depth)
+        .map(|format| format.bits_per_pixel);
+    if bits_per_pixel != Some(32) {
+        return Ok(None);
+    }
+
+    let rgb: Vec<u8> = image
+        .data
+        .chunks_exact(4)
+        .flat_map(|pixel| match byte_order {
+            ImageOrder::LSB_FIRST => [pixel[2], pixel[1], pixel[0]],
+            _ => [pixel[1], pixel[2], pixel[3]],
+        })
+        .collect();
+
+    Ok(Some(encode_png(width as u32, height as u32, &rgb)))
+}
+
+/// The part of `(x, y, width, height)` inside a root window of
+/// `root_width` by `root_height`, since reading outside it is an error.
+fn clamp_area(
+    (x, y, width, height): (i16, i16, u16, u16),
+    root_width: u16,
+    root_height: u16,
+) -> Option<(i16, i16, u16, u16)> {
+    let left = (x as i32).max(0);
+    let top = (y as i32).max(0);
+    let right = (x as i32 + width as i32).min(root_width as i32);
+    let bottom = (y as i32 + height as i32).min(root_height as i32);
+    if right <= left || bottom <= top {
+        return None;
+    }
+
+    Some((left as i16, top as i16, (right - left) as u16, (bottom - top) as u16))
+}
+
+/// Writes `png` to `dir` under a timestamped name.
+pub fn save(png: &[u8], dir: &Path) -> io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let name = chrono::Local::now().format("oxwm-%Y-%m-%d-%H%M%S.png").to_string();
+    let path = dir.join(name);
+    std::fs::write(&path, png)?;
+    Ok(path)
+}
+
+/// Hands `png` to xclip, which keeps serving the clipboard after we return.
+pub fn copy_to_clipboard(png: Vec<u8>) -> io::Result<()> {
+    let mut child = Command::new("xclip")
+        .args(["-selection", "clipboard", "-t", "image/png"])
+        .stdin(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().ok_or(io::ErrorKind::BrokenPipe)?;
+    std::thread::spawn(move || {
+        if let Err(error) = stdin.write_all(&png) {
+            log::error!("Failed to copy screenshot to clipboard: {}", error);
+        }
+        drop(stdin);
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+/// Minimal PNG encoder for 8-bit RGB images. The image data is stored
+/// without compression, which keeps this free of a zlib dependency.
+fn encode_png(width: u32, height: u32, rgb: &[u8]) -> Vec<u8> {
+    let row_length = width as usize * 3;
+    let mut raw = Vec::with_capacity((row_length + 1) * height as usize);
+    for row in rgb.chunks_exact(row_length) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xffff;
+
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK * 5 + 11);
+    out.extend_from_slice(&[0x78, 0x01]);
+
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_last = blocks.peek().is_none();
+        let length = block.len() as u16;
+        out.push(is_last as u8);
+        out.extend_from_slice(&length.to_le_bytes());
+        out.extend_from_slice(&(!length).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MODULUS;
+        b %= MODULUS;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn area_on_screen_is_kept() {
+        assert_eq!(clamp_area((10, 20, 300, 200), 1920, 1080), Some((10, 20, 300, 200)));
+    }
+
+    #[test]
+    fn area_is_cut_at_screen_edges() {
+        assert_eq!(clamp_area((-50, -10, 300, 200), 1920, 1080), Some((0, 0, 250, 190)));
+        assert_eq!(clamp_area((1800, 1000, 300, 200), 1920, 1080), Some((1800, 1000, 120, 80)));
+    }
+
+    #[test]
+    fn area_off_screen_is_empty() {
+        assert_eq!(clamp_area((-400, 0, 300, 200), 1920, 1080), None);
+        assert_eq!(clamp_area((1920, 0, 300, 200), 1920, 1080), None);
+        assert_eq!(clamp_area((10, 10, 0, 200), 1920, 1080), None);
+    }
+}
@@ -0,0 +1,487 @@
+use std::collections::HashMap;
+
+use crate::backend::{InputOps, PropertyOps, WindowId, WindowOps};
+use crate::client::{Client, STASH_TAG, TagMask};
+use crate::monitor::Monitor;
+use x11rb::protocol::xproto::AtomEnum;
+
+/// The atoms tags are saved under, see `save_client_tag`.
+#[derive(Clone, Copy)]
+pub struct TagAtoms {
+    pub net_client_info: u32,
+    pub net_wm_desktop: u32,
+}
+
+/// What a focus change did, for the caller to redraw: borders of windows
+/// that lost focus and the window that gained it, if any.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FocusChange {
+    pub unfocused: Vec<WindowId>,
+    pub focused: Option<WindowId>,
+}
+
+/// Which monitor and client are selected and the moves between them:
+/// focusing, switching monitors and moving clients between tags.
+///
+/// Borrows the window manager's state and talks to the server only through
+/// the backend traits, so it runs the same against `MockBackend`. Drawing
+/// borders, restacking and rearranging stay with the caller.
+pub struct Selection<'a, B> {
+    pub backend: &'a B,
+    pub root: WindowId,
+    pub atoms: TagAtoms,
+    /// Every managed window, whether or not it is a client yet.
+    pub windows: &'a [WindowId],
+    pub clients: &'a mut HashMap<WindowId, Client>,
+    pub monitors: &'a mut [Monitor],
+    pub selected_monitor: &'a mut usize,
+}
+
+impl<'a, B> Selection<'a, B>
+where
+    B: WindowOps + InputOps + PropertyOps,
+{
+    pub fn selected_client(&self) -> Option<WindowId> {
+        self.monitors
+            .get(*self.selected_monitor)
+            .and_then(|monitor| monitor.selected_client)
+    }
+
+    /// Whether the window is on a tag its monitor shows.
+    pub fn is_visible(&self, window: WindowId) -> bool {
+        let Some(client) = self.clients.get(&window) else {
+            return false;
+        };
+        let Some(monitor) = self.monitors.get(client.monitor_index) else {
+            return false;
+        };
+
+        (client.tags & monitor.tagset[monitor.selected_tags_index]) != 0
+    }
+
+    fn can_focus(&self, window: WindowId) -> bool {
+        self.is_visible(window)
+            && self
+                .clients
+                .get(&window)
+                .is_none_or(|client| client.window_type.takes_focus())
+    }
+
+    /// The monitor after (`direction` > 0) or before the selected one,
+    /// wrapping around. None with a single monitor.
+    pub fn adjacent_monitor(&self, direction: i32) -> Option<usize> {
+        let count = self.monitors.len();
+        if count <= 1 {
+            return None;
+        }
+
+        let step = if direction > 0 { 1 } else { count - 1 };
+        Some((*self.selected_monitor + step) % count)
+    }
+
+    pub fn monitor_at_point(&self, x: i32, y: i32) -> Option<usize> {
+        self.monitors
+            .iter()
+            .position(|monitor| monitor.contains_point(x, y))
+    }
+
+    fn unfocus(&self, window: WindowId, change: &mut FocusChange) -> Result<(), B::Error> {
+        if !self.windows.contains(&window) {
+            return Ok(());
+        }
+
+        self.backend.grab_client_buttons(window)?;
+        change.unfocused.push(window);
+        Ok(())
+    }
+
+    /// Focuses `window`, or when it can't take focus the most recently
+    /// focused window that can on the selected monitor. With nothing to
+    /// focus, input goes to the root window. Focusing a client on another
+    /// monitor selects that monitor.
+    pub fn focus(&mut self, window: Option<WindowId>) -> Result<FocusChange, B::Error> {
+        let mut change = FocusChange::default();
+
+        if let Some(old) = self.selected_client()
+            && Some(old) != window
+        {
+            self.unfocus(old, &mut change)?;
+        }
+
+        let target = match window {
+            Some(window) if self.can_focus(window) => Some(window),
+            _ => self
+                .monitors
+                .get(*self.selected_monitor)
+                .and_then(|monitor| monitor.clients.stack().find(|&w| self.can_focus(w))),
+        };
+
+        let Some(target) = target else {
+            self.backend.focus(self.root)?;
+            if let Some(monitor) = self.monitors.get_mut(*self.selected_monitor) {
+                monitor.selected_client = None;
+            }
+            return Ok(change);
+        };
+
+        if !self.windows.contains(&target) {
+            return Ok(change);
+        }
+
+        if let Some(client) = self.clients.get(&target) {
+            *self.selected_monitor = client.monitor_index;
+        }
+        if let Some(monitor) = self.monitors.get_mut(*self.selected_monitor) {
+            monitor.clients.detach_stack(target);
+            monitor.clients.attach_stack(target);
+            monitor.selected_client = Some(target);
+        }
+
+        self.backend.focus(target)?;
+        change.focused = Some(target);
+        Ok(change)
+    }
+
+    /// Makes `monitor_index` the selected monitor and focuses on it.
+    pub fn select_monitor(&mut self, monitor_index: usize) -> Result<FocusChange, B::Error> {
+        let mut change = FocusChange::default();
+        if let Some(old) = self.selected_client() {
+            self.unfocus(old, &mut change)?;
+        }
+
+        *self.selected_monitor = monitor_index;
+        let mut focused = self.focus(None)?;
+        change.unfocused.append(&mut focused.unfocused);
+        change.focused = focused.focused;
+        Ok(change)
+    }
+
+    /// Selects the monitor in `direction`. None when there is no other
+    /// monitor to go to.
+    pub fn focus_monitor(&mut self, direction: i32) -> Result<Option<FocusChange>, B::Error> {
+        match self.adjacent_monitor(direction) {
+            Some(index) if index != *self.selected_monitor => self.select_monitor(index).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Focuses a window the pointer entered, selecting its monitor.
+    pub fn enter(&mut self, window: WindowId) -> Result<Option<FocusChange>, B::Error> {
+        if !self.windows.contains(&window) {
+            return Ok(None);
+        }
+        if let Some(client) = self.clients.get(&window) {
+            *self.selected_monitor = client.monitor_index;
+        }
+        self.focus(Some(window)).map(Some)
+    }
+
+    /// Selects the monitor under the pointer when it crossed onto another
+    /// one over the root window.
+    pub fn pointer_moved(&mut self, x: i32, y: i32) -> Result<Option<FocusChange>, B::Error> {
+        match self.monitor_at_point(x, y) {
+            Some(index) if index != *self.selected_monitor => self.select_monitor(index).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Shows `mask` on the selected monitor, keeping the tags shown before
+    /// as the previous tagset. False when they were shown already.
+    pub fn view(&mut self, mask: TagMask) -> bool {
+        let Some(monitor) = self.monitors.get_mut(*self.selected_monitor) else {
+            return false;
+        };
+        if mask == monitor.tagset[monitor.selected_tags_index] {
+            return false;
+        }
+
+        monitor.selected_tags_index ^= 1;
+        monitor.tagset[monitor.selected_tags_index] = mask;
+        true
+    }
+
+    /// Puts `window` on exactly the tags in `mask`, saves them on the window
+    /// and refocuses, since it may have left the shown tags.
+    pub fn set_client_tags(&mut self, window: WindowId, mask: TagMask) -> Result<FocusChange, B::Error> {
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.tags = mask;
+        }
+        save_client_tag(self.backend, self.atoms, window, mask)?;
+        self.focus(None)
+    }
+}
+
+/// Stores `tag` on the window so it survives a restart, and publishes its
+/// lowest tag as _NET_WM_DESKTOP for pagers and taskbars. Stashed windows
+/// are on no desktop.
+pub fn save_client_tag<B: PropertyOps>(
+    backend: &B,
+    atoms: TagAtoms,
+    window: WindowId,
+    tag: TagMask,
+) -> Result<(), B::Error> {
+    let cardinal = AtomEnum::CARDINAL.into();
+
+    backend.set_property32(window, atoms.net_client_info, cardinal, &[tag])?;
+
+    match tag & !STASH_TAG {
+        0 => backend.remove_property(window, atoms.net_wm_desktop),
+        tags => backend.set_property32(window, atoms.net_wm_desktop, cardinal, &[tags.trailing_zeros()]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::{MockBackend, Request};
+    use crate::client::WindowType;
+
+    const ROOT: WindowId = 1;
+    const ATOMS: TagAtoms = TagAtoms {
+        net_client_info: 100,
+        net_wm_desktop: 101,
+    };
+
+    /// Owns what a `Selection` borrows, with monitors of 1920x1080 side by
+    /// side.
+    struct Fixture {
+        backend: MockBackend,
+        windows: Vec<WindowId>,
+        clients: HashMap<WindowId, Client>,
+        monitors: Vec<Monitor>,
+        selected_monitor: usize,
+    }
+
+    impl Fixture {
+        fn new(monitor_count: i32) -> Self {
+            Self {
+                backend: MockBackend::default(),
+                windows: Vec::new(),
+                clients: HashMap::new(),
+                monitors: (0..monitor_count)
+                    .map(|index| Monitor::new(index * 1920, 0, 1920, 1080))
+                    .collect(),
+                selected_monitor: 0,
+            }
+        }
+
+        /// Manages `window` as the most recently focused on its monitor.
+        fn add(&mut self, window: WindowId, monitor_index: usize, tags: TagMask) {
+            self.clients.insert(window, Client::new(window, monitor_index, tags));
+            self.monitors[monitor_index].clients.attach(window);
+            self.monitors[monitor_index].clients.attach_stack(window);
+            self.windows.push(window);
+        }
+
+        fn selection(&mut self) -> Selection<'_, MockBackend> {
+            Selection {
+                backend: &self.backend,
+                root: ROOT,
+                atoms: ATOMS,
+                windows: &self.windows,
+                clients: &mut self.clients,
+                monitors: &mut self.monitors,
+                selected_monitor: &mut self.selected_monitor,
+            }
+        }
+
+        fn stack(&self, monitor_index: usize) -> Vec<WindowId> {
+            self.monitors[monitor_index].clients.stack().collect()
+        }
+    }
+
+    #[test]
+    fn focus_selects_window_and_raises_it_in_focus_order() {
+        let mut fixture = Fixture::new(1);
+        fixture.add(10, 0, 1);
+        fixture.add(11, 0, 1);
+
+        let change = fixture.selection().focus(Some(10)).unwrap();
+
+        assert_eq!(change.focused, Some(10));
+        assert_eq!(fixture.monitors[0].selected_client, Some(10));
+        assert_eq!(fixture.stack(0), vec![10, 11]);
+        assert_eq!(fixture.backend.take_requests(), vec![Request::Focus(10)]);
+    }
+
+    #[test]
+    fn focus_unfocuses_previous_selection() {
+        let mut fixture = Fixture::new(1);
+        fixture.add(10, 0, 1);
+        fixture.add(11, 0, 1);
+        fixture.selection().focus(Some(11)).unwrap();
+        fixture.backend.take_requests();
+
+        let change = fixture.selection().focus(Some(10)).unwrap();
+
+        assert_eq!(change.unfocused, vec![11]);
+        assert_eq!(
+            fixture.backend.take_requests(),
+            vec![Request::GrabClientButtons(11), Request::Focus(10)]
+        );
+    }
+
+    #[test]
+    fn focus_falls_back_to_most_recent_visible_window() {
+        let mut fixture = Fixture::new(1);
+        fixture.add(10, 0, 1);
+        fixture.add(11, 0, 1);
+        fixture.add(12, 0, 2);
+
+        let change = fixture.selection().focus(Some(12)).unwrap();
+
+        assert_eq!(change.focused, Some(11));
+        assert_eq!(fixture.monitors[0].selected_client, Some(11));
+    }
+
+    #[test]
+    fn focus_skips_windows_that_dont_take_focus() {
+        let mut fixture = Fixture::new(1);
+        fixture.add(10, 0, 1);
+        fixture.add(11, 0, 1);
+        fixture.clients.get_mut(&11).unwrap().window_type = WindowType::Notification;
+
+        let change = fixture.selection().focus(None).unwrap();
+
+        assert_eq!(change.focused, Some(10));
+    }
+
+    #[test]
+    fn focus_without_visible_windows_goes_to_root() {
+        let mut fixture = Fixture::new(1);
+        fixture.add(10, 0, 2);
+
+        let change = fixture.selection().focus(None).unwrap();
+
+        assert_eq!(change.focused, None);
+        assert_eq!(fixture.monitors[0].selected_client, None);
+        assert_eq!(fixture.backend.take_requests(), vec![Request::Focus(ROOT)]);
+    }
+
+    #[test]
+    fn focus_on_another_monitor_selects_it() {
+        let mut fixture = Fixture::new(2);
+        fixture.add(10, 0, 1);
+        fixture.add(20, 1, 1);
+
+        fixture.selection().focus(Some(20)).unwrap();
+
+        assert_eq!(fixture.selected_monitor, 1);
+        assert_eq!(fixture.monitors[1].selected_client, Some(20));
+    }
+
+    #[test]
+    fn moving_focused_window_to_hidden_tag_refocuses() {
+        let mut fixture = Fixture::new(1);
+        fixture.add(10, 0, 1);
+        fixture.add(11, 0, 1);
+        fixture.selection().focus(Some(11)).unwrap();
+
+        let change = fixture.selection().set_client_tags(11, 1 << 2).unwrap();
+
+        assert_eq!(fixture.clients[&11].tags, 1 << 2);
+        assert_eq!(change.unfocused, vec![11]);
+        assert_eq!(change.focused, Some(10));
+        assert_eq!(fixture.backend.property32(11, ATOMS.net_client_info, 0).unwrap(), vec![1 << 2]);
+        assert_eq!(fixture.backend.property32(11, ATOMS.net_wm_desktop, 0).unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn stashed_window_is_on_no_desktop() {
+        let mut fixture = Fixture::new(1);
+        fixture.add(10, 0, 1);
+        save_client_tag(&fixture.backend, ATOMS, 10, 1).unwrap();
+
+        fixture.selection().set_client_tags(10, STASH_TAG).unwrap();
+
+        assert_eq!(fixture.backend.property32(10, ATOMS.net_client_info, 0).unwrap(), vec![STASH_TAG]);
+        assert!(fixture.backend.property32(10, ATOMS.net_wm_desktop, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn viewing_a_tag_keeps_previous_tagset() {
+        let mut fixture = Fixture::new(1);
+        fixture.add(10, 0, 1);
+        fixture.add(11, 0, 2);
+
+        assert!(fixture.selection().view(2));
+        assert!(!fixture.selection().view(2));
+        let change = fixture.selection().focus(None).unwrap();
+
+        let monitor = &fixture.monitors[0];
+        assert_eq!(monitor.tagset[monitor.selected_tags_index], 2);
+        assert_eq!(monitor.tagset[monitor.selected_tags_index ^ 1], 1);
+        assert_eq!(change.focused, Some(11));
+    }
+
+    #[test]
+    fn adjacent_monitor_wraps_around() {
+        let mut fixture = Fixture::new(3);
+        assert_eq!(fixture.selection().adjacent_monitor(1), Some(1));
+        assert_eq!(fixture.selection().adjacent_monitor(-1), Some(2));
+
+        fixture.selected_monitor = 2;
+        assert_eq!(fixture.selection().adjacent_monitor(1), Some(0));
+        assert_eq!(Fixture::new(1).selection().adjacent_monitor(1), None);
+    }
+
+    #[test]
+    fn focus_monitor_moves_focus_to_its_selection() {
+        let mut fixture = Fixture::new(2);
+        fixture.add(10, 0, 1);
+        fixture.add(20, 1, 1);
+        fixture.add(21, 1, 1);
+        fixture.selection().focus(Some(10)).unwrap();
+        fixture.backend.take_requests();
+
+        let change = fixture.selection().focus_monitor(1).unwrap();
+
+        assert_eq!(fixture.selected_monitor, 1);
+        assert_eq!(
+            change,
+            Some(FocusChange {
+                unfocused: vec![10],
+                focused: Some(21),
+            })
+        );
+        assert_eq!(
+            fixture.backend.take_requests(),
+            vec![Request::GrabClientButtons(10), Request::Focus(21)]
+        );
+    }
+
+    #[test]
+    fn focus_monitor_with_one_monitor_does_nothing() {
+        let mut fixture = Fixture::new(1);
+        fixture.add(10, 0, 1);
+
+        assert_eq!(fixture.selection().focus_monitor(1).unwrap(), None);
+        assert!(fixture.backend.take_requests().is_empty());
+    }
+
+    #[test]
+    fn pointer_crossing_monitors_selects_the_one_under_it() {
+        let mut fixture = Fixture::new(2);
+        fixture.add(10, 0, 1);
+
+        assert_eq!(fixture.selection().pointer_moved(100, 100).unwrap(), None);
+
+        let change = fixture.selection().pointer_moved(2000, 100).unwrap();
+        assert_eq!(fixture.selected_monitor, 1);
+        assert_eq!(change.map(|change| change.focused), Some(None));
+        assert_eq!(fixture.backend.take_requests(), vec![Request::Focus(ROOT)]);
+    }
+
+    #[test]
+    fn entering_a_window_selects_its_monitor() {
+        let mut fixture = Fixture::new(2);
+        fixture.add(10, 0, 1);
+        fixture.add(20, 1, 1);
+
+        assert_eq!(fixture.selection().enter(99).unwrap(), None);
+
+        let change = fixture.selection().enter(20).unwrap();
+        assert_eq!(fixture.selected_monitor, 1);
+        assert_eq!(change.and_then(|change| change.focused), Some(20));
+    }
+}
@@ -0,0 +1,124 @@
+use x11rb::connection::Connection;
+use x11rb::protocol::shape::{self, ConnectionExt as _, SK, SO};
+use x11rb::protocol::xproto::{ClipOrdering, Rectangle, Window};
+
+use crate::errors::X11Error;
+
+/// Whether the server has the shape extension, without which windows are
+/// left square.
+pub fn is_supported(connection: &impl Connection) -> bool {
+    matches!(connection.extension_information(shape::X11_EXTENSION_NAME), Ok(Some(_)))
+}
+
+/// Clips `window` to a rectangle with corners rounded to `radius`. The
+/// rectangle is `width` by `height` with its top left at `(x, y)` relative
+/// to the window's inside, so a negative offset covers the border.
+pub fn round_corners(
+    connection: &impl Connection,
+    window: Window,
+    (x, y): (i16, i16),
+    (width, height): (u16, u16),
+    radius: u16,
+) -> Result<(), X11Error> {
+    let rectangles = rounded_rectangles(x, y, width, height, radius);
+    connection.shape_rectangles(SO::SET, SK::BOUNDING, ClipOrdering::UNSORTED, window, 0, 0, &rectangles)?;
+    Ok(())
+}
+
+/// Removes any shape from `window` so it is a plain rectangle again.
+pub fn clear(connection: &impl Connection, window: Window) -> Result<(), X11Error> {
+    connection.shape_mask(SO::SET, SK::BOUNDING, window, 0, 0, x11rb::NONE)?;
+    Ok(())
+}
+
+/// The rectangle as one row per pixel line of the rounded corners and a
+/// single block for the straight part in between.
+fn rounded_rectangles(x: i16, y: i16, width: u16, height: u16, radius: u16) -> Vec<Rectangle> {
+    let radius = radius.min(width / 2).min(height / 2);
+    let mut rectangles = Vec::with_capacity(radius as usize * 2 + 1);
+
+    for row in 0..radius {
+        let distance = radius as f32 - row as f32 - 0.5;
+        let inset = (radius as f32 - (radius as f32 * radius as f32 - distance * distance).sqrt()).round() as u16;
+        let row_width = width - inset * 2;
+        rectangles.push(Rectangle {
+            x: x + inset as i16,
+            y: y + row as i16,
+            width: row_width,
+            height: 1,
+        });
+        rectangles.push(Rectangle {
+            x: x + inset as i16,
+            y: y + (height - row - 1) as i16,
+            width: row_width,
+            height: 1,
+        });
+    }
+
+    rectangles.push(Rectangle {
+        x,
+        y: y + radius as i16,
+        width,
+        height: height - radius * 2,
+    });
+    rectangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(rectangle: &Rectangle) -> (i16, i16, u16, u16) {
+        (rectangle.x, rectangle.y, rectangle.width, rectangle.height)
+    }
+
+    fn area(rectangles: &[Rectangle]) -> u32 {
+        rectangles
+            .iter()
+            .map(|rectangle| rectangle.width as u32 * rectangle.height as u32)
+            .sum()
+    }
+
+    #[test]
+    fn zero_radius_is_one_rectangle() {
+        let rectangles = rounded_rectangles(-2, -2, 100, 50, 0);
+        assert_eq!(rectangles.iter().map(bounds).collect::<Vec<_>>(), vec![(-2, -2, 100, 50)]);
+    }
+
+    #[test]
+    fn rows_cover_the_rectangle_without_overlap() {
+        let rectangles = rounded_rectangles(0, 0, 100, 50, 8);
+        assert_eq!(rectangles.len(), 8 * 2 + 1);
+        assert_eq!(rectangles.iter().map(|r| r.height as u32).sum::<u32>(), 50);
+        assert!(area(&rectangles) < 100 * 50);
+    }
+
+    #[test]
+    fn corners_are_inset_and_symmetric() {
+        let rectangles = rounded_rectangles(10, 20, 100, 50, 8);
+        let top = rectangles[0];
+        let bottom = rectangles[1];
+        assert!(top.x > 10);
+        assert_eq!(top.y, 20);
+        assert_eq!(bottom.y, 20 + 49);
+        assert_eq!((top.x, top.width), (bottom.x, bottom.width));
+        assert_eq!(top.x - 10, (100 - top.width as i16) / 2);
+
+        assert_eq!(bounds(rectangles.last().unwrap()), (10, 28, 100, 34));
+    }
+
+    #[test]
+    fn rows_widen_towards_the_middle() {
+        let rectangles = rounded_rectangles(0, 0, 100, 50, 8);
+        let top_rows: Vec<u16> = rectangles.iter().step_by(2).take(8).map(|r| r.width).collect();
+        assert!(top_rows.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn radius_is_capped_at_half_the_shorter_side() {
+        let rectangles = rounded_rectangles(0, 0, 100, 10, 20);
+        assert_eq!(rectangles.len(), 5 * 2 + 1);
+        assert_eq!(rectangles.last().unwrap().height, 0);
+        assert_eq!(rectangles.iter().map(|r| r.height as u32).sum::<u32>(), 10);
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Starts listening for the session ending: SIGTERM from a display manager
+/// logging us out, and with the `logind` feature systemd-logind announcing
+/// a shutdown. Either makes `requested` return true so the run loop can
+/// close clients the same way Quit does.
+pub fn listen() {
+    unsafe {
+        libc::signal(libc::SIGTERM, on_sigterm as *const () as libc::sighandler_t);
+    }
+
+    #[cfg(feature = "logind")]
+    std::thread::spawn(|| {
+        if let Err(error) = logind::watch() {
+            log::error!("Failed to watch logind for shutdown: {}", error);
+        }
+    });
+}
+
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::Relaxed)
+}
+
+extern "C" fn on_sigterm(_signal: libc::c_int) {
+    REQUESTED.store(true, Ordering::Relaxed);
+}
+
+#[cfg(feature = "logind")]
+mod logind {
+    use std::sync::atomic::Ordering;
+
+    use zbus::blocking::{Connection, Proxy};
+    use zbus::zvariant::OwnedFd;
+
+    /// Holds a delay inhibitor lock, which gives clients a few seconds to
+    /// close before logind goes ahead, and waits for PrepareForShutdown.
+    pub fn watch() -> zbus::Result<()> {
+        let connection = Connection::system()?;
+        let manager = Proxy::new(
+            &connection,
+            "org.freedesktop.login1",
+            "/org/freedesktop/login1",
+            "org.freedesktop.login1.Manager",
+        )?;
+
+        let _lock: OwnedFd = manager.call(
+            "Inhibit",
+            &("shutdown", "oxwm", "Closing windows", "delay"),
+        )?;
+
+        for message in manager.receive_signal("PrepareForShutdown")? {
+            let starting: bool = message.body().deserialize()?;
+            if starting {
+                super::REQUESTED.store(true, Ordering::Relaxed);
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,53 @@
 use crate::bar::font::{Font, FontDraw};
 use crate::errors::X11Error;
-use crate::layout::tabbed::TAB_BAR_HEIGHT;
-use crate::ColorScheme;
+use crate::monitor::Monitor;
+use crate::{ColorScheme, Config};
 use x11rb::connection::Connection;
 use x11rb::protocol::xproto::*;
 use x11rb::rust_connection::RustConnection;
 use x11rb::COPY_DEPTH_FROM_PARENT;
 
+const CLOSE_BUTTON_WIDTH: u16 = 20;
+const CLOSE_GLYPH: &str = "×";
+/// Marks floating and fullscreen windows.
+pub const FLOATING_INDICATOR: &str = "●";
+
+pub enum TabClick {
+    Select(Window),
+    Close(Window),
+}
+
+/// A window shown as a tab.
+pub struct Tab {
+    pub window: Window,
+    pub label: String,
+    pub is_urgent: bool,
+}
+
+/// How a monitor's tab bar looks, from the `oxwm.tab_bar` settings.
+pub struct TabStyle {
+    pub scheme_active: ColorScheme,
+    pub scheme_inactive: ColorScheme,
+    pub urgent_color: u32,
+    pub max_tab_width: Option<u16>,
+    pub separator: String,
+}
+
+impl TabStyle {
+    pub fn new(config: &Config, monitor: &Monitor) -> Self {
+        Self {
+            scheme_active: config.tab_bar_scheme_active.unwrap_or(config.scheme_selected),
+            scheme_inactive: config.tab_bar_scheme_inactive.unwrap_or(config.scheme_occupied),
+            urgent_color: config
+                .tab_bar_urgent
+                .or(config.border_urgent)
+                .unwrap_or(config.scheme_selected.underline),
+            max_tab_width: config.tab_bar_max_tab_width.map(|width| monitor.scaled(width) as u16),
+            separator: config.tab_bar_separator.clone(),
+        }
+    }
+}
+
 pub struct TabBar {
     window: Window,
     width: u16,
@@ -17,8 +58,9 @@ pub struct TabBar {
     pixmap: x11::xlib::Pixmap,
     display: *mut x11::xlib::Display,
     font_draw: FontDraw,
-    scheme_normal: ColorScheme,
-    scheme_selected: ColorScheme,
+    style: TabStyle,
+    /// Horizontal position and width of each tab as last drawn.
+    tab_areas: Vec<(i16, u16)>,
 }
 
 impl TabBar {
@@ -31,14 +73,12 @@ impl TabBar {
         x: i16,
         y: i16,
         width: u16,
-        scheme_normal: ColorScheme,
-        scheme_selected: ColorScheme,
+        height: u16,
+        style: TabStyle,
     ) -> Result<Self, X11Error> {
         let window = connection.generate_id()?;
         let graphics_context = connection.generate_id()?;
 
-        let height = TAB_BAR_HEIGHT as u16;
-
         connection.create_window(
             COPY_DEPTH_FROM_PARENT,
             window,
@@ -51,7 +91,7 @@ impl TabBar {
             WindowClass::INPUT_OUTPUT,
             screen.root_visual,
             &CreateWindowAux::new()
-                .background_pixel(scheme_normal.background)
+                .background_pixel(style.scheme_inactive.background)
                 .event_mask(EventMask::EXPOSURE | EventMask::BUTTON_PRESS)
                 .override_redirect(1),
         )?;
@@ -60,8 +100,8 @@ impl TabBar {
             graphics_context,
             window,
             &CreateGCAux::new()
-                .foreground(scheme_normal.foreground)
-                .background(scheme_normal.background),
+                .foreground(style.scheme_inactive.foreground)
+                .background(style.scheme_inactive.background),
         )?;
 
         connection.map_window(window)?;
@@ -93,8 +133,8 @@ impl TabBar {
             pixmap,
             display,
             font_draw,
-            scheme_normal,
-            scheme_selected,
+            style,
+            tab_areas: Vec::new(),
         })
     }
 
@@ -106,93 +146,126 @@ impl TabBar {
         &mut self,
         connection: &RustConnection,
         font: &Font,
-        windows: &[(Window, String)],
+        tabs: &[Tab],
         focused_window: Option<Window>,
     ) -> Result<(), X11Error> {
         connection.change_gc(
             self.graphics_context,
-            &ChangeGCAux::new().foreground(self.scheme_normal.background),
+            &ChangeGCAux::new().foreground(self.style.scheme_inactive.background),
         )?;
         connection.flush()?;
 
-        unsafe {
-            let gc = x11::xlib::XCreateGC(self.display, self.pixmap, 0, std::ptr::null_mut());
-            x11::xlib::XSetForeground(
-                self.display,
-                gc,
-                self.scheme_normal.background as u64,
-            );
-            x11::xlib::XFillRectangle(
-                self.display,
-                self.pixmap,
-                gc,
-                0,
-                0,
-                self.width as u32,
-                self.height as u32,
-            );
-            x11::xlib::XFreeGC(self.display, gc);
-        }
+        self.fill_rectangle(self.style.scheme_inactive.background, 0, 0, self.width, self.height);
 
-        if windows.is_empty() {
+        self.tab_areas = self.layout_tabs(font, tabs.len());
+        if tabs.is_empty() {
             self.copy_pixmap_to_window();
             return Ok(());
         }
 
-        let tab_width = self.width / windows.len() as u16;
-        let mut x_position: i16 = 0;
+        let top_padding = 6;
+        let text_y = top_padding + font.ascent();
+        let separator_width = font.text_width(&self.style.separator);
 
-        for (index, &(window, ref title)) in windows.iter().enumerate() {
-            let is_focused = Some(window) == focused_window;
+        for (index, (tab, &(x_position, tab_width))) in tabs.iter().zip(&self.tab_areas).enumerate() {
+            let is_focused = Some(tab.window) == focused_window;
             let scheme = if is_focused {
-                &self.scheme_selected
+                &self.style.scheme_active
             } else {
-                &self.scheme_normal
+                &self.style.scheme_inactive
             };
 
-            let display_title = if title.is_empty() {
+            // Like urgent tags in the bar, an urgent tab is filled with the
+            // urgent color and its text drawn in the background color.
+            let is_urgent = tab.is_urgent && !is_focused;
+            let (fill, text_color) = if is_urgent {
+                (self.style.urgent_color, scheme.background)
+            } else {
+                (scheme.background, scheme.foreground)
+            };
+            self.fill_rectangle(fill, x_position, 0, tab_width, self.height);
+
+            let display_label = if tab.label.is_empty() {
                 format!("Window {}", index + 1)
             } else {
-                title.clone()
+                tab.label.clone()
             };
 
-            let text_width = font.text_width(&display_title);
-            let text_x = x_position + ((tab_width.saturating_sub(text_width)) / 2) as i16;
+            let title_area_width = tab_width.saturating_sub(CLOSE_BUTTON_WIDTH);
+            let text_width = font.text_width(&display_label);
+            let text_x = x_position + ((title_area_width.saturating_sub(text_width)) / 2) as i16;
+
+            self.font_draw
+                .draw_text(font, text_color, text_x, text_y, &display_label);
 
-            let top_padding = 6;
-            let text_y = top_padding + font.ascent();
+            let close_glyph_width = font.text_width(CLOSE_GLYPH);
+            let close_x = x_position
+                + title_area_width as i16
+                + ((CLOSE_BUTTON_WIDTH.saturating_sub(close_glyph_width)) / 2) as i16;
 
             self.font_draw
-                .draw_text(font, scheme.foreground, text_x, text_y, &display_title);
+                .draw_text(font, text_color, close_x, text_y, CLOSE_GLYPH);
 
             if is_focused {
                 let underline_height = 3;
                 let underline_y = self.height as i16 - underline_height;
-
-                unsafe {
-                    let gc =
-                        x11::xlib::XCreateGC(self.display, self.pixmap, 0, std::ptr::null_mut());
-                    x11::xlib::XSetForeground(self.display, gc, scheme.underline as u64);
-                    x11::xlib::XFillRectangle(
-                        self.display,
-                        self.pixmap,
-                        gc,
-                        x_position as i32,
-                        underline_y as i32,
-                        tab_width as u32,
-                        underline_height as u32,
-                    );
-                    x11::xlib::XFreeGC(self.display, gc);
-                }
+                self.fill_rectangle(scheme.underline, x_position, underline_y, tab_width, underline_height as u16);
             }
 
-            x_position += tab_width as i16;
+            let is_last = index + 1 == tabs.len();
+            if !is_last && separator_width > 0 {
+                self.font_draw.draw_text(
+                    font,
+                    self.style.scheme_inactive.foreground,
+                    x_position + tab_width as i16,
+                    text_y,
+                    &self.style.separator,
+                );
+            }
         }
 
         self.copy_pixmap_to_window();
         Ok(())
     }
 
+    /// Splits the bar between `tab_count` tabs with the separator between
+    /// them, capping each at the configured maximum width.
+    fn layout_tabs(&self, font: &Font, tab_count: usize) -> Vec<(i16, u16)> {
+        if tab_count == 0 {
+            return Vec::new();
+        }
+
+        let separator_width = font.text_width(&self.style.separator);
+        let available = self
+            .width
+            .saturating_sub(separator_width * (tab_count as u16 - 1));
+        let mut tab_width = available / tab_count as u16;
+        if let Some(max_tab_width) = self.style.max_tab_width {
+            tab_width = tab_width.min(max_tab_width);
+        }
+
+        (0..tab_count)
+            .map(|index| ((index as u16 * (tab_width + separator_width)) as i16, tab_width))
+            .collect()
+    }
+
+    fn fill_rectangle(&self, color: u32, x: i16, y: i16, width: u16, height: u16) {
+        unsafe {
+            let gc = x11::xlib::XCreateGC(self.display, self.pixmap, 0, std::ptr::null_mut());
+            x11::xlib::XSetForeground(self.display, gc, color as u64);
+            x11::xlib::XFillRectangle(
+                self.display,
+                self.pixmap,
+                gc,
+                x as i32,
+                y as i32,
+                width as u32,
+                height as u32,
+            );
+            x11::xlib::XFreeGC(self.display, gc);
+        }
+    }
+
     fn copy_pixmap_to_window(&self) {
         unsafe {
             let gc = x11::xlib::XCreateGC(self.display, self.window as u64, 0, std::ptr::null_mut());
@@ -212,19 +285,37 @@ impl TabBar {
         }
     }
 
-    pub fn get_clicked_window(
-        &self,
-        windows: &[(Window, String)],
-        click_x: i16,
-    ) -> Option<Window> {
-        if windows.is_empty() {
+    /// The tab under `click_x`, as laid out by the last draw. Clicks on a
+    /// separator go to the tab before it and clicks past the last tab to
+    /// the last one, so a dragged tab can be moved to either end.
+    pub fn get_tab_index(&self, tab_count: usize, click_x: i16) -> Option<usize> {
+        if tab_count == 0 {
             return None;
         }
 
-        let tab_width = self.width / windows.len() as u16;
-        let tab_index = (click_x as u16 / tab_width) as usize;
+        let tab_index = self
+            .tab_areas
+            .iter()
+            .take(tab_count)
+            .rposition(|&(x, _)| x <= click_x)
+            .unwrap_or(0);
 
-        windows.get(tab_index).map(|&(win, _)| win)
+        Some(tab_index.min(tab_count - 1))
+    }
+
+    pub fn get_click(&self, tabs: &[Tab], click_x: i16) -> Option<TabClick> {
+        let tab_index = self.get_tab_index(tabs.len(), click_x)?;
+        let tab = tabs.get(tab_index)?;
+        let &(tab_x, tab_width) = self.tab_areas.get(tab_index)?;
+
+        let offset_in_tab = click_x.saturating_sub(tab_x).max(0) as u16;
+        if offset_in_tab >= tab_width {
+            None
+        } else if offset_in_tab >= tab_width.saturating_sub(CLOSE_BUTTON_WIDTH) {
+            Some(TabClick::Close(tab.window))
+        } else {
+            Some(TabClick::Select(tab.window))
+        }
     }
 
     pub fn reposition(
@@ -233,17 +324,20 @@ impl TabBar {
         x: i16,
         y: i16,
         width: u16,
+        height: u16,
     ) -> Result<(), X11Error> {
         self.x_offset = x;
         self.y_offset = y;
         self.width = width;
+        self.height = height;
 
         connection.configure_window(
             self.window,
             &ConfigureWindowAux::new()
                 .x(x as i32)
                 .y(y as i32)
-                .width(width as u32),
+                .width(width as u32)
+                .height(height as u32),
         )?;
 
         unsafe {
@@ -269,6 +363,10 @@ impl TabBar {
         Ok(())
     }
 
+    pub fn set_style(&mut self, style: TabStyle) {
+        self.style = style;
+    }
+
     pub fn hide(&self, connection: &RustConnection) -> Result<(), X11Error> {
         connection.unmap_window(self.window)?;
         connection.flush()?;
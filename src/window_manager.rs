@@ -1,14 +1,20 @@
 use crate::Config;
-use crate::bar::Bar;
-use crate::client::{Client, TagMask};
+use crate::backend::{InputOps, PropertyOps, WindowOps};
+use crate::selection::{self, FocusChange, Selection, TagAtoms};
+use crate::bar::{Bar, BarClick, BarPosition};
+use crate::client::{Client, Geometry, MAX_TAGS, STASH_TAG, TagMask, WindowType};
 use crate::errors::WmError;
 use crate::keyboard::{self, Arg, KeyAction, handlers};
-use crate::layout::GapConfig;
+use crate::layout::{Arrangement, GapConfig};
 use crate::layout::tiling::TilingLayout;
-use crate::layout::{Layout, LayoutBox, LayoutType, layout_from_str, next_layout};
-use crate::monitor::{Monitor, detect_monitors};
-use crate::overlay::{ErrorOverlay, KeybindOverlay, Overlay};
-use std::collections::{HashMap, HashSet};
+use crate::layout::{Layout, LayoutBox, LayoutType, MasterAxis, layout_from_str, next_layout, prev_layout};
+use crate::media::MediaCommand;
+use crate::monitor::{Monitor, Strut, apply_monitor_config, detect_monitors};
+use crate::overlay::{
+    ErrorOverlay, ErrorScroll, KeybindOverlay, LayoutMenu, Osd, Overlay, SwitcherEntry, TextInput, WindowSwitcher,
+};
+use crate::tab_bar::TabClick;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::process::Command;
 use x11rb::cursor::Handle as CursorHandle;
 
@@ -18,10 +24,151 @@ use x11rb::protocol::xproto::*;
 use x11rb::rust_connection::RustConnection;
 
 
+/// How long the busy cursor is shown for an application that never maps a
+/// window.
+const STARTUP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Lowest opacity a client can be set to, so it can't become invisible
+/// and hard to get back.
+const MIN_OPACITY: f32 = 0.1;
+
+/// An application launched through `Spawn` that has not mapped a window yet.
+struct PendingLaunch {
+    startup_id: String,
+    pid: u32,
+    monitor_index: usize,
+    tags: TagMask,
+    started: std::time::Instant,
+    /// The spawned process exited before mapping a window, so the busy
+    /// cursor is no longer shown for it. The launch is kept until it times
+    /// out in case the process handed off to another one that maps it.
+    exited: bool,
+}
+
+/// A layout forced onto a tag by window rules, kept until the last window
+/// that matched leaves.
+struct RuleLayout {
+    previous: String,
+    windows: Vec<Window>,
+}
+
 pub fn tag_mask(tag: usize) -> TagMask {
     1 << tag
 }
 
+/// Removes bit `tag` from `mask`, shifting all higher tags down by one.
+/// The stash tag keeps its place.
+fn remove_tag_bit(mask: TagMask, tag: usize) -> TagMask {
+    let stash = mask & STASH_TAG;
+    let mask = mask & !STASH_TAG;
+    let below = mask & (tag_mask(tag) - 1);
+    let above = mask.checked_shr(tag as u32 + 1).unwrap_or(0) << tag;
+    below | above | stash
+}
+
+fn blend_colors(from: u32, to: u32, amount: f32) -> u32 {
+    let amount = amount.clamp(0.0, 1.0);
+    let channel = |shift: u32| {
+        let from = ((from >> shift) & 0xff) as f32;
+        let to = ((to >> shift) & 0xff) as f32;
+        ((from + (to - from) * amount).round() as u32) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
+
+/// Which edge along one axis a resize grabbed at `offset` into a window of
+/// `size` moves: `Some(true)` for the far edge, `Some(false)` for the near
+/// one and `None` for the middle third, which leaves that axis alone.
+fn resize_edge(offset: i32, size: i32) -> Option<bool> {
+    if offset < size / 3 {
+        Some(false)
+    } else if offset >= size * 2 / 3 {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Snaps a window's outer rectangle to the edges of `area` and of `others`
+/// when an edge is closer than `distance`.
+fn snap_position(
+    (x, y): (i32, i32),
+    (width, height): (i32, i32),
+    (area_x, area_y, area_width, area_height): (i32, i32, i32, i32),
+    others: &[(i32, i32, i32, i32)],
+    distance: i32,
+) -> (i32, i32) {
+    let snap_axis = |position: i32, size: i32, edges: &[i32]| -> i32 {
+        let mut best = position;
+        let mut best_distance = distance;
+        for &edge in edges {
+            for candidate in [edge, edge - size] {
+                let candidate_distance = (candidate - position).abs();
+                if candidate_distance < best_distance {
+                    best = candidate;
+                    best_distance = candidate_distance;
+                }
+            }
+        }
+        best
+    };
+
+    let mut x_edges = vec![area_x, area_x + area_width];
+    let mut y_edges = vec![area_y, area_y + area_height];
+    for &(other_x, other_y, other_width, other_height) in others {
+        let overlaps_vertically = y < other_y + other_height && other_y < y + height;
+        let overlaps_horizontally = x < other_x + other_width && other_x < x + width;
+        if overlaps_vertically {
+            x_edges.extend([other_x, other_x + other_width]);
+        }
+        if overlaps_horizontally {
+            y_edges.extend([other_y, other_y + other_height]);
+        }
+    }
+
+    (snap_axis(x, width, &x_edges), snap_axis(y, height, &y_edges))
+}
+
+/// Picks the position in `area` where a window of `size` overlaps the other
+/// floating windows the least, preferring positions near the top left.
+fn smart_placement(
+    (area_x, area_y, area_width, area_height): (i32, i32, i32, i32),
+    (width, height): (i32, i32),
+    others: &[(i32, i32, i32, i32)],
+) -> (i32, i32) {
+    if others.is_empty() {
+        return (area_x + (area_width - width) / 2, area_y + (area_height - height) / 2);
+    }
+
+    let max_x = (area_x + area_width - width).max(area_x);
+    let max_y = (area_y + area_height - height).max(area_y);
+
+    let mut xs = vec![area_x, max_x];
+    let mut ys = vec![area_y, max_y];
+    for &(x, y, w, h) in others {
+        xs.extend([x + w, x - width]);
+        ys.extend([y + h, y - height]);
+    }
+    xs.retain(|&x| (area_x..=max_x).contains(&x));
+    ys.retain(|&y| (area_y..=max_y).contains(&y));
+
+    let overlap = |x: i32, y: i32| -> i64 {
+        others
+            .iter()
+            .map(|&(ox, oy, ow, oh)| {
+                let overlap_width = ((x + width).min(ox + ow) - x.max(ox)).max(0) as i64;
+                let overlap_height = ((y + height).min(oy + oh) - y.max(oy)).max(0) as i64;
+                overlap_width * overlap_height
+            })
+            .sum()
+    };
+
+    ys.iter()
+        .flat_map(|&y| xs.iter().map(move |&x| (x, y)))
+        .min_by_key(|&(x, y)| (overlap(x, y), y, x))
+        .unwrap_or((area_x, area_y))
+}
+
 struct AtomCache {
     net_current_desktop: Atom,
     net_client_info: Atom,
@@ -30,12 +177,28 @@ struct AtomCache {
     wm_delete_window: Atom,
     net_wm_state: Atom,
     net_wm_state_fullscreen: Atom,
+    net_wm_state_demands_attention: Atom,
     net_wm_window_type: Atom,
     net_wm_window_type_dialog: Atom,
+    net_wm_window_type_dock: Atom,
+    net_wm_window_type_utility: Atom,
+    net_wm_window_type_toolbar: Atom,
+    net_wm_window_type_splash: Atom,
+    net_wm_window_type_notification: Atom,
+    net_wm_strut: Atom,
+    net_wm_strut_partial: Atom,
+    wm_change_state: Atom,
+    net_startup_id: Atom,
+    net_wm_pid: Atom,
+    net_desktop_names: Atom,
+    net_number_of_desktops: Atom,
+    net_wm_desktop: Atom,
     wm_name: Atom,
     net_wm_name: Atom,
     utf8_string: Atom,
     net_active_window: Atom,
+    net_wm_window_opacity: Atom,
+    net_wm_bypass_compositor: Atom,
 }
 
 impl AtomCache {
@@ -72,6 +235,11 @@ impl AtomCache {
             .reply()?
             .atom;
 
+        let net_wm_state_demands_attention = connection
+            .intern_atom(false, b"_NET_WM_STATE_DEMANDS_ATTENTION")?
+            .reply()?
+            .atom;
+
         let net_wm_window_type = connection
             .intern_atom(false, b"_NET_WM_WINDOW_TYPE")?
             .reply()?
@@ -86,6 +254,24 @@ impl AtomCache {
         let net_wm_name = connection.intern_atom(false, b"_NET_WM_NAME")?.reply()?.atom;
         let utf8_string = connection.intern_atom(false, b"UTF8_STRING")?.reply()?.atom;
         let net_active_window = connection.intern_atom(false, b"_NET_ACTIVE_WINDOW")?.reply()?.atom;
+        let net_wm_window_opacity = connection.intern_atom(false, b"_NET_WM_WINDOW_OPACITY")?.reply()?.atom;
+        let net_wm_bypass_compositor = connection.intern_atom(false, b"_NET_WM_BYPASS_COMPOSITOR")?.reply()?.atom;
+        let net_wm_window_type_dock = connection.intern_atom(false, b"_NET_WM_WINDOW_TYPE_DOCK")?.reply()?.atom;
+        let net_wm_window_type_utility = connection.intern_atom(false, b"_NET_WM_WINDOW_TYPE_UTILITY")?.reply()?.atom;
+        let net_wm_window_type_toolbar = connection.intern_atom(false, b"_NET_WM_WINDOW_TYPE_TOOLBAR")?.reply()?.atom;
+        let net_wm_window_type_splash = connection.intern_atom(false, b"_NET_WM_WINDOW_TYPE_SPLASH")?.reply()?.atom;
+        let net_wm_window_type_notification = connection.intern_atom(false, b"_NET_WM_WINDOW_TYPE_NOTIFICATION")?.reply()?.atom;
+        let net_wm_strut = connection.intern_atom(false, b"_NET_WM_STRUT")?.reply()?.atom;
+        let net_wm_strut_partial = connection.intern_atom(false, b"_NET_WM_STRUT_PARTIAL")?.reply()?.atom;
+        let wm_change_state = connection.intern_atom(false, b"WM_CHANGE_STATE")?.reply()?.atom;
+        let net_startup_id = connection.intern_atom(false, b"_NET_STARTUP_ID")?.reply()?.atom;
+        let net_wm_pid = connection.intern_atom(false, b"_NET_WM_PID")?.reply()?.atom;
+        let net_desktop_names = connection.intern_atom(false, b"_NET_DESKTOP_NAMES")?.reply()?.atom;
+        let net_number_of_desktops = connection
+            .intern_atom(false, b"_NET_NUMBER_OF_DESKTOPS")?
+            .reply()?
+            .atom;
+        let net_wm_desktop = connection.intern_atom(false, b"_NET_WM_DESKTOP")?.reply()?.atom;
 
         Ok(Self {
             net_current_desktop,
@@ -95,12 +281,28 @@ impl AtomCache {
             wm_delete_window,
             net_wm_state,
             net_wm_state_fullscreen,
+            net_wm_state_demands_attention,
             net_wm_window_type,
             net_wm_window_type_dialog,
+            net_wm_window_type_dock,
+            net_wm_window_type_utility,
+            net_wm_window_type_toolbar,
+            net_wm_window_type_splash,
+            net_wm_window_type_notification,
+            net_wm_strut,
+            net_wm_strut_partial,
+            wm_change_state,
+            net_startup_id,
+            net_wm_pid,
+            net_desktop_names,
+            net_number_of_desktops,
+            net_wm_desktop,
             wm_name,
             net_wm_name,
             utf8_string,
             net_active_window,
+            net_wm_window_opacity,
+            net_wm_bypass_compositor,
         })
     }
 }
@@ -111,71 +313,134 @@ pub struct WindowManager {
     screen_number: usize,
     root: Window,
     screen: Screen,
+    /// Whether oxwm was started with `--test`, and on which `--display`,
+    /// so a restart comes back the same way.
+    test_mode: bool,
+    test_display: Option<String>,
     windows: Vec<Window>,
     clients: HashMap<Window, Client>,
     layout: LayoutBox,
     gaps_enabled: bool,
     floating_windows: HashSet<Window>,
     fullscreen_windows: HashSet<Window>,
-    floating_geometry_before_fullscreen: HashMap<Window, (i16, i16, u16, u16, u16)>,
+    /// Fullscreen windows covering every monitor instead of just their own.
+    spanning_windows: HashSet<Window>,
+    docks: HashMap<Window, Strut>,
+    focus_lock: bool,
+    minimized: Vec<Window>,
+    /// Windows on the stash tag, most recently stashed last.
+    stashed: Vec<Window>,
+    pending_launches: Vec<PendingLaunch>,
+    launch_counter: u32,
+    /// Events that arrived while a nested event loop was running, handled
+    /// by the main loop before it reads new ones.
+    deferred_events: VecDeque<Event>,
+    normal_cursor: Cursor,
+    busy_cursor: Cursor,
+    crosshair_cursor: Cursor,
     bars: Vec<Bar>,
     tab_bars: Vec<crate::tab_bar::TabBar>,
-    show_bar: bool,
-    last_layout: Option<&'static str>,
     monitors: Vec<Monitor>,
     selected_monitor: usize,
     atoms: AtomCache,
     previous_focused: Option<Window>,
     display: *mut x11::xlib::Display,
     font: crate::bar::font::Font,
+    monitor_fonts: Vec<crate::bar::font::Font>,
     keychord_state: keyboard::handlers::KeychordState,
     current_key: usize,
-    keyboard_mapping: Option<keyboard::KeyboardMapping>,
+    /// Keycode and action of the hold binding currently pressed.
+    held_key: Option<(Keycode, KeyAction, Arg)>,
+    /// Mode entered with `oxwm.mode.enter`, left with Escape.
+    active_mode: Option<String>,
+    keyboard_mapping: keyboard::KeyboardMapping,
     error_message: Option<String>,
     overlay: ErrorOverlay,
     keybind_overlay: KeybindOverlay,
+    layout_menu: LayoutMenu,
+    window_switcher: WindowSwitcher,
+    text_input: TextInput,
+    osd: Osd,
+    /// When the on screen display hides again.
+    osd_deadline: Option<std::time::Instant>,
+    renaming_tag: Option<usize>,
+    /// Tags before this index come from the config, the rest were added at
+    /// runtime and are removed again once empty.
+    static_tag_count: usize,
+    supervisor: crate::autostart::Supervisor,
+    quit_deadline: Option<std::time::Instant>,
+    closing_windows: HashSet<Window>,
+    /// Clients asked to close with WM_DELETE_WINDOW and when.
+    pending_closes: HashMap<Window, std::time::Instant>,
+    /// Layout last used on each tag, by monitor and tag index.
+    tag_layouts: HashMap<(usize, usize), String>,
+    rule_layouts: HashMap<(usize, usize), RuleLayout>,
 }
 
 type WmResult<T> = Result<T, WmError>;
 
 impl WindowManager {
     pub fn new(config: Config) -> WmResult<Self> {
-        let (connection, screen_number) = x11rb::connect(None)?;
+        Self::with_display(config, None, false)
+    }
+
+    /// Connects to `display_name`, or `$DISPLAY` if `None`. In `test_mode`
+    /// another window manager already running on the display is not an
+    /// error: oxwm then runs alongside it without managing windows, which
+    /// is still enough to try out the bar, keybindings and config.
+    pub fn with_display(config: Config, display_name: Option<&str>, test_mode: bool) -> WmResult<Self> {
+        let (connection, screen_number) = x11rb::connect(display_name)?;
         let root = connection.setup().roots[screen_number].root;
         let screen = connection.setup().roots[screen_number].clone();
 
-        let normal_cursor = CursorHandle::new(
+        let cursor_handle = CursorHandle::new(
             &connection,
             screen_number,
             &x11rb::resource_manager::new_from_default(&connection)?,
         )?
-        .reply()?
-        .load_cursor(&connection, "left_ptr")?;
-
-        connection
+        .reply()?;
+        let normal_cursor = cursor_handle.load_cursor(&connection, "left_ptr")?;
+        let busy_cursor = cursor_handle.load_cursor(&connection, "watch")?;
+        let crosshair_cursor = cursor_handle.load_cursor(&connection, "crosshair")?;
+
+        let root_events = EventMask::SUBSTRUCTURE_NOTIFY
+            | EventMask::PROPERTY_CHANGE
+            | EventMask::KEY_PRESS
+            | EventMask::BUTTON_PRESS
+            | EventMask::POINTER_MOTION;
+        let redirect = connection
             .change_window_attributes(
                 root,
                 &ChangeWindowAttributesAux::new()
                     .cursor(normal_cursor)
-                    .event_mask(
-                        EventMask::SUBSTRUCTURE_REDIRECT
-                            | EventMask::SUBSTRUCTURE_NOTIFY
-                            | EventMask::PROPERTY_CHANGE
-                            | EventMask::KEY_PRESS
-                            | EventMask::BUTTON_PRESS
-                            | EventMask::POINTER_MOTION,
-                    ),
+                    .event_mask(root_events | EventMask::SUBSTRUCTURE_REDIRECT),
             )?
-            .check()?;
+            .check();
+        match redirect {
+            Ok(()) => {}
+            Err(x11rb::errors::ReplyError::X11Error(error)) if error.error_kind == x11rb::protocol::ErrorKind::Access => {
+                if !test_mode {
+                    return Err(WmError::X11(crate::errors::X11Error::AnotherWmRunning));
+                }
+                log::warn!("Another window manager is running, continuing without managing windows");
+                // Only one client may select ButtonPress on the root, and
+                // the running window manager already has it.
+                connection
+                    .change_window_attributes(
+                        root,
+                        &ChangeWindowAttributesAux::new().event_mask(root_events.remove(EventMask::BUTTON_PRESS)),
+                    )?
+                    .check()?;
+            }
+            Err(error) => return Err(error.into()),
+        }
 
-        let ignore_modifiers = [
-            0,
-            u16::from(ModMask::LOCK),
-            u16::from(ModMask::M2),
-            u16::from(ModMask::LOCK | ModMask::M2),
-        ];
+        if !keyboard::handlers::select_keyboard_events(&connection)? {
+            log::warn!("XKB is not available, keyboard layout changes may need a restart");
+        }
+        let keyboard_mapping = keyboard::handlers::get_keyboard_mapping(&connection)?;
 
-        for &ignore_mask in &ignore_modifiers {
+        for ignore_mask in keyboard_mapping.lock_masks() {
             let grab_mask = u16::from(config.modkey) | ignore_mask;
 
             connection.grab_button(
@@ -203,24 +468,55 @@ impl WindowManager {
             )?;
         }
 
-        let monitors = detect_monitors(&connection, &screen, root)?;
+        let mut monitors = detect_monitors(&connection, &screen, root, &config.monitor_order)?;
+        apply_monitor_config(&mut monitors, &config);
 
-        let display = unsafe { x11::xlib::XOpenDisplay(std::ptr::null()) };
+        let display_cstring = display_name
+            .map(std::ffi::CString::new)
+            .transpose()
+            .map_err(|_| WmError::X11(crate::errors::X11Error::DisplayOpenFailed))?;
+        let display = unsafe {
+            x11::xlib::XOpenDisplay(display_cstring.as_ref().map_or(std::ptr::null(), |name| name.as_ptr()))
+        };
         if display.is_null() {
             return Err(WmError::X11(crate::errors::X11Error::DisplayOpenFailed));
         }
 
-        let font = crate::bar::font::Font::new(display, screen_number as i32, &config.font)?;
+        let font = crate::bar::font::Font::new(
+            display,
+            screen_number as i32,
+            &config.font,
+            &config.font_fallbacks,
+        )?;
+
+        let mut monitor_fonts = Vec::new();
+        for monitor in monitors.iter() {
+            let scaled = |font_name: &str| {
+                if monitor.scale == 1.0 {
+                    font_name.to_string()
+                } else {
+                    format!("{}:scale={}", font_name, monitor.scale)
+                }
+            };
+            let fallbacks: Vec<String> = config.font_fallbacks.iter().map(|name| scaled(name)).collect();
+            monitor_fonts.push(crate::bar::font::Font::new(
+                display,
+                screen_number as i32,
+                &scaled(&config.font),
+                &fallbacks,
+            )?);
+        }
 
         let mut bars = Vec::new();
-        for monitor in monitors.iter() {
+        for (monitor_index, (monitor, monitor_font)) in monitors.iter().zip(monitor_fonts.iter()).enumerate() {
             let bar = Bar::new(
                 &connection,
                 &screen,
                 screen_number,
                 &config,
                 display,
-                &font,
+                monitor_font,
+                monitor_index,
                 monitor.screen_x as i16,
                 monitor.screen_y as i16,
                 monitor.screen_width as u16,
@@ -228,25 +524,37 @@ impl WindowManager {
             bars.push(bar);
         }
 
-        let bar_height = font.height() as f32 * 1.4;
         let mut tab_bars = Vec::new();
-        for monitor in monitors.iter() {
+        for (monitor, monitor_font) in monitors.iter().zip(monitor_fonts.iter()) {
+            let bar_height = monitor_font.height() as f32 * 1.4;
+            let tab_bar_height = monitor.scaled(config.tab_bar_height);
+            let gap_outer_horizontal = monitor.scaled(config.gap_outer_horizontal);
+            let gap_outer_vertical = monitor.scaled(config.gap_outer_vertical);
+            let tab_bar_y = match config.tab_bar_position {
+                BarPosition::Top => {
+                    (monitor.screen_y as f32 + bar_height + gap_outer_vertical as f32) as i16
+                }
+                BarPosition::Bottom => (monitor.screen_y + monitor.screen_height
+                    - gap_outer_vertical as i32
+                    - tab_bar_height as i32) as i16,
+            };
             let tab_bar = crate::tab_bar::TabBar::new(
                 &connection,
                 &screen,
                 screen_number,
                 display,
-                &font,
-                (monitor.screen_x + config.gap_outer_horizontal as i32) as i16,
-                (monitor.screen_y as f32 + bar_height + config.gap_outer_vertical as f32) as i16,
-                monitor.screen_width.saturating_sub(2 * config.gap_outer_horizontal as i32) as u16,
-                config.scheme_occupied,
-                config.scheme_selected,
+                monitor_font,
+                (monitor.screen_x + gap_outer_horizontal as i32) as i16,
+                tab_bar_y,
+                monitor.screen_width.saturating_sub(2 * gap_outer_horizontal as i32) as u16,
+                tab_bar_height as u16,
+                crate::tab_bar::TabStyle::new(&config, monitor),
             )?;
             tab_bars.push(tab_bar);
         }
 
         let gaps_enabled = config.gaps_enabled;
+        let config_tag_count = config.tags.len();
 
         let atoms = AtomCache::new(&connection)?;
 
@@ -262,50 +570,88 @@ impl WindowManager {
         let keybind_overlay =
             KeybindOverlay::new(&connection, &screen, screen_number, display, config.modkey)?;
 
+        let layout_menu = LayoutMenu::new(&connection, &screen, screen_number, display)?;
+        let window_switcher = WindowSwitcher::new(&connection, &screen, screen_number, display)?;
+        let text_input = TextInput::new(&connection, &screen, screen_number, display)?;
+        let osd = Osd::new(&connection, &screen, screen_number, display)?;
+
         let mut window_manager = Self {
             config,
             connection,
             screen_number,
             root,
             screen,
+            test_mode,
+            test_display: display_name.map(str::to_owned),
             windows: Vec::new(),
             clients: HashMap::new(),
             layout: Box::new(TilingLayout),
             gaps_enabled,
             floating_windows: HashSet::new(),
             fullscreen_windows: HashSet::new(),
-            floating_geometry_before_fullscreen: HashMap::new(),
+            spanning_windows: HashSet::new(),
+            docks: HashMap::new(),
+            focus_lock: false,
+            minimized: Vec::new(),
+            stashed: Vec::new(),
+            pending_launches: Vec::new(),
+            launch_counter: 0,
+            deferred_events: VecDeque::new(),
+            normal_cursor,
+            busy_cursor,
+            crosshair_cursor,
             bars,
             tab_bars,
-            show_bar: true,
-            last_layout: None,
             monitors,
             selected_monitor: 0,
             atoms,
             previous_focused: None,
             display,
             font,
+            monitor_fonts,
             keychord_state: keyboard::handlers::KeychordState::Idle,
             current_key: 0,
-            keyboard_mapping: None,
+            held_key: None,
+            active_mode: None,
+            keyboard_mapping,
             error_message: None,
             overlay,
             keybind_overlay,
+            layout_menu,
+            window_switcher,
+            text_input,
+            osd,
+            osd_deadline: None,
+            renaming_tag: None,
+            static_tag_count: config_tag_count,
+            supervisor: crate::autostart::Supervisor::new(),
+            quit_deadline: None,
+            closing_windows: HashSet::new(),
+            pending_closes: HashMap::new(),
+            tag_layouts: HashMap::new(),
+            rule_layouts: HashMap::new(),
         };
 
         for tab_bar in &window_manager.tab_bars {
             tab_bar.hide(&window_manager.connection)?;
         }
 
+        window_manager.restore_dynamic_tags()?;
+        window_manager.update_work_areas();
         window_manager.scan_existing_windows()?;
         window_manager.update_bar()?;
         window_manager.run_autostart_commands()?;
+        window_manager
+            .supervisor
+            .ensure_running(&window_manager.config.autostart_once);
 
         Ok(window_manager)
     }
 
-    pub fn show_migration_overlay(&mut self) {
-        let message = "We are on version 0.8.0 now.\n\n\
+    /// Explains that the user's config failed to load and the default one
+    /// is used instead. `error` names the file and line that failed.
+    pub fn show_migration_overlay(&mut self, error: &str) {
+        let notice = "We are on version 0.8.0 now.\n\n\
                        Your config file has been deprecated once again.\n\
                        Backup your current config, and run oxwm --init to generate a new one with correct values.\n\n\
                        Please reach out to Tony, or check out the\n\
@@ -313,6 +659,7 @@ impl WindowManager {
                        We appreciate you testing oxwm!\n\n\
                        Press Mod+Shift+/ to see keybinds\n\
                        Press Mod+Shift+R to reload after fixing your config";
+        let message = format!("{}\n\nError: {}", notice, error);
 
         let monitor = &self.monitors[self.selected_monitor];
         let monitor_x = monitor.screen_x as i16;
@@ -323,17 +670,22 @@ impl WindowManager {
         if let Err(e) = self.overlay.show_error(
             &self.connection,
             &self.font,
-            message,
+            &message,
             monitor_x,
             monitor_y,
             screen_width,
             screen_height,
         ) {
-            eprintln!("Failed to show migration overlay: {:?}", e);
+            log::error!("Failed to show migration overlay: {:?}", e);
         }
     }
 
-    fn try_reload_config(&mut self) -> Result<(), String> {
+    /// The config file we were started with, or the default location.
+    fn config_path(&self) -> Result<std::path::PathBuf, String> {
+        if let Some(path) = &self.config.path {
+            return Ok(path.clone());
+        }
+
         let config_dir = if let Some(xdg_config) = std::env::var_os("XDG_CONFIG_HOME") {
             std::path::PathBuf::from(xdg_config).join("oxwm")
         } else if let Some(home) = std::env::var_os("HOME") {
@@ -341,32 +693,186 @@ impl WindowManager {
         } else {
             return Err("Could not find config directory".to_string());
         };
+        Ok(config_dir.join("config.lua"))
+    }
 
-        let lua_path = config_dir.join("config.lua");
+    /// Reads and parses the config file without applying it.
+    fn load_config_file(&self) -> Result<Config, String> {
+        let lua_path = self.config_path()?;
 
         if !lua_path.exists() {
-            return Err("No config.lua file found".to_string());
+            return Err(format!("No config file found at {}", lua_path.display()));
         }
 
         let config_str = std::fs::read_to_string(&lua_path)
             .map_err(|e| format!("Failed to read config: {}", e))?;
 
-        let new_config = crate::config::parse_lua_config(&config_str, Some(&config_dir))
-            .map_err(|e| format!("{}", e))?;
+        let state = self.runtime_state();
+        crate::config::parse_lua_config(&config_str, Some(&lua_path), state)
+            .map_err(|e| format!("{}", e))
+    }
+
+    fn try_reload_config(&mut self) -> Result<(), String> {
+        let new_config = self.load_config_file()?;
+        crate::logging::set_file(new_config.log_file);
+        log::info!(
+            "Reloaded config from {}",
+            new_config.path.as_ref().map_or("the default location".into(), |path| path.display().to_string())
+        );
+
+        let dynamic_tags = self.config.tags.split_off(self.static_tag_count);
 
         self.config = new_config;
         self.error_message = None;
+        self.supervisor.ensure_running(&self.config.autostart_once);
+
+        self.static_tag_count = self.config.tags.len();
+        let room = MAX_TAGS - self.static_tag_count;
+        self.config.tags.extend(dynamic_tags.into_iter().take(room));
+
+        if self
+            .active_mode
+            .as_ref()
+            .is_some_and(|mode| !self.config.modes.contains_key(mode))
+        {
+            self.active_mode = None;
+        }
+        self.grab_keys().map_err(|e| format!("{}", e))?;
 
         for bar in &mut self.bars {
             bar.update_from_config(&self.config);
         }
+        self.refresh_tags().map_err(|e| format!("{}", e))?;
+
+        for (&window, client) in &self.clients {
+            self.update_shape(window, client.geometry())
+                .map_err(|e| format!("{}", e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces this process with a fresh `oxwm`, e.g. after installing a
+    /// new build. The config is checked first so a broken one doesn't leave
+    /// the session without a window manager, and if exec itself fails we
+    /// keep running. Either way the error is shown in the overlay.
+    fn reexec(&mut self) -> WmResult<()> {
+        use std::os::unix::process::CommandExt;
+
+        let result: Result<(), String> = self.load_config_file().and_then(|_| {
+            let path = self.config_path()?;
+            let program = std::env::current_exe()
+                .map_err(|e| format!("Failed to find the oxwm binary: {}", e))?;
+            self.connection.flush().map_err(|e| format!("{}", e))?;
+
+            let mut command = Command::new(&program);
+            if self.test_mode {
+                command.arg("--test");
+                if let Some(display) = &self.test_display {
+                    command.arg("--display").arg(display);
+                }
+            }
+            crate::reaper::hand_over(&mut command);
+            let error = command.arg("--config").arg(&path).exec();
+            Err(format!("Failed to restart {}: {}", program.display(), error))
+        });
 
+        if let Err(err) = result {
+            log::error!("Restart error: {}", err);
+            self.show_error_overlay(&err);
+        }
         Ok(())
     }
 
+    /// Called after a panic while handling an event, which the panic hook
+    /// has already logged with a backtrace. Releases any grab the handler
+    /// was holding and tells the user, so a bug costs them a glitch rather
+    /// than the whole X session.
+    fn recover_from_panic(&mut self, payload: Box<dyn std::any::Any + Send>) {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+
+        let _ = self.connection.release_grabs();
+        let _ = self.connection.flush();
+
+        self.show_error_overlay(&format!(
+            "oxwm ran into a bug and recovered:\n\n{}\n\n\
+             The backtrace is in the log. If something misbehaves, restart oxwm.",
+            message
+        ));
+    }
+
+    fn show_error_overlay(&mut self, err: &str) {
+        self.error_message = Some(err.to_string());
+        let monitor = &self.monitors[self.selected_monitor];
+        let monitor_x = monitor.screen_x as i16;
+        let monitor_y = monitor.screen_y as i16;
+        let screen_width = monitor.screen_width as u16;
+        let screen_height = monitor.screen_height as u16;
+        match self.overlay.show_error(
+            &self.connection,
+            &self.font,
+            err,
+            monitor_x,
+            monitor_y,
+            screen_width,
+            screen_height,
+        ) {
+            Ok(()) => log::debug!("Error modal displayed"),
+            Err(e) => log::error!("Failed to show error modal: {:?}", e),
+        }
+    }
+
+    /// Snapshot of clients and monitors for `oxwm.state` in a reloaded config.
+    fn runtime_state(&self) -> crate::config::RuntimeState {
+        use crate::config::{ClientState, MonitorState, RuntimeState};
+
+        let tag_indices = |mask: TagMask| -> Vec<usize> {
+            (0..self.config.tags.len())
+                .filter(|&index| mask & tag_mask(index) != 0)
+                .collect()
+        };
+
+        let clients = self
+            .windows
+            .iter()
+            .filter_map(|window| self.clients.get(window))
+            .map(|client| ClientState {
+                window: client.window,
+                class: client.class.clone(),
+                instance: client.instance.clone(),
+                title: client.name.clone(),
+                tags: tag_indices(client.tags),
+                floating: self.floating_windows.contains(&client.window),
+                monitor: client.monitor_index,
+            })
+            .collect();
+
+        let monitors = self
+            .monitors
+            .iter()
+            .map(|monitor| MonitorState {
+                x: monitor.screen_x,
+                y: monitor.screen_y,
+                width: monitor.screen_width,
+                height: monitor.screen_height,
+                name: monitor.output_name.clone(),
+                tags: tag_indices(monitor.tagset[monitor.selected_tags_index]),
+            })
+            .collect();
+
+        RuntimeState {
+            clients,
+            monitors,
+            selected_monitor: self.selected_monitor,
+        }
+    }
+
     fn scan_existing_windows(&mut self) -> WmResult<()> {
         let tree = self.connection.query_tree(self.root)?.reply()?;
-        let net_client_info = self.atoms.net_client_info;
         let wm_state_atom = self.atoms.wm_state;
 
         for &window in &tree.children {
@@ -382,9 +888,13 @@ impl WindowManager {
                 continue;
             }
 
+            if attrs.map_state == MapState::VIEWABLE && self.is_dock(window) {
+                self.manage_dock(window)?;
+                continue;
+            }
+
             if attrs.map_state == MapState::VIEWABLE {
-                let _tag = self.get_saved_tag(window, net_client_info)?;
-                self.windows.push(window);
+                self.manage_existing(window)?;
                 continue;
             }
 
@@ -406,9 +916,7 @@ impl WindowManager {
                     .is_ok_and(|prop| !prop.value.is_empty());
 
                 if has_wm_class {
-                    let _tag = self.get_saved_tag(window, net_client_info)?;
-                    self.connection.map_window(window)?;
-                    self.windows.push(window);
+                    self.manage_existing(window)?;
                 }
             }
         }
@@ -421,7 +929,30 @@ impl WindowManager {
         Ok(())
     }
 
-    fn get_saved_tag(&self, window: Window, net_client_info: Atom) -> WmResult<TagMask> {
+    /// Manages a window the previous instance left behind and puts it back
+    /// on the tags it saved, the stash included.
+    fn manage_existing(&mut self, window: Window) -> WmResult<()> {
+        // Managing saves the tags it picks, so read the old ones first.
+        let saved_tag = self.get_saved_tag(window, self.atoms.net_client_info)?;
+        self.manage_window(window)?;
+
+        let Some(tags) = saved_tag else {
+            return Ok(());
+        };
+        let Some(client) = self.clients.get_mut(&window) else {
+            return Ok(());
+        };
+        client.tags = tags;
+        if tags == STASH_TAG {
+            self.stashed.retain(|&w| w != window);
+            self.stashed.push(window);
+        }
+        self.save_client_tag(window, tags)
+    }
+
+    /// The tags saved on a window by `save_client_tag`, if they are still
+    /// valid tags or the stash.
+    fn get_saved_tag(&self, window: Window, net_client_info: Atom) -> WmResult<Option<TagMask>> {
         match self
             .connection
             .get_property(false, window, net_client_info, AtomEnum::CARDINAL, 0, 2)?
@@ -435,89 +966,123 @@ impl WindowManager {
                     prop.value[3],
                 ]);
 
-                if tags != 0 && tags < (1 << self.config.tags.len()) {
-                    return Ok(tags);
+                if tags == STASH_TAG || (tags != 0 && tags < (1 << self.config.tags.len())) {
+                    return Ok(Some(tags));
                 }
             }
             Ok(_) => {}
             Err(e) => {
-                eprintln!("No _NET_CLIENT_INFO property ({})", e);
+                log::debug!("No _NET_CLIENT_INFO property ({})", e);
             }
         }
 
-        Ok(self
-            .monitors
-            .get(self.selected_monitor)
-            .map(|m| m.tagset[m.selected_tags_index])
-            .unwrap_or(tag_mask(0)))
+        Ok(None)
     }
 
+    /// Stores `tag` on the window so it survives a restart, and publishes
+    /// its lowest tag as _NET_WM_DESKTOP for pagers and taskbars. Stashed
+    /// windows are on no desktop.
     fn save_client_tag(&self, window: Window, tag: TagMask) -> WmResult<()> {
-        let net_client_info = self.atoms.net_client_info;
-
-        let bytes = tag.to_ne_bytes().to_vec();
-
-        self.connection.change_property(
-            PropMode::REPLACE,
-            window,
-            net_client_info,
-            AtomEnum::CARDINAL,
-            32,
-            1,
-            &bytes,
-        )?;
-
+        selection::save_client_tag(&self.connection, self.tag_atoms(), window, tag)?;
         self.connection.flush()?;
         Ok(())
     }
 
-    fn set_wm_state(&self, window: Window, state: u32) -> WmResult<()> {
-        let wm_state_atom = self.atoms.wm_state;
+    fn tag_atoms(&self) -> TagAtoms {
+        TagAtoms {
+            net_client_info: self.atoms.net_client_info,
+            net_wm_desktop: self.atoms.net_wm_desktop,
+        }
+    }
 
-        let data = [state, 0u32];
-        let bytes: Vec<u8> = data.iter().flat_map(|&v| v.to_ne_bytes()).collect();
+    /// The focus and monitor selection state, for the moves between them.
+    fn selection(&mut self) -> Selection<'_, RustConnection> {
+        Selection {
+            backend: &self.connection,
+            root: self.root,
+            atoms: self.tag_atoms(),
+            windows: &self.windows,
+            clients: &mut self.clients,
+            monitors: &mut self.monitors,
+            selected_monitor: &mut self.selected_monitor,
+        }
+    }
 
-        self.connection.change_property(
-            PropMode::REPLACE,
-            window,
-            wm_state_atom,
-            wm_state_atom,
-            32,
-            2,
-            &bytes,
-        )?;
+    fn set_wm_state(&self, window: Window, state: u32) -> WmResult<()> {
+        let wm_state_atom = self.atoms.wm_state;
+        self.connection
+            .set_property32(window, wm_state_atom, wm_state_atom, &[state, 0])?;
 
         self.connection.flush()?;
         Ok(())
     }
 
     pub fn run(&mut self) -> WmResult<bool> {
-        println!("oxwm started on display {}", self.screen_number);
+        log::info!("oxwm started on display {}", self.screen_number);
 
         self.grab_keys()?;
         self.update_bar()?;
+        crate::shutdown::listen();
+        std::panic::set_hook(Box::new(|info| {
+            log::error!("{}\n{}", info, std::backtrace::Backtrace::force_capture());
+        }));
 
         let mut last_bar_update = std::time::Instant::now();
         const BAR_UPDATE_INTERVAL_MS: u64 = 100;
 
         loop {
-            match self.connection.poll_for_event_with_sequence()? {
-                Some((event, _sequence)) => {
-                    if let Some(should_restart) = self.handle_event(event)? {
-                        return Ok(should_restart);
+            let next = match self.deferred_events.pop_front() {
+                Some(event) => Some(event),
+                None => self.connection.poll_for_event_with_sequence()?.map(|(event, _sequence)| event),
+            };
+            match next {
+                Some(event) => {
+                    let handled = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        self.handle_event(event)
+                    }));
+                    match handled {
+                        Ok(result) => {
+                            if let Some(should_restart) = result? {
+                                return Ok(should_restart);
+                            }
+                        }
+                        Err(payload) => self.recover_from_panic(payload),
                     }
+                    self.prune_dynamic_tags()?;
                 }
                 None => {
                     if last_bar_update.elapsed().as_millis() >= BAR_UPDATE_INTERVAL_MS as u128 {
-                        if let Some(bar) = self.bars.get_mut(self.selected_monitor) {
-                            bar.update_blocks();
+                        for monitor_index in 0..self.bars.len() {
+                            if self.bar_shows_blocks(monitor_index) {
+                                self.bars[monitor_index].update_blocks();
+                            }
                         }
                         if self.bars.iter().any(|bar| bar.needs_redraw()) {
                             self.update_bar()?;
                         }
+                        if self.window_switcher.is_visible() && self.window_switcher.is_live() {
+                            self.window_switcher.draw(&self.connection, &self.font)?;
+                        }
+                        self.supervisor.poll();
+                        let exited = crate::reaper::reap();
+                        self.end_exited_launches(&exited)?;
                         last_bar_update = std::time::Instant::now();
                     }
 
+                    self.expire_launches()?;
+                    self.expire_close_requests()?;
+                    self.expire_osd()?;
+                    if crate::shutdown::requested() && self.quit_deadline.is_none() {
+                        self.begin_quit()?;
+                        if self.quit_deadline.is_none() {
+                            self.supervisor.shutdown();
+                            return Ok(false);
+                        }
+                    }
+                    if self.quit_finished() {
+                        self.supervisor.shutdown();
+                        return Ok(false);
+                    }
                     self.connection.flush()?;
                     std::thread::sleep(std::time::Duration::from_millis(16));
                 }
@@ -554,6 +1119,7 @@ impl WindowManager {
             self.floating_windows.remove(&focused);
             if let Some(client) = self.clients.get_mut(&focused) {
                 client.is_floating = false;
+                client.pre_maximize_geometry = None;
             }
         } else {
             self.floating_windows.insert(focused);
@@ -576,136 +1142,817 @@ impl WindowManager {
         Ok(())
     }
 
-    fn set_master_factor(&mut self, delta: f32) -> WmResult<()> {
-        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
-            let new_mfact = (monitor.master_factor + delta).max(0.05).min(0.95);
-            monitor.master_factor = new_mfact;
-            self.apply_layout()?;
+    /// Takes a tiled window out of the layout so it can be placed freely.
+    fn make_floating(&mut self, window: Window) -> WmResult<()> {
+        let is_floating = self.clients.get(&window).is_some_and(|c| c.is_floating);
+        if is_floating || self.layout.name() == "normie" {
+            return Ok(());
         }
-        Ok(())
-    }
 
-    fn inc_num_master(&mut self, delta: i32) -> WmResult<()> {
-        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
-            let new_nmaster = (monitor.num_master + delta).max(0);
-            monitor.num_master = new_nmaster;
-            self.apply_layout()?;
+        self.floating_windows.insert(window);
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.is_floating = true;
         }
-        Ok(())
+        self.apply_layout()
     }
 
+    fn center_window(&mut self, window: Window) -> WmResult<()> {
+        let Some(client) = self.clients.get(&window) else {
+            return Ok(());
+        };
+        if client.is_fullscreen {
+            return Ok(());
+        }
+        let monitor_index = client.monitor_index;
 
-    fn get_layout_symbol(&self) -> String {
-        let layout_name = self.layout.name();
-        self.config
-            .layout_symbols
-            .iter()
-            .find(|l| l.name == layout_name)
-            .map(|l| l.symbol.clone())
-            .unwrap_or_else(|| self.layout.symbol().to_string())
-    }
+        // Like maximizing or snapping, this floats a tiled window for good;
+        // toggling floating tiles it again.
+        self.make_floating(window)?;
 
-    fn get_keychord_indicator(&self) -> Option<String> {
-        match &self.keychord_state {
-            keyboard::handlers::KeychordState::Idle => None,
-            keyboard::handlers::KeychordState::InProgress {
-                candidates,
-                keys_pressed,
-            } => {
-                if candidates.is_empty() {
-                    return None;
-                }
+        let Some(client) = self.clients.get(&window) else {
+            return Ok(());
+        };
+        let (area_x, area_y, area_width, area_height) = self.monitors[monitor_index].work_area();
+        let outer_width = client.width_with_border() as i32;
+        let outer_height = client.height_with_border() as i32;
 
-                let binding = &self.config.keybindings[candidates[0]];
-                let mut indicator = String::new();
+        // Windows larger than the work area stay pinned to its top left
+        // corner rather than spilling onto the neighbouring monitor.
+        let x = area_x + ((area_width - outer_width) / 2).max(0);
+        let y = area_y + ((area_height - outer_height) / 2).max(0);
 
-                for (i, key_press) in binding.keys.iter().take(*keys_pressed).enumerate() {
-                    if i > 0 {
-                        indicator.push(' ');
-                    }
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.x_position = x as i16;
+            client.y_position = y as i16;
+            client.pre_maximize_geometry = None;
+        }
 
-                    for modifier in &key_press.modifiers {
-                        indicator.push_str(Self::format_modifier(*modifier));
-                        indicator.push('+');
-                    }
+        self.connection.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(x)
+                .y(y)
+                .stack_mode(StackMode::ABOVE),
+        )?;
+        Ok(())
+    }
 
-                    indicator.push_str(&keyboard::keysyms::format_keysym(key_press.keysym));
-                }
+    /// Grows a window to the monitor's work area without making it
+    /// fullscreen, or puts it back where it was.
+    fn toggle_maximize(&mut self, window: Window) -> WmResult<()> {
+        let Some(client) = self.clients.get(&window) else {
+            return Ok(());
+        };
+        if client.is_fullscreen {
+            return Ok(());
+        }
+        let monitor_index = client.monitor_index;
 
-                indicator.push('-');
-                Some(indicator)
+        if let Some(geometry) = client.pre_maximize_geometry {
+            if let Some(client) = self.clients.get_mut(&window) {
+                client.pre_maximize_geometry = None;
+                client.set_geometry(geometry);
             }
+            self.configure_geometry(window, geometry)?;
+            return Ok(());
         }
-    }
 
-    fn format_modifier(modifier: KeyButMask) -> &'static str {
-        match modifier {
-            KeyButMask::MOD1 => "Alt",
-            KeyButMask::MOD4 => "Super",
-            KeyButMask::SHIFT => "Shift",
-            KeyButMask::CONTROL => "Ctrl",
-            _ => "Mod",
-        }
-    }
+        self.make_floating(window)?;
 
+        let (area_x, area_y, area_width, area_height) = self.monitors[monitor_index].work_area();
+        let (gap_horizontal, gap_vertical) = match self.monitors.get(monitor_index) {
+            Some(monitor) if self.gaps_enabled => (
+                monitor.scaled(self.config.gap_outer_horizontal) as i32,
+                monitor.scaled(self.config.gap_outer_vertical) as i32,
+            ),
+            _ => (0, 0),
+        };
+        let border_width = self.client_border_width(window) as i32;
 
-    fn update_bar(&mut self) -> WmResult<()> {
-        let layout_symbol = self.get_layout_symbol();
-        let keychord_indicator = self.get_keychord_indicator();
+        let x = area_x + gap_horizontal;
+        let y = area_y + gap_vertical;
+        let width = (area_width - 2 * gap_horizontal - 2 * border_width).max(1);
+        let height = (area_height - 2 * gap_vertical - 2 * border_width).max(1);
 
-        for (monitor_index, monitor) in self.monitors.iter().enumerate() {
-            if let Some(bar) = self.bars.get_mut(monitor_index) {
-                let mut occupied_tags: TagMask = 0;
-                for client in self.clients.values() {
-                    if client.monitor_index == monitor_index {
-                        occupied_tags |= client.tags;
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.pre_maximize_geometry = Some(client.geometry());
+            client.x_position = x as i16;
+            client.y_position = y as i16;
+            client.width = width as u16;
+            client.height = height as u16;
+        }
+
+        self.connection.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(x)
+                .y(y)
+                .width(width as u32)
+                .height(height as u32)
+                .stack_mode(StackMode::ABOVE),
+        )?;
+        Ok(())
+    }
+
+    /// Floats a window and fills half or a quarter of its monitor's work
+    /// area with it, keeping the outer gaps and an inner gap towards the
+    /// neighbouring cells. `region` is one of the names the
+    /// `oxwm.client.snap` Lua function accepts.
+    fn snap_window(&mut self, window: Window, region: &str) -> WmResult<()> {
+        let Some(client) = self.clients.get(&window) else {
+            return Ok(());
+        };
+        if client.is_fullscreen {
+            return Ok(());
+        }
+        let monitor_index = client.monitor_index;
+
+        // Which of the two columns and rows the window covers.
+        let (columns, rows) = match region {
+            "left-half" => ((0, 1), (0, 2)),
+            "right-half" => ((1, 2), (0, 2)),
+            "top-half" => ((0, 2), (0, 1)),
+            "bottom-half" => ((0, 2), (1, 2)),
+            "top-left" => ((0, 1), (0, 1)),
+            "top-right" => ((1, 2), (0, 1)),
+            "bottom-left" => ((0, 1), (1, 2)),
+            "bottom-right" => ((1, 2), (1, 2)),
+            _ => return Ok(()),
+        };
+
+        self.make_floating(window)?;
+
+        let Some(monitor) = self.monitors.get(monitor_index) else {
+            return Ok(());
+        };
+        let (area_x, area_y, area_width, area_height) = monitor.work_area();
+        let gap = |value: u32| if self.gaps_enabled { monitor.scaled(value) as i32 } else { 0 };
+        let (outer_horizontal, outer_vertical) = (
+            gap(self.config.gap_outer_horizontal),
+            gap(self.config.gap_outer_vertical),
+        );
+        let (inner_horizontal, inner_vertical) = (
+            gap(self.config.gap_inner_horizontal),
+            gap(self.config.gap_inner_vertical),
+        );
+
+        // Splits `length` into two cells with `gap` between them and
+        // returns the offset and length of the cells from `start` to `end`.
+        let span = |length: i32, gap: i32, (start, end): (i32, i32)| {
+            let first = (length - gap) / 2;
+            let second = length - gap - first;
+            match (start, end) {
+                (0, 1) => (0, first),
+                (1, 2) => (first + gap, second),
+                _ => (0, length),
+            }
+        };
+        let (x, width) = span(area_width - 2 * outer_horizontal, inner_horizontal, columns);
+        let (y, height) = span(area_height - 2 * outer_vertical, inner_vertical, rows);
+
+        let border_width = self.client_border_width(window) as i32;
+        let geometry = Geometry {
+            x: (area_x + outer_horizontal + x) as i16,
+            y: (area_y + outer_vertical + y) as i16,
+            width: (width - 2 * border_width).max(1) as u16,
+            height: (height - 2 * border_width).max(1) as u16,
+            border_width: border_width as u16,
+        };
+
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.pre_maximize_geometry = None;
+            client.set_geometry(geometry);
+        }
+        self.configure_geometry(window, geometry)?;
+        self.connection.configure_window(
+            window,
+            &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
+        )?;
+        Ok(())
+    }
+
+    fn set_master_factor(&mut self, delta: f32) -> WmResult<()> {
+        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
+            let new_mfact = (monitor.master_factor + delta).max(0.05).min(0.95);
+            monitor.master_factor = new_mfact;
+            self.apply_layout()?;
+            self.show_osd("Master factor", &format!("{:.0}%", new_mfact * 100.0), new_mfact)?;
+        }
+        Ok(())
+    }
+
+    /// Moves the edge of the master area in `direction`, so the same keys
+    /// resize it sideways in tiling and up and down in bstack. Directions
+    /// along the other axis, and layouts without a master, are ignored.
+    fn resize_master(&mut self, direction: &str, amount: f32) -> WmResult<()> {
+        let delta = match (self.layout.master_axis(), direction) {
+            (Some(MasterAxis::Horizontal), "right") | (Some(MasterAxis::Vertical), "down") => amount,
+            (Some(MasterAxis::Horizontal), "left") | (Some(MasterAxis::Vertical), "up") => -amount,
+            _ => return Ok(()),
+        };
+        self.set_master_factor(delta)
+    }
+
+    fn adjust_gaps(&mut self, inner_delta: i32, outer_delta: i32) -> WmResult<()> {
+        let adjust = |gap: u32, delta: i32| (gap as i32 + delta).max(0) as u32;
+
+        self.config.gap_inner_horizontal = adjust(self.config.gap_inner_horizontal, inner_delta);
+        self.config.gap_inner_vertical = adjust(self.config.gap_inner_vertical, inner_delta);
+        self.config.gap_outer_horizontal = adjust(self.config.gap_outer_horizontal, outer_delta);
+        self.config.gap_outer_vertical = adjust(self.config.gap_outer_vertical, outer_delta);
+
+        self.apply_layout()?;
+        self.restack()?;
+
+        // The OSD bar spans 0 to this many pixels.
+        const GAP_RANGE: f32 = 50.0;
+        let (label, gap) = match (inner_delta, outer_delta) {
+            (_, 0) => ("Inner gaps", self.config.gap_inner_horizontal),
+            (0, _) => ("Outer gaps", self.config.gap_outer_horizontal),
+            _ => ("Gaps", self.config.gap_inner_horizontal),
+        };
+        self.show_osd(label, &format!("{}px", gap), gap as f32 / GAP_RANGE)
+    }
+
+    /// Shows the on screen display on the selected monitor until
+    /// `osd_timeout` passes without another change.
+    fn show_osd(&mut self, label: &str, value: &str, fraction: f32) -> WmResult<()> {
+        if self.config.osd_timeout == 0 {
+            return Ok(());
+        }
+        let Some(monitor) = self.monitors.get(self.selected_monitor) else {
+            return Ok(());
+        };
+        let area = (
+            monitor.screen_x as i16,
+            monitor.screen_y as i16,
+            monitor.screen_width as u16,
+            monitor.screen_height as u16,
+        );
+        self.osd
+            .show(&self.connection, &self.font, label, value, fraction, area)?;
+        self.osd_deadline = Some(
+            std::time::Instant::now() + std::time::Duration::from_millis(self.config.osd_timeout),
+        );
+        Ok(())
+    }
+
+    fn expire_osd(&mut self) -> WmResult<()> {
+        if self
+            .osd_deadline
+            .is_some_and(|deadline| std::time::Instant::now() >= deadline)
+        {
+            self.osd_deadline = None;
+            self.osd.hide(&self.connection)?;
+        }
+        Ok(())
+    }
+
+    fn inc_num_master(&mut self, delta: i32) -> WmResult<()> {
+        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
+            let new_nmaster = (monitor.num_master + delta).max(0);
+            monitor.num_master = new_nmaster;
+            self.apply_layout()?;
+        }
+        Ok(())
+    }
+
+
+    fn get_layout_symbol(&self) -> String {
+        let layout_name = self.layout.name();
+        self.config
+            .layout_symbols
+            .iter()
+            .find(|l| l.name == layout_name)
+            .map(|l| l.symbol.clone())
+            .unwrap_or_else(|| self.layout.symbol().to_string())
+    }
+
+    fn set_layout(&mut self, layout_name: &str) -> WmResult<()> {
+        if self.load_layout(layout_name) {
+            if let Some(key) = self.viewed_tag_key() {
+                self.tag_layouts.insert(key, self.layout.name().to_string());
+            }
+            self.apply_layout()?;
+            self.update_bar()?;
+            self.restack()?;
+        }
+        Ok(())
+    }
+
+    /// Switches to `layout_name` without arranging anything. Returns false
+    /// if there is no such layout.
+    fn load_layout(&mut self, layout_name: &str) -> bool {
+        match layout_from_str(layout_name) {
+            Ok(layout) => {
+                self.layout = layout;
+                if layout_name != "normie" && layout_name != "floating" {
+                    self.floating_windows.clear();
+                }
+                true
+            }
+            Err(e) => {
+                log::error!("Failed to change layout: {}", e);
+                false
+            }
+        }
+    }
+
+    /// The selected monitor and its first viewed tag, which is what the
+    /// layout memory is keyed on.
+    fn viewed_tag_key(&self) -> Option<(usize, usize)> {
+        let monitor = self.monitors.get(self.selected_monitor)?;
+        let viewed = monitor.tagset[monitor.selected_tags_index];
+        Some((self.selected_monitor, viewed.trailing_zeros() as usize))
+    }
+
+    /// Switches back to the layout last used on the now viewed tag, if it
+    /// had one.
+    fn restore_tag_layout(&mut self) {
+        let Some(layout_name) = self
+            .viewed_tag_key()
+            .and_then(|key| self.tag_layouts.get(&key))
+            .cloned()
+        else {
+            return;
+        };
+        if layout_name != self.layout.name() {
+            self.load_layout(&layout_name);
+        }
+    }
+
+    /// Loads the layout remembered for the selected monitor's viewed tag
+    /// after the selected monitor changed, and rearranges if it differs.
+    fn sync_tag_layout(&mut self) -> WmResult<()> {
+        let previous = self.layout.name();
+        self.restore_tag_layout();
+        if self.layout.name() != previous {
+            self.apply_layout()?;
+            self.update_bar()?;
+        }
+        Ok(())
+    }
+
+    /// Switches the tag `window` was placed on to a rule's layout, keeping
+    /// the tag's own layout to return to once no matching window is left.
+    fn apply_rule_layout(&mut self, window: Window, layout_name: &str) {
+        let Some(client) = self.clients.get(&window) else {
+            return;
+        };
+        let key = (client.monitor_index, client.tags.trailing_zeros() as usize);
+
+        let previous = self
+            .tag_layouts
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| self.layout.name().to_string());
+        self.rule_layouts
+            .entry(key)
+            .or_insert_with(|| RuleLayout {
+                previous,
+                windows: Vec::new(),
+            })
+            .windows
+            .push(window);
+        self.tag_layouts.insert(key, layout_name.to_string());
+
+        if self.viewed_tag_key() == Some(key) && layout_name != self.layout.name() {
+            self.load_layout(layout_name);
+        }
+    }
+
+    /// Reverts rule layouts `window` was keeping alive.
+    fn release_rule_layout(&mut self, window: Window) {
+        let mut released = Vec::new();
+        for (key, rule_layout) in &mut self.rule_layouts {
+            rule_layout.windows.retain(|&w| w != window);
+            if rule_layout.windows.is_empty() {
+                released.push(*key);
+            }
+        }
+
+        for key in released {
+            let Some(rule_layout) = self.rule_layouts.remove(&key) else {
+                continue;
+            };
+            if self.viewed_tag_key() == Some(key) && rule_layout.previous != self.layout.name() {
+                self.load_layout(&rule_layout.previous);
+            }
+            self.tag_layouts.insert(key, rule_layout.previous);
+        }
+    }
+
+    /// Opens the layout menu below the layout symbol of a monitor's bar.
+    fn show_layout_menu(&mut self, monitor_index: usize) -> WmResult<()> {
+        let Some(monitor) = self.monitors.get(monitor_index) else {
+            return Ok(());
+        };
+        let (symbol_x, _) = self.bars[monitor_index].layout_symbol_area();
+        let x = monitor.screen_x as i16 + symbol_x;
+        let y = (monitor.screen_y + self.bar_height(monitor_index)) as i16;
+
+        let entries = LayoutType::ALL
+            .iter()
+            .map(|layout_type| {
+                let layout = layout_type.new();
+                let symbol = self
+                    .config
+                    .layout_symbols
+                    .iter()
+                    .find(|l| l.name == layout.name())
+                    .map(|l| l.symbol.clone())
+                    .unwrap_or_else(|| layout.symbol().to_string());
+                (layout.name().to_string(), format!("{}  {}", symbol, layout.name()))
+            })
+            .collect();
+
+        self.layout_menu
+            .show(&self.connection, &self.font, entries, self.layout.name(), x, y)?;
+        Ok(())
+    }
+
+    fn handle_layout_menu_key(&mut self, keycode: Keycode) -> WmResult<()> {
+        use crate::keyboard::keysyms;
+
+        let keysym = self.keyboard_mapping.keycode_to_keysym(keycode);
+        match keysym {
+            keysyms::XK_J | keysyms::XK_DOWN => {
+                self.layout_menu.move_selection(&self.connection, &self.font, 1)?;
+            }
+            keysyms::XK_K | keysyms::XK_UP => {
+                self.layout_menu.move_selection(&self.connection, &self.font, -1)?;
+            }
+            keysyms::XK_RETURN => {
+                let selected = self.layout_menu.selected_layout();
+                self.layout_menu.hide(&self.connection)?;
+                if let Some(layout_name) = selected {
+                    self.set_layout(&layout_name)?;
+                }
+            }
+            keysym if !keysyms::is_modifier(keysym) => {
+                self.layout_menu.hide(&self.connection)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Opens the window switcher on the selected monitor with the windows of
+    /// every monitor, most recently focused first and minimized ones last.
+    /// The window after the focused one is highlighted, or the last one for
+    /// a negative `direction`.
+    fn show_window_switcher(&mut self, direction: i32) -> WmResult<()> {
+        let Some(monitor) = self.monitors.get(self.selected_monitor) else {
+            return Ok(());
+        };
+        let area = (
+            monitor.screen_x as i16,
+            monitor.screen_y as i16,
+            monitor.screen_width as u16,
+            monitor.screen_height as u16,
+        );
+
+        let monitor_order = std::iter::once(self.selected_monitor)
+            .chain((0..self.monitors.len()).filter(|&index| index != self.selected_monitor));
+        let entries: Vec<SwitcherEntry> = monitor_order
+            .flat_map(|index| self.monitors[index].clients.stack())
+            .chain(self.minimized.iter().copied())
+            .filter(|window| !self.stashed.contains(window))
+            .filter_map(|window| {
+                let client = self.clients.get(&window)?;
+                Some(SwitcherEntry {
+                    window,
+                    class: client.class.clone(),
+                    title: client.name.clone(),
+                })
+            })
+            .collect();
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let selected = match entries.len() {
+            1 => 0,
+            count if direction < 0 => count - 1,
+            _ => 1,
+        };
+        self.window_switcher
+            .show(&self.connection, &self.font, entries, selected, area)?;
+
+        // The switch happens when the modifier is released, so if it was let
+        // go before the switcher came up there is no release left to wait for.
+        let pointer = self.connection.query_pointer(self.root)?.reply()?;
+        let held = KeyButMask::CONTROL | KeyButMask::MOD1 | KeyButMask::MOD3 | KeyButMask::MOD4 | KeyButMask::MOD5;
+        if u16::from(pointer.mask & held) == 0 {
+            self.commit_window_switcher()?;
+        }
+        Ok(())
+    }
+
+    fn handle_window_switcher_key(&mut self, event: &KeyPressEvent) -> WmResult<()> {
+        use crate::keyboard::keysyms;
+
+        let keysym = self.keyboard_mapping.keycode_to_keysym(event.detail);
+        match keysym {
+            keysyms::XK_TAB => {
+                let delta = if event.state.contains(KeyButMask::SHIFT) { -1 } else { 1 };
+                self.window_switcher
+                    .move_selection(&self.connection, &self.font, delta)?;
+            }
+            keysyms::XK_L | keysyms::XK_J | keysyms::XK_RIGHT | keysyms::XK_DOWN => {
+                self.window_switcher.move_selection(&self.connection, &self.font, 1)?;
+            }
+            keysyms::XK_H | keysyms::XK_K | keysyms::XK_LEFT | keysyms::XK_UP => {
+                self.window_switcher.move_selection(&self.connection, &self.font, -1)?;
+            }
+            keysyms::XK_RETURN => self.commit_window_switcher()?,
+            keysym if !keysyms::is_modifier(keysym) => {
+                self.window_switcher.hide(&self.connection)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Closes the window switcher and brings up the highlighted window.
+    fn commit_window_switcher(&mut self) -> WmResult<()> {
+        let selected = self.window_switcher.selected_window();
+        self.window_switcher.hide(&self.connection)?;
+        if let Some(window) = selected {
+            self.activate(window)?;
+        }
+        Ok(())
+    }
+
+    fn get_keychord_indicator(&self) -> Option<String> {
+        match &self.keychord_state {
+            keyboard::handlers::KeychordState::Idle => None,
+            keyboard::handlers::KeychordState::InProgress {
+                candidates,
+                keys_pressed,
+            } => {
+                if candidates.is_empty() {
+                    return None;
+                }
+
+                let binding = &self.active_keybindings()[candidates[0]];
+                let mut indicator = String::new();
+
+                for (i, key_press) in binding.keys.iter().take(*keys_pressed).enumerate() {
+                    if i > 0 {
+                        indicator.push(' ');
                     }
+
+                    for modifier in &key_press.modifiers {
+                        indicator.push_str(Self::format_modifier(*modifier));
+                        indicator.push('+');
+                    }
+
+                    indicator.push_str(&keyboard::keysyms::format_keysym(key_press.keysym));
+                }
+
+                indicator.push('-');
+                Some(indicator)
+            }
+        }
+    }
+
+    fn format_modifier(modifier: KeyButMask) -> &'static str {
+        match modifier {
+            KeyButMask::MOD1 => "Alt",
+            KeyButMask::MOD4 => "Super",
+            KeyButMask::SHIFT => "Shift",
+            KeyButMask::CONTROL => "Ctrl",
+            _ => "Mod",
+        }
+    }
+
+
+    /// Blocks follow the selected monitor, unless some are pinned to a
+    /// monitor, in which case every bar shows its own blocks.
+    fn bar_shows_blocks(&self, monitor_index: usize) -> bool {
+        monitor_index == self.selected_monitor
+            || self.config.status_blocks.iter().any(|block| block.monitor.is_some())
+    }
+
+    fn get_mode_indicators(&self) -> Vec<String> {
+        let mut indicators = Vec::new();
+        if self.focus_lock {
+            indicators.push("DND".to_string());
+        }
+        if !self.minimized.is_empty() {
+            indicators.push(format!("[{} minimized]", self.minimized.len()));
+        }
+        if !self.stashed.is_empty() {
+            indicators.push(format!("[{} stashed]", self.stashed.len()));
+        }
+        if let Some(mode) = &self.active_mode {
+            indicators.push(format!("[{}]", mode));
+        }
+        indicators
+    }
+
+    fn occupied_tags(&self, monitor_index: usize) -> TagMask {
+        self.clients
+            .values()
+            .filter(|client| client.monitor_index == monitor_index)
+            .fold(0, |occupied, client| occupied | client.tags)
+    }
+
+    /// Number of clients on each tag of a monitor.
+    fn tag_counts(&self, monitor_index: usize) -> Vec<usize> {
+        let mut counts = vec![0; self.config.tags.len()];
+        for client in self.clients.values().filter(|client| client.monitor_index == monitor_index) {
+            for (tag_index, count) in counts.iter_mut().enumerate() {
+                if client.tags & tag_mask(tag_index) != 0 {
+                    *count += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    /// Icon for each tag of a monitor from the class of the most recently
+    /// focused window on it that has one configured.
+    fn tag_icons(&self, monitor_index: usize) -> Vec<Option<String>> {
+        let mut icons = vec![None; self.config.tags.len()];
+        let Some(monitor) = self.monitors.get(monitor_index) else {
+            return icons;
+        };
+        if self.config.tag_icons.is_empty() {
+            return icons;
+        }
+
+        for window in monitor.clients.stack() {
+            let Some(client) = self.clients.get(&window) else {
+                continue;
+            };
+            let Some(icon) = self
+                .config
+                .tag_icons
+                .get(&client.class.to_lowercase())
+                .or_else(|| self.config.tag_icons.get(&client.instance.to_lowercase()))
+            else {
+                continue;
+            };
+            for (tag_index, tag_icon) in icons.iter_mut().enumerate() {
+                if client.tags & tag_mask(tag_index) != 0 && tag_icon.is_none() {
+                    *tag_icon = Some(icon.clone());
                 }
+            }
+        }
+        icons
+    }
+
+    fn urgent_tags(&self, monitor_index: usize) -> TagMask {
+        self.clients
+            .values()
+            .filter(|client| client.monitor_index == monitor_index && client.is_urgent)
+            .fold(0, |urgent, client| urgent | client.tags)
+    }
+
+    /// Floating windows visible on a monitor, not counting the normie
+    /// layout where every window floats.
+    fn floating_count(&self, monitor_index: usize) -> usize {
+        if self.layout.name() == LayoutType::Normie.as_str() {
+            return 0;
+        }
+        let Some(monitor) = self.monitors.get(monitor_index) else {
+            return 0;
+        };
+        let visible_tags = monitor.tagset[monitor.selected_tags_index];
+        self.floating_windows
+            .iter()
+            .filter_map(|window| self.clients.get(window))
+            .filter(|client| {
+                client.monitor_index == monitor_index
+                    && client.tags & visible_tags != 0
+                    && !self.minimized.contains(&client.window)
+            })
+            .count()
+    }
+
+    fn update_bar(&mut self) -> WmResult<()> {
+        let layout_symbol = self.get_layout_symbol();
+        let keychord_indicator = self.get_keychord_indicator();
+        let mode_indicators = self.get_mode_indicators();
+
+        for (monitor_index, monitor) in self.monitors.iter().enumerate() {
+            let draw_blocks = self.bar_shows_blocks(monitor_index);
+            let occupied_tags = self.occupied_tags(monitor_index);
+            let floating_count = self.floating_count(monitor_index);
+            let urgent_tags = self.urgent_tags(monitor_index);
+            let tag_counts = self.tag_counts(monitor_index);
+            let tag_icons = self.tag_icons(monitor_index);
+            if let Some(bar) = self.bars.get_mut(monitor_index) {
+                let font = self.monitor_fonts.get(monitor_index).unwrap_or(&self.font);
+                bar.set_floating_count(floating_count);
+                bar.set_urgent_tags(urgent_tags);
+                bar.set_tag_counts(tag_counts, font);
+                bar.set_tag_icons(tag_icons, font);
 
-                let draw_blocks = monitor_index == self.selected_monitor;
                 bar.invalidate();
                 bar.draw(
                     &self.connection,
-                    &self.font,
+                    font,
                     self.display,
                     monitor.tagset[monitor.selected_tags_index],
                     occupied_tags,
                     draw_blocks,
                     &layout_symbol,
                     keychord_indicator.as_deref(),
+                    &mode_indicators,
                 )?;
             }
         }
         Ok(())
     }
 
-    fn update_tab_bars(&mut self) -> WmResult<()> {
-        for (monitor_index, monitor) in self.monitors.iter().enumerate() {
-            if let Some(tab_bar) = self.tab_bars.get_mut(monitor_index) {
-                let visible_windows: Vec<(Window, String)> = self
-                    .windows
-                    .iter()
-                    .filter_map(|&window| {
-                        if let Some(client) = self.clients.get(&window) {
-                            if client.monitor_index != monitor_index
-                                || self.floating_windows.contains(&window)
-                                || self.fullscreen_windows.contains(&window)
-                            {
-                                return None;
-                            }
-                            if (client.tags & monitor.tagset[monitor.selected_tags_index]) != 0 {
-                                return Some((window, client.name.clone()));
-                            }
-                        }
-                        None
-                    })
-                    .collect();
+    /// Runs the `on_click` handler of a status block.
+    fn handle_block_click(&mut self, block_index: usize, button: u8, x: i16) -> WmResult<()> {
+        let Some(button) = crate::bar::button_name(button) else {
+            return Ok(());
+        };
+        let Some(block) = self.config.status_blocks.get(block_index) else {
+            return Ok(());
+        };
+        let Some(click) = &block.click else {
+            return Ok(());
+        };
+
+        match click.action(block.name.as_deref().unwrap_or_default(), button, x) {
+            Ok(Some((action, arg))) => self.handle_key_action(action, &arg)?,
+            Ok(None) => {}
+            Err(error) => log::error!("Block click handler failed: {}", error),
+        }
+        Ok(())
+    }
+
+    /// The monitor and tag whose bar cell is under a root window position.
+    fn bar_tag_at(&self, root_x: i32, root_y: i32) -> Option<(usize, usize)> {
+        let monitor_index = self.get_monitor_at_point(root_x, root_y)?;
+        let monitor = &self.monitors[monitor_index];
+        if root_y - monitor.screen_y >= self.bar_height(monitor_index) {
+            return None;
+        }
+        let bar = self.bars.get(monitor_index)?;
+        match bar.handle_click((root_x - monitor.screen_x) as i16)? {
+            BarClick::Tag(tag_index) => Some((monitor_index, tag_index)),
+            BarClick::LayoutSymbol | BarClick::Block(..) => None,
+        }
+    }
 
-                let focused_window = monitor.selected_client;
+    fn tab_bar_windows(&self, monitor_index: usize) -> Vec<crate::tab_bar::Tab> {
+        let monitor_tags = self
+            .monitors
+            .get(monitor_index)
+            .map(|m| m.tagset[m.selected_tags_index])
+            .unwrap_or(0);
 
+        self.windows
+            .iter()
+            .filter_map(|&window| {
+                if let Some(client) = self.clients.get(&window) {
+                    let is_floating = self.floating_windows.contains(&window);
+                    let is_fullscreen = self.fullscreen_windows.contains(&window);
+                    if client.monitor_index != monitor_index
+                        || (is_floating && !self.config.tab_bar_show_floating)
+                        || self.minimized.contains(&window)
+                    {
+                        return None;
+                    }
+                    if (client.tags & monitor_tags) != 0 {
+                        let label = match self.config.tab_bar_label {
+                            crate::TabLabel::Title => client.name.clone(),
+                            crate::TabLabel::Class => client.class.clone(),
+                        };
+                        let label = if is_floating || is_fullscreen {
+                            format!("{} {}", crate::tab_bar::FLOATING_INDICATOR, label)
+                        } else {
+                            label
+                        };
+                        return Some(crate::tab_bar::Tab {
+                            window,
+                            label,
+                            is_urgent: client.is_urgent,
+                        });
+                    }
+                }
+                None
+            })
+            .collect()
+    }
+
+    fn update_tab_bars(&mut self) -> WmResult<()> {
+        for monitor_index in 0..self.monitors.len() {
+            let visible_windows = self.tab_bar_windows(monitor_index);
+            let focused_window = self.monitors[monitor_index].selected_client;
+
+            if let Some(tab_bar) = self.tab_bars.get_mut(monitor_index) {
+                let font = self.monitor_fonts.get(monitor_index).unwrap_or(&self.font);
                 tab_bar.draw(
                     &self.connection,
-                    &self.font,
+                    font,
                     &visible_windows,
                     focused_window,
                 )?;
@@ -714,14 +1961,48 @@ impl WindowManager {
         Ok(())
     }
 
+    /// Ends a hold binding when its key goes up, otherwise runs the
+    /// matching release binding.
+    fn handle_key_release(&mut self, event: KeyReleaseEvent) -> WmResult<()> {
+        if let Some((_, action, arg)) = self
+            .held_key
+            .take_if(|(keycode, _, _)| *keycode == event.detail)
+        {
+            return match action {
+                KeyAction::ShowKeybindOverlay => {
+                    self.keybind_overlay.hide(&self.connection)?;
+                    Ok(())
+                }
+                _ => self.handle_key_action(action, &arg),
+            };
+        }
+
+        if let Some((action, arg)) = keyboard::handlers::handle_key_release(
+            event,
+            self.active_keybindings(),
+            &self.keyboard_mapping,
+        ) {
+            self.handle_key_action(action, &arg)?;
+        }
+        Ok(())
+    }
+
     fn handle_key_action(&mut self, action: KeyAction, arg: &Arg) -> WmResult<()> {
         match action {
-            KeyAction::Spawn => handlers::handle_spawn_action(action, arg, self.selected_monitor)?,
+            KeyAction::Spawn => {
+                let startup_id = self.next_startup_id();
+                let pid = handlers::handle_spawn_action(action, arg, self.selected_monitor, &startup_id)?;
+                if let Some(pid) = pid {
+                    self.begin_launch(startup_id, pid)?;
+                }
+            }
             KeyAction::SpawnTerminal => {
                 use std::process::Command;
+                let startup_id = self.next_startup_id();
                 let terminal = &self.config.terminal;
-                if let Err(error) = Command::new(terminal).spawn() {
-                    eprintln!("Failed to spawn terminal {}: {:?}", terminal, error);
+                match Command::new(terminal).env("DESKTOP_STARTUP_ID", &startup_id).spawn() {
+                    Ok(child) => self.begin_launch(startup_id, crate::reaper::track(child))?,
+                    Err(error) => log::error!("Failed to spawn terminal {}: {:?}", terminal, error),
                 }
             }
             KeyAction::KillClient => {
@@ -733,42 +2014,54 @@ impl WindowManager {
                     self.kill_client(focused)?;
                 }
             }
+            KeyAction::ForceKill => {
+                if let Some(focused) = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .and_then(|m| m.selected_client)
+                {
+                    self.force_kill(focused)?;
+                }
+            }
             KeyAction::ToggleFullScreen => {
-                self.fullscreen()?;
-                self.restack()?;
+                let focused = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .and_then(|m| m.selected_client);
+                if let Some(focused) = focused {
+                    let is_fullscreen = self.fullscreen_windows.contains(&focused);
+                    self.set_window_fullscreen(focused, !is_fullscreen)?;
+                }
             }
+            KeyAction::FullscreenSpan => self.toggle_fullscreen_span()?,
             KeyAction::ChangeLayout => {
                 if let Arg::Str(layout_name) = arg {
-                    match layout_from_str(layout_name) {
-                        Ok(layout) => {
-                            self.layout = layout;
-                            if layout_name != "normie" && layout_name != "floating" {
-                                self.floating_windows.clear();
-                            }
-                            self.apply_layout()?;
-                            self.update_bar()?;
-                            self.restack()?;
-                        }
-                        Err(e) => eprintln!("Failed to change layout: {}", e),
-                    }
+                    self.set_layout(layout_name)?;
                 }
             }
             KeyAction::CycleLayout => {
-                let current_name = self.layout.name();
-                let next_name = next_layout(current_name);
-                match layout_from_str(next_name) {
-                    Ok(layout) => {
-                        self.layout = layout;
-                        if next_name != "normie" && next_name != "floating" {
-                            self.floating_windows.clear();
-                        }
-                        self.apply_layout()?;
-                        self.update_bar()?;
-                        self.restack()?;
+                let next_name = next_layout(self.layout.name(), &self.config.layout_cycle_skip);
+                self.set_layout(next_name)?;
+            }
+            KeyAction::RenameTag => {
+                let current_tag = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .map(|m| m.tagset[m.selected_tags_index].trailing_zeros() as usize);
+                if let Some(tag_index) = current_tag {
+                    match arg {
+                        Arg::Str(name) => self.rename_tag(tag_index, name)?,
+                        _ => self.prompt_rename_tag(tag_index)?,
                     }
-                    Err(e) => eprintln!("Failed to cycle layout: {}", e),
                 }
             }
+            KeyAction::AddTag => {
+                let name = match arg {
+                    Arg::Str(name) => Some(name.as_str()),
+                    _ => None,
+                };
+                self.add_tag(name)?;
+            }
             KeyAction::ToggleFloating => {
                 self.toggle_floating()?;
                 self.restack()?;
@@ -784,7 +2077,17 @@ impl WindowManager {
                     self.move_stack(*direction)?;
                 }
             }
-            KeyAction::Quit | KeyAction::Restart => {
+            KeyAction::Zoom => self.zoom()?,
+            KeyAction::WindowSwitcher => {
+                let direction = if let Arg::Int(direction) = arg { *direction } else { 1 };
+                if self.window_switcher.is_visible() {
+                    self.window_switcher
+                        .move_selection(&self.connection, &self.font, direction)?;
+                } else {
+                    self.show_window_switcher(direction)?;
+                }
+            }
+            KeyAction::Quit | KeyAction::ForceQuit | KeyAction::Restart => {
                 // Handled in handle_event
             }
             KeyAction::Recompile => {
@@ -792,8 +2095,11 @@ impl WindowManager {
                     .arg("--recompile")
                     .spawn()
                 {
-                    Ok(_) => eprintln!("Recompiling in background"),
-                    Err(e) => eprintln!("Failed to spawn recompile: {}", e),
+                    Ok(child) => {
+                        crate::reaper::track(child);
+                        log::info!("Recompiling in background");
+                    }
+                    Err(e) => log::error!("Failed to spawn recompile: {}", e),
                 }
             }
             KeyAction::ViewTag => {
@@ -816,11 +2122,107 @@ impl WindowManager {
                     self.toggletag(*tag_index as usize)?;
                 }
             }
+            KeyAction::ToggleFocusLock => {
+                self.focus_lock = !self.focus_lock;
+                self.update_bar()?;
+            }
+            KeyAction::ToggleBar => self.toggle_bar()?,
+            KeyAction::CenterWindow => {
+                let focused = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .and_then(|m| m.selected_client);
+                if let Some(window) = focused {
+                    self.center_window(window)?;
+                }
+            }
+            KeyAction::ToggleMaximize => {
+                let focused = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .and_then(|m| m.selected_client);
+                if let Some(window) = focused {
+                    self.toggle_maximize(window)?;
+                }
+            }
+            KeyAction::MediaPlayPause => crate::media::control(MediaCommand::PlayPause),
+            KeyAction::MediaNext => crate::media::control(MediaCommand::Next),
+            KeyAction::MediaPrevious => crate::media::control(MediaCommand::Previous),
+            KeyAction::SnapWindow => {
+                let focused = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .and_then(|m| m.selected_client);
+                if let (Some(window), Arg::Str(region)) = (focused, arg) {
+                    self.snap_window(window, region)?;
+                }
+            }
+            KeyAction::MinimizeClient => {
+                let focused = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .and_then(|m| m.selected_client);
+                if let Some(window) = focused {
+                    self.minimize(window)?;
+                }
+            }
+            KeyAction::RestoreLast => {
+                if let Some(&window) = self.minimized.last() {
+                    self.restore(window)?;
+                }
+            }
+            KeyAction::StashWindow => {
+                let focused = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .and_then(|m| m.selected_client);
+                if let Some(window) = focused {
+                    self.stash(window)?;
+                }
+            }
+            KeyAction::UnstashWindow => {
+                if let Some(&window) = self.stashed.last() {
+                    self.unstash(window)?;
+                }
+            }
+            KeyAction::EnterMode => {
+                if let Arg::Str(name) = arg {
+                    self.enter_mode(name)?;
+                }
+            }
+            KeyAction::ExitMode => {
+                self.exit_mode()?;
+            }
+            KeyAction::ReloadTheme => {
+                self.reload_theme()?;
+            }
+            KeyAction::Screenshot => {
+                let mode = match arg {
+                    Arg::Str(mode) => crate::screenshot::ScreenshotMode::parse(mode),
+                    _ => None,
+                };
+                self.take_screenshot(mode.unwrap_or(crate::screenshot::ScreenshotMode::Full))?;
+            }
             KeyAction::ToggleGaps => {
                 self.gaps_enabled = !self.gaps_enabled;
                 self.apply_layout()?;
                 self.restack()?;
             }
+            KeyAction::AdjustGaps => {
+                if let Arg::Int(delta) = arg {
+                    self.adjust_gaps(*delta, *delta)?;
+                }
+            }
+            KeyAction::AdjustInnerGaps => {
+                if let Arg::Int(delta) = arg {
+                    self.adjust_gaps(*delta, 0)?;
+                }
+            }
+            KeyAction::AdjustOuterGaps => {
+                if let Arg::Int(delta) = arg {
+                    self.adjust_gaps(0, *delta)?;
+                }
+            }
             KeyAction::FocusMonitor => {
                 if let Arg::Int(direction) = arg {
                     self.focus_monitor(*direction)?;
@@ -828,7 +2230,12 @@ impl WindowManager {
             }
             KeyAction::TagMonitor => {
                 if let Arg::Int(direction) = arg {
-                    self.send_window_to_adjacent_monitor(*direction)?;
+                    self.send_window_to_adjacent_monitor(*direction, false)?;
+                }
+            }
+            KeyAction::TagMonitorFollow => {
+                if let Arg::Int(direction) = arg {
+                    self.send_window_to_adjacent_monitor(*direction, true)?;
                 }
             }
             KeyAction::ShowKeybindOverlay => {
@@ -848,11 +2255,35 @@ impl WindowManager {
                     self.set_master_factor(*delta as f32 / 100.0)?;
                 }
             }
+            KeyAction::ResizeMaster => {
+                if let Arg::Array(arg) = arg
+                    && let [direction, amount] = arg.as_slice()
+                {
+                    let amount = amount.parse::<i32>().unwrap_or(5);
+                    self.resize_master(direction, amount as f32 / 100.0)?;
+                }
+            }
             KeyAction::IncNumMaster => {
                 if let Arg::Int(delta) = arg {
                     self.inc_num_master(*delta)?;
                 }
             }
+            KeyAction::FocusTabNext => self.focus_tab(1)?,
+            KeyAction::FocusTabPrev => self.focus_tab(-1)?,
+            KeyAction::SetOpacity => {
+                let focused = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .and_then(|m| m.selected_client);
+                if let (Arg::Int(delta), Some(focused)) = (arg, focused) {
+                    let opacity = self
+                        .clients
+                        .get(&focused)
+                        .map(|c| c.opacity)
+                        .unwrap_or(1.0);
+                    self.set_window_opacity(focused, opacity + *delta as f32 / 100.0)?;
+                }
+            }
             KeyAction::None => {}
         }
         Ok(())
@@ -860,6 +2291,9 @@ impl WindowManager {
 
 
     fn is_window_visible(&self, window: Window) -> bool {
+        if self.minimized.contains(&window) {
+            return false;
+        }
         if let Some(client) = self.clients.get(&window) {
             let monitor = self.monitors.get(client.monitor_index);
             let selected_tags = monitor.map(|m| m.tagset[m.selected_tags_index]).unwrap_or(0);
@@ -870,41 +2304,25 @@ impl WindowManager {
     }
 
     fn visible_windows(&self) -> Vec<Window> {
-        let mut result = Vec::new();
-        for monitor in &self.monitors {
-            let mut current = monitor.clients_head;
-            while let Some(window) = current {
-                if let Some(client) = self.clients.get(&window) {
-                    let visible_tags = client.tags & monitor.tagset[monitor.selected_tags_index];
-                    if visible_tags != 0 {
-                        result.push(window);
-                    }
-                    current = client.next;
-                } else {
-                    break;
-                }
-            }
-        }
-        result
+        (0..self.monitors.len())
+            .flat_map(|monitor_index| self.visible_windows_on_monitor(monitor_index))
+            .collect()
     }
 
     fn visible_windows_on_monitor(&self, monitor_index: usize) -> Vec<Window> {
-        let mut result = Vec::new();
-        if let Some(monitor) = self.monitors.get(monitor_index) {
-            let mut current = monitor.clients_head;
-            while let Some(window) = current {
-                if let Some(client) = self.clients.get(&window) {
-                    let visible_tags = client.tags & monitor.tagset[monitor.selected_tags_index];
-                    if visible_tags != 0 {
-                        result.push(window);
-                    }
-                    current = client.next;
-                } else {
-                    break;
-                }
-            }
-        }
-        result
+        let Some(monitor) = self.monitors.get(monitor_index) else {
+            return Vec::new();
+        };
+
+        monitor
+            .clients
+            .iter()
+            .filter(|window| {
+                self.clients
+                    .get(window)
+                    .is_some_and(|client| client.tags & monitor.tagset[monitor.selected_tags_index] != 0)
+            })
+            .collect()
     }
 
     fn get_monitor_at_point(&self, x: i32, y: i32) -> Option<usize> {
@@ -918,8 +2336,9 @@ impl WindowManager {
         let mut max_area = 0;
 
         for (idx, monitor) in self.monitors.iter().enumerate() {
-            let intersect_width = 0.max((x + w).min(monitor.window_area_x + monitor.window_area_width) - x.max(monitor.window_area_x));
-            let intersect_height = 0.max((y + h).min(monitor.window_area_y + monitor.window_area_height) - y.max(monitor.window_area_y));
+            let (area_x, area_y, area_width, area_height) = monitor.work_area();
+            let intersect_width = 0.max((x + w).min(area_x + area_width) - x.max(area_x));
+            let intersect_height = 0.max((y + h).min(area_y + area_height) - y.max(area_y));
             let area = intersect_width * intersect_height;
 
             if area > max_area {
@@ -946,9 +2365,10 @@ impl WindowManager {
         self.detach(window);
         self.detach_stack(window);
 
+        let keep_tags = self.config.monitor_keep_tags;
         if let Some(client) = self.clients.get_mut(&window) {
             client.monitor_index = target_monitor_index;
-            if let Some(target_monitor) = self.monitors.get(target_monitor_index) {
+            if let Some(target_monitor) = self.monitors.get(target_monitor_index).filter(|_| !keep_tags) {
                 client.tags = target_monitor.tagset[target_monitor.selected_tags_index];
             }
         }
@@ -962,139 +2382,368 @@ impl WindowManager {
         Ok(())
     }
 
-    fn get_adjacent_monitor(&self, direction: i32) -> Option<usize> {
-        if self.monitors.len() <= 1 {
-            return None;
+    fn is_visible(&self, window: Window) -> bool {
+        let Some(client) = self.clients.get(&window) else {
+            return false;
+        };
+
+        let Some(monitor) = self.monitors.get(client.monitor_index) else {
+            return false;
+        };
+
+        (client.tags & monitor.tagset[monitor.selected_tags_index]) != 0
+    }
+
+    /// Whether both windows belong to the same application, going by their
+    /// WM_HINTS window group. A window without a group leads its own.
+    fn same_window_group(&self, a: Window, b: Window) -> bool {
+        let group = |window: Window| {
+            self.clients
+                .get(&window)
+                .and_then(|client| client.window_group)
+                .unwrap_or(window)
+        };
+        self.clients.contains_key(&a) && group(a) == group(b)
+    }
+
+    fn showhide(&mut self, monitor_index: usize) -> WmResult<()> {
+        let Some(monitor) = self.monitors.get(monitor_index) else {
+            return Ok(());
+        };
+
+        let selected_tags = monitor.tagset[monitor.selected_tags_index];
+        let (visible, hidden): (Vec<Window>, Vec<Window>) = monitor
+            .clients
+            .stack()
+            .filter(|window| self.clients.contains_key(window))
+            .partition(|window| self.clients[window].tags & selected_tags != 0);
+
+        // Show top-down and hide bottom-up, like dwm.
+        for window in visible {
+            self.show_client(window)?;
+        }
+        for window in hidden.into_iter().rev() {
+            self.hide_client(window)?;
         }
 
-        if direction > 0 {
-            if self.selected_monitor + 1 < self.monitors.len() {
-                Some(self.selected_monitor + 1)
-            } else {
-                Some(0)
-            }
-        } else {
-            if self.selected_monitor == 0 {
-                Some(self.monitors.len() - 1)
-            } else {
-                Some(self.selected_monitor - 1)
+        Ok(())
+    }
+
+    fn show_client(&mut self, window: Window) -> WmResult<()> {
+        let Some(client) = self.clients.get(&window).cloned() else {
+            return Ok(());
+        };
+
+        self.connection.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(client.x_position as i32)
+                .y(client.y_position as i32),
+        )?;
+
+        let is_floating = client.is_floating;
+        let is_fullscreen = client.is_fullscreen;
+        let has_no_layout = self.layout.name() == LayoutType::Normie.as_str();
+
+        if (has_no_layout || is_floating) && !is_fullscreen {
+            let (x, y, w, h, changed) = self.apply_size_hints(
+                window,
+                client.x_position as i32,
+                client.y_position as i32,
+                client.width as i32,
+                client.height as i32,
+            );
+            if changed {
+                if let Some(c) = self.clients.get_mut(&window) {
+                    c.x_position = x as i16;
+                    c.y_position = y as i16;
+                    c.width = w as u16;
+                    c.height = h as u16;
+                }
+                self.connection.configure_window(
+                    window,
+                    &ConfigureWindowAux::new()
+                        .x(x)
+                        .y(y)
+                        .width(w as u32)
+                        .height(h as u32)
+                        .border_width(client.border_width as u32),
+                )?;
+                self.send_configure_notify(window)?;
             }
         }
+
+        Ok(())
     }
 
-    fn is_visible(&self, window: Window) -> bool {
+    fn hide_client(&mut self, window: Window) -> WmResult<()> {
         let Some(client) = self.clients.get(&window) else {
-            return false;
+            return Ok(());
         };
 
-        let Some(monitor) = self.monitors.get(client.monitor_index) else {
-            return false;
-        };
+        let width = client.width_with_border() as i32;
+        self.connection.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(width * -2)
+                .y(client.y_position as i32),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn view_tag(&mut self, tag_index: usize) -> WmResult<()> {
+        if tag_index >= self.config.tags.len() {
+            return Ok(());
+        }
+
+        if !self.selection().view(tag_mask(tag_index)) {
+            return Ok(());
+        }
+
+        self.save_selected_tags()?;
+        self.restore_tag_layout();
+        self.focus(None)?;
+        self.apply_layout()?;  
+        self.update_bar()?;
+
+        Ok(())
+    }
+
+    /// Renames a tag for the rest of the session. Empty names are ignored.
+    fn rename_tag(&mut self, tag_index: usize, name: &str) -> WmResult<()> {
+        let name = name.trim();
+        if name.is_empty() || tag_index >= self.config.tags.len() {
+            return Ok(());
+        }
+
+        self.config.tags[tag_index] = name.to_string();
+        self.refresh_tags()
+    }
+
+    /// Pushes the current tag list to the bars and to the root window.
+    fn refresh_tags(&mut self) -> WmResult<()> {
+        for (monitor_index, bar) in self.bars.iter_mut().enumerate() {
+            let font = self.monitor_fonts.get(monitor_index).unwrap_or(&self.font);
+            bar.set_tags(&self.config.tags, font, self.config.bar_tag_padding);
+        }
+        self.publish_tags()?;
+        self.update_bar()?;
+        Ok(())
+    }
+
+    /// Publishes the tag names as EWMH desktops. They also carry runtime
+    /// tags across restarts.
+    fn publish_tags(&self) -> WmResult<()> {
+        let count = self.config.tags.len() as u32;
+        self.connection.change_property(
+            PropMode::REPLACE,
+            self.root,
+            self.atoms.net_number_of_desktops,
+            AtomEnum::CARDINAL,
+            32,
+            1,
+            &count.to_ne_bytes(),
+        )?;
+
+        let names: Vec<u8> = self
+            .config
+            .tags
+            .iter()
+            .flat_map(|tag| tag.bytes().chain(std::iter::once(0)))
+            .collect();
+        self.connection.change_property(
+            PropMode::REPLACE,
+            self.root,
+            self.atoms.net_desktop_names,
+            self.atoms.utf8_string,
+            8,
+            names.len() as u32,
+            &names,
+        )?;
+
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    /// Re-adds the runtime tags published by the instance we were restarted
+    /// from, so windows on them keep their tags.
+    fn restore_dynamic_tags(&mut self) -> WmResult<()> {
+        let names = self
+            .connection
+            .get_property(
+                false,
+                self.root,
+                self.atoms.net_desktop_names,
+                self.atoms.utf8_string,
+                0,
+                u32::MAX / 4,
+            )?
+            .reply()
+            .map(|prop| prop.value)
+            .unwrap_or_default();
+
+        let saved_tags: Vec<String> = names
+            .split(|&byte| byte == 0)
+            .filter(|name| !name.is_empty())
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .collect();
+
+        let dynamic_tags = saved_tags
+            .into_iter()
+            .skip(self.static_tag_count)
+            .take(MAX_TAGS - self.static_tag_count);
+        self.config.tags.extend(dynamic_tags);
+
+        self.refresh_tags()
+    }
+
+    /// Appends a runtime tag and views it.
+    fn add_tag(&mut self, name: Option<&str>) -> WmResult<()> {
+        if self.config.tags.len() >= MAX_TAGS {
+            log::warn!("Cannot add tag: at most {} tags are supported", MAX_TAGS);
+            return Ok(());
+        }
+
+        let tag_index = self.config.tags.len();
+        let name = name
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| (tag_index + 1).to_string());
+        self.config.tags.push(name);
+
+        self.refresh_tags()?;
+        self.view_tag(tag_index)
+    }
+
+    /// Drops runtime tags that hold no windows and are not being viewed,
+    /// renumbering the tags after them.
+    fn prune_dynamic_tags(&mut self) -> WmResult<()> {
+        let mut removed = false;
+
+        for tag_index in (self.static_tag_count..self.config.tags.len()).rev() {
+            let mask = tag_mask(tag_index);
+            let occupied = self.clients.values().any(|client| client.tags & mask != 0);
+            let viewed = self
+                .monitors
+                .iter()
+                .any(|monitor| monitor.tagset[monitor.selected_tags_index] & mask != 0);
+            if occupied || viewed {
+                continue;
+            }
+
+            self.config.tags.remove(tag_index);
+            for monitor in &mut self.monitors {
+                for tagset in &mut monitor.tagset {
+                    *tagset = match remove_tag_bit(*tagset, tag_index) {
+                        0 => tag_mask(0),
+                        tags => tags,
+                    };
+                }
+            }
+            let shifted: Vec<(Window, TagMask)> = self
+                .clients
+                .iter_mut()
+                .filter(|(_, client)| client.tags >= tag_mask(tag_index))
+                .map(|(&window, client)| {
+                    client.tags = remove_tag_bit(client.tags, tag_index);
+                    (window, client.tags)
+                })
+                .collect();
+            for (window, tags) in shifted {
+                self.save_client_tag(window, tags)?;
+            }
+            for launch in &mut self.pending_launches {
+                launch.tags = match remove_tag_bit(launch.tags, tag_index) {
+                    0 => tag_mask(0),
+                    tags => tags,
+                };
+            }
+            removed = true;
+        }
 
-        (client.tags & monitor.tagset[monitor.selected_tags_index]) != 0
+        if removed {
+            self.save_selected_tags()?;
+            self.refresh_tags()?;
+        }
+        Ok(())
     }
 
-    fn showhide(&mut self, window: Option<Window>) -> WmResult<()> {
-        let Some(window) = window else {
+    fn prompt_rename_tag(&mut self, tag_index: usize) -> WmResult<()> {
+        let Some(monitor) = self.monitors.get(self.selected_monitor) else {
             return Ok(());
         };
-
-        let Some(client) = self.clients.get(&window).cloned() else {
+        let Some(current_name) = self.config.tags.get(tag_index) else {
             return Ok(());
         };
 
-        let monitor = match self.monitors.get(client.monitor_index) {
-            Some(m) => m,
-            None => return Ok(()),
-        };
+        let x = monitor.screen_x as i16;
+        let y = (monitor.screen_y + self.bar_height(self.selected_monitor)) as i16;
+        let width = monitor.screen_width as u16;
 
-        let is_visible = (client.tags & monitor.tagset[monitor.selected_tags_index]) != 0;
+        self.renaming_tag = Some(tag_index);
+        self.text_input
+            .show(&self.connection, &self.font, "Rename tag:", current_name, (x, y, width))?;
+        Ok(())
+    }
 
-        if is_visible {
-            self.connection.configure_window(
-                window,
-                &ConfigureWindowAux::new()
-                    .x(client.x_position as i32)
-                    .y(client.y_position as i32),
-            )?;
+    fn handle_text_input_key(&mut self, keycode: Keycode, state: KeyButMask) -> WmResult<()> {
+        use crate::keyboard::keysyms;
 
-            let is_floating = client.is_floating;
-            let is_fullscreen = client.is_fullscreen;
-            let has_no_layout = self.layout.name() == LayoutType::Normie.as_str();
+        let keysym = if state.contains(KeyButMask::SHIFT) {
+            self.keyboard_mapping.keycode_to_shifted_keysym(keycode)
+        } else {
+            self.keyboard_mapping.keycode_to_keysym(keycode)
+        };
 
-            if (has_no_layout || is_floating) && !is_fullscreen {
-                let (x, y, w, h, changed) = self.apply_size_hints(
-                    window,
-                    client.x_position as i32,
-                    client.y_position as i32,
-                    client.width as i32,
-                    client.height as i32,
-                );
-                if changed {
-                    if let Some(c) = self.clients.get_mut(&window) {
-                        c.old_x_position = c.x_position;
-                        c.old_y_position = c.y_position;
-                        c.old_width = c.width;
-                        c.old_height = c.height;
-                        c.x_position = x as i16;
-                        c.y_position = y as i16;
-                        c.width = w as u16;
-                        c.height = h as u16;
-                    }
-                    self.connection.configure_window(
-                        window,
-                        &ConfigureWindowAux::new()
-                            .x(x)
-                            .y(y)
-                            .width(w as u32)
-                            .height(h as u32)
-                            .border_width(self.config.border_width),
-                    )?;
-                    self.send_configure_notify(window)?;
-                    self.connection.flush()?;
+        match keysym {
+            keysyms::XK_RETURN => {
+                let name = self.text_input.text().to_string();
+                self.text_input.hide(&self.connection)?;
+                if let Some(tag_index) = self.renaming_tag.take() {
+                    self.rename_tag(tag_index, &name)?;
+                }
+            }
+            keysyms::XK_ESCAPE => {
+                self.text_input.hide(&self.connection)?;
+                self.renaming_tag = None;
+            }
+            keysyms::XK_BACKSPACE => {
+                self.text_input.pop(&self.connection, &self.font)?;
+            }
+            _ => {
+                if let Some(character) = keysyms::keysym_to_char(keysym) {
+                    self.text_input.push(&self.connection, &self.font, character)?;
                 }
             }
-
-            self.showhide(client.stack_next)?;
-        } else {
-            self.showhide(client.stack_next)?;
-
-            let width = client.width_with_border() as i32;
-            self.connection.configure_window(
-                window,
-                &ConfigureWindowAux::new()
-                    .x(width * -2)
-                    .y(client.y_position as i32),
-            )?;
         }
-
         Ok(())
     }
 
-    pub fn view_tag(&mut self, tag_index: usize) -> WmResult<()> {
-        if tag_index >= self.config.tags.len() {
+    /// Views the next (`direction > 0`) or previous tag on the selected
+    /// monitor, wrapping around and optionally skipping empty tags.
+    fn shift_view(&mut self, direction: i32) -> WmResult<()> {
+        let tag_count = self.config.tags.len() as i32;
+        let Some(monitor) = self.monitors.get(self.selected_monitor) else {
             return Ok(());
-        }
-
-        let monitor = match self.monitors.get_mut(self.selected_monitor) {
-            Some(m) => m,
-            None => return Ok(()),
         };
-
-        let new_tagset = tag_mask(tag_index);
-
-        if new_tagset == monitor.tagset[monitor.selected_tags_index] {
+        if tag_count == 0 {
             return Ok(());
         }
 
-        monitor.selected_tags_index ^= 1;
-        monitor.tagset[monitor.selected_tags_index] = new_tagset;
-
-        self.save_selected_tags()?;
-        self.focus(None)?;
-        self.apply_layout()?;  
-        self.update_bar()?;
+        let current_tags = monitor.tagset[monitor.selected_tags_index];
+        let current = current_tags.trailing_zeros() as i32;
+        let occupied = self.occupied_tags(self.selected_monitor);
+        let step = direction.signum();
 
+        let mut tag_index = current;
+        for _ in 1..tag_count {
+            tag_index = (tag_index + step).rem_euclid(tag_count);
+            if !self.config.bar_scroll_skip_empty || occupied & tag_mask(tag_index as usize) != 0 {
+                return self.view_tag(tag_index as usize);
+            }
+        }
         Ok(())
     }
 
@@ -1118,6 +2767,7 @@ impl WindowManager {
         monitor.tagset[monitor.selected_tags_index] = new_tagset;
 
         self.save_selected_tags()?;
+        self.restore_tag_layout();
         self.focus(None)?;
         self.apply_layout()?;
         self.update_bar()?;
@@ -1164,17 +2814,13 @@ impl WindowManager {
             None => return Ok(()),
         };
 
-        let mask = tag_mask(tag_index);
-
-        if let Some(client) = self.clients.get_mut(&focused) {
-            client.tags = mask;
-        }
-
-        if let Err(error) = self.save_client_tag(focused, mask) {
-            eprintln!("Failed to save client tag: {:?}", error);
-        }
+        self.set_client_tags(focused, tag_mask(tag_index))
+    }
 
-        self.focus(None)?;
+    /// Puts `window` on exactly the tags in `mask` and rearranges.
+    fn set_client_tags(&mut self, window: Window, mask: TagMask) -> WmResult<()> {
+        let change = self.selection().set_client_tags(window, mask)?;
+        self.finish_focus(change)?;
         self.apply_layout()?;
         self.update_bar()?;
 
@@ -1208,7 +2854,7 @@ impl WindowManager {
         }
 
         if let Err(error) = self.save_client_tag(focused, new_tags) {
-            eprintln!("Failed to save client tag: {:?}", error);
+            log::error!("Failed to save client tag: {:?}", error);
         }
 
         self.focus(None)?;
@@ -1245,7 +2891,7 @@ impl WindowManager {
             visible[0]
         };
 
-        let is_tabbed = self.layout.name() == "tabbed";
+        let is_tabbed = self.layout.uses_tab_bar();
         if is_tabbed {
             self.connection.configure_window(
                 next_window,
@@ -1262,23 +2908,316 @@ impl WindowManager {
         Ok(())
     }
 
+    /// The bindings of the active mode, or the regular ones outside a mode.
+    fn active_keybindings(&self) -> &[handlers::KeyBinding] {
+        self.active_mode
+            .as_ref()
+            .and_then(|mode| self.config.modes.get(mode))
+            .unwrap_or(&self.config.keybindings)
+    }
+
     fn grab_keys(&mut self) -> WmResult<()> {
-        self.keyboard_mapping = Some(keyboard::grab_keys(
+        keyboard::grab_keys(
             &self.connection,
             self.root,
-            &self.config.keybindings,
+            self.active_keybindings(),
             self.current_key,
-        )?);
+            &self.keyboard_mapping,
+        )?;
+
+        if self.active_mode.is_some() && self.current_key == 0 {
+            for &keycode in self.keyboard_mapping.keysym_to_keycodes(keyboard::keysyms::XK_ESCAPE) {
+                self.connection
+                    .grab_key_combination(self.root, u16::from(ModMask::ANY), keycode)?;
+            }
+            self.connection.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Re-reads the theme file and repaints borders, bars and tab bars
+    /// without reloading the rest of the config.
+    fn reload_theme(&mut self) -> WmResult<()> {
+        if let Err(error) = crate::config::apply_theme(&mut self.config) {
+            log::error!("{}", error);
+            return Ok(());
+        }
+
+        for bar in &mut self.bars {
+            bar.update_colors(&self.config);
+        }
+        for (tab_bar, monitor) in self.tab_bars.iter_mut().zip(&self.monitors) {
+            tab_bar.set_style(crate::tab_bar::TabStyle::new(&self.config, monitor));
+        }
+
+        let focused = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|monitor| monitor.selected_client);
+        let windows: Vec<Window> = self.clients.keys().copied().collect();
+        for window in windows {
+            self.draw_border(window, Some(window) == focused)?;
+        }
+
+        self.update_bar()?;
+        self.update_tab_bars()?;
+        Ok(())
+    }
+
+    fn take_screenshot(&mut self, mode: crate::screenshot::ScreenshotMode) -> WmResult<()> {
+        use crate::screenshot::{self, ScreenshotMode};
+
+        let area = match mode {
+            ScreenshotMode::Full => Some((
+                0,
+                0,
+                self.screen.width_in_pixels,
+                self.screen.height_in_pixels,
+            )),
+            ScreenshotMode::Window => self
+                .monitors
+                .get(self.selected_monitor)
+                .and_then(|monitor| monitor.selected_client)
+                .and_then(|window| self.clients.get(&window))
+                .map(|client| {
+                    let border = 2 * client.border_width;
+                    (
+                        client.x_position,
+                        client.y_position,
+                        client.width + border,
+                        client.height + border,
+                    )
+                }),
+            ScreenshotMode::Selection => self.select_region()?,
+        };
+        let Some(area) = area else {
+            return Ok(());
+        };
+
+        let Some(png) = screenshot::capture(&self.connection, self.root, area)? else {
+            log::warn!("Nothing to capture: the area is off screen, or the screen is not 24 or 32 bit TrueColor");
+            return Ok(());
+        };
+
+        if self.config.screenshot_save {
+            let dir = self
+                .config
+                .screenshot_dir
+                .as_deref()
+                .map(handlers::expand_home)
+                .or_else(dirs::picture_dir)
+                .or_else(dirs::home_dir)
+                .unwrap_or_default();
+            match screenshot::save(&png, &dir) {
+                Ok(path) => log::info!("Saved screenshot to {}", path.display()),
+                Err(error) => log::error!("Failed to save screenshot: {}", error),
+            }
+        }
+        let copied = match self.config.screenshot_clipboard {
+            true => screenshot::copy_to_clipboard(png),
+            false => Ok(()),
+        };
+        if let Err(error) = copied {
+            log::error!("Failed to copy screenshot to clipboard (is xclip installed?): {}", error);
+        }
+        Ok(())
+    }
+
+    /// Lets the user drag out a rectangle with the pointer. Returns `None`
+    /// if a key is pressed or the rectangle is empty.
+    fn select_region(&mut self) -> WmResult<Option<(i16, i16, u16, u16)>> {
+        let grab = self
+            .connection
+            .grab_pointer(
+                false,
+                self.root,
+                EventMask::POINTER_MOTION | EventMask::BUTTON_RELEASE | EventMask::BUTTON_PRESS,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+                x11rb::NONE,
+                self.crosshair_cursor,
+                x11rb::CURRENT_TIME,
+            )?
+            .reply()?;
+        if grab.status != GrabStatus::SUCCESS {
+            return Ok(None);
+        }
+        self.connection
+            .grab_keyboard(false, self.root, x11rb::CURRENT_TIME, GrabMode::ASYNC, GrabMode::ASYNC)?
+            .reply()?;
+
+        // Drawn with XOR so drawing the same rectangle again erases it.
+        let graphics_context = self.connection.generate_id()?;
+        self.connection.create_gc(
+            graphics_context,
+            self.root,
+            &CreateGCAux::new()
+                .function(GX::XOR)
+                .foreground(0xffffff)
+                .subwindow_mode(SubwindowMode::INCLUDE_INFERIORS),
+        )?;
+
+        let rectangle = |(x1, y1): (i16, i16), (x2, y2): (i16, i16)| Rectangle {
+            x: x1.min(x2),
+            y: y1.min(y2),
+            width: x1.abs_diff(x2),
+            height: y1.abs_diff(y2),
+        };
+
+        let mut start: Option<(i16, i16)> = None;
+        let mut drawn: Option<Rectangle> = None;
+        let mut selection = None;
+
+        loop {
+            let event = self.connection.wait_for_event()?;
+            match event {
+                Event::ButtonPress(e) => start = Some((e.root_x, e.root_y)),
+                Event::MotionNotify(e) => {
+                    let Some(start) = start else {
+                        continue;
+                    };
+                    let next = rectangle(start, (e.root_x, e.root_y));
+                    let erase = drawn.take();
+                    let rectangles: Vec<Rectangle> = erase.into_iter().chain([next]).collect();
+                    self.connection
+                        .poly_rectangle(self.root, graphics_context, &rectangles)?;
+                    self.connection.flush()?;
+                    drawn = Some(next);
+                }
+                Event::ButtonRelease(e) => {
+                    if let Some(start) = start {
+                        let area = rectangle(start, (e.root_x, e.root_y));
+                        if area.width > 0 && area.height > 0 {
+                            selection = Some((area.x, area.y, area.width, area.height));
+                        }
+                    }
+                    break;
+                }
+                Event::KeyPress(_) => break,
+                event => self.deferred_events.push_back(event),
+            }
+        }
+
+        if let Some(drawn) = drawn {
+            self.connection
+                .poly_rectangle(self.root, graphics_context, &[drawn])?;
+        }
+        self.connection.free_gc(graphics_context)?;
+        self.connection.release_grabs()?;
+        self.connection.flush()?;
+        Ok(selection)
+    }
+
+    fn enter_mode(&mut self, name: &str) -> WmResult<()> {
+        if !self.config.modes.contains_key(name) {
+            log::warn!("Unknown mode '{}'", name);
+            return Ok(());
+        }
+        self.active_mode = Some(name.to_string());
+        self.keychord_state = keyboard::handlers::KeychordState::Idle;
+        self.current_key = 0;
+        self.grab_keys()?;
+        self.update_bar()
+    }
+
+    fn exit_mode(&mut self) -> WmResult<()> {
+        if self.active_mode.take().is_none() {
+            return Ok(());
+        }
+        self.keychord_state = keyboard::handlers::KeychordState::Idle;
+        self.current_key = 0;
+        self.grab_keys()?;
+        self.update_bar()
+    }
+
+    fn refresh_keyboard_mapping(&mut self) -> WmResult<()> {
+        self.keyboard_mapping = keyboard::handlers::get_keyboard_mapping(&self.connection)?;
+        self.grab_keys()
+    }
+
+    /// Asks every client that supports WM_DELETE_WINDOW to close. Quitting
+    /// finishes once they are gone or `quit_grace_period` runs out; pressing
+    /// Quit again in the meantime exits right away.
+    fn begin_quit(&mut self) -> WmResult<()> {
+        let grace_period = std::time::Duration::from_millis(self.config.quit_grace_period);
+        if grace_period.is_zero() {
+            return Ok(());
+        }
+
+        let windows: Vec<Window> = self.clients.keys().copied().collect();
+        for window in windows {
+            if self.send_event(window, self.atoms.wm_delete_window)? {
+                self.closing_windows.insert(window);
+            }
+        }
+
+        if !self.closing_windows.is_empty() {
+            self.quit_deadline = Some(std::time::Instant::now() + grace_period);
+        }
         Ok(())
     }
 
-    fn kill_client(&self, window: Window) -> WmResult<()> {
+    fn quit_finished(&mut self) -> bool {
+        let Some(deadline) = self.quit_deadline else {
+            return false;
+        };
+        let clients = &self.clients;
+        self.closing_windows.retain(|window| clients.contains_key(window));
+        self.closing_windows.is_empty() || std::time::Instant::now() >= deadline
+    }
+
+    /// Asks a client to close. Closing a client that has ignored the request
+    /// for `kill_timeout` kills it instead.
+    fn kill_client(&mut self, window: Window) -> WmResult<()> {
+        let timeout = std::time::Duration::from_millis(self.config.kill_timeout);
+        let is_hung = self
+            .pending_closes
+            .get(&window)
+            .is_some_and(|requested| requested.elapsed() >= timeout);
+        if is_hung {
+            log::warn!("Window {} ignored the close request, killing forcefully", window);
+            return self.force_kill(window);
+        }
+
         if self.send_event(window, self.atoms.wm_delete_window)? {
+            self.pending_closes
+                .entry(window)
+                .or_insert_with(std::time::Instant::now);
             self.connection.flush()?;
         } else {
-            eprintln!("Window {} doesn't support WM_DELETE_WINDOW, killing forcefully", window);
-            self.connection.kill_client(window)?;
-            self.connection.flush()?;
+            log::warn!("Window {} doesn't support WM_DELETE_WINDOW, killing forcefully", window);
+            self.force_kill(window)?;
+        }
+        Ok(())
+    }
+
+    fn force_kill(&mut self, window: Window) -> WmResult<()> {
+        self.pending_closes.remove(&window);
+        self.connection.kill(window)?;
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    /// Forgets close requests of clients that went away and, with
+    /// `auto_force_kill`, kills those that ignored theirs.
+    fn expire_close_requests(&mut self) -> WmResult<()> {
+        let clients = &self.clients;
+        self.pending_closes
+            .retain(|window, _| clients.contains_key(window));
+        if !self.config.auto_force_kill {
+            return Ok(());
+        }
+
+        let timeout = std::time::Duration::from_millis(self.config.kill_timeout);
+        let hung: Vec<Window> = self
+            .pending_closes
+            .iter()
+            .filter(|(_, requested)| requested.elapsed() >= timeout)
+            .map(|(&window, _)| window)
+            .collect();
+        for window in hung {
+            log::warn!("Window {} ignored the close request, killing forcefully", window);
+            self.force_kill(window)?;
         }
         Ok(())
     }
@@ -1331,6 +3270,7 @@ impl WindowManager {
         if let Some(client) = self.clients.get_mut(&window) {
             client.is_urgent = urgent;
         }
+        self.set_net_wm_state(window, self.atoms.net_wm_state_demands_attention, urgent)?;
 
         let hints_reply = self.connection.get_property(
             false,
@@ -1374,166 +3314,54 @@ impl WindowManager {
         Ok(())
     }
 
-    fn get_window_atom_property(&self, window: Window, property: Atom) -> WmResult<Option<Atom>> {
-        let reply = self.connection.get_property(
-            false,
-            window,
-            property,
-            AtomEnum::ATOM,
-            0,
-            1,
-        )?.reply();
-
-        match reply {
-            Ok(prop) if !prop.value.is_empty() && prop.value.len() >= 4 => {
-                let atom = u32::from_ne_bytes([
-                    prop.value[0],
-                    prop.value[1],
-                    prop.value[2],
-                    prop.value[3],
-                ]);
-                Ok(Some(atom))
-            }
-            _ => Ok(None),
-        }
+    fn get_window_atoms(&self, window: Window, property: Atom) -> WmResult<Vec<Atom>> {
+        Ok(self
+            .connection
+            .property32(window, property, AtomEnum::ATOM.into())?)
     }
 
-    fn fullscreen(&mut self) -> WmResult<()> {
-        if self.show_bar {
-            let windows: Vec<Window> = self.windows.iter()
-                .filter(|&&w| self.is_window_visible(w))
-                .copied()
-                .collect();
-
-            for window in &windows {
-                if let Ok(geom) = self.connection.get_geometry(*window)?.reply() {
-                        self.floating_geometry_before_fullscreen.insert(
-                            *window,
-                            (geom.x, geom.y, geom.width, geom.height, geom.border_width as u16),
-                        );
-                    }
-            }
-
-            self.last_layout = Some(self.layout.name());
-            if let Ok(layout) = layout_from_str("monocle") {
-                self.layout = layout;
-            }
-            self.toggle_bar()?;
-            self.apply_layout()?;
-
-            let border_width = self.config.border_width;
-            let floating_windows: Vec<Window> = windows.iter()
-                .filter(|&&w| self.floating_windows.contains(&w))
-                .copied()
-                .collect();
-
-            for window in floating_windows {
-                let monitor_idx = self.clients.get(&window)
-                    .map(|c| c.monitor_index)
-                    .unwrap_or(self.selected_monitor);
-                let monitor = &self.monitors[monitor_idx];
-
-                let (outer_gap_h, outer_gap_v) = if self.gaps_enabled {
-                    (
-                        self.config.gap_outer_horizontal,
-                        self.config.gap_outer_vertical,
-                    )
-                } else {
-                    (0, 0)
-                };
-
-                let window_x = monitor.screen_x + outer_gap_h as i32;
-                let window_y = monitor.screen_y + outer_gap_v as i32;
-                let window_width = monitor.screen_width.saturating_sub(2 * outer_gap_h as i32).saturating_sub(2 * border_width as i32);
-                let window_height = monitor.screen_height.saturating_sub(2 * outer_gap_v as i32).saturating_sub(2 * border_width as i32);
-
-                self.connection.configure_window(
-                    window,
-                    &x11rb::protocol::xproto::ConfigureWindowAux::new()
-                        .x(window_x)
-                        .y(window_y)
-                        .width(window_width as u32)
-                        .height(window_height as u32),
-                )?;
-            }
-            self.connection.flush()?;
+    /// Adds or removes one atom of a window's _NET_WM_STATE, keeping the
+    /// rest.
+    fn set_net_wm_state(&self, window: Window, state: Atom, present: bool) -> WmResult<()> {
+        let mut states = self.get_window_atoms(window, self.atoms.net_wm_state)?;
+        if states.contains(&state) == present {
+            return Ok(());
+        }
+        if present {
+            states.push(state);
         } else {
-            if let Some(last) = self.last_layout {
-                if let Ok(layout) = layout_from_str(last) {
-                    self.layout = layout;
-                }
-            }
-
-            let windows_to_restore: Vec<Window> = self.floating_geometry_before_fullscreen
-                .keys()
-                .copied()
-                .collect();
-
-            for window in windows_to_restore {
-                if let Some(&(x, y, width, height, border_width)) = self.floating_geometry_before_fullscreen.get(&window) {
-                    self.connection.configure_window(
-                        window,
-                        &ConfigureWindowAux::new()
-                            .x(x as i32)
-                            .y(y as i32)
-                            .width(width as u32)
-                            .height(height as u32)
-                            .border_width(border_width as u32),
-                    )?;
-
-                    if let Some(c) = self.clients.get_mut(&window) {
-                        c.x_position = x;
-                        c.y_position = y;
-                        c.width = width;
-                        c.height = height;
-                        c.border_width = border_width;
-                    }
-
-                    self.floating_geometry_before_fullscreen.remove(&window);
-                }
-            }
-            self.connection.flush()?;
-
-            self.toggle_bar()?;
-
-            if self.layout.name() != "normie" {
-                self.apply_layout()?;
-            } else {
-                if let Some(bar) = self.bars.get(self.selected_monitor) {
-                    self.connection.configure_window(
-                        bar.window(),
-                        &x11rb::protocol::xproto::ConfigureWindowAux::new()
-                            .stack_mode(x11rb::protocol::xproto::StackMode::ABOVE),
-                    )?;
-                    self.connection.flush()?;
-                }
-            }
+            states.retain(|&atom| atom != state);
         }
+
+        self.connection.set_property32(
+            window,
+            self.atoms.net_wm_state,
+            AtomEnum::ATOM.into(),
+            &states,
+        )?;
         Ok(())
     }
 
+    fn get_window_atom_property(&self, window: Window, property: Atom) -> WmResult<Option<Atom>> {
+        Ok(self.get_window_atoms(window, property)?.first().copied())
+    }
+
     fn set_window_fullscreen(&mut self, window: Window, fullscreen: bool) -> WmResult<()> {
         let monitor_idx = self.clients.get(&window)
             .map(|c| c.monitor_index)
             .unwrap_or(self.selected_monitor);
-        let monitor = &self.monitors[monitor_idx];
+        let (screen_x, screen_y, screen_width, screen_height) = if self.spanning_windows.contains(&window) {
+            self.spanned_area()
+        } else {
+            self.monitors[monitor_idx].screen_area()
+        };
 
         if fullscreen && !self.fullscreen_windows.contains(&window) {
-            let bytes = self.atoms.net_wm_state_fullscreen.to_ne_bytes().to_vec();
-            self.connection.change_property(
-                PropMode::REPLACE,
-                window,
-                self.atoms.net_wm_state,
-                AtomEnum::ATOM,
-                32,
-                1,
-                &bytes,
-            )?;
+            self.set_net_wm_state(window, self.atoms.net_wm_state_fullscreen, true)?;
 
             if let Some(client) = self.clients.get_mut(&window) {
                 client.is_fullscreen = true;
-                client.old_state = client.is_floating;
-                client.old_border_width = client.border_width;
+                client.save_geometry();
                 client.border_width = 0;
                 client.is_floating = true;
             }
@@ -1544,47 +3372,27 @@ impl WindowManager {
                 window,
                 &x11rb::protocol::xproto::ConfigureWindowAux::new()
                     .border_width(0)
-                    .x(monitor.screen_x)
-                    .y(monitor.screen_y)
-                    .width(monitor.screen_width as u32)
-                    .height(monitor.screen_height as u32)
+                    .x(screen_x)
+                    .y(screen_y)
+                    .width(screen_width as u32)
+                    .height(screen_height as u32)
                     .stack_mode(x11rb::protocol::xproto::StackMode::ABOVE),
             )?;
+            self.set_bypass_compositor(window, true)?;
+            self.sync_bar_visibility()?;
 
             self.connection.flush()?;
         } else if !fullscreen && self.fullscreen_windows.contains(&window) {
-            self.connection.change_property(
-                PropMode::REPLACE,
-                window,
-                self.atoms.net_wm_state,
-                AtomEnum::ATOM,
-                32,
-                0,
-                &[],
-            )?;
+            self.set_net_wm_state(window, self.atoms.net_wm_state_fullscreen, false)?;
+            self.set_bypass_compositor(window, false)?;
 
             self.fullscreen_windows.remove(&window);
+            self.spanning_windows.remove(&window);
 
             if let Some(client) = self.clients.get_mut(&window) {
                 client.is_fullscreen = false;
-                client.is_floating = client.old_state;
-                client.border_width = client.old_border_width;
-
-                let x = client.old_x_position;
-                let y = client.old_y_position;
-                let w = client.old_width;
-                let h = client.old_height;
-                let bw = client.border_width;
-
-                self.connection.configure_window(
-                    window,
-                    &x11rb::protocol::xproto::ConfigureWindowAux::new()
-                        .x(x as i32)
-                        .y(y as i32)
-                        .width(w as u32)
-                        .height(h as u32)
-                        .border_width(bw as u32),
-                )?;
+                let geometry = client.restore_geometry().unwrap_or_else(|| client.geometry());
+                self.configure_geometry(window, geometry)?;
             }
 
             self.apply_layout()?;
@@ -1593,17 +3401,63 @@ impl WindowManager {
         Ok(())
     }
 
-    fn toggle_bar(&mut self) -> WmResult<()> {
-        self.show_bar = !self.show_bar;
-        if let Some(bar) = self.bars.get(self.selected_monitor) {
-            if self.show_bar {
-                self.connection.map_window(bar.window())?;
-            } else {
-                self.connection.unmap_window(bar.window())?;
-            }
-            self.connection.flush()?;
+    /// Fullscreens the focused client across all monitors, or takes it out
+    /// of fullscreen again when it already spans them. A client fullscreen
+    /// on its own monitor is stretched over the others.
+    fn toggle_fullscreen_span(&mut self) -> WmResult<()> {
+        let Some(focused) = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client)
+        else {
+            return Ok(());
+        };
+
+        if self.spanning_windows.contains(&focused) {
+            return self.set_window_fullscreen(focused, false);
         }
-        self.apply_layout()?;
+
+        self.spanning_windows.insert(focused);
+        if !self.fullscreen_windows.contains(&focused) {
+            return self.set_window_fullscreen(focused, true);
+        }
+
+        let (x, y, width, height) = self.spanned_area();
+        self.connection.configure_window(
+            focused,
+            &ConfigureWindowAux::new()
+                .x(x)
+                .y(y)
+                .width(width as u32)
+                .height(height as u32)
+                .stack_mode(StackMode::ABOVE),
+        )?;
+        self.sync_bar_visibility()?;
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    /// The bounding box of every monitor as `(x, y, width, height)`.
+    fn spanned_area(&self) -> (i32, i32, i32, i32) {
+        let areas = self.monitors.iter().map(|monitor| monitor.screen_area());
+        let left = areas.clone().map(|(x, _, _, _)| x).min().unwrap_or(0);
+        let top = areas.clone().map(|(_, y, _, _)| y).min().unwrap_or(0);
+        let right = areas.clone().map(|(x, _, width, _)| x + width).max().unwrap_or(0);
+        let bottom = areas.map(|(_, y, _, height)| y + height).max().unwrap_or(0);
+        (left, top, right - left, bottom - top)
+    }
+
+    /// Sends a client geometry to the X server as it is, without size hints.
+    fn configure_geometry(&self, window: Window, geometry: Geometry) -> WmResult<()> {
+        self.connection.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(geometry.x as i32)
+                .y(geometry.y as i32)
+                .width(geometry.width as u32)
+                .height(geometry.height as u32)
+                .border_width(geometry.border_width as u32),
+        )?;
         Ok(())
     }
 
@@ -1656,12 +3510,20 @@ impl WindowManager {
     }
 
     fn apply_rules(&mut self, window: Window) -> WmResult<()> {
-        let (instance, class) = self.get_window_class_instance(window);
-        let title = self.clients.get(&window).map(|c| c.name.clone()).unwrap_or_default();
+        let Some((instance, class, title)) = self
+            .clients
+            .get(&window)
+            .map(|c| (c.instance.clone(), c.class.clone(), c.name.clone()))
+        else {
+            return Ok(());
+        };
 
         let mut rule_tags: Option<u32> = None;
         let mut rule_floating: Option<bool> = None;
         let mut rule_monitor: Option<usize> = None;
+        let mut rule_opacity: Option<f32> = None;
+        let mut rule_attach: Option<crate::AttachPosition> = None;
+        let mut rule_layout: Option<String> = None;
 
         for rule in &self.config.window_rules {
             if rule.matches(&class, &instance, &title) {
@@ -1674,10 +3536,21 @@ impl WindowManager {
                 if rule.monitor.is_some() {
                     rule_monitor = rule.monitor;
                 }
+                if rule.opacity.is_some() {
+                    rule_opacity = rule.opacity;
+                }
+                if rule.attach.is_some() {
+                    rule_attach = rule.attach;
+                }
+                if rule.layout.is_some() {
+                    rule_layout = rule.layout.clone();
+                }
             }
         }
 
         if let Some(client) = self.clients.get_mut(&window) {
+            client.attach_position = rule_attach;
+
             if let Some(is_floating) = rule_floating {
                 client.is_floating = is_floating;
                 if is_floating {
@@ -1703,9 +3576,272 @@ impl WindowManager {
             client.tags = tags;
         }
 
+        if let Some(opacity) = rule_opacity {
+            self.set_window_opacity(window, opacity)?;
+        }
+
+        if let Some(layout_name) = rule_layout {
+            self.apply_rule_layout(window, &layout_name);
+        }
+
+        Ok(())
+    }
+
+    /// Applies `on_title_change` rules that start matching after a client
+    /// retitles itself, for apps that only set their real title late.
+    fn apply_title_rules(&mut self, window: Window, old_title: &str) -> WmResult<()> {
+        let Some((instance, class, title)) = self
+            .clients
+            .get(&window)
+            .map(|c| (c.instance.clone(), c.class.clone(), c.name.clone()))
+        else {
+            return Ok(());
+        };
+
+        let rules: Vec<crate::WindowRule> = self
+            .config
+            .window_rules
+            .iter()
+            .filter(|rule| rule.on_title_change)
+            .filter(|rule| {
+                rule.matches(&class, &instance, &title)
+                    && !rule.matches(&class, &instance, old_title)
+            })
+            .cloned()
+            .collect();
+        if rules.is_empty() {
+            return Ok(());
+        }
+
+        let monitor_count = self.monitors.len();
+        for rule in rules {
+            if let Some(client) = self.clients.get_mut(&window)
+                && let Some(is_floating) = rule.is_floating
+            {
+                client.is_floating = is_floating;
+            }
+            match rule.is_floating {
+                Some(true) => {
+                    self.floating_windows.insert(window);
+                }
+                Some(false) => {
+                    self.floating_windows.remove(&window);
+                }
+                None => {}
+            }
+            if let Some(monitor_index) = rule.monitor.filter(|&m| m < monitor_count) {
+                self.move_window_to_monitor(window, monitor_index)?;
+            }
+            if let Some(tags) = rule.tags {
+                self.set_client_tags(window, tags)?;
+            }
+            if let Some(opacity) = rule.opacity {
+                self.set_window_opacity(window, opacity)?;
+            }
+        }
+
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()?;
+        Ok(())
+    }
+
+    /// Sets _NET_WM_BYPASS_COMPOSITOR to 1 while `window` is fullscreen
+    /// when enabled in the config, and removes it afterwards. A value the
+    /// client set itself is never touched.
+    fn set_bypass_compositor(&mut self, window: Window, bypass: bool) -> WmResult<()> {
+        let Some(client) = self.clients.get(&window) else {
+            return Ok(());
+        };
+
+        if bypass {
+            if !self.config.bypass_compositor || client.bypassing_compositor {
+                return Ok(());
+            }
+            let existing = self
+                .connection
+                .get_property(false, window, self.atoms.net_wm_bypass_compositor, AtomEnum::CARDINAL, 0, 1)?
+                .reply()?;
+            if !existing.value.is_empty() {
+                return Ok(());
+            }
+            self.connection.change_property(
+                PropMode::REPLACE,
+                window,
+                self.atoms.net_wm_bypass_compositor,
+                AtomEnum::CARDINAL,
+                32,
+                1,
+                &1u32.to_ne_bytes(),
+            )?;
+        } else if client.bypassing_compositor {
+            self.connection
+                .delete_property(window, self.atoms.net_wm_bypass_compositor)?;
+        } else {
+            return Ok(());
+        }
+
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.bypassing_compositor = bypass;
+        }
+        Ok(())
+    }
+
+    fn set_window_opacity(&mut self, window: Window, opacity: f32) -> WmResult<()> {
+        let opacity = opacity.clamp(MIN_OPACITY, 1.0);
+
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.opacity = opacity;
+        }
+
+        if opacity >= 1.0 {
+            self.connection
+                .delete_property(window, self.atoms.net_wm_window_opacity)?;
+        } else {
+            let value = (opacity as f64 * u32::MAX as f64) as u32;
+            self.connection.change_property(
+                PropMode::REPLACE,
+                window,
+                self.atoms.net_wm_window_opacity,
+                AtomEnum::CARDINAL,
+                32,
+                1,
+                &value.to_ne_bytes(),
+            )?;
+        }
+
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    fn next_startup_id(&mut self) -> String {
+        self.launch_counter += 1;
+        format!("oxwm-{}-{}", std::process::id(), self.launch_counter)
+    }
+
+    /// Remembers where a spawned application was launched from so its window
+    /// opens there, and shows the busy cursor until it maps.
+    fn begin_launch(&mut self, startup_id: String, pid: u32) -> WmResult<()> {
+        let tags = self
+            .monitors
+            .get(self.selected_monitor)
+            .map(|m| m.tagset[m.selected_tags_index])
+            .unwrap_or(tag_mask(0));
+
+        self.pending_launches.push(PendingLaunch {
+            startup_id,
+            pid,
+            monitor_index: self.selected_monitor,
+            tags,
+            started: std::time::Instant::now(),
+            exited: false,
+        });
+        self.update_root_cursor()
+    }
+
+    /// Stops the busy cursor for launches whose process has exited without
+    /// mapping a window.
+    fn end_exited_launches(&mut self, exited: &[u32]) -> WmResult<()> {
+        let mut changed = false;
+        for launch in &mut self.pending_launches {
+            if !launch.exited && exited.contains(&launch.pid) {
+                launch.exited = true;
+                changed = true;
+            }
+        }
+        if changed {
+            self.update_root_cursor()?;
+        }
+        Ok(())
+    }
+
+    fn expire_launches(&mut self) -> WmResult<()> {
+        let count = self.pending_launches.len();
+        self.pending_launches
+            .retain(|launch| launch.started.elapsed() < STARTUP_TIMEOUT);
+        if self.pending_launches.len() != count {
+            self.update_root_cursor()?;
+        }
+        Ok(())
+    }
+
+    fn update_root_cursor(&self) -> WmResult<()> {
+        let cursor = if self.pending_launches.iter().all(|launch| launch.exited) {
+            self.normal_cursor
+        } else {
+            self.busy_cursor
+        };
+        self.connection.change_window_attributes(
+            self.root,
+            &ChangeWindowAttributesAux::new().cursor(cursor),
+        )?;
         Ok(())
     }
 
+    /// Finds the pending launch a newly mapped window belongs to, matching on
+    /// `_NET_STARTUP_ID` first and falling back to `_NET_WM_PID` for clients
+    /// running on this machine.
+    fn claim_launch(&mut self, window: Window) -> WmResult<Option<PendingLaunch>> {
+        if self.pending_launches.is_empty() {
+            return Ok(None);
+        }
+
+        let startup_id = self
+            .connection
+            .get_property(false, window, self.atoms.net_startup_id, self.atoms.utf8_string, 0, 256)?
+            .reply()?
+            .value;
+        let startup_id = String::from_utf8_lossy(&startup_id);
+
+        let mut position = self
+            .pending_launches
+            .iter()
+            .position(|launch| !startup_id.is_empty() && launch.startup_id == startup_id);
+
+        if position.is_none() && self.is_local_client(window)? {
+            let pid = self
+                .connection
+                .get_property(false, window, self.atoms.net_wm_pid, AtomEnum::CARDINAL, 0, 1)?
+                .reply()?
+                .value32()
+                .and_then(|mut values| values.next());
+            position = pid.and_then(|pid| {
+                self.pending_launches
+                    .iter()
+                    .position(|launch| launch.pid == pid)
+            });
+        }
+
+        let Some(position) = position else {
+            return Ok(None);
+        };
+        let launch = self.pending_launches.remove(position);
+        self.update_root_cursor()?;
+        Ok(Some(launch))
+    }
+
+    /// A pid is only meaningful if `WM_CLIENT_MACHINE` names this host.
+    fn is_local_client(&self, window: Window) -> WmResult<bool> {
+        let machine = self
+            .connection
+            .get_property(
+                false,
+                window,
+                AtomEnum::WM_CLIENT_MACHINE,
+                AtomEnum::STRING,
+                0,
+                256,
+            )?
+            .reply()?
+            .value;
+        if machine.is_empty() {
+            return Ok(true);
+        }
+
+        let hostname = std::fs::read_to_string("/proc/sys/kernel/hostname").unwrap_or_default();
+        Ok(String::from_utf8_lossy(&machine) == hostname.trim())
+    }
+
     fn manage_window(&mut self, window: Window) -> WmResult<()> {
         let geometry = self.connection.get_geometry(window)?.reply()?;
         let border_width = self.config.border_width;
@@ -1722,6 +3858,8 @@ impl WindowManager {
                     .unwrap_or(tag_mask(0));
                 (self.selected_monitor, tags)
             }
+        } else if let Some(launch) = self.claim_launch(window)? {
+            (launch.monitor_index, launch.tags)
         } else {
             let tags = self.monitors.get(self.selected_monitor)
                 .map(|m| m.tagset[m.selected_tags_index])
@@ -1734,12 +3872,12 @@ impl WindowManager {
         client.y_position = geometry.y;
         client.width = geometry.width;
         client.height = geometry.height;
-        client.old_x_position = geometry.x;
-        client.old_y_position = geometry.y;
-        client.old_width = geometry.width;
-        client.old_height = geometry.height;
-        client.old_border_width = geometry.border_width;
+        client.depth = geometry.depth;
         client.border_width = border_width as u16;
+        (client.instance, client.class) = self.get_window_class_instance(window);
+        if let Ok(attributes) = self.connection.get_window_attributes(window)?.reply() {
+            client.colormap = attributes.colormap;
+        }
 
         self.clients.insert(window, client);
         self.update_window_title(window)?;
@@ -1748,7 +3886,21 @@ impl WindowManager {
             self.apply_rules(window)?;
         }
 
-        let client_monitor = self.clients.get(&window).map(|c| c.monitor_index).unwrap_or(monitor_index);
+        if let Some(client) = self.clients.get(&window) {
+            log::debug!(
+                "manage window={:#x} class={:?} instance={:?} title={:?} monitor={} tags={:#b} floating={} transient={}",
+                window,
+                client.class,
+                client.instance,
+                client.name,
+                client.monitor_index,
+                client.tags,
+                client.is_floating,
+                is_transient
+            );
+        }
+
+        let mut client_monitor = self.clients.get(&window).map(|c| c.monitor_index).unwrap_or(monitor_index);
         let monitor = &self.monitors[client_monitor];
 
         let mut x = self.clients.get(&window).map(|c| c.x_position as i32).unwrap_or(0);
@@ -1757,14 +3909,7 @@ impl WindowManager {
         let h = self.clients.get(&window).map(|c| c.height as i32).unwrap_or(1);
         let bw = border_width as i32;
 
-        if x + w + 2 * bw > monitor.window_area_x + monitor.window_area_width {
-            x = monitor.window_area_x + monitor.window_area_width - w - 2 * bw;
-        }
-        if y + h + 2 * bw > monitor.window_area_y + monitor.window_area_height {
-            y = monitor.window_area_y + monitor.window_area_height - h - 2 * bw;
-        }
-        x = x.max(monitor.window_area_x);
-        y = y.max(monitor.window_area_y);
+        (x, y) = monitor.clamp_to_work_area(x, y, w + 2 * bw, h + 2 * bw);
 
         if let Some(c) = self.clients.get_mut(&window) {
             c.x_position = x as i16;
@@ -1775,10 +3920,7 @@ impl WindowManager {
             window,
             &ConfigureWindowAux::new().border_width(border_width),
         )?;
-        self.connection.change_window_attributes(
-            window,
-            &ChangeWindowAttributesAux::new().border_pixel(self.config.border_unfocused),
-        )?;
+        self.draw_border(window, false)?;
         self.send_configure_notify(window)?;
         self.update_window_type(window)?;
         self.update_size_hints(window)?;
@@ -1787,15 +3929,49 @@ impl WindowManager {
         self.connection.change_window_attributes(
             window,
             &ChangeWindowAttributesAux::new().event_mask(
-                EventMask::ENTER_WINDOW | EventMask::FOCUS_CHANGE | EventMask::PROPERTY_CHANGE | EventMask::STRUCTURE_NOTIFY
+                EventMask::ENTER_WINDOW
+                    | EventMask::FOCUS_CHANGE
+                    | EventMask::PROPERTY_CHANGE
+                    | EventMask::STRUCTURE_NOTIFY
+                    | EventMask::COLOR_MAP_CHANGE
             ),
         )?;
 
+        let group_leader = if is_transient { None } else { self.utility_group_leader(window) };
+        if let Some(leader) = group_leader.and_then(|leader| self.clients.get(&leader)) {
+            let (leader_monitor, leader_tags) = (leader.monitor_index, leader.tags);
+            if let Some(c) = self.clients.get_mut(&window) {
+                c.monitor_index = leader_monitor;
+                c.tags = leader_tags;
+            }
+            client_monitor = leader_monitor;
+        }
+        let parent = transient_parent
+            .or(group_leader)
+            .filter(|parent| self.clients.contains_key(parent));
+
         let is_fixed = self.clients.get(&window).map(|c| c.is_fixed).unwrap_or(false);
         if let Some(c) = self.clients.get_mut(&window) {
             if !c.is_floating {
                 c.is_floating = is_transient || is_fixed;
-                c.old_state = c.is_floating;
+            }
+        }
+
+        let needs_placement = self
+            .clients
+            .get(&window)
+            .is_some_and(|c| c.is_floating && !c.is_fullscreen);
+        let placement = match parent {
+            _ if !needs_placement => None,
+            Some(parent) => self.center_over_parent(window, parent),
+            None => self.place_floating(window, client_monitor)?,
+        };
+        if let Some((placed_x, placed_y)) = placement {
+            x = placed_x;
+            y = placed_y;
+            if let Some(c) = self.clients.get_mut(&window) {
+                c.x_position = x as i16;
+                c.y_position = y as i16;
             }
         }
 
@@ -1807,7 +3983,7 @@ impl WindowManager {
             )?;
         }
 
-        self.attach_aside(window, client_monitor);
+        self.attach_new(window, client_monitor);
         self.attach_stack(window, client_monitor);
         self.windows.push(window);
 
@@ -1826,22 +4002,48 @@ impl WindowManager {
         let final_tags = self.clients.get(&window).map(|c| c.tags).unwrap_or(tags);
         let _ = self.save_client_tag(window, final_tags);
 
-        if client_monitor == self.selected_monitor {
-            if let Some(old_sel) = self.monitors.get(self.selected_monitor).and_then(|m| m.selected_client) {
-                self.unfocus(old_sel)?;
+        let has_focused_client = self
+            .monitors
+            .get(self.selected_monitor)
+            .is_some_and(|m| m.selected_client.is_some());
+        let is_locked_out = self.focus_lock && has_focused_client;
+        let takes_focus = !is_locked_out
+            && self
+                .clients
+                .get(&window)
+                .is_some_and(|c| c.window_type.takes_focus());
+
+        if takes_focus {
+            if client_monitor == self.selected_monitor {
+                if let Some(old_sel) = self.monitors.get(self.selected_monitor).and_then(|m| m.selected_client) {
+                    self.unfocus(old_sel)?;
+                }
             }
-        }
 
-        if let Some(m) = self.monitors.get_mut(client_monitor) {
-            m.selected_client = Some(window);
+            if let Some(m) = self.monitors.get_mut(client_monitor) {
+                m.selected_client = Some(window);
+            }
         }
 
         self.apply_layout()?;
-        self.connection.map_window(window)?;
-        self.focus(Some(window))?;
+        if self.config.border_radius > 0
+            && let Some(geometry) = self.clients.get(&window).map(Client::geometry)
+        {
+            self.update_shape(window, geometry)?;
+        }
+        self.connection.map(window)?;
+        if takes_focus {
+            self.focus(Some(window))?;
+        } else if is_locked_out {
+            self.connection.configure_window(
+                window,
+                &ConfigureWindowAux::new().stack_mode(StackMode::BELOW),
+            )?;
+            self.set_urgent(window, true)?;
+        }
         self.update_bar()?;
 
-        if self.layout.name() == "tabbed" {
+        if self.layout.uses_tab_bar() {
             self.update_tab_bars()?;
         }
 
@@ -1855,116 +4057,85 @@ impl WindowManager {
             monitor.selected_client = Some(window);
         }
 
-        self.connection
-            .set_input_focus(InputFocus::POINTER_ROOT, window, x11rb::CURRENT_TIME)?;
-        self.connection.flush()?;
+        self.connection.focus(window)?;
 
         self.update_focus_visuals(old_focused, window)?;
         self.previous_focused = Some(window);
 
-        if self.layout.name() == "tabbed" {
+        if self.layout.uses_tab_bar() {
             self.update_tab_bars()?;
         }
 
         Ok(())
     }
 
-    fn unfocus(&self, window: Window) -> WmResult<()> {
-        if !self.windows.contains(&window) {
+    /// Focuses the client a click landed on. The passive grab keeps the
+    /// pointer frozen until `allow_events`, so a plain click is replayed
+    /// right after the focus change and before the slower redraws, letting
+    /// the first click both focus the window and act in it. Modkey drags
+    /// consume the click instead of replaying it once the drag is over.
+    fn handle_client_click(&mut self, window: Window, event: &ButtonPressEvent) -> WmResult<()> {
+        let state_clean = self.keyboard_mapping.clean_state(u16::from(event.state));
+        let modkey_held = state_clean & u16::from(self.config.modkey) != 0;
+        let is_move = event.detail == u8::from(ButtonIndex::M1);
+        let is_resize = event.detail == u8::from(ButtonIndex::M3);
+
+        if modkey_held && (is_move || is_resize) && self.clients.contains_key(&window) {
+            self.connection.allow_events(Allow::ASYNC_POINTER, event.time)?;
+            self.focus(Some(window))?;
+            self.update_tab_bars()?;
+            if is_move {
+                self.drag_window(window)?;
+            } else {
+                self.resize_window_with_mouse(window)?;
+            }
             return Ok(());
         }
 
-        self.connection.change_window_attributes(
-            window,
-            &ChangeWindowAttributesAux::new().border_pixel(self.config.border_unfocused),
-        )?;
-
-        self.connection.grab_button(
-            false,
-            window,
-            EventMask::BUTTON_PRESS.into(),
-            GrabMode::SYNC,
-            GrabMode::SYNC,
-            x11rb::NONE,
-            x11rb::NONE,
-            ButtonIndex::ANY,
-            ModMask::ANY,
-        )?;
-
+        self.focus(Some(window))?;
+        self.connection.allow_events(Allow::REPLAY_POINTER, event.time)?;
+        self.connection.flush()?;
+        self.update_tab_bars()?;
         Ok(())
     }
 
-    fn focus(&mut self, window: Option<Window>) -> WmResult<()> {
-        let monitor = self.monitors.get_mut(self.selected_monitor).unwrap();
-        let old_selected = monitor.selected_client;
-
-        if let Some(old_win) = old_selected {
-            if old_selected != window {
-                self.unfocus(old_win)?;
-            }
-        }
-
-        let mut win = window;
-        if win.is_none() || !self.is_visible(win.unwrap()) {
-            let mut current = self.monitors.get(self.selected_monitor)
-                .and_then(|m| m.stack_head);
-
-            while let Some(w) = current {
-                if self.is_visible(w) {
-                    win = Some(w);
-                    break;
-                }
-                current = self.clients.get(&w).and_then(|c| c.stack_next);
-            }
+    fn unfocus(&self, window: Window) -> WmResult<()> {
+        if !self.windows.contains(&window) {
+            return Ok(());
         }
 
-        if let Some(win) = win {
-            if !self.windows.contains(&win) {
-                return Ok(());
-            }
-
-            let monitor_idx = self.clients.get(&win)
-                .map(|c| c.monitor_index)
-                .unwrap_or(self.selected_monitor);
-            if monitor_idx != self.selected_monitor {
-                self.selected_monitor = monitor_idx;
-            }
+        self.draw_border(window, false)?;
 
-            self.detach_stack(win);
-            self.attach_stack(win, monitor_idx);
+        self.connection.grab_client_buttons(window)?;
 
-            self.connection.change_window_attributes(
-                win,
-                &ChangeWindowAttributesAux::new().border_pixel(self.config.border_focused),
-            )?;
+        Ok(())
+    }
 
-            self.connection.ungrab_button(ButtonIndex::ANY, win, ModMask::ANY)?;
+    fn focus(&mut self, window: Option<Window>) -> WmResult<()> {
+        let change = self.selection().focus(window)?;
+        self.finish_focus(change)
+    }
 
-            self.connection.set_input_focus(
-                InputFocus::POINTER_ROOT,
-                win,
-                x11rb::CURRENT_TIME,
-            )?;
+    /// Redraws what a focus change touched and restacks.
+    fn finish_focus(&mut self, change: FocusChange) -> WmResult<()> {
+        for window in change.unfocused {
+            self.draw_border(window, false)?;
+        }
 
-            if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
-                monitor.selected_client = Some(win);
+        if let Some(win) = change.focused {
+            if self.clients.get(&win).is_some_and(|client| client.is_urgent) {
+                self.set_urgent(win, false)?;
             }
+            self.draw_border(win, true)?;
 
-            self.previous_focused = Some(win);
-        } else {
-            self.connection.set_input_focus(
-                InputFocus::POINTER_ROOT,
-                self.root,
-                x11rb::CURRENT_TIME,
-            )?;
+            self.connection.ungrab_button(ButtonIndex::ANY, win, ModMask::ANY)?;
+            self.install_colormap(win)?;
 
-            if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
-                monitor.selected_client = None;
-            }
+            self.previous_focused = Some(win);
         }
 
+        self.sync_tag_layout()?;
         self.restack()?;
-        self.connection.flush()?;
 
         Ok(())
     }
@@ -1983,22 +4154,19 @@ impl WindowManager {
             }
         }
 
-        let mut current = monitor.stack_head;
-        while let Some(win) = current {
-            if self.windows.contains(&win) && self.floating_windows.contains(&win) {
-                if Some(win) != monitor.selected_client {
-                    windows_to_restack.push(win);
-                }
+        for win in monitor.clients.stack() {
+            if self.windows.contains(&win)
+                && self.floating_windows.contains(&win)
+                && Some(win) != monitor.selected_client
+            {
+                windows_to_restack.push(win);
             }
-            current = self.clients.get(&win).and_then(|c| c.stack_next);
         }
 
-        current = monitor.stack_head;
-        while let Some(win) = current {
+        for win in monitor.clients.stack() {
             if self.windows.contains(&win) && !self.floating_windows.contains(&win) {
                 windows_to_restack.push(win);
             }
-            current = self.clients.get(&win).and_then(|c| c.stack_next);
         }
 
         for (i, &win) in windows_to_restack.iter().enumerate() {
@@ -2020,6 +4188,40 @@ impl WindowManager {
         Ok(())
     }
 
+    /// Raises and focuses the tab next to the focused one in the tabbed
+    /// layout, wrapping around at either end. Other layouts have no tabs,
+    /// so this moves focus through the stack instead.
+    fn focus_tab(&mut self, direction: i32) -> WmResult<()> {
+        if !self.layout.uses_tab_bar() {
+            return self.focusstack(direction);
+        }
+
+        let tabs = self.tab_bar_windows(self.selected_monitor);
+        if tabs.is_empty() {
+            return Ok(());
+        }
+
+        let selected = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|monitor| monitor.selected_client);
+        let next_index = match selected.and_then(|window| tabs.iter().position(|tab| tab.window == window)) {
+            Some(index) if direction > 0 => (index + 1) % tabs.len(),
+            Some(index) => (index + tabs.len() - 1) % tabs.len(),
+            None => 0,
+        };
+        let next_window = tabs[next_index].window;
+
+        self.connection.configure_window(
+            next_window,
+            &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
+        )?;
+        self.focus(Some(next_window))?;
+        self.update_tab_bars()?;
+
+        Ok(())
+    }
+
     fn focusstack(&mut self, direction: i32) -> WmResult<()> {
         let monitor = match self.monitors.get(self.selected_monitor) {
             Some(m) => m,
@@ -2033,18 +4235,15 @@ impl WindowManager {
 
         let selected_tags = monitor.tagset[monitor.selected_tags_index];
 
-        let mut stack_windows: Vec<Window> = Vec::new();
-        let mut current = monitor.clients_head;
-        while let Some(win) = current {
-            if let Some(client) = self.clients.get(&win) {
-                if client.tags & selected_tags != 0 && !client.is_floating {
-                    stack_windows.push(win);
-                }
-                current = client.next;
-            } else {
-                break;
-            }
-        }
+        let stack_windows: Vec<Window> = monitor
+            .clients
+            .iter()
+            .filter(|win| {
+                self.clients
+                    .get(win)
+                    .is_some_and(|client| client.tags & selected_tags != 0 && !client.is_floating)
+            })
+            .collect();
 
         if stack_windows.is_empty() {
             return Ok(());
@@ -2088,152 +4287,81 @@ impl WindowManager {
             None => return Ok(()),
         };
 
-        let selected_client = match self.clients.get(&selected) {
-            Some(c) => c,
-            None => return Ok(()),
+        let order: Vec<Window> = monitor.clients.iter().collect();
+        let Some(position) = order.iter().position(|&w| w == selected) else {
+            return Ok(());
         };
+        let is_tiled = |window: &&Window| self.is_tiled(**window, &monitor);
 
         let target = if direction > 0 {
-            let next = self.next_tiled(selected_client.next, &monitor);
-            if next.is_some() {
-                next
-            } else {
-                self.next_tiled(monitor.clients_head, &monitor)
-            }
+            order[position + 1..]
+                .iter()
+                .find(is_tiled)
+                .or_else(|| order.iter().find(is_tiled))
         } else {
-            let mut previous = None;
-            let mut current = monitor.clients_head;
-            while let Some(window) = current {
-                if window == selected {
-                    break;
-                }
-                if let Some(client) = self.clients.get(&window) {
-                    let visible_tags = client.tags & monitor.tagset[monitor.selected_tags_index];
-                    if visible_tags != 0 && !client.is_floating {
-                        previous = Some(window);
-                    }
-                    current = client.next;
-                } else {
-                    break;
-                }
-            }
-            if previous.is_none() {
-                let mut last = None;
-                let mut current = monitor.clients_head;
-                while let Some(window) = current {
-                    if let Some(client) = self.clients.get(&window) {
-                        let visible_tags = client.tags & monitor.tagset[monitor.selected_tags_index];
-                        if visible_tags != 0 && !client.is_floating {
-                            last = Some(window);
-                        }
-                        current = client.next;
-                    } else {
-                        break;
-                    }
-                }
-                last
-            } else {
-                previous
-            }
+            order[..position]
+                .iter()
+                .rev()
+                .find(is_tiled)
+                .or_else(|| order.iter().rev().find(is_tiled))
         };
 
         let target = match target {
-            Some(t) if t != selected => t,
+            Some(&t) if t != selected => t,
             _ => return Ok(()),
         };
 
-        let mut prev_selected = None;
-        let mut prev_target = None;
-        let mut current = monitor.clients_head;
-
-        while let Some(window) = current {
-            if let Some(client) = self.clients.get(&window) {
-                if client.next == Some(selected) {
-                    prev_selected = Some(window);
-                }
-                if client.next == Some(target) {
-                    prev_target = Some(window);
-                }
-                current = client.next;
-            } else {
-                break;
-            }
+        if let Some(monitor) = self.monitors.get_mut(monitor_index) {
+            monitor.clients.swap(selected, target);
         }
 
-        let selected_next = self.clients.get(&selected).and_then(|c| c.next);
-        let target_next = self.clients.get(&target).and_then(|c| c.next);
+        self.apply_layout()?;
+        Ok(())
+    }
 
-        let temp = if selected_next == Some(target) {
-            Some(selected)
-        } else {
-            selected_next
+    /// Moves the focused window to the front of the master area. When it
+    /// already is the master, the next tiled window takes its place, so
+    /// repeating this swaps the two back and forth.
+    pub fn zoom(&mut self) -> WmResult<()> {
+        let monitor_index = self.selected_monitor;
+        let Some(monitor) = self.monitors.get(monitor_index).cloned() else {
+            return Ok(());
         };
-
-        if let Some(client) = self.clients.get_mut(&selected) {
-            client.next = if target_next == Some(selected) {
-                Some(target)
-            } else {
-                target_next
-            };
-        }
-
-        if let Some(client) = self.clients.get_mut(&target) {
-            client.next = temp;
-        }
-
-        if let Some(prev) = prev_selected {
-            if prev != target {
-                if let Some(client) = self.clients.get_mut(&prev) {
-                    client.next = Some(target);
-                }
-            }
+        let Some(selected) = monitor.selected_client else {
+            return Ok(());
+        };
+        if !self.is_tiled(selected, &monitor) {
+            return Ok(());
         }
 
-        if let Some(prev) = prev_target {
-            if prev != selected {
-                if let Some(client) = self.clients.get_mut(&prev) {
-                    client.next = Some(selected);
-                }
+        let mut tiled = monitor.clients.iter().filter(|&window| self.is_tiled(window, &monitor));
+        let promoted = if tiled.next() == Some(selected) {
+            match tiled.next() {
+                Some(next) => next,
+                None => return Ok(()),
             }
-        }
+        } else {
+            selected
+        };
 
         if let Some(monitor) = self.monitors.get_mut(monitor_index) {
-            if monitor.clients_head == Some(selected) {
-                monitor.clients_head = Some(target);
-            } else if monitor.clients_head == Some(target) {
-                monitor.clients_head = Some(selected);
-            }
+            monitor.clients.attach(promoted);
         }
-
+        self.focus(Some(promoted))?;
         self.apply_layout()?;
         Ok(())
     }
 
     pub fn focus_monitor(&mut self, direction: i32) -> WmResult<()> {
-        if self.monitors.len() <= 1 {
-            return Ok(());
-        }
-
-        let target_monitor = match self.get_adjacent_monitor(direction) {
-            Some(idx) if idx != self.selected_monitor => idx,
-            _ => return Ok(()),
-        };
-
-        let old_selected = self.monitors
-            .get(self.selected_monitor)
-            .and_then(|m| m.selected_client);
-
-        if let Some(win) = old_selected {
-            self.unfocus(win)?;
+        match self.selection().focus_monitor(direction)? {
+            Some(change) => self.finish_focus(change),
+            None => Ok(()),
         }
-
-        self.selected_monitor = target_monitor;
-        self.focus(None)?;
-
-        Ok(())
     }
 
-    pub fn send_window_to_adjacent_monitor(&mut self, direction: i32) -> WmResult<()> {
+    /// Moves the focused window to the monitor in `direction`. With `follow`,
+    /// focus and the pointer go along with it.
+    pub fn send_window_to_adjacent_monitor(&mut self, direction: i32, follow: bool) -> WmResult<()> {
         if self.monitors.len() <= 1 {
             return Ok(());
         }
@@ -2247,16 +4375,43 @@ impl WindowManager {
             None => return Ok(()),
         };
 
-        let target_monitor = match self.get_adjacent_monitor(direction) {
+        let target_monitor = match self.selection().adjacent_monitor(direction) {
             Some(idx) => idx,
             None => return Ok(()),
         };
 
         self.move_window_to_monitor(window, target_monitor)?;
 
+        if follow {
+            self.selected_monitor = target_monitor;
+            if !self.is_window_visible(window) {
+                let tags = self.clients.get(&window).map(|c| c.tags).unwrap_or(0);
+                self.view_tag(tags.trailing_zeros() as usize)?;
+            }
+            self.focus(Some(window))?;
+            self.warp_pointer_to(window)?;
+            self.update_bar()?;
+        }
+
+        Ok(())
+    }
+
+    fn warp_pointer_to(&self, window: Window) -> WmResult<()> {
+        let Some(client) = self.clients.get(&window) else {
+            return Ok(());
+        };
+        self.connection
+            .move_pointer(window, (client.width / 2) as i16, (client.height / 2) as i16)?;
         Ok(())
     }
 
+    fn client_border_width(&self, window: Window) -> u32 {
+        self.clients
+            .get(&window)
+            .map(|client| client.border_width as u32)
+            .unwrap_or(self.config.border_width)
+    }
+
     fn update_focus_visuals(
         &self,
         old_focused: Option<Window>,
@@ -2266,27 +4421,75 @@ impl WindowManager {
             if old_win != new_focused {
                 self.connection.configure_window(
                     old_win,
-                    &ConfigureWindowAux::new().border_width(self.config.border_width),
+                    &ConfigureWindowAux::new().border_width(self.client_border_width(old_win)),
                 )?;
 
-                self.connection.change_window_attributes(
-                    old_win,
-                    &ChangeWindowAttributesAux::new().border_pixel(self.config.border_unfocused),
-                )?;
+                self.draw_border(old_win, false)?;
             }
         }
 
         self.connection.configure_window(
             new_focused,
-            &ConfigureWindowAux::new().border_width(self.config.border_width),
+            &ConfigureWindowAux::new().border_width(self.client_border_width(new_focused)),
         )?;
 
-        self.connection.change_window_attributes(
-            new_focused,
-            &ChangeWindowAttributesAux::new().border_pixel(self.config.border_focused),
-        )?;
+        self.draw_border(new_focused, true)?;
 
-        self.connection.flush()?;
+        Ok(())
+    }
+
+    fn drag_tab(&mut self, monitor_index: usize, window: Window) -> WmResult<()> {
+        let Some(tab_bar_window) = self.tab_bars.get(monitor_index).map(|tab_bar| tab_bar.window()) else {
+            return Ok(());
+        };
+
+        self.connection.grab_pointer(
+            false,
+            tab_bar_window,
+            EventMask::POINTER_MOTION | EventMask::BUTTON_RELEASE,
+            GrabMode::ASYNC,
+            GrabMode::ASYNC,
+            x11rb::NONE,
+            x11rb::NONE,
+            x11rb::CURRENT_TIME,
+        )?.reply()?;
+
+        loop {
+            let event = self.connection.wait_for_event()?;
+            match event {
+                Event::ConfigureRequest(_) | Event::MapRequest(_) | Event::Expose(_) => {}
+                Event::MotionNotify(e) => {
+                    let tab_windows = self.tab_bar_windows(monitor_index);
+                    let target_index = self.tab_bars[monitor_index].get_tab_index(tab_windows.len(), e.event_x);
+                    let current_index = tab_windows.iter().position(|tab| tab.window == window);
+
+                    let (Some(target_index), Some(current_index)) = (target_index, current_index) else {
+                        continue;
+                    };
+                    if target_index == current_index {
+                        continue;
+                    }
+
+                    let target_window = tab_windows[target_index].window;
+                    self.windows.retain(|&w| w != window);
+                    if let Some(target_position) = self.windows.iter().position(|&w| w == target_window) {
+                        let insert_position = if target_index > current_index {
+                            target_position + 1
+                        } else {
+                            target_position
+                        };
+                        self.windows.insert(insert_position, window);
+                    } else {
+                        self.windows.push(window);
+                    }
+                    self.update_tab_bars()?;
+                }
+                Event::ButtonRelease(_) => break,
+                _ => {}
+            }
+        }
+
+        self.connection.ungrab_pointer(x11rb::CURRENT_TIME)?.check()?;
         Ok(())
     }
 
@@ -2300,6 +4503,10 @@ impl WindowManager {
             return Ok(());
         }
 
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.pre_maximize_geometry = None;
+        }
+
         let client_info = self.clients.get(&window).map(|c| {
             (c.x_position, c.y_position, c.width, c.height, c.is_floating, c.monitor_index)
         });
@@ -2308,34 +4515,44 @@ impl WindowManager {
             return Ok(());
         };
 
-        let monitor = self.monitors.get(monitor_idx).cloned();
-        let Some(monitor) = monitor else {
+        if self.monitors.get(monitor_idx).is_none() {
             return Ok(());
-        };
+        }
 
-        let snap = 32;
+        let snap = self.config.snap_distance as i32;
         let is_normie = self.layout.name() == "normie";
 
         if !was_floating && !is_normie {
             self.toggle_floating()?;
         }
 
-        self.connection.grab_pointer(
-            false,
-            self.root,
-            (EventMask::POINTER_MOTION | EventMask::BUTTON_RELEASE |
-             EventMask::BUTTON_PRESS).into(),
-            GrabMode::ASYNC,
-            GrabMode::ASYNC,
-            x11rb::NONE,
-            x11rb::NONE,
-            x11rb::CURRENT_TIME,
-        )?.reply()?;
+        let area = self.monitors[monitor_idx].work_area();
+        let border_width = self.client_border_width(window) as i32;
+        let outer_size = (width as i32 + 2 * border_width, height as i32 + 2 * border_width);
+        let others: Vec<(i32, i32, i32, i32)> = self
+            .visible_windows_on_monitor(monitor_idx)
+            .into_iter()
+            .filter(|&other| other != window && self.floating_windows.contains(&other))
+            .filter_map(|other| self.clients.get(&other))
+            .map(|client| {
+                let border = 2 * client.border_width as i32;
+                (
+                    client.x_position as i32,
+                    client.y_position as i32,
+                    client.width as i32 + border,
+                    client.height as i32 + border,
+                )
+            })
+            .collect();
 
-        let pointer = self.connection.query_pointer(self.root)?.reply()?;
-        let (start_x, start_y) = (pointer.root_x as i32, pointer.root_y as i32);
+        if !self.connection.grab_pointer_motion(self.root)? {
+            return Ok(());
+        }
+
+        let (start_x, start_y) = self.connection.pointer_position(self.root)?;
 
         let mut last_time = 0u32;
+        let mut drop_target: Option<(usize, usize)> = None;
 
         loop {
             let event = self.connection.wait_for_event()?;
@@ -2347,19 +4564,35 @@ impl WindowManager {
                     }
                     last_time = e.time;
 
-                    let mut new_x = orig_x as i32 + (e.root_x as i32 - start_x);
-                    let mut new_y = orig_y as i32 + (e.root_y as i32 - start_y);
-
-                    if (monitor.window_area_x - new_x).abs() < snap {
-                        new_x = monitor.window_area_x;
-                    } else if ((monitor.window_area_x + monitor.window_area_width) - (new_x + width as i32)).abs() < snap {
-                        new_x = monitor.window_area_x + monitor.window_area_width - width as i32;
+                    let hovered = self.bar_tag_at(e.root_x as i32, e.root_y as i32);
+                    if hovered != drop_target {
+                        drop_target = hovered;
+                        for (monitor_index, bar) in self.bars.iter_mut().enumerate() {
+                            let tag = hovered
+                                .filter(|&(target, _)| target == monitor_index)
+                                .map(|(_, tag)| tag);
+                            bar.set_hovered_tag(tag);
+                        }
+                        self.update_bar()?;
+                        self.connection.flush()?;
                     }
 
-                    if (monitor.window_area_y - new_y).abs() < snap {
-                        new_y = monitor.window_area_y;
-                    } else if ((monitor.window_area_y + monitor.window_area_height) - (new_y + height as i32)).abs() < snap {
-                        new_y = monitor.window_area_y + monitor.window_area_height - height as i32;
+                    let (mut new_x, mut new_y) = snap_position(
+                        (
+                            orig_x as i32 + (e.root_x as i32 - start_x),
+                            orig_y as i32 + (e.root_y as i32 - start_y),
+                        ),
+                        outer_size,
+                        area,
+                        &others,
+                        snap,
+                    );
+
+                    let is_unconstrained = u16::from(e.state) & u16::from(KeyButMask::SHIFT) != 0;
+                    if self.config.constrain_drag && !is_unconstrained {
+                        let (area_x, area_y, area_width, area_height) = area;
+                        new_x = new_x.clamp(area_x, (area_x + area_width - outer_size.0).max(area_x));
+                        new_y = new_y.clamp(area_y, (area_y + area_height - outer_size.1).max(area_y));
                     }
 
                     let should_resize = is_normie || self.clients
@@ -2389,6 +4622,13 @@ impl WindowManager {
 
         self.connection.ungrab_pointer(x11rb::CURRENT_TIME)?.check()?;
 
+        if let Some((target_monitor, tag_index)) = drop_target {
+            for bar in &mut self.bars {
+                bar.set_hovered_tag(None);
+            }
+            return self.drop_window_on_tag(window, (orig_x, orig_y), target_monitor, tag_index);
+        }
+
         let final_client = self.clients.get(&window).map(|c| {
             (c.x_position, c.y_position, c.width, c.height)
         });
@@ -2405,6 +4645,28 @@ impl WindowManager {
         Ok(())
     }
 
+    /// Retags a window dropped onto a bar tag, putting it back where the drag
+    /// started.
+    fn drop_window_on_tag(
+        &mut self,
+        window: Window,
+        (orig_x, orig_y): (i16, i16),
+        target_monitor: usize,
+        tag_index: usize,
+    ) -> WmResult<()> {
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.x_position = orig_x;
+            client.y_position = orig_y;
+        }
+        self.connection.configure_window(
+            window,
+            &ConfigureWindowAux::new().x(orig_x as i32).y(orig_y as i32),
+        )?;
+
+        self.move_window_to_monitor(window, target_monitor)?;
+        self.set_client_tags(window, tag_mask(tag_index))
+    }
+
     fn resize_window_with_mouse(&mut self, window: Window) -> WmResult<()> {
         let is_fullscreen = self.clients
             .get(&window)
@@ -2415,6 +4677,10 @@ impl WindowManager {
             return Ok(());
         }
 
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.pre_maximize_geometry = None;
+        }
+
         let client_info = self.clients.get(&window).map(|c| {
             (c.x_position, c.y_position, c.width, c.height, c.border_width, c.is_floating, c.monitor_index)
         });
@@ -2433,28 +4699,43 @@ impl WindowManager {
             self.toggle_floating()?;
         }
 
-        self.connection.warp_pointer(
-            x11rb::NONE,
+        // Resize from the edge or corner nearest to where the window was
+        // grabbed; the opposite edges stay put.
+        let (pointer_x, pointer_y) = self.connection.pointer_position(self.root)?;
+        let outer_width = orig_width as i32 + 2 * border_width as i32;
+        let outer_height = orig_height as i32 + 2 * border_width as i32;
+        let grab_x = pointer_x - orig_x as i32;
+        let grab_y = pointer_y - orig_y as i32;
+        let mut horizontal = resize_edge(grab_x, outer_width);
+        let mut vertical = resize_edge(grab_y, outer_height);
+        if horizontal.is_none() && vertical.is_none() {
+            horizontal = Some(grab_x >= outer_width / 2);
+            vertical = Some(grab_y >= outer_height / 2);
+        }
+
+        let corner_x = |width: i32| match horizontal {
+            Some(true) => width + border_width as i32 - 1,
+            Some(false) => -(border_width as i32),
+            None => grab_x - border_width as i32,
+        };
+        let corner_y = |height: i32| match vertical {
+            Some(true) => height + border_width as i32 - 1,
+            Some(false) => -(border_width as i32),
+            None => grab_y - border_width as i32,
+        };
+
+        self.connection.move_pointer(
             window,
-            0,
-            0,
-            0,
-            0,
-            (orig_width + border_width - 1) as i16,
-            (orig_height + border_width - 1) as i16,
+            corner_x(orig_width as i32) as i16,
+            corner_y(orig_height as i32) as i16,
         )?;
 
-        self.connection.grab_pointer(
-            false,
-            self.root,
-            (EventMask::POINTER_MOTION | EventMask::BUTTON_RELEASE |
-             EventMask::BUTTON_PRESS).into(),
-            GrabMode::ASYNC,
-            GrabMode::ASYNC,
-            x11rb::NONE,
-            x11rb::NONE,
-            x11rb::CURRENT_TIME,
-        )?.reply()?;
+        let right_edge = orig_x as i32 + outer_width;
+        let bottom_edge = orig_y as i32 + outer_height;
+
+        if !self.connection.grab_pointer_motion(self.root)? {
+            return Ok(());
+        }
 
         let mut last_time = 0u32;
 
@@ -2468,59 +4749,70 @@ impl WindowManager {
                     }
                     last_time = e.time;
 
-                    let new_width = ((e.root_x as i32 - orig_x as i32 - 2 * border_width as i32 + 1).max(1)) as u32;
-                    let new_height = ((e.root_y as i32 - orig_y as i32 - 2 * border_width as i32 + 1).max(1)) as u32;
+                    let bw = border_width as i32;
+                    let (new_x, new_width) = match horizontal {
+                        Some(true) => (orig_x as i32, e.root_x as i32 - orig_x as i32 - 2 * bw + 1),
+                        Some(false) => (e.root_x as i32, right_edge - e.root_x as i32 - 2 * bw),
+                        None => (orig_x as i32, orig_width as i32),
+                    };
+                    let (new_y, new_height) = match vertical {
+                        Some(true) => (orig_y as i32, e.root_y as i32 - orig_y as i32 - 2 * bw + 1),
+                        Some(false) => (e.root_y as i32, bottom_edge - e.root_y as i32 - 2 * bw),
+                        None => (orig_y as i32, orig_height as i32),
+                    };
 
                     let should_resize = is_normie || self.clients
                         .get(&window)
                         .map(|c| c.is_floating)
                         .unwrap_or(false);
 
-                    if should_resize {
-                        if let Some(client) = self.clients.get(&window).cloned() {
-                            let (_, _, hint_width, hint_height, _) = self.apply_size_hints(
-                                window,
-                                client.x_position as i32,
-                                client.y_position as i32,
-                                new_width as i32,
-                                new_height as i32,
-                            );
-
-                            if let Some(client_mut) = self.clients.get_mut(&window) {
-                                client_mut.width = hint_width as u16;
-                                client_mut.height = hint_height as u16;
-                            }
+                    if should_resize && self.clients.contains_key(&window) {
+                        let (_, _, hint_width, hint_height, _) = self.apply_size_hints(
+                            window,
+                            new_x,
+                            new_y,
+                            new_width.max(1),
+                            new_height.max(1),
+                        );
 
-                            self.connection.configure_window(
-                                window,
-                                &ConfigureWindowAux::new()
-                                    .width(hint_width as u32)
-                                    .height(hint_height as u32),
-                            )?;
-                            self.connection.flush()?;
+                        // Size hints may round the size; keep the anchored
+                        // edge in place.
+                        let hint_x = match horizontal {
+                            Some(false) => right_edge - hint_width - 2 * bw,
+                            _ => orig_x as i32,
+                        };
+                        let hint_y = match vertical {
+                            Some(false) => bottom_edge - hint_height - 2 * bw,
+                            _ => orig_y as i32,
+                        };
+
+                        if let Some(client_mut) = self.clients.get_mut(&window) {
+                            client_mut.x_position = hint_x as i16;
+                            client_mut.y_position = hint_y as i16;
+                            client_mut.width = hint_width as u16;
+                            client_mut.height = hint_height as u16;
                         }
+
+                        self.connection.configure_window(
+                            window,
+                            &ConfigureWindowAux::new()
+                                .x(hint_x)
+                                .y(hint_y)
+                                .width(hint_width as u32)
+                                .height(hint_height as u32),
+                        )?;
+                        self.connection.flush()?;
                     }
                 }
                 Event::ButtonRelease(_) => break,
                 _ => {}
             }
         }
-
-        let final_client = self.clients.get(&window).map(|c| {
-            (c.width, c.border_width)
-        });
-
-        if let Some((w, bw)) = final_client {
-            self.connection.warp_pointer(
-                x11rb::NONE,
-                window,
-                0,
-                0,
-                0,
-                0,
-                (w + bw - 1) as i16,
-                (w + bw - 1) as i16,
-            )?;
+
+        let final_size = self.clients.get(&window).map(|c| (c.width as i32, c.height as i32));
+
+        if let Some((w, h)) = final_size {
+            self.connection.move_pointer(window, corner_x(w) as i16, corner_y(h) as i16)?;
         }
 
         self.connection.ungrab_pointer(x11rb::CURRENT_TIME)?.check()?;
@@ -2541,20 +4833,51 @@ impl WindowManager {
         Ok(())
     }
 
+    fn handle_error_overlay_key(&mut self, keycode: Keycode) -> WmResult<()> {
+        use crate::keyboard::keysyms;
+
+        let keysym = self.keyboard_mapping.keycode_to_keysym(keycode);
+        let direction = match keysym {
+            keysyms::XK_J | keysyms::XK_DOWN => Some(ErrorScroll::Down),
+            keysyms::XK_K | keysyms::XK_UP => Some(ErrorScroll::Up),
+            keysyms::XK_PAGE_DOWN | keysyms::XK_SPACE => Some(ErrorScroll::PageDown),
+            keysyms::XK_PAGE_UP => Some(ErrorScroll::PageUp),
+            keysyms::XK_HOME => Some(ErrorScroll::Top),
+            keysyms::XK_END => Some(ErrorScroll::Bottom),
+            _ => None,
+        };
+
+        if let Some(direction) = direction {
+            self.overlay.scroll(&self.connection, &self.font, direction)?;
+        } else if keysym == keysyms::XK_Y || keysym == keysyms::XK_C {
+            self.overlay.copy_to_clipboard();
+        } else if !keysyms::is_modifier(keysym) {
+            if let Err(error) = self.overlay.hide(&self.connection) {
+                log::error!("Failed to hide overlay: {:?}", error);
+            }
+        }
+        Ok(())
+    }
+
     fn handle_event(&mut self, event: Event) -> WmResult<Option<bool>> {
         match event {
             Event::KeyPress(ref key_event) if key_event.event == self.overlay.window() => {
                 if self.overlay.is_visible() {
-                    if let Err(error) = self.overlay.hide(&self.connection) {
-                        eprintln!("Failed to hide overlay: {:?}", error);
-                    }
+                    self.handle_error_overlay_key(key_event.detail)?;
                 }
                 return Ok(None);
             }
             Event::ButtonPress(ref button_event) if button_event.event == self.overlay.window() => {
-                if self.overlay.is_visible() {
+                let scroll = match button_event.detail {
+                    4 => Some(ErrorScroll::Up),
+                    5 => Some(ErrorScroll::Down),
+                    _ => None,
+                };
+                if let Some(direction) = scroll {
+                    self.overlay.scroll(&self.connection, &self.font, direction)?;
+                } else if self.overlay.is_visible() {
                     if let Err(error) = self.overlay.hide(&self.connection) {
-                        eprintln!("Failed to hide overlay: {:?}", error);
+                        log::error!("Failed to hide overlay: {:?}", error);
                     }
                 }
                 return Ok(None);
@@ -2562,7 +4885,7 @@ impl WindowManager {
             Event::Expose(ref expose_event) if expose_event.window == self.overlay.window() => {
                 if self.overlay.is_visible() {
                     if let Err(error) = self.overlay.draw(&self.connection, &self.font) {
-                        eprintln!("Failed to draw overlay: {:?}", error);
+                        log::error!("Failed to draw overlay: {:?}", error);
                     }
                 }
                 return Ok(None);
@@ -2572,19 +4895,84 @@ impl WindowManager {
                     && !self.keybind_overlay.should_suppress_input()
                 {
                     use crate::keyboard::keysyms;
-                    if let Some(mapping) = &self.keyboard_mapping {
-                        let keysym = mapping.keycode_to_keysym(e.detail);
-                        let is_escape = keysym == keysyms::XK_ESCAPE;
-                        let is_q = keysym == keysyms::XK_Q || keysym == 0x0051;
-                        if is_escape || is_q {
-                            if let Err(error) = self.keybind_overlay.hide(&self.connection) {
-                                eprintln!("Failed to hide keybind overlay: {:?}", error);
-                            }
+                    let keysym = self.keyboard_mapping.keycode_to_keysym(e.detail);
+                    let is_escape = keysym == keysyms::XK_ESCAPE;
+                    let is_q = keysym == keysyms::XK_Q || keysym == 0x0051;
+                    if is_escape || is_q {
+                        if let Err(error) = self.keybind_overlay.hide(&self.connection) {
+                            log::error!("Failed to hide keybind overlay: {:?}", error);
                         }
                     }
                 }
                 return Ok(None);
             }
+            Event::KeyPress(ref e) if e.event == self.text_input.window() => {
+                if self.text_input.is_visible() {
+                    self.handle_text_input_key(e.detail, e.state)?;
+                }
+                return Ok(None);
+            }
+            Event::Expose(ref e) if e.window == self.text_input.window() => {
+                if let Err(error) = self.text_input.draw(&self.connection, &self.font) {
+                    log::error!("Failed to draw text input: {:?}", error);
+                }
+                return Ok(None);
+            }
+            Event::KeyPress(ref e) if e.event == self.layout_menu.window() => {
+                if self.layout_menu.is_visible() {
+                    self.handle_layout_menu_key(e.detail)?;
+                }
+                return Ok(None);
+            }
+            Event::ButtonPress(ref e) if e.event == self.layout_menu.window() => {
+                let picked = self.layout_menu.layout_at(e.event_y);
+                self.layout_menu.hide(&self.connection)?;
+                if let Some(layout_name) = picked {
+                    self.set_layout(&layout_name)?;
+                }
+                return Ok(None);
+            }
+            Event::Expose(ref e) if e.window == self.osd.window() => {
+                if let Err(error) = self.osd.draw(&self.connection, &self.font) {
+                    log::error!("Failed to draw OSD: {:?}", error);
+                }
+            }
+            Event::KeyPress(ref e) if e.event == self.window_switcher.window() => {
+                if self.window_switcher.is_visible() {
+                    self.handle_window_switcher_key(e)?;
+                }
+                return Ok(None);
+            }
+            Event::KeyRelease(ref e) if e.event == self.window_switcher.window() => {
+                use crate::keyboard::keysyms;
+
+                let keysym = self.keyboard_mapping.keycode_to_keysym(e.detail);
+                let is_shift = matches!(keysym, keysyms::XK_SHIFT_L | keysyms::XK_SHIFT_R);
+                if self.window_switcher.is_visible() && keysyms::is_modifier(keysym) && !is_shift {
+                    self.commit_window_switcher()?;
+                }
+                return Ok(None);
+            }
+            Event::ButtonPress(ref e) if e.event == self.window_switcher.window() => {
+                let picked = self.window_switcher.window_at(e.event_x, e.event_y);
+                self.window_switcher.hide(&self.connection)?;
+                if let Some(window) = picked {
+                    self.activate(window)?;
+                }
+                return Ok(None);
+            }
+            Event::Expose(ref e) if e.window == self.window_switcher.window() => {
+                if let Err(error) = self.window_switcher.draw(&self.connection, &self.font) {
+                    log::error!("Failed to draw window switcher: {:?}", error);
+                }
+                return Ok(None);
+            }
+            Event::Expose(ref e) if e.window == self.layout_menu.window() => {
+                if let Err(error) = self.layout_menu.draw(&self.connection, &self.font) {
+                    log::error!("Failed to draw layout menu: {:?}", error);
+                }
+                return Ok(None);
+            }
             Event::ButtonPress(ref e) if e.event == self.keybind_overlay.window() => {
                 self.connection.allow_events(Allow::REPLAY_POINTER, e.time)?;
                 return Ok(None);
@@ -2592,7 +4980,7 @@ impl WindowManager {
             Event::Expose(ref expose_event) if expose_event.window == self.keybind_overlay.window() => {
                 if self.keybind_overlay.is_visible() {
                     if let Err(error) = self.keybind_overlay.draw(&self.connection, &self.font) {
-                        eprintln!("Failed to draw keybind overlay: {:?}", error);
+                        log::error!("Failed to draw keybind overlay: {:?}", error);
                     }
                 }
                 return Ok(None);
@@ -2607,16 +4995,37 @@ impl WindowManager {
                     return Ok(None);
                 }
 
-                if !self.windows.contains(&event.window) {
+                if self.is_dock(event.window) {
+                    self.manage_dock(event.window)?;
+                } else if self.minimized.contains(&event.window) {
+                    self.restore(event.window)?;
+                } else if !self.windows.contains(&event.window) {
                     self.manage_window(event.window)?;
                 }
             }
             Event::UnmapNotify(event) => {
+                if self.docks.remove(&event.window).is_some() {
+                    self.apply_layout()?;
+                    return Ok(None);
+                }
+                // We unmapped minimized windows ourselves; only a synthetic
+                // UnmapNotify (ICCCM withdrawal) means the client went away.
+                let is_synthetic = event.response_type & 0x80 != 0;
+                if self.minimized.contains(&event.window) {
+                    if is_synthetic {
+                        self.remove_window(event.window)?;
+                    }
+                    return Ok(None);
+                }
                 if self.windows.contains(&event.window) && self.is_window_visible(event.window) {
                     self.remove_window(event.window)?;
                 }
             }
             Event::DestroyNotify(event) => {
+                if self.docks.remove(&event.window).is_some() {
+                    self.apply_layout()?;
+                    return Ok(None);
+                }
                 if self.windows.contains(&event.window) {
                     self.remove_window(event.window)?;
                 }
@@ -2626,6 +5035,15 @@ impl WindowManager {
                     return Ok(None);
                 }
 
+                let is_strut = event.atom == self.atoms.net_wm_strut
+                    || event.atom == self.atoms.net_wm_strut_partial;
+                if is_strut && self.docks.contains_key(&event.window) {
+                    let strut = self.read_strut(event.window).unwrap_or_default();
+                    self.docks.insert(event.window, strut);
+                    self.apply_layout()?;
+                    return Ok(None);
+                }
+
                 if !self.clients.contains_key(&event.window) {
                     return Ok(None);
                 }
@@ -2650,11 +5068,26 @@ impl WindowManager {
                 } else if event.atom == AtomEnum::WM_HINTS.into() {
                     self.update_window_hints(event.window)?;
                     self.update_bar()?;
+                    self.update_tab_bars()?;
+                } else if event.atom == AtomEnum::WM_CLASS.into() {
+                    let (instance, class) = self.get_window_class_instance(event.window);
+                    if let Some(client) = self.clients.get_mut(&event.window) {
+                        client.instance = instance;
+                        client.class = class;
+                    }
+                    self.update_bar()?;
+                    self.update_tab_bars()?;
                 }
 
                 if event.atom == self.atoms.wm_name || event.atom == self.atoms.net_wm_name {
+                    let old_title = self
+                        .clients
+                        .get(&event.window)
+                        .map(|client| client.name.clone())
+                        .unwrap_or_default();
                     let _ = self.update_window_title(event.window);
-                    if self.layout.name() == "tabbed" {
+                    self.apply_title_rules(event.window, &old_title)?;
+                    if self.layout.uses_tab_bar() {
                         self.update_tab_bars()?;
                     }
                 }
@@ -2667,14 +5100,12 @@ impl WindowManager {
                 if event.mode != x11rb::protocol::xproto::NotifyMode::NORMAL {
                     return Ok(None);
                 }
-                if self.windows.contains(&event.event) {
-                    if let Some(client) = self.clients.get(&event.event) {
-                        if client.monitor_index != self.selected_monitor {
-                            self.selected_monitor = client.monitor_index;
-                            self.update_bar()?;
-                        }
+                let previous_monitor = self.selected_monitor;
+                if let Some(change) = self.selection().enter(event.event)? {
+                    if self.selected_monitor != previous_monitor {
+                        self.update_bar()?;
                     }
-                    self.focus(Some(event.event))?;
+                    self.finish_focus(change)?;
                     self.update_tab_bars()?;
                 }
             }
@@ -2683,31 +5114,34 @@ impl WindowManager {
                     return Ok(None);
                 }
 
-                if let Some(monitor_index) =
-                    self.get_monitor_at_point(event.root_x as i32, event.root_y as i32)
+                if let Some(change) = self
+                    .selection()
+                    .pointer_moved(event.root_x as i32, event.root_y as i32)?
                 {
-                    if monitor_index != self.selected_monitor {
-                        self.selected_monitor = monitor_index;
-                        self.update_bar()?;
-
-                        let visible = self.visible_windows_on_monitor(monitor_index);
-                        if let Some(&win) = visible.first() {
-                            self.focus(Some(win))?;
-                            self.update_tab_bars()?;
-                        }
-                    }
+                    self.update_bar()?;
+                    self.finish_focus(change)?;
+                    self.update_tab_bars()?;
                 }
             }
             Event::KeyPress(event) => {
-                let Some(mapping) = &self.keyboard_mapping else {
+                let in_chord = matches!(
+                    self.keychord_state,
+                    keyboard::handlers::KeychordState::InProgress { .. }
+                );
+                if self.active_mode.is_some()
+                    && !in_chord
+                    && self.keyboard_mapping.event_keysym(event.detail, u16::from(event.state))
+                        == keyboard::keysyms::XK_ESCAPE
+                {
+                    self.exit_mode()?;
                     return Ok(None);
-                };
+                }
 
                 let result = keyboard::handle_key_press(
                     event,
-                    &self.config.keybindings,
+                    self.active_keybindings(),
                     &self.keychord_state,
-                    mapping,
+                    &self.keyboard_mapping,
                 );
 
                 match result {
@@ -2718,42 +5152,52 @@ impl WindowManager {
                         self.update_bar()?;
 
                         match action {
-                            KeyAction::Quit => return Ok(Some(false)),
+                            KeyAction::Quit if self.quit_deadline.is_none() => {
+                                self.begin_quit()?;
+                                if self.quit_deadline.is_none() {
+                                    self.supervisor.shutdown();
+                                    return Ok(Some(false));
+                                }
+                            }
+                            KeyAction::Quit | KeyAction::ForceQuit => {
+                                self.supervisor.shutdown();
+                                return Ok(Some(false));
+                            }
+                            KeyAction::Restart if matches!(&arg, Arg::Str(mode) if mode == "exec") => {
+                                self.reexec()?;
+                            }
                             KeyAction::Restart => match self.try_reload_config() {
                                 Ok(()) => {
                                     self.gaps_enabled = self.config.gaps_enabled;
                                     self.error_message = None;
                                     if let Err(error) = self.overlay.hide(&self.connection) {
-                                        eprintln!("Failed to hide overlay after config reload: {:?}", error);
+                                        log::error!("Failed to hide overlay after config reload: {:?}", error);
                                     }
                                     self.apply_layout()?;
                                     self.update_bar()?;
                                 }
                                 Err(err) => {
-                                    eprintln!("Config reload error: {}", err);
-                                    self.error_message = Some(err.clone());
-                                    let monitor = &self.monitors[self.selected_monitor];
-                                    let monitor_x = monitor.screen_x as i16;
-                                    let monitor_y = monitor.screen_y as i16;
-                                    let screen_width = monitor.screen_width as u16;
-                                    let screen_height = monitor.screen_height as u16;
-                                    match self.overlay.show_error(
-                                        &self.connection,
-                                        &self.font,
-                                        &err,
-                                        monitor_x,
-                                        monitor_y,
-                                        screen_width,
-                                        screen_height,
-                                    ) {
-                                        Ok(()) => eprintln!("Error modal displayed"),
-                                        Err(e) => eprintln!("Failed to show error modal: {:?}", e),
-                                    }
+                                    log::error!("Config reload error: {}", err);
+                                    self.show_error_overlay(&err);
                                 }
                             },
                             _ => self.handle_key_action(action, &arg)?,
                         }
                     }
+                    keyboard::handlers::KeychordResult::Held(action, arg) => {
+                        // Auto repeat keeps sending presses while held.
+                        if self
+                            .held_key
+                            .as_ref()
+                            .is_some_and(|(keycode, _, _)| *keycode == event.detail)
+                        {
+                            return Ok(None);
+                        }
+                        self.keychord_state = keyboard::handlers::KeychordState::Idle;
+                        self.current_key = 0;
+                        self.held_key = Some((event.detail, action, arg.clone()));
+                        self.handle_key_action(action, &arg)?;
+                    }
                     keyboard::handlers::KeychordResult::InProgress(candidates) => {
                         self.current_key += 1;
                         self.keychord_state = keyboard::handlers::KeychordState::InProgress {
@@ -2772,12 +5216,21 @@ impl WindowManager {
                     }
                 }
             }
+            Event::KeyRelease(event) => {
+                self.handle_key_release(event)?;
+            }
             Event::ButtonPress(event) => {
                 if self.keybind_overlay.is_visible() && event.event != self.keybind_overlay.window() {
                     if let Err(error) = self.keybind_overlay.hide(&self.connection) {
-                        eprintln!("Failed to hide keybind overlay: {:?}", error);
+                        log::error!("Failed to hide keybind overlay: {:?}", error);
                     }
                 }
+                if let Err(error) = self.layout_menu.hide(&self.connection) {
+                    log::error!("Failed to hide layout menu: {:?}", error);
+                }
+                if let Err(error) = self.window_switcher.hide(&self.connection) {
+                    log::error!("Failed to hide window switcher: {:?}", error);
+                }
 
                 let is_bar_click = self
                     .bars
@@ -2786,11 +5239,40 @@ impl WindowManager {
                     .find(|(_, bar)| bar.window() == event.event);
 
                 if let Some((monitor_index, bar)) = is_bar_click {
-                    if let Some(tag_index) = bar.handle_click(event.event_x) {
-                        if monitor_index != self.selected_monitor {
-                            self.selected_monitor = monitor_index;
+                    match bar.handle_click(event.event_x) {
+                        Some(BarClick::Tag(tag_index)) => {
+                            if monitor_index != self.selected_monitor {
+                                self.selected_monitor = monitor_index;
+                                self.sync_tag_layout()?;
+                            }
+                            match event.detail {
+                                4 => self.shift_view(-1)?,
+                                5 => self.shift_view(1)?,
+                                _ => self.view_tag(tag_index)?,
+                            }
+                        }
+                        Some(BarClick::LayoutSymbol) => {
+                            if monitor_index != self.selected_monitor {
+                                self.selected_monitor = monitor_index;
+                                self.sync_tag_layout()?;
+                            }
+                            match event.detail {
+                                1 | 5 => {
+                                    self.set_layout(next_layout(self.layout.name(), &self.config.layout_cycle_skip))?
+                                }
+                                4 => self.set_layout(prev_layout(self.layout.name(), &self.config.layout_cycle_skip))?,
+                                3 => self.show_layout_menu(monitor_index)?,
+                                _ => {}
+                            }
+                        }
+                        Some(BarClick::Block(block_index, block_x)) => {
+                            if monitor_index != self.selected_monitor {
+                                self.selected_monitor = monitor_index;
+                                self.sync_tag_layout()?;
+                            }
+                            self.handle_block_click(block_index, event.detail, block_x)?;
                         }
-                        self.view_tag(tag_index)?;
+                        None => {}
                     }
                 } else {
                     let is_tab_bar_click = self
@@ -2804,70 +5286,34 @@ impl WindowManager {
                             self.selected_monitor = monitor_index;
                         }
 
-                        let visible_windows: Vec<(Window, String)> = self
-                            .windows
-                            .iter()
-                            .filter_map(|&window| {
-                                if let Some(client) = self.clients.get(&window) {
-                                    if client.monitor_index != monitor_index
-                                        || self.floating_windows.contains(&window)
-                                        || self.fullscreen_windows.contains(&window)
-                                    {
-                                        return None;
-                                    }
-                                    let monitor_tags = self.monitors.get(monitor_index).map(|m| m.tagset[m.selected_tags_index]).unwrap_or(0);
-                                    if (client.tags & monitor_tags) != 0 {
-                                        return Some((window, client.name.clone()));
-                                    }
-                                }
-                                None
-                            })
-                            .collect();
-
-                        if let Some(clicked_window) = tab_bar.get_clicked_window(&visible_windows, event.event_x) {
-                            self.connection.configure_window(
-                                clicked_window,
-                                &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
-                            )?;
-                            self.focus(Some(clicked_window))?;
-                            self.update_tab_bars()?;
-                        }
-                    } else if event.child != x11rb::NONE {
-                        self.focus(Some(event.child))?;
-                        self.update_tab_bars()?;
+                        let visible_windows = self.tab_bar_windows(monitor_index);
 
-                        let state_clean = u16::from(event.state) & !(u16::from(ModMask::LOCK) | u16::from(ModMask::M2));
-                        let modkey_held = state_clean & u16::from(self.config.modkey) != 0;
-
-                        if modkey_held && event.detail == ButtonIndex::M1.into() {
-                            if self.clients.contains_key(&event.child) {
-                                self.drag_window(event.child)?;
+                        match tab_bar.get_click(&visible_windows, event.event_x) {
+                            Some(TabClick::Close(clicked_window)) => {
+                                self.kill_client(clicked_window)?;
                             }
-                            self.connection.allow_events(Allow::REPLAY_POINTER, event.time)?;
-                        } else if modkey_held && event.detail == ButtonIndex::M3.into() {
-                            if self.clients.contains_key(&event.child) {
-                                self.resize_window_with_mouse(event.child)?;
+                            Some(TabClick::Select(clicked_window)) => {
+                                self.connection.configure_window(
+                                    clicked_window,
+                                    &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
+                                )?;
+                                self.focus(Some(clicked_window))?;
+                                self.update_tab_bars()?;
+
+                                let is_floating = self.floating_windows.contains(&clicked_window);
+                                if event.detail == ButtonIndex::M1.into() && !is_floating {
+                                    self.drag_tab(monitor_index, clicked_window)?;
+                                }
                             }
-                            self.connection.allow_events(Allow::REPLAY_POINTER, event.time)?;
-                        } else {
-                            self.connection.allow_events(Allow::REPLAY_POINTER, event.time)?;
+                            None => {}
                         }
                     } else if self.windows.contains(&event.event) {
-                        self.focus(Some(event.event))?;
-                        self.update_tab_bars()?;
-
-                        let state_clean = u16::from(event.state) & !(u16::from(ModMask::LOCK) | u16::from(ModMask::M2));
-                        let modkey_held = state_clean & u16::from(self.config.modkey) != 0;
-
-                        if modkey_held && event.detail == ButtonIndex::M1.into() {
-                            self.drag_window(event.event)?;
-                            self.connection.allow_events(Allow::REPLAY_POINTER, event.time)?;
-                        } else if modkey_held && event.detail == ButtonIndex::M3.into() {
-                            self.resize_window_with_mouse(event.event)?;
-                            self.connection.allow_events(Allow::REPLAY_POINTER, event.time)?;
-                        } else {
-                            self.connection.allow_events(Allow::REPLAY_POINTER, event.time)?;
-                        }
+                        // Grabbed on an unfocused client: event.child is
+                        // one of the application's own subwindows.
+                        self.handle_client_click(event.event, &event)?;
+                    } else if self.windows.contains(&event.child) {
+                        // Modkey grab on the root window.
+                        self.handle_client_click(event.child, &event)?;
                     } else {
                         self.connection.allow_events(Allow::REPLAY_POINTER, event.time)?;
                     }
@@ -2889,7 +5335,7 @@ impl WindowManager {
                 }
             }
             Event::ConfigureRequest(event) => {
-                if let Some(client) = self.clients.get(&event.window) {
+                if let Some(client) = self.clients.get(&event.window).cloned() {
                     let monitor = &self.monitors[client.monitor_index];
                     let is_floating = client.is_floating;
                     let has_layout = self.layout.name() != "normie";
@@ -2905,31 +5351,19 @@ impl WindowManager {
                         let mut h = client.height as i32;
 
                         if event.value_mask.contains(ConfigWindow::X) {
-                            if let Some(c) = self.clients.get_mut(&event.window) {
-                                c.old_x_position = c.x_position;
-                            }
                             x = monitor.screen_x + event.x as i32;
                         }
                         if event.value_mask.contains(ConfigWindow::Y) {
-                            if let Some(c) = self.clients.get_mut(&event.window) {
-                                c.old_y_position = c.y_position;
-                            }
                             y = monitor.screen_y + event.y as i32;
                         }
                         if event.value_mask.contains(ConfigWindow::WIDTH) {
-                            if let Some(c) = self.clients.get_mut(&event.window) {
-                                c.old_width = c.width;
-                            }
                             w = event.width as i32;
                         }
                         if event.value_mask.contains(ConfigWindow::HEIGHT) {
-                            if let Some(c) = self.clients.get_mut(&event.window) {
-                                c.old_height = c.height;
-                            }
                             h = event.height as i32;
                         }
 
-                        let bw = self.config.border_width as i32;
+                        let bw = client.border_width as i32;
                         let width_with_border = w + 2 * bw;
                         let height_with_border = h + 2 * bw;
 
@@ -2940,6 +5374,9 @@ impl WindowManager {
                             y = monitor.screen_y + (monitor.screen_height as i32 / 2 - height_with_border / 2);
                         }
 
+                        let (old_width, old_height) = (client.width as i32, client.height as i32);
+                        let (x, y, w, h, _) = self.apply_size_hints(event.window, x, y, w, h);
+
                         if let Some(c) = self.clients.get_mut(&event.window) {
                             c.x_position = x as i16;
                             c.y_position = y as i16;
@@ -2947,13 +5384,10 @@ impl WindowManager {
                             c.height = h as u16;
                         }
 
-                        let only_position_change = event.value_mask.contains(ConfigWindow::X) || event.value_mask.contains(ConfigWindow::Y);
-                        let no_size_change = !event.value_mask.contains(ConfigWindow::WIDTH) && !event.value_mask.contains(ConfigWindow::HEIGHT);
-                        if only_position_change && no_size_change {
-                            self.send_configure_notify(event.window)?;
-                        }
-
-                        if self.is_visible(event.window) {
+                        // ICCCM 4.1.5: the client only gets a real ConfigureNotify when
+                        // its size changes, so report moves and refused resizes ourselves.
+                        let is_visible = self.is_visible(event.window);
+                        if is_visible {
                             self.connection.configure_window(
                                 event.window,
                                 &ConfigureWindowAux::new()
@@ -2963,6 +5397,9 @@ impl WindowManager {
                                     .height(h as u32),
                             )?;
                         }
+                        if !is_visible || (w == old_width && h == old_height) {
+                            self.send_configure_notify(event.window)?;
+                        }
                     } else {
                         self.send_configure_notify(event.window)?;
                     }
@@ -2993,25 +5430,79 @@ impl WindowManager {
                 }
                 self.connection.flush()?;
             }
+            Event::ColormapNotify(event) => {
+                if !event.new {
+                    return Ok(None);
+                }
+                if let Some(client) = self.clients.get_mut(&event.window) {
+                    client.colormap = event.colormap;
+                }
+                let selected = self.monitors.get(self.selected_monitor).and_then(|m| m.selected_client);
+                if selected == Some(event.window) {
+                    self.install_colormap(event.window)?;
+                }
+            }
             Event::ClientMessage(event) => {
                 if !self.clients.contains_key(&event.window) {
                     return Ok(None);
                 }
 
-                if event.type_ == self.atoms.net_wm_state {
-                    if let Some(data) = event.data.as_data32().get(1) {
-                        if *data == self.atoms.net_wm_state_fullscreen {
-                            let action = event.data.as_data32()[0];
-                            let fullscreen = match action {
-                                1 => true,
-                                0 => false,
-                                2 => !self.fullscreen_windows.contains(&event.window),
-                                _ => return Ok(None),
-                            };
-                            self.set_window_fullscreen(event.window, fullscreen)?;
-                        }
+                if event.type_ == self.atoms.wm_change_state {
+                    const ICONIC_STATE: u32 = 3;
+                    if event.data.as_data32()[0] == ICONIC_STATE {
+                        self.minimize(event.window)?;
+                    }
+                } else if event.type_ == self.atoms.net_wm_state {
+                    let data = event.data.as_data32();
+                    let action = data[0];
+                    let states = [data[1], data[2]];
+
+                    if states.contains(&self.atoms.net_wm_state_fullscreen) {
+                        let fullscreen = match action {
+                            1 => true,
+                            0 => false,
+                            2 => !self.fullscreen_windows.contains(&event.window),
+                            _ => return Ok(None),
+                        };
+                        self.set_window_fullscreen(event.window, fullscreen)?;
+                    }
+
+                    if states.contains(&self.atoms.net_wm_state_demands_attention) {
+                        let is_urgent = self
+                            .clients
+                            .get(&event.window)
+                            .is_some_and(|client| client.is_urgent);
+                        let is_selected = self
+                            .monitors
+                            .get(self.selected_monitor)
+                            .and_then(|m| m.selected_client)
+                            == Some(event.window);
+                        let urgent = match action {
+                            1 => !is_selected,
+                            0 => false,
+                            2 => !is_urgent && !is_selected,
+                            _ => return Ok(None),
+                        };
+                        self.set_urgent(event.window, urgent)?;
+                        self.draw_border(event.window, is_selected)?;
+                        self.update_bar()?;
                     }
+                } else if event.type_ == self.atoms.net_wm_desktop {
+                    // A pager moving the window to another desktop, or to
+                    // all of them with 0xFFFFFFFF.
+                    const ALL_DESKTOPS: u32 = 0xFFFFFFFF;
+                    let desktop = event.data.as_data32()[0];
+                    let tag_count = self.config.tags.len();
+                    let mask = if desktop == ALL_DESKTOPS {
+                        tag_mask(tag_count) - 1
+                    } else if (desktop as usize) < tag_count {
+                        tag_mask(desktop as usize)
+                    } else {
+                        return Ok(None);
+                    };
+                    self.set_client_tags(event.window, mask)?;
                 } else if event.type_ == self.atoms.net_active_window {
+                    const SOURCE_PAGER: u32 = 2;
                     let selected_window = self.monitors
                         .get(self.selected_monitor)
                         .and_then(|m| m.selected_client);
@@ -3021,7 +5512,15 @@ impl WindowManager {
                         .map(|c| c.is_urgent)
                         .unwrap_or(false);
 
-                    if Some(event.window) != selected_window && !is_urgent {
+                    let from_pager = event.data.as_data32()[0] == SOURCE_PAGER;
+                    let allowed = from_pager || self.config.allow_activation;
+
+                    if Some(event.window) == selected_window {
+                        return Ok(None);
+                    }
+                    if allowed {
+                        self.activate(event.window)?;
+                    } else if !is_urgent {
                         self.set_urgent(event.window, true)?;
                     }
                 }
@@ -3031,18 +5530,49 @@ impl WindowManager {
                     .get(self.selected_monitor)
                     .and_then(|m| m.selected_client);
 
+                // Clients may focus their own dialogs, anything else taking
+                // focus is reverted to the selected client.
                 if let Some(sel_win) = selected_window {
-                    if event.event != sel_win {
+                    if event.event == sel_win {
+                        return Ok(None);
+                    }
+                    if self.same_window_group(event.event, sel_win) && self.is_visible(event.event) {
+                        self.focus(Some(event.event))?;
+                        self.update_bar()?;
+                    } else {
                         self.set_focus(sel_win)?;
                     }
                 }
             }
             Event::MappingNotify(event) => {
                 if event.request == x11rb::protocol::xproto::Mapping::KEYBOARD {
-                    self.grab_keys()?;
+                    self.refresh_keyboard_mapping()?;
                 }
             }
+            Event::XkbNewKeyboardNotify(_) | Event::XkbMapNotify(_) => {
+                self.refresh_keyboard_mapping()?;
+            }
             Event::ConfigureNotify(event) => {
+                if !self.config.border_style.is_flat() && self.clients.contains_key(&event.window) {
+                    let is_focused = self
+                        .monitors
+                        .get(self.selected_monitor)
+                        .and_then(|m| m.selected_client)
+                        == Some(event.window);
+                    self.draw_border(event.window, is_focused)?;
+                }
+
+                if self.config.border_radius > 0 {
+                    let geometry = Geometry {
+                        x: event.x,
+                        y: event.y,
+                        width: event.width,
+                        height: event.height,
+                        border_width: event.border_width,
+                    };
+                    self.update_shape(event.window, geometry)?;
+                }
+
                 if event.window == self.root {
                     let old_width = self.screen.width_in_pixels;
                     let old_height = self.screen.height_in_pixels;
@@ -3053,15 +5583,55 @@ impl WindowManager {
                     }
                 }
             }
+            Event::Error(error) => {
+                // Requests on windows that were destroyed in the meantime
+                // fail all the time and are harmless.
+                let level = match error.error_kind {
+                    x11rb::protocol::ErrorKind::Window | x11rb::protocol::ErrorKind::Drawable => log::Level::Debug,
+                    _ => log::Level::Warn,
+                };
+                log::log!(
+                    level,
+                    "X error kind={:?} request={} major={} minor={} value={:#x} sequence={}",
+                    error.error_kind,
+                    error.request_name.unwrap_or("unknown"),
+                    error.major_opcode,
+                    error.minor_opcode,
+                    error.bad_value,
+                    error.sequence
+                );
+            }
             _ => {}
         }
         Ok(None)
     }
 
+    /// Overrides for the first tag viewed on a monitor, if it has any.
+    fn tag_override(&self, monitor: &Monitor) -> Option<&crate::TagOverride> {
+        let viewed = monitor.tagset[monitor.selected_tags_index];
+        let first_tag = viewed.trailing_zeros() as usize;
+        self.config
+            .tag_overrides
+            .iter()
+            .find(|tag_override| tag_override.tag == first_tag)
+    }
+
     fn apply_layout(&mut self) -> WmResult<()> {
         for monitor_index in 0..self.monitors.len() {
-            let stack_head = self.monitors.get(monitor_index).and_then(|m| m.stack_head);
-            self.showhide(stack_head)?;
+            self.showhide(monitor_index)?;
+        }
+        self.sync_bar_visibility()?;
+        self.update_work_areas();
+
+        // Without tabs the strip goes back to the windows. Hiding the tab
+        // bars first keeps them from covering windows that moved into it.
+        let uses_tab_bar = self.layout.uses_tab_bar();
+        if !uses_tab_bar {
+            for tab_bar in &self.tab_bars {
+                if let Err(e) = tab_bar.hide(&self.connection) {
+                    log::error!("Failed to hide tab bar: {:?}", e);
+                }
+            }
         }
 
         let is_normie = self.layout.name() == LayoutType::Normie.as_str();
@@ -3070,14 +5640,17 @@ impl WindowManager {
             let monitor_count = self.monitors.len();
             for monitor_index in 0..monitor_count {
             let monitor = &self.monitors[monitor_index];
-            let border_width = self.config.border_width;
+            let tag_override = self.tag_override(monitor);
+            let gaps_enabled = tag_override
+                .and_then(|tag_override| tag_override.gaps)
+                .unwrap_or(self.gaps_enabled);
 
-            let gaps = if self.gaps_enabled {
+            let gaps = if gaps_enabled {
                 GapConfig {
-                    inner_horizontal: self.config.gap_inner_horizontal,
-                    inner_vertical: self.config.gap_inner_vertical,
-                    outer_horizontal: self.config.gap_outer_horizontal,
-                    outer_vertical: self.config.gap_outer_vertical,
+                    inner_horizontal: monitor.scaled(self.config.gap_inner_horizontal),
+                    inner_vertical: monitor.scaled(self.config.gap_inner_vertical),
+                    outer_horizontal: monitor.scaled(self.config.gap_outer_horizontal),
+                    outer_vertical: monitor.scaled(self.config.gap_outer_vertical),
                 }
             } else {
                 GapConfig {
@@ -3088,55 +5661,58 @@ impl WindowManager {
                 }
             };
 
-            let monitor_x = monitor.screen_x;
-            let monitor_y = monitor.screen_y;
-            let monitor_width = monitor.screen_width;
-            let monitor_height = monitor.screen_height;
+            let (area_x, area_y, area_width, area_height) = monitor.work_area();
 
-            let mut visible: Vec<Window> = Vec::new();
-            let mut current = self.next_tiled(monitor.clients_head, monitor);
-            while let Some(window) = current {
-                visible.push(window);
-                if let Some(client) = self.clients.get(&window) {
-                    current = self.next_tiled(client.next, monitor);
-                } else {
-                    break;
-                }
-            }
+            let visible: Vec<Window> = monitor
+                .clients
+                .iter()
+                .filter(|&window| self.is_tiled(window, monitor))
+                .collect();
 
-            let bar_height = if self.show_bar {
-                self.bars
-                    .get(monitor_index)
-                    .map(|bar| bar.height() as u32)
-                    .unwrap_or(0)
-            } else {
+            let is_monocle = self.layout.name() == LayoutType::Monocle.as_str();
+            let border_width = if self.config.smart_borders && (visible.len() == 1 || is_monocle) {
                 0
+            } else {
+                let border_width = tag_override
+                    .and_then(|tag_override| tag_override.border_width)
+                    .unwrap_or(self.config.border_width);
+                monitor.scaled(border_width)
             };
-            let usable_height = monitor_height.saturating_sub(bar_height as i32);
-            let master_factor = monitor.master_factor;
-            let num_master = monitor.num_master;
-            let smartgaps_enabled = self.config.smartgaps_enabled;
-
-            let geometries = self.layout.arrange(
-                &visible,
-                monitor_width as u32,
-                usable_height as u32,
-                &gaps,
-                master_factor,
-                num_master,
-                smartgaps_enabled,
-            );
 
-            for (window, geometry) in visible.iter().zip(geometries.iter()) {
-                let mut adjusted_width = geometry.width.saturating_sub(2 * border_width);
-                let mut adjusted_height = geometry.height.saturating_sub(2 * border_width);
+            let mut usable_height = area_height;
+            let mut tab_bar_offset = 0;
+            if uses_tab_bar {
+                let tab_bar_space = monitor.scaled(self.config.tab_bar_height + self.config.tab_bar_gap) as i32;
+                usable_height = usable_height.saturating_sub(tab_bar_space);
+                if self.config.tab_bar_position == BarPosition::Top {
+                    tab_bar_offset = tab_bar_space;
+                }
+            }
+            let arrangement = Arrangement {
+                x: area_x,
+                y: area_y + tab_bar_offset,
+                width: area_width as u32,
+                height: usable_height.max(0) as u32,
+                gaps,
+                master_factor: monitor.master_factor,
+                num_master: monitor.num_master,
+                smartgaps_enabled: self.config.smartgaps_enabled,
+                border_width,
+            };
+            let slots = arrangement.slots(self.layout.as_ref(), &visible);
+
+            for (window, slot) in visible.iter().zip(slots.iter()) {
+                let slot_width = slot.width;
+                let slot_height = slot.height;
+                let mut adjusted_width = slot_width;
+                let mut adjusted_height = slot_height;
 
                 if let Some(client) = self.clients.get(window).cloned() {
                     if !client.is_floating {
                         let (_, _, hint_width, hint_height, _) = self.apply_size_hints(
                             *window,
-                            geometry.x_coordinate as i32,
-                            geometry.y_coordinate as i32,
+                            slot.x_coordinate,
+                            slot.y_coordinate,
                             adjusted_width as i32,
                             adjusted_height as i32,
                         );
@@ -3145,125 +5721,208 @@ impl WindowManager {
                     }
                 }
 
-                let adjusted_x = geometry.x_coordinate + monitor_x;
-                let adjusted_y = geometry.y_coordinate + monitor_y + bar_height as i32;
-
-                if let Some(client) = self.clients.get_mut(window) {
-                    client.x_position = adjusted_x as i16;
-                    client.y_position = adjusted_y as i16;
-                    client.width = adjusted_width as u16;
-                    client.height = adjusted_height as u16;
-                }
+                // Pixels lost to size increments become padding on both
+                // sides instead of dead space on the right and bottom.
+                let padding_x = (slot_width.saturating_sub(adjusted_width) / 2) as i32;
+                let padding_y = (slot_height.saturating_sub(adjusted_height) / 2) as i32;
 
-                self.connection.configure_window(
-                    *window,
-                    &ConfigureWindowAux::new()
-                        .x(adjusted_x)
-                        .y(adjusted_y)
-                        .width(adjusted_width)
-                        .height(adjusted_height)
-                        .border_width(border_width),
-                )?;
+                let adjusted_x = slot.x_coordinate + padding_x;
+                let adjusted_y = slot.y_coordinate + padding_y;
 
-                if let Some(c) = self.clients.get_mut(window) {
-                    c.x_position = adjusted_x as i16;
-                    c.y_position = adjusted_y as i16;
-                    c.width = adjusted_width as u16;
-                    c.height = adjusted_height as u16;
-                    c.border_width = border_width as u16;
+                if let Some(client) = self.clients.get_mut(window) {
+                    client.configure(
+                        &self.connection,
+                        Geometry {
+                            x: adjusted_x as i16,
+                            y: adjusted_y as i16,
+                            width: adjusted_width as u16,
+                            height: adjusted_height as u16,
+                            border_width: border_width as u16,
+                        },
+                    )?;
                 }
             }
             }
         }
 
-        for monitor_index in 0..self.monitors.len() {
-            let stack_head = self.monitors[monitor_index].stack_head;
-            self.showhide(stack_head)?;
-        }
-
-        self.connection.flush()?;
-
-        let is_tabbed = self.layout.name() == LayoutType::Tabbed.as_str();
-
-        if is_tabbed {
-            let outer_horizontal = if self.gaps_enabled {
-                self.config.gap_outer_horizontal
-            } else {
-                0
-            };
-            let outer_vertical = if self.gaps_enabled {
-                self.config.gap_outer_vertical
-            } else {
-                0
-            };
-
+        if uses_tab_bar {
             for monitor_index in 0..self.tab_bars.len() {
                 if let Some(monitor) = self.monitors.get(monitor_index) {
-                    let bar_height = if self.show_bar {
-                        self.bars
-                            .get(monitor_index)
-                            .map(|bar| bar.height() as f32)
-                            .unwrap_or(0.0)
+                    let gaps_enabled = self
+                        .tag_override(monitor)
+                        .and_then(|tag_override| tag_override.gaps)
+                        .unwrap_or(self.gaps_enabled);
+                    let (outer_horizontal, outer_vertical) = if gaps_enabled {
+                        (
+                            monitor.scaled(self.config.gap_outer_horizontal),
+                            monitor.scaled(self.config.gap_outer_vertical),
+                        )
                     } else {
-                        0.0
+                        (0, 0)
                     };
-
-                    let tab_bar_x = (monitor.screen_x + outer_horizontal as i32) as i16;
-                    let tab_bar_y = (monitor.screen_y as f32 + bar_height + outer_vertical as f32) as i16;
-                    let tab_bar_width = monitor.screen_width.saturating_sub(2 * outer_horizontal as i32) as u16;
+                    let (area_x, area_y, area_width, area_height) = monitor.work_area();
+
+                    let tab_bar_height = monitor.scaled(self.config.tab_bar_height);
+                    let tab_bar_x = (area_x + outer_horizontal as i32) as i16;
+                    let tab_bar_y = match self.config.tab_bar_position {
+                        BarPosition::Top => (area_y + outer_vertical as i32) as i16,
+                        BarPosition::Bottom => (area_y + area_height
+                            - outer_vertical as i32
+                            - tab_bar_height as i32) as i16,
+                    };
+                    let tab_bar_width = area_width.saturating_sub(2 * outer_horizontal as i32) as u16;
 
                     if let Err(e) = self.tab_bars[monitor_index].reposition(
                         &self.connection,
                         tab_bar_x,
                         tab_bar_y,
                         tab_bar_width,
+                        tab_bar_height as u16,
                     ) {
-                        eprintln!("Failed to reposition tab bar: {:?}", e);
+                        log::error!("Failed to reposition tab bar: {:?}", e);
                     }
                 }
             }
-        }
-
-        for monitor_index in 0..self.tab_bars.len() {
-            let has_visible_windows = self
-                .windows
-                .iter()
-                .any(|&window| {
-                    if let Some(client) = self.clients.get(&window) {
-                        if client.monitor_index != monitor_index
-                            || self.floating_windows.contains(&window)
-                            || self.fullscreen_windows.contains(&window)
-                        {
-                            return false;
-                        }
-                        if let Some(monitor) = self.monitors.get(monitor_index) {
-                            return (client.tags & monitor.tagset[monitor.selected_tags_index]) != 0;
-                        }
-                    }
-                    false
-                });
 
-            if is_tabbed && has_visible_windows {
-                if let Err(e) = self.tab_bars[monitor_index].show(&self.connection) {
-                    eprintln!("Failed to show tab bar: {:?}", e);
-                }
-            } else {
-                if let Err(e) = self.tab_bars[monitor_index].hide(&self.connection) {
-                    eprintln!("Failed to hide tab bar: {:?}", e);
+            for monitor_index in 0..self.tab_bars.len() {
+                let has_visible_windows = !self.tab_bar_windows(monitor_index).is_empty();
+                let result = if has_visible_windows {
+                    self.tab_bars[monitor_index].show(&self.connection)
+                } else {
+                    self.tab_bars[monitor_index].hide(&self.connection)
+                };
+                if let Err(e) = result {
+                    log::error!("Failed to show or hide tab bar: {:?}", e);
                 }
             }
-        }
 
-        if is_tabbed {
             self.update_tab_bars()?;
         }
 
         Ok(())
     }
 
-    pub fn change_layout<L: Layout + 'static>(&mut self, new_layout: L) -> WmResult<()> {
-        self.layout = Box::new(new_layout);
-        self.apply_layout()?;
+    pub fn change_layout<L: Layout + 'static>(&mut self, new_layout: L) -> WmResult<()> {
+        self.layout = Box::new(new_layout);
+        self.apply_layout()?;
+        Ok(())
+    }
+
+    /// Rounds the corners of a client with `geometry`, border included, or
+    /// makes it square again when it is fullscreen or `border_radius` is 0.
+    fn update_shape(&self, window: Window, geometry: Geometry) -> WmResult<()> {
+        let Some(client) = self.clients.get(&window) else {
+            return Ok(());
+        };
+        if !crate::shape::is_supported(&self.connection) {
+            return Ok(());
+        }
+
+        if client.is_fullscreen || self.config.border_radius == 0 {
+            crate::shape::clear(&self.connection, window)?;
+        } else {
+            let border_width = geometry.border_width as i16;
+            crate::shape::round_corners(
+                &self.connection,
+                window,
+                (-border_width, -border_width),
+                (
+                    geometry.width + geometry.border_width * 2,
+                    geometry.height + geometry.border_width * 2,
+                ),
+                self.config.border_radius as u16,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn draw_border(&self, window: Window, focused: bool) -> WmResult<()> {
+        let Some(client) = self.clients.get(&window) else {
+            return Ok(());
+        };
+
+        let style = &self.config.border_style;
+        let (color, gradient) = if focused {
+            (self.config.border_focused, style.focused)
+        } else if client.is_urgent {
+            (
+                self.config.border_urgent.unwrap_or(self.config.border_unfocused),
+                style.urgent,
+            )
+        } else {
+            (self.config.border_unfocused, style.unfocused)
+        };
+
+        // Border pixmaps must match the window depth, so ARGB windows keep flat borders.
+        let border_width = client.border_width;
+        if style.is_flat() || border_width == 0 || client.depth != self.screen.root_depth {
+            self.connection.change_window_attributes(
+                window,
+                &ChangeWindowAttributesAux::new().border_pixel(color),
+            )?;
+            return Ok(());
+        }
+
+        // The border tile origin is the window's inner top-left corner, so the right
+        // and bottom borders start at the window size and the left and top borders
+        // wrap around to the end of the pixmap.
+        let width = client.width;
+        let height = client.height;
+        let pixmap_width = width + 2 * border_width;
+        let pixmap_height = height + 2 * border_width;
+
+        let pixmap = self.connection.generate_id()?;
+        let graphics_context = self.connection.generate_id()?;
+        self.connection
+            .create_pixmap(client.depth, pixmap, window, pixmap_width, pixmap_height)?;
+        self.connection
+            .create_gc(graphics_context, pixmap, &CreateGCAux::new().foreground(color))?;
+
+        for distance in 0..border_width {
+            let ring_color = if (distance as u32) < style.inner_width {
+                style.inner_color
+            } else if let Some(gradient) = gradient {
+                let steps = (border_width as u32).saturating_sub(style.inner_width + 1).max(1);
+                let step = distance as u32 - style.inner_width;
+                blend_colors(gradient.inner, gradient.outer, step as f32 / steps as f32)
+            } else {
+                color
+            };
+
+            self.connection.change_gc(
+                graphics_context,
+                &ChangeGCAux::new().foreground(ring_color),
+            )?;
+            self.connection.poly_fill_rectangle(
+                pixmap,
+                graphics_context,
+                &[
+                    Rectangle { x: (width + distance) as i16, y: 0, width: 1, height: pixmap_height },
+                    Rectangle { x: (pixmap_width - 1 - distance) as i16, y: 0, width: 1, height: pixmap_height },
+                    Rectangle { x: 0, y: (height + distance) as i16, width: pixmap_width, height: 1 },
+                    Rectangle { x: 0, y: (pixmap_height - 1 - distance) as i16, width: pixmap_width, height: 1 },
+                ],
+            )?;
+        }
+
+        self.connection.change_window_attributes(
+            window,
+            &ChangeWindowAttributesAux::new().border_pixmap(pixmap),
+        )?;
+        self.connection.free_gc(graphics_context)?;
+        self.connection.free_pixmap(pixmap)?;
+        Ok(())
+    }
+
+    /// ICCCM 4.1.8: the window manager installs the colormap of the focused client.
+    fn install_colormap(&self, window: Window) -> WmResult<()> {
+        let colormap = self
+            .clients
+            .get(&window)
+            .map(|client| client.colormap)
+            .filter(|&colormap| colormap != x11rb::NONE)
+            .unwrap_or(self.screen.default_colormap);
+        self.connection.install_colormap(colormap)?;
         Ok(())
     }
 
@@ -3479,6 +6138,7 @@ impl WindowManager {
                     if let Some(client) = self.clients.get_mut(&window) {
                         client.is_urgent = (flags & 256) != 0;
                     }
+                    self.draw_border(window, false)?;
                 }
 
                 if hints.value.len() >= 8 && (flags & 1) != 0 {
@@ -3497,6 +6157,21 @@ impl WindowManager {
                         client.never_focus = false;
                     }
                 }
+
+                const WINDOW_GROUP_HINT: u32 = 64;
+                let window_group = (hints.value.len() >= 36 && (flags & WINDOW_GROUP_HINT) != 0)
+                    .then(|| {
+                        u32::from_ne_bytes([
+                            hints.value[32],
+                            hints.value[33],
+                            hints.value[34],
+                            hints.value[35],
+                        ])
+                    })
+                    .filter(|&leader| leader != x11rb::NONE);
+                if let Some(client) = self.clients.get_mut(&window) {
+                    client.window_group = window_group;
+                }
             }
         }
 
@@ -3504,24 +6179,276 @@ impl WindowManager {
     }
 
     fn update_window_type(&mut self, window: Window) -> WmResult<()> {
-        if let Ok(Some(state_atom)) = self.get_window_atom_property(window, self.atoms.net_wm_state) {
-            if state_atom == self.atoms.net_wm_state_fullscreen {
-                self.set_window_fullscreen(window, true)?;
+        let states = self.get_window_atoms(window, self.atoms.net_wm_state)?;
+        if states.contains(&self.atoms.net_wm_state_fullscreen) {
+            self.set_window_fullscreen(window, true)?;
+        }
+        let is_selected = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client)
+            == Some(window);
+        if states.contains(&self.atoms.net_wm_state_demands_attention) && !is_selected {
+            self.set_urgent(window, true)?;
+        }
+
+        let window_type = match self.get_window_atom_property(window, self.atoms.net_wm_window_type) {
+            Ok(Some(atom)) if atom == self.atoms.net_wm_window_type_dialog => WindowType::Dialog,
+            Ok(Some(atom)) if atom == self.atoms.net_wm_window_type_utility => WindowType::Utility,
+            Ok(Some(atom)) if atom == self.atoms.net_wm_window_type_toolbar => WindowType::Toolbar,
+            Ok(Some(atom)) if atom == self.atoms.net_wm_window_type_splash => WindowType::Splash,
+            Ok(Some(atom)) if atom == self.atoms.net_wm_window_type_notification => {
+                WindowType::Notification
             }
+            _ => WindowType::Normal,
+        };
+
+        let Some(client) = self.clients.get_mut(&window) else {
+            return Ok(());
+        };
+        client.window_type = window_type;
+
+        if window_type == WindowType::Normal {
+            return Ok(());
         }
 
-        if let Ok(Some(type_atom)) = self.get_window_atom_property(window, self.atoms.net_wm_window_type) {
-            if type_atom == self.atoms.net_wm_window_type_dialog {
-                if let Some(client) = self.clients.get_mut(&window) {
-                    client.is_floating = true;
-                }
-                self.floating_windows.insert(window);
+        client.is_floating = true;
+        self.floating_windows.insert(window);
+
+        if !window_type.takes_focus() {
+            client.border_width = 0;
+            self.connection.configure_window(
+                window,
+                &ConfigureWindowAux::new().border_width(0),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Height of the bar on a monitor, or 0 while it is hidden.
+    fn bar_height(&self, monitor_index: usize) -> i32 {
+        let shows_bar = self.monitors.get(monitor_index).is_some_and(|m| m.show_bar);
+        if !shows_bar {
+            return 0;
+        }
+        self.bars
+            .get(monitor_index)
+            .map(|bar| bar.height() as i32)
+            .unwrap_or(0)
+    }
+
+    fn toggle_bar(&mut self) -> WmResult<()> {
+        let monitor_index = self.selected_monitor;
+        let Some(monitor) = self.monitors.get_mut(monitor_index) else {
+            return Ok(());
+        };
+        monitor.show_bar = !monitor.show_bar;
+        let show_bar = monitor.show_bar;
+
+        self.apply_layout()?;
+        if show_bar {
+            self.update_bar()?;
+        }
+        Ok(())
+    }
+
+    /// Maps each monitor's bar when it should be shown, and unmaps it while
+    /// toggled off or while a visible client on that monitor is fullscreen.
+    /// Other monitors keep their bars.
+    fn sync_bar_visibility(&mut self) -> WmResult<()> {
+        for monitor_index in 0..self.monitors.len() {
+            let covered = self.fullscreen_windows.iter().any(|&window| {
+                self.clients.get(&window).is_some_and(|client| {
+                    client.monitor_index == monitor_index || self.spanning_windows.contains(&window)
+                }) && self.is_window_visible(window)
+            });
+            let Some(bar) = self.bars.get(monitor_index) else {
+                continue;
+            };
+            let monitor = &mut self.monitors[monitor_index];
+            let visible = monitor.show_bar && !covered;
+            if visible == monitor.bar_mapped {
+                continue;
+            }
+
+            if visible {
+                self.connection.map(bar.window())?;
+            } else {
+                self.connection.unmap(bar.window())?;
+            }
+            monitor.bar_mapped = visible;
+        }
+        Ok(())
+    }
+
+    /// Recomputes every monitor's work area from its bar, the dock struts
+    /// and the configured padding.
+    fn update_work_areas(&mut self) {
+        for monitor_index in 0..self.monitors.len() {
+            let reserved = self.dock_reserved_space(&self.monitors[monitor_index]);
+            let bar_height = self.bar_height(monitor_index);
+            self.monitors[monitor_index].update_work_area(reserved, bar_height);
+        }
+    }
+
+    fn place_floating(&self, window: Window, monitor_index: usize) -> WmResult<Option<(i32, i32)>> {
+        const CASCADE_STEP: u32 = 32;
+
+        let (Some(client), Some(monitor)) = (self.clients.get(&window), self.monitors.get(monitor_index)) else {
+            return Ok(None);
+        };
+
+        let width = client.width_with_border() as i32;
+        let height = client.height_with_border() as i32;
+        let area = self.monitors[monitor_index].work_area();
+        let (area_x, area_y, area_width, area_height) = area;
+
+        let others: Vec<(i32, i32, i32, i32)> = self
+            .floating_windows
+            .iter()
+            .filter(|&&other| other != window && self.is_window_visible(other))
+            .filter_map(|other| self.clients.get(other))
+            .filter(|other| other.monitor_index == monitor_index)
+            .map(|other| {
+                (
+                    other.x_position as i32,
+                    other.y_position as i32,
+                    other.width_with_border() as i32,
+                    other.height_with_border() as i32,
+                )
+            })
+            .collect();
+
+        let (x, y) = match self.config.floating_placement {
+            crate::FloatingPlacement::Requested => return Ok(None),
+            crate::FloatingPlacement::Center => (
+                area_x + (area_width - width) / 2,
+                area_y + (area_height - height) / 2,
+            ),
+            crate::FloatingPlacement::UnderPointer => {
+                let (pointer_x, pointer_y) = self.connection.pointer_position(self.root)?;
+                (pointer_x - width / 2, pointer_y - height / 2)
             }
+            crate::FloatingPlacement::Cascade => {
+                let step = monitor.scaled(CASCADE_STEP).max(1) as i32;
+                let max_steps = ((area_width - width).min(area_height - height) / step).max(1);
+                let offset = step * (others.len() as i32 % max_steps);
+                (area_x + offset, area_y + offset)
+            }
+            crate::FloatingPlacement::Smart => smart_placement(area, (width, height), &others),
+        };
+
+        let x = x.clamp(area_x, (area_x + area_width - width).max(area_x));
+        let y = y.clamp(area_y, (area_y + area_height - height).max(area_y));
+        Ok(Some((x, y)))
+    }
+
+    /// Where a floating window goes centered over `parent`, kept inside the
+    /// work area of the parent's monitor. None with requested placement.
+    fn center_over_parent(&self, window: Window, parent: Window) -> Option<(i32, i32)> {
+        if self.config.floating_placement == crate::FloatingPlacement::Requested {
+            return None;
+        }
+
+        let client = self.clients.get(&window)?;
+        let parent_client = self.clients.get(&parent)?;
+        let (area_x, area_y, area_width, area_height) =
+            self.monitors.get(parent_client.monitor_index)?.work_area();
+
+        let width = client.width_with_border() as i32;
+        let height = client.height_with_border() as i32;
+        let x = parent_client.x_position as i32 + (parent_client.width_with_border() as i32 - width) / 2;
+        let y = parent_client.y_position as i32 + (parent_client.height_with_border() as i32 - height) / 2;
+
+        let x = x.clamp(area_x, (area_x + area_width - width).max(area_x));
+        let y = y.clamp(area_y, (area_y + area_height - height).max(area_y));
+        Some((x, y))
+    }
+
+    /// The leader of a utility window's group, when the window names no
+    /// parent through WM_TRANSIENT_FOR. Toolboxes and palettes often only
+    /// say which application they belong to that way.
+    fn utility_group_leader(&self, window: Window) -> Option<Window> {
+        let client = self.clients.get(&window)?;
+        if client.window_type != WindowType::Utility {
+            return None;
         }
+        client
+            .window_group
+            .filter(|&leader| leader != window && self.clients.contains_key(&leader))
+    }
+
+    fn is_dock(&self, window: Window) -> bool {
+        matches!(
+            self.get_window_atom_property(window, self.atoms.net_wm_window_type),
+            Ok(Some(atom)) if atom == self.atoms.net_wm_window_type_dock
+        )
+    }
+
+    fn read_strut(&self, window: Window) -> Option<Strut> {
+        [self.atoms.net_wm_strut_partial, self.atoms.net_wm_strut]
+            .into_iter()
+            .find_map(|property| {
+                let reply = self
+                    .connection
+                    .get_property(false, window, property, AtomEnum::CARDINAL, 0, 12)
+                    .ok()?
+                    .reply()
+                    .ok()?;
+                let values: Vec<u32> = reply.value32()?.collect();
+                Strut::from_values(&values)
+            })
+    }
+
+    /// Docks are mapped but never managed; their struts shrink the area
+    /// available to the layout.
+    fn manage_dock(&mut self, window: Window) -> WmResult<()> {
+        let strut = self.read_strut(window).unwrap_or_default();
+        self.docks.insert(window, strut);
 
+        self.connection.change_window_attributes(
+            window,
+            &ChangeWindowAttributesAux::new()
+                .event_mask(EventMask::PROPERTY_CHANGE | EventMask::STRUCTURE_NOTIFY),
+        )?;
+        self.connection.map(window)?;
+        self.apply_layout()?;
         Ok(())
     }
 
+    /// Space taken from each edge of a monitor as `(left, right, top,
+    /// bottom)`: the largest dock strut plus the configured padding.
+    fn dock_reserved_space(&self, monitor: &Monitor) -> (i32, i32, i32, i32) {
+        let root_width = self.screen.width_in_pixels as i32;
+        let root_height = self.screen.height_in_pixels as i32;
+
+        let (left, right, top, bottom) = self.docks.values().fold((0, 0, 0, 0), |reserved, strut| {
+            let (left, right, top, bottom) = monitor.reserved_by(strut, root_width, root_height);
+            (
+                reserved.0.max(left),
+                reserved.1.max(right),
+                reserved.2.max(top),
+                reserved.3.max(bottom),
+            )
+        });
+
+        match self
+            .config
+            .monitor_padding
+            .iter()
+            .find(|padding| padding.monitor == monitor.monitor_number)
+        {
+            Some(padding) => (
+                left + padding.left as i32,
+                right + padding.right as i32,
+                top + padding.top as i32,
+                bottom + padding.bottom as i32,
+            ),
+            None => (left, right, top, bottom),
+        }
+    }
+
     fn apply_size_hints(&mut self, window: Window, mut x: i32, mut y: i32, mut w: i32, mut h: i32) -> (i32, i32, i32, i32, bool) {
         let bh = 20;
 
@@ -3541,17 +6468,18 @@ impl WindowManager {
         w = w.max(1);
         h = h.max(1);
 
-        if x >= monitor.window_area_x + monitor.window_area_width {
-            x = monitor.window_area_x + monitor.window_area_width - client_width;
+        let (area_x, area_y, area_width, area_height) = monitor.work_area();
+        if x >= area_x + area_width {
+            x = area_x + area_width - client_width;
         }
-        if y >= monitor.window_area_y + monitor.window_area_height {
-            y = monitor.window_area_y + monitor.window_area_height - client_height;
+        if y >= area_y + area_height {
+            y = area_y + area_height - client_height;
         }
-        if x + w + 2 * bw <= monitor.window_area_x {
-            x = monitor.window_area_x;
+        if x + w + 2 * bw <= area_x {
+            x = area_x;
         }
-        if y + h + 2 * bw <= monitor.window_area_y {
-            y = monitor.window_area_y;
+        if y + h + 2 * bw <= area_y {
+            y = area_y;
         }
 
         if h < bh {
@@ -3561,7 +6489,7 @@ impl WindowManager {
             w = bh;
         }
 
-        if is_floating || self.layout.name() == "normie" {
+        if is_floating || self.layout.name() == "normie" || self.resize_hints_enabled() {
             if !hints_valid {
                 let _ = self.update_size_hints(window);
                 hints_valid = self.clients.get(&window).map(|c| c.hints_valid).unwrap_or(false);
@@ -3619,160 +6547,254 @@ impl WindowManager {
         (x, y, w, h, changed)
     }
 
-    fn next_tiled(&self, start: Option<Window>, monitor: &Monitor) -> Option<Window> {
-        let mut current = start;
-        while let Some(window) = current {
-            if let Some(client) = self.clients.get(&window) {
-                let visible_tags = client.tags & monitor.tagset[monitor.selected_tags_index];
-                if visible_tags != 0 && !client.is_floating {
-                    return Some(window);
-                }
-                current = client.next;
-            } else {
-                break;
-            }
-        }
-        None
+    /// Whether the current layout keeps tiled clients to their size hints.
+    fn resize_hints_enabled(&self) -> bool {
+        self.config
+            .layout_resize_hints
+            .get(self.layout.name())
+            .copied()
+            .unwrap_or(self.config.resize_hints)
     }
 
-    fn next_tagged(&self, start: Option<Window>, tags: u32) -> Option<Window> {
-        let mut current = start;
-        while let Some(window) = current {
-            if let Some(client) = self.clients.get(&window) {
-                let visible_on_tags = (client.tags & tags) != 0;
-                if !client.is_floating && visible_on_tags {
-                    return Some(window);
-                }
-                current = client.next;
-            } else {
-                break;
-            }
-        }
-        None
+    fn is_tiled(&self, window: Window, monitor: &Monitor) -> bool {
+        self.clients.get(&window).is_some_and(|client| {
+            let visible_tags = client.tags & monitor.tagset[monitor.selected_tags_index];
+            visible_tags != 0 && !client.is_floating
+        })
     }
 
     fn attach(&mut self, window: Window, monitor_index: usize) {
         if let Some(monitor) = self.monitors.get_mut(monitor_index) {
-            if let Some(client) = self.clients.get_mut(&window) {
-                client.next = monitor.clients_head;
-                monitor.clients_head = Some(window);
-            }
+            monitor.clients.attach(window);
         }
     }
 
     fn attach_aside(&mut self, window: Window, monitor_index: usize) {
-        let monitor = match self.monitors.get(monitor_index) {
-            Some(m) => m,
-            None => return,
+        let Some(monitor) = self.monitors.get(monitor_index) else {
+            return;
         };
 
         let new_window_tags = self.clients.get(&window).map(|c| c.tags).unwrap_or(0);
-        let first_tagged = self.next_tagged(monitor.clients_head, new_window_tags);
+        let first_tagged = monitor.clients.iter().find(|other| {
+            self.clients
+                .get(other)
+                .is_some_and(|client| !client.is_floating && client.tags & new_window_tags != 0)
+        });
 
-        if first_tagged.is_none() {
-            self.attach(window, monitor_index);
-            return;
+        match first_tagged {
+            Some(insert_after_window) => {
+                if let Some(monitor) = self.monitors.get_mut(monitor_index) {
+                    monitor.clients.attach_after(window, insert_after_window);
+                }
+            }
+            None => self.attach(window, monitor_index),
         }
+    }
 
-        if let Some(insert_after_window) = first_tagged {
-            if let Some(after_client) = self.clients.get(&insert_after_window) {
-                let old_next = after_client.next;
-                if let Some(new_client) = self.clients.get_mut(&window) {
-                    new_client.next = old_next;
+    /// Attaches a newly managed window where its rule or the config says.
+    fn attach_new(&mut self, window: Window, monitor_index: usize) {
+        let position = self
+            .clients
+            .get(&window)
+            .and_then(|client| client.attach_position)
+            .unwrap_or(self.config.attach_position);
+        let focused = self
+            .monitors
+            .get(monitor_index)
+            .and_then(|monitor| monitor.selected_client)
+            .filter(|&focused| focused != window);
+
+        match (position, focused) {
+            (crate::AttachPosition::Master, _) => self.attach(window, monitor_index),
+            (crate::AttachPosition::Bottom, _) => {
+                if let Some(monitor) = self.monitors.get_mut(monitor_index) {
+                    monitor.clients.attach_bottom(window);
+                }
+            }
+            (crate::AttachPosition::AboveFocused, Some(focused)) => {
+                if let Some(monitor) = self.monitors.get_mut(monitor_index) {
+                    monitor.clients.attach_before(window, focused);
                 }
-                if let Some(after_client_mut) = self.clients.get_mut(&insert_after_window) {
-                    after_client_mut.next = Some(window);
+            }
+            (crate::AttachPosition::AfterFocused, Some(focused)) => {
+                if let Some(monitor) = self.monitors.get_mut(monitor_index) {
+                    monitor.clients.attach_after(window, focused);
                 }
             }
+            _ => self.attach_aside(window, monitor_index),
         }
     }
 
     fn detach(&mut self, window: Window) {
         let monitor_index = self.clients.get(&window).map(|c| c.monitor_index);
-        if let Some(monitor_index) = monitor_index {
-            if let Some(monitor) = self.monitors.get_mut(monitor_index) {
-                if monitor.clients_head == Some(window) {
-                    if let Some(client) = self.clients.get(&window) {
-                        monitor.clients_head = client.next;
-                    }
-                } else {
-                    let mut current = monitor.clients_head;
-                    while let Some(current_window) = current {
-                        if let Some(current_client) = self.clients.get(&current_window) {
-                            if current_client.next == Some(window) {
-                                let new_next = self.clients.get(&window).and_then(|c| c.next);
-                                if let Some(current_client_mut) = self.clients.get_mut(&current_window) {
-                                    current_client_mut.next = new_next;
-                                }
-                                break;
-                            }
-                            current = current_client.next;
-                        } else {
-                            break;
-                        }
-                    }
-                }
-            }
+        if let Some(monitor) = monitor_index.and_then(|index| self.monitors.get_mut(index)) {
+            monitor.clients.detach(window);
         }
     }
 
     fn attach_stack(&mut self, window: Window, monitor_index: usize) {
         if let Some(monitor) = self.monitors.get_mut(monitor_index) {
-            if let Some(client) = self.clients.get_mut(&window) {
-                client.stack_next = monitor.stack_head;
-                monitor.stack_head = Some(window);
-            }
+            monitor.clients.attach_stack(window);
         }
     }
 
     fn detach_stack(&mut self, window: Window) {
-        let monitor_index = self.clients.get(&window).map(|c| c.monitor_index);
-        if let Some(monitor_index) = monitor_index {
-            if let Some(monitor) = self.monitors.get_mut(monitor_index) {
-                if monitor.stack_head == Some(window) {
-                    if let Some(client) = self.clients.get(&window) {
-                        monitor.stack_head = client.stack_next;
-                    }
-                    let should_update_selected = monitor.selected_client == Some(window);
-                    let mut new_selected: Option<Window> = None;
-                    if should_update_selected {
-                        let mut stack_current = monitor.stack_head;
-                        while let Some(stack_window) = stack_current {
-                            if let Some(stack_client) = self.clients.get(&stack_window) {
-                                if self.is_window_visible(stack_window) {
-                                    new_selected = Some(stack_window);
-                                    break;
-                                }
-                                stack_current = stack_client.stack_next;
-                            } else {
-                                break;
-                            }
-                        }
-                    }
-                    if should_update_selected {
-                        if let Some(monitor) = self.monitors.get_mut(monitor_index) {
-                            monitor.selected_client = new_selected;
-                        }
-                    }
-                } else {
-                    let mut current = monitor.stack_head;
-                    while let Some(current_window) = current {
-                        if let Some(current_client) = self.clients.get(&current_window) {
-                            if current_client.stack_next == Some(window) {
-                                let new_stack_next = self.clients.get(&window).and_then(|c| c.stack_next);
-                                if let Some(current_client_mut) = self.clients.get_mut(&current_window) {
-                                    current_client_mut.stack_next = new_stack_next;
-                                }
-                                break;
-                            }
-                            current = current_client.stack_next;
-                        } else {
-                            break;
-                        }
-                    }
-                }
-            }
+        let Some(monitor_index) = self.clients.get(&window).map(|c| c.monitor_index) else {
+            return;
+        };
+        let Some(monitor) = self.monitors.get_mut(monitor_index) else {
+            return;
+        };
+
+        monitor.clients.detach_stack(window);
+
+        if monitor.selected_client == Some(window) {
+            let new_selected = self.monitors[monitor_index]
+                .clients
+                .stack()
+                .find(|&stack_window| self.is_window_visible(stack_window));
+            self.monitors[monitor_index].selected_client = new_selected;
+        }
+    }
+
+    /// Iconifies a client: it leaves the layout and focus order and is
+    /// unmapped until restored.
+    fn minimize(&mut self, window: Window) -> WmResult<()> {
+        if !self.clients.contains_key(&window) || self.minimized.contains(&window) {
+            return Ok(());
+        }
+
+        self.detach(window);
+        self.detach_stack(window);
+        self.minimized.push(window);
+
+        const ICONIC_STATE: u32 = 3;
+        self.set_wm_state(window, ICONIC_STATE)?;
+        self.connection.unmap(window)?;
+
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()?;
+        if self.layout.uses_tab_bar() {
+            self.update_tab_bars()?;
+        }
+        Ok(())
+    }
+
+    fn restore(&mut self, window: Window) -> WmResult<()> {
+        if !self.minimized.contains(&window) {
+            return Ok(());
+        }
+        self.minimized.retain(|&w| w != window);
+
+        let Some((monitor_index, tags)) = self
+            .clients
+            .get(&window)
+            .map(|client| (client.monitor_index, client.tags))
+        else {
+            return Ok(());
+        };
+
+        self.attach(window, monitor_index);
+        self.attach_stack(window, monitor_index);
+
+        const NORMAL_STATE: u32 = 1;
+        self.set_wm_state(window, NORMAL_STATE)?;
+        self.connection.map(window)?;
+
+        self.selected_monitor = monitor_index;
+        if !self.is_window_visible(window) {
+            self.view_tag(tags.trailing_zeros() as usize)?;
+        }
+
+        self.focus(Some(window))?;
+        self.apply_layout()?;
+        self.update_bar()?;
+        if self.layout.uses_tab_bar() {
+            self.update_tab_bars()?;
+        }
+        Ok(())
+    }
+
+    /// Brings a client into view wherever it is, switching to its monitor
+    /// and tag, and focuses it.
+    fn activate(&mut self, window: Window) -> WmResult<()> {
+        if self.minimized.contains(&window) {
+            return self.restore(window);
+        }
+        if self.stashed.contains(&window) {
+            return self.unstash(window);
+        }
+        let Some((monitor_index, tags)) = self
+            .clients
+            .get(&window)
+            .map(|client| (client.monitor_index, client.tags))
+        else {
+            return Ok(());
+        };
+
+        self.selected_monitor = monitor_index;
+        if !self.is_window_visible(window) {
+            self.view_tag(tags.trailing_zeros() as usize)?;
+        }
+
+        self.focus(Some(window))?;
+        self.restack()?;
+        self.update_bar()?;
+        if self.layout.uses_tab_bar() {
+            self.update_tab_bars()?;
+        }
+        Ok(())
+    }
+
+    /// Moves a window to the hidden stash tag.
+    fn stash(&mut self, window: Window) -> WmResult<()> {
+        let Some(client) = self.clients.get_mut(&window) else {
+            return Ok(());
+        };
+        client.tags = STASH_TAG;
+        self.stashed.retain(|&w| w != window);
+        self.stashed.push(window);
+
+        if let Err(error) = self.save_client_tag(window, STASH_TAG) {
+            log::error!("Failed to save client tag: {:?}", error);
+        }
+
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()?;
+        Ok(())
+    }
+
+    /// Brings a stashed window back onto the tags viewed on the selected
+    /// monitor.
+    fn unstash(&mut self, window: Window) -> WmResult<()> {
+        if !self.stashed.contains(&window) {
+            return Ok(());
+        }
+        self.stashed.retain(|&w| w != window);
+
+        let Some(tags) = self
+            .monitors
+            .get(self.selected_monitor)
+            .map(|m| m.tagset[m.selected_tags_index])
+        else {
+            return Ok(());
+        };
+
+        self.move_window_to_monitor(window, self.selected_monitor)?;
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.tags = tags;
+        }
+        if let Err(error) = self.save_client_tag(window, tags) {
+            log::error!("Failed to save client tag: {:?}", error);
         }
+
+        self.focus(Some(window))?;
+        self.apply_layout()?;
+        self.restack()?;
+        self.update_bar()?;
+        Ok(())
     }
 
     fn remove_window(&mut self, window: Window) -> WmResult<()> {
@@ -3784,13 +6806,18 @@ impl WindowManager {
             .and_then(|m| m.selected_client);
 
         if self.clients.contains_key(&window) {
+            log::debug!("unmanage window={:#x}", window);
             self.detach(window);
             self.detach_stack(window);
             self.clients.remove(&window);
+            self.release_rule_layout(window);
         }
 
         self.windows.retain(|&w| w != window);
         self.floating_windows.remove(&window);
+        self.spanning_windows.remove(&window);
+        self.minimized.retain(|&w| w != window);
+        self.stashed.retain(|&w| w != window);
 
         if self.windows.len() < initial_count {
             if focused == Some(window) {
@@ -3814,8 +6841,9 @@ impl WindowManager {
                 .arg("-c")
                 .arg(command)
                 .spawn()
+                .map(crate::reaper::track)
                 .map_err(|e| WmError::Autostart(command.clone(), e))?;
-            eprintln!("[autostart] Spawned: {}", command);
+            log::info!("Spawned: {}", command);
         }
         Ok(())
     }
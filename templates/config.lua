@@ -25,9 +25,11 @@ local modkey = "Mod4"
 local terminal = "alacritty"
 
 -- Color palette - customize these to match your theme
--- Alternatively you can import other files in here, such as 
--- local colors = require("colors.lua") and make colors.lua a file
--- in the ~/.config/oxwm directory
+-- Alternatively you can import other files in here, such as
+-- local colors = require("colors") and make colors.lua a file
+-- in the ~/.config/oxwm directory (or ~/.config/oxwm/lib)
+-- To follow your terminal theme (e.g. pywal), read colors from xrdb:
+-- blue = oxwm.xresources("color4", "#6dade3")
 local colors = {
     fg = "#bbbbbb",
     red = "#f7768e",
@@ -56,6 +58,9 @@ local blocks = {
         interval = 5,
         color = colors.light_blue,
         underline = true,
+        -- Clicks can run a different action per button, or call a function:
+        -- on_click = function(click) if click.button == "right" then return oxwm.spawn({ terminal, "-e", "htop" }) end end
+        on_click = { left = oxwm.spawn({ terminal, "-e", "htop" }) },
     }),
     oxwm.bar.block.static({
         text = " │  ",
@@ -106,10 +111,13 @@ oxwm.set_tags(tags)
 -- Layouts
 -------------------------------------------------------------------------------
 -- Set custom symbols for layouts (displayed in the status bar)
--- Available layouts: "tiling", "normie" (floating), "grid", "monocle", "tabbed"
+-- Available layouts: "tiling", "bstack", "normie" (floating), "grid", "monocle", "tabbed"
 oxwm.set_layout_symbol("tiling", "[T]")
 oxwm.set_layout_symbol("normie", "[F]")
 oxwm.set_layout_symbol("tabbed", "[=]")
+-- Or register a layout to set its symbol and whether cycling reaches it at once:
+-- local grid = oxwm.layout.register("grid", { symbol = "[#]", cycle = false })
+-- oxwm.key.bind({ modkey }, "G", grid.set())
 
 -------------------------------------------------------------------------------
 -- Appearance
@@ -122,6 +130,10 @@ oxwm.border.set_width(2)
 oxwm.border.set_focused_color(colors.blue)
 -- Color of unfocused window borders
 oxwm.border.set_unfocused_color(colors.grey)
+-- Hide borders when only one window is visible (or in monocle)
+oxwm.border.set_smart(false)
+-- Rounded window corners in pixels (0 keeps them square):
+-- oxwm.border.set_radius(8)
 
 -- Smart Enabled = No border if 1 window
 oxwm.gaps.set_smart(enabled)
@@ -129,6 +141,10 @@ oxwm.gaps.set_smart(enabled)
 oxwm.gaps.set_inner(5, 5)
 -- Outer gaps (horizontal, vertical) in pixels
 oxwm.gaps.set_outer(5, 5)
+-- Per-tag overrides, e.g. no gaps or borders on tag 9
+-- oxwm.tag.set_overrides(8, { gaps = false, border_width = 0 })
+-- Show an app's icon in place of the tag name while it is open there (requires a Nerd Font)
+-- oxwm.tag.set_icons({ firefox = "󰈹", alacritty = "", discord = "󰙯" })
 
 -------------------------------------------------------------------------------
 -- Window Rules
@@ -146,6 +162,10 @@ oxwm.gaps.set_outer(5, 5)
 oxwm.rule.add({ instance = "gimp", floating = true })                             
 -- oxwm.rule.add({ class = "firefox", title = "Library", floating = true })  
 -- oxwm.rule.add({ instance = "mpv", floating = true })                      
+-- Apps like Spotify set their title after mapping; match it once it appears:
+-- oxwm.rule.add({ title = "Spotify", tag = 9, on_title_change = true })
+-- Switch a tag to another layout while a window is open on it:
+-- oxwm.rule.add({ class = "Gimp", tag = 5, layout = "normie" })
 
 -- To find window properties, use xprop and click on the window
 -- WM_CLASS(STRING) shows both instance and class (instance, class)
@@ -155,6 +175,12 @@ oxwm.rule.add({ instance = "gimp", floating = true })
 -------------------------------------------------------------------------------
 -- Font configuration
 oxwm.bar.set_font(bar_font)
+-- Fall back to other fonts for glyphs the bar font lacks (icons, emoji, CJK):
+-- oxwm.bar.set_font(bar_font, { "Symbols Nerd Font:size=10", "Noto Color Emoji:size=10" })
+-- Translucent bar background (needs a compositor such as picom):
+-- oxwm.bar.set_alpha(0.85)
+-- Rounded bar corners:
+-- oxwm.bar.set_radius(6)
 
 -- Set your blocks here (defined above)
 oxwm.bar.set_blocks(blocks)
@@ -169,50 +195,83 @@ oxwm.bar.set_scheme_occupied(colors.cyan, colors.bg, colors.cyan)
 -- Currently selected tag
 oxwm.bar.set_scheme_selected(colors.cyan, colors.bg, colors.purple)
 
+-- Follow pywal: read colors from its output and reload them after changing wallpaper
+-- oxwm.theme.set_file("~/.cache/wal/colors.json")
+-- oxwm.key.bind({ modkey, "Shift" }, "T", oxwm.theme.reload(), "Reload theme")
+
 -------------------------------------------------------------------------------
 -- Keybindings
 -------------------------------------------------------------------------------
--- Keybindings are defined using oxwm.key.bind(modifiers, key, action)
+-- Keybindings are defined using oxwm.key.bind(modifiers, key, action, description)
 -- Modifiers: {"Mod4"}, {"Mod1"}, {"Shift"}, {"Control"}, or combinations like {"Mod4", "Shift"}
 -- Keys: Use uppercase for letters (e.g., "Return", "H", "J", "K", "L")
 -- Actions: Functions that return actions (e.g., oxwm.spawn(), oxwm.client.kill())
+-- Description: Optional text listed in the keybind overlay (Mod+Shift+/)
 --
 -- A list of available keysyms can be found in the X11 keysym definitions.
 -- Common keys: Return, Space, Tab, Escape, Backspace, Delete, Left, Right, Up, Down
 
 -- Basic window management
 
-oxwm.key.bind({ modkey }, "Return", oxwm.spawn_terminal())
+oxwm.key.bind({ modkey }, "Return", oxwm.spawn_terminal(), "Launch terminal")
 -- Launch Dmenu
-oxwm.key.bind({ modkey }, "D", oxwm.spawn({ "sh", "-c", "dmenu_run -l 10" }))
--- Copy screenshot to clipboard
-oxwm.key.bind({ modkey }, "S", oxwm.spawn({ "sh", "-c", "maim -s | xclip -selection clipboard -t image/png" }))
+oxwm.key.bind({ modkey }, "D", oxwm.spawn({ "sh", "-c", "dmenu_run -l 10" }), "Launch dmenu")
+-- Screenshot a selection, saved to ~/Pictures and copied to the clipboard (needs xclip)
+oxwm.screenshot.set_clipboard(true)
+oxwm.key.bind({ modkey }, "S", oxwm.screenshot.selection(), "Screenshot selection")
+oxwm.key.bind({ modkey, "Shift" }, "S", oxwm.screenshot.full(), "Screenshot screen")
+-- Media keys
+oxwm.key.bind({}, "XF86AudioPlay", oxwm.media.play_pause())
+oxwm.key.bind({}, "XF86AudioNext", oxwm.media.next())
+oxwm.key.bind({}, "XF86AudioPrev", oxwm.media.previous())
 oxwm.key.bind({ modkey }, "Q", oxwm.client.kill()) 
+oxwm.key.bind({ modkey, "Control" }, "Q", oxwm.client.force_kill(), "Kill hung window")
 
 -- Keybind overlay - Shows important keybindings on screen
 oxwm.key.bind({ modkey, "Shift" }, "Slash", oxwm.show_keybinds())
+-- Or keep it open only while the key is held:
+-- oxwm.key.hold({ modkey }, "F1", oxwm.show_keybinds())
 
 -- Window state toggles
 oxwm.key.bind({ modkey, "Shift" }, "F", oxwm.client.toggle_fullscreen())
+-- Fullscreen across all monitors
+-- oxwm.key.bind({ modkey, "Control", "Shift" }, "F", oxwm.client.toggle_fullscreen_span())
 oxwm.key.bind({ modkey, "Shift" }, "Space", oxwm.client.toggle_floating())
+-- Snap the focused window to a half of the screen, floating it
+-- (also "top-half", "bottom-half", "top-left", "top-right", "bottom-left", "bottom-right")
+-- oxwm.key.bind({ modkey, "Mod1" }, "Left", oxwm.client.snap("left-half"))
+-- oxwm.key.bind({ modkey, "Mod1" }, "Right", oxwm.client.snap("right-half"))
+-- Show/hide the bar
+oxwm.key.bind({ modkey }, "B", oxwm.bar.toggle())
 
 -- Layout management
 oxwm.key.bind({ modkey }, "F", oxwm.layout.set("normie"))
 oxwm.key.bind({ modkey }, "C", oxwm.layout.set("tiling"))
 -- Cycle through layouts
 oxwm.key.bind({ modkey }, "N", oxwm.layout.cycle())
+-- Next/previous tab in the tabbed layout (next/previous window elsewhere)
+oxwm.key.bind({ modkey }, "Tab", oxwm.layout.tab_next())
+oxwm.key.bind({ modkey, "Shift" }, "Tab", oxwm.layout.tab_prev())
 
 -- Master area controls (tiling layout)
 
 -- Decrease/Increase master area width
-oxwm.key.bind({ modkey }, "H", oxwm.set_master_factor(-5))
-oxwm.key.bind({ modkey }, "L", oxwm.set_master_factor(5))
+oxwm.key.bind({ modkey }, "H", oxwm.layout.inc_master_factor(-5))
+oxwm.key.bind({ modkey }, "L", oxwm.layout.inc_master_factor(5))
+-- Move the master edge with the arrows: left/right in tiling, up/down in bstack
+oxwm.key.bind({ modkey, "Control" }, "Left", oxwm.layout.resize_master("left"))
+oxwm.key.bind({ modkey, "Control" }, "Right", oxwm.layout.resize_master("right"))
+oxwm.key.bind({ modkey, "Control" }, "Up", oxwm.layout.resize_master("up"))
+oxwm.key.bind({ modkey, "Control" }, "Down", oxwm.layout.resize_master("down"))
 -- Increment/Decrement number of master windows
-oxwm.key.bind({ modkey }, "I", oxwm.inc_num_master(1))
-oxwm.key.bind({ modkey }, "P", oxwm.inc_num_master(-1))
+oxwm.key.bind({ modkey }, "I", oxwm.layout.inc_num_master(1))
+oxwm.key.bind({ modkey }, "P", oxwm.layout.inc_num_master(-1))
 
 -- Gaps toggle
 oxwm.key.bind({ modkey }, "A", oxwm.toggle_gaps())
+-- Grow/Shrink gaps
+oxwm.key.bind({ modkey }, "Equal", oxwm.gaps.adjust(2))
+oxwm.key.bind({ modkey }, "Minus", oxwm.gaps.adjust(-2))
 
 -- Window manager controls
 oxwm.key.bind({ modkey, "Shift" }, "Q", oxwm.quit())
@@ -225,6 +284,11 @@ oxwm.key.bind({ modkey }, "K", oxwm.client.focus_stack(-1))
 -- Window movement (swap position in stack)
 oxwm.key.bind({ modkey, "Shift" }, "J", oxwm.client.move_stack(1))
 oxwm.key.bind({ modkey, "Shift" }, "K", oxwm.client.move_stack(-1))
+-- Swap the focused window with the master
+oxwm.key.bind({ modkey, "Shift" }, "Return", oxwm.client.zoom())
+-- Alt-Tab window switcher across all tags and monitors
+oxwm.key.bind({ "Mod1" }, "Tab", oxwm.client.switcher(1))
+oxwm.key.bind({ "Mod1", "Shift" }, "Tab", oxwm.client.switcher(-1))
 
 -- Multi-monitor support
 
@@ -282,6 +346,12 @@ oxwm.key.bind({ modkey, "Control", "Shift" }, "7", oxwm.tag.toggletag(6))
 oxwm.key.bind({ modkey, "Control", "Shift" }, "8", oxwm.tag.toggletag(7))
 oxwm.key.bind({ modkey, "Control", "Shift" }, "9", oxwm.tag.toggletag(8))
 
+-- Rename the current tag (Return to confirm, Escape to cancel)
+oxwm.key.bind({ modkey }, "F2", oxwm.tag.rename())
+
+-- Add a temporary tag, removed again once it is empty
+oxwm.key.bind({ modkey, "Shift" }, "N", oxwm.tag.add())
+
 -------------------------------------------------------------------------------
 -- Advanced: Keychords
 -------------------------------------------------------------------------------
@@ -293,6 +363,15 @@ oxwm.key.chord({
     { {},         "T" }
 }, oxwm.spawn_terminal())
 
+-- Modes keep their bindings active until you press Escape (like i3 modes)
+-- Format: oxwm.mode.define(name, { { {modifiers}, key, action, description? }, ... })
+oxwm.mode.define("resize", {
+    { {}, "H",      oxwm.layout.inc_master_factor(-5), "Shrink master" },
+    { {}, "L",      oxwm.layout.inc_master_factor(5),  "Grow master" },
+    { {}, "Return", oxwm.mode.exit(),                  "Leave resize mode" },
+})
+oxwm.key.bind({ modkey }, "R", oxwm.mode.enter("resize"), "Resize mode")
+
 -------------------------------------------------------------------------------
 -- Autostart
 -------------------------------------------------------------------------------
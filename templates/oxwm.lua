@@ -16,8 +16,14 @@
 ---@class oxwm
 oxwm = {}
 
+---@class SpawnOptions
+---@field cmd string|string[] Command to spawn
+---@field cwd string? Working directory (a leading ~ expands to the home directory)
+---@field env table<string, string>? Extra environment variables
+---@field shell boolean? Run cmd through `sh -c` (default: false)
+
 ---Spawn a command
----@param cmd string|string[] Command to spawn (string or array of strings)
+---@param cmd string|string[]|SpawnOptions Command to spawn (string, array of strings, or options table)
 ---@return table Action table for keybinding
 function oxwm.spawn(cmd) end
 
@@ -38,7 +44,7 @@ function oxwm.set_modkey(modkey) end
 function oxwm.set_tags(tags) end
 
 ---Set layout symbol override
----@param name string Layout name (e.g., "tiling", "normie", "tabbed", "grid", "monocle")
+---@param name string Layout name (e.g., "tiling", "bstack", "normie", "tabbed", "grid", "monocle")
 ---@param symbol string Symbol to display (e.g., "[T]", "[F]", "[=]")
 function oxwm.set_layout_symbol(name, symbol) end
 
@@ -47,16 +53,38 @@ function oxwm.set_layout_symbol(name, symbol) end
 oxwm.rule = {}
 
 ---Add a window rule
----@param rule {class: string?, instance: string?, title: string?, role: string?, floating: boolean?, tag: integer?, fullscreen: boolean?} Rule configuration
+---@param rule {class: string?, instance: string?, title: string?, role: string?, floating: boolean?, tag: integer?, fullscreen: boolean?, opacity: number?, on_title_change: boolean?, attach: "master"|"aside"|"bottom"|"above_focused"|"after_focused"?, layout: string?} Rule configuration. Opacity goes from 0.1 to 1. With on_title_change, the rule is also applied when a window's title changes to match it. With layout, the window's tag switches to that layout until the last matching window closes
 function oxwm.rule.add(rule) end
 
 ---Quit the window manager
+---Windows are asked to close first; oxwm exits once they have or the grace period ends
 ---@return table Action table for keybinding
 function oxwm.quit() end
 
----Restart the window manager
+---Quit the window manager immediately, without asking windows to close
+---@return table Action table for keybinding
+function oxwm.force_quit() end
+
+---Set how long quitting waits for windows to close
+---@param milliseconds integer Grace period (default: 5000, 0 to quit immediately)
+function oxwm.set_quit_grace_period(milliseconds) end
+
+---Set how long the on screen display shown when changing the master factor or gaps stays up
+---@param milliseconds integer Timeout (default: 1000, 0 to disable)
+function oxwm.set_osd_timeout(milliseconds) end
+
+---Also write the log to ~/.local/share/oxwm/oxwm.log, which is rotated to oxwm.log.1
+---once it reaches 1 MiB. Log levels are set with the OXWM_LOG environment variable,
+---e.g. OXWM_LOG=debug or OXWM_LOG=info,oxwm::window_manager=debug
+---@param enabled boolean Log to a file (default false)
+function oxwm.set_log_file(enabled) end
+
+---Restart the window manager by reloading the config in place. With `exec`, oxwm
+---re-executes its binary with the same config instead, e.g. after installing a new
+---build; the config is checked first and oxwm keeps running if it or exec fails
+---@param options {exec: boolean?}? Restart options
 ---@return table Action table for keybinding
-function oxwm.restart() end
+function oxwm.restart(options) end
 
 ---Recompile the window manager
 ---@return table Action table for keybinding
@@ -66,6 +94,10 @@ function oxwm.recompile() end
 ---@return table Action table for keybinding
 function oxwm.toggle_gaps() end
 
+---Toggle focus lock (do not disturb): new windows open behind the focused one and are marked urgent instead of taking focus
+---@return table Action table for keybinding
+function oxwm.toggle_focus_lock() end
+
 ---Show keybind overlay
 ---@return table Action table for keybinding
 function oxwm.show_keybinds() end
@@ -86,14 +118,50 @@ oxwm.key = {}
 
 ---Bind a key combination to an action
 ---@param modifiers string|string[] Modifier keys (e.g., {"Mod4"}, {"Mod4", "Shift"})
----@param key string Key name (e.g., "Return", "Q", "1")
+---@param key string Key name (e.g., "Return", "Q", "1") or any X keysym name (e.g., "XF86AudioPlay", "Print")
+---@param action table Action returned by oxwm functions
+---@param description string? Text shown in the keybind overlay instead of the action name
+function oxwm.key.bind(modifiers, key, action, description) end
+
+---Bind a key combination to an action that runs when the key is released
+---Modifier keys can be bound on their own, e.g. oxwm.key.bind_release({}, "Super_L", ...)
+---@param modifiers string|string[] Modifier keys (e.g., {"Mod4"}, {"Mod4", "Shift"})
+---@param key string Key name or any X keysym name
+---@param action table Action returned by oxwm functions
+---@param description string? Text shown in the keybind overlay instead of the action name
+function oxwm.key.bind_release(modifiers, key, action, description) end
+
+---Bind a key combination to an action that runs on press and again on release
+---Useful for toggles such as oxwm.show_keybinds(), which stays open only while the key is held
+---@param modifiers string|string[] Modifier keys (e.g., {"Mod4"}, {"Mod4", "Shift"})
+---@param key string Key name or any X keysym name
 ---@param action table Action returned by oxwm functions
-function oxwm.key.bind(modifiers, key, action) end
+---@param description string? Text shown in the keybind overlay instead of the action name
+function oxwm.key.hold(modifiers, key, action, description) end
 
 ---Bind a keychord (multi-key sequence) to an action
 ---@param keys table[] Array of key presses, each: {{modifiers}, key}
 ---@param action table Action returned by oxwm functions
-function oxwm.key.chord(keys, action) end
+---@param description string? Text shown in the keybind overlay instead of the action name
+function oxwm.key.chord(keys, action, description) end
+
+---Mode module, for persistent sets of bindings like i3 modes
+---@class oxwm.mode
+oxwm.mode = {}
+
+---Define a mode. While it is active only its bindings are grabbed; Escape leaves it
+---@param name string Mode name, shown in the bar while active
+---@param bindings table[] Array of bindings, each: {{modifiers}, key, action, description?}
+function oxwm.mode.define(name, bindings) end
+
+---Enter a mode defined with oxwm.mode.define
+---@param name string Mode name
+---@return table Action table for keybinding
+function oxwm.mode.enter(name) end
+
+---Leave the active mode
+---@return table Action table for keybinding
+function oxwm.mode.exit() end
 
 ---Gap configuration module
 ---@class oxwm.gaps
@@ -123,6 +191,21 @@ function oxwm.gaps.set_outer(horizontal, vertical) end
 ---@param enabled boolean Enable or disable smart gaps
 function oxwm.gaps.set_smart(enabled) end
 
+---Grow or shrink all gaps at runtime (clamped at 0, reset on reload)
+---@param delta integer Pixels to add (negative to shrink)
+---@return table Action table for keybinding
+function oxwm.gaps.adjust(delta) end
+
+---Grow or shrink inner gaps at runtime (clamped at 0, reset on reload)
+---@param delta integer Pixels to add (negative to shrink)
+---@return table Action table for keybinding
+function oxwm.gaps.adjust_inner(delta) end
+
+---Grow or shrink outer gaps at runtime (clamped at 0, reset on reload)
+---@param delta integer Pixels to add (negative to shrink)
+---@return table Action table for keybinding
+function oxwm.gaps.adjust_outer(delta) end
+
 ---Border configuration module
 ---@class oxwm.border
 oxwm.border = {}
@@ -139,18 +222,113 @@ function oxwm.border.set_focused_color(color) end
 ---@param color string|integer Color as hex string ("#666666", "0x666666") or integer
 function oxwm.border.set_unfocused_color(color) end
 
+---Set urgent window border color (defaults to the unfocused color)
+---@param color string|integer Color as hex string or integer
+function oxwm.border.set_urgent_color(color) end
+
+---Set a two-tone or gradient border style
+---Each of focused/unfocused/urgent is either a color or an {inner, outer} pair blended across the border width
+---@param style {inner_width: integer?, inner_color: string|integer?, focused: string|integer|table?, unfocused: string|integer|table?, urgent: string|integer|table?}
+function oxwm.border.set_style(style) end
+
+---Set smart borders (hide borders when only one window is visible or in monocle)
+---@param enabled boolean Enable or disable smart borders
+function oxwm.border.set_smart(enabled) end
+
+---Round the corners of windows, border included. Fullscreen windows stay square
+---@param radius integer Corner radius in pixels (0, the default, for square corners)
+function oxwm.border.set_radius(radius) end
+
+---Tab bar configuration module (used by the tabbed layout)
+---@class oxwm.tab_bar
+oxwm.tab_bar = {}
+
+---Set tab bar position
+---@param position "top"|"bottom" Place the tab bar below the status bar or at the bottom of the monitor
+function oxwm.tab_bar.set_position(position) end
+
+---Set tab bar height
+---@param height integer Tab bar height in pixels
+function oxwm.tab_bar.set_height(height) end
+
+---Set spacing between the tab bar and the windows
+---@param gap integer Gap in pixels
+function oxwm.tab_bar.set_gap(gap) end
+
+---Include floating windows in the tab bar, marked with ●
+---@param enabled boolean Show floating windows as tabs (default: false)
+function oxwm.tab_bar.set_show_floating(enabled) end
+
+---Limit how wide a tab gets; by default tabs share the whole tab bar
+---@param width integer Maximum tab width in pixels (0 to remove the limit)
+function oxwm.tab_bar.set_max_tab_width(width) end
+
+---Set the text drawn between tabs
+---@param separator string Separator text or glyph (e.g., "|")
+function oxwm.tab_bar.set_separator(separator) end
+
+---Choose what tabs are labelled with
+---@param label "title"|"class" Window title (default) or WM_CLASS class
+function oxwm.tab_bar.set_label(label) end
+
+---Set colors of the focused tab (defaults to the bar's selected scheme)
+---@param foreground string|integer Text color
+---@param background string|integer Background color
+---@param underline string|integer Underline color
+function oxwm.tab_bar.set_scheme_active(foreground, background, underline) end
+
+---Set colors of the other tabs (defaults to the bar's occupied scheme)
+---@param foreground string|integer Text color
+---@param background string|integer Background color
+---@param underline string|integer Underline color
+function oxwm.tab_bar.set_scheme_inactive(foreground, background, underline) end
+
+---Set the fill behind tabs of urgent windows (defaults to the urgent border color)
+---@param color string|integer Color as hex string or integer
+function oxwm.tab_bar.set_urgent_color(color) end
+
 ---Client/window management module
 ---@class oxwm.client
 oxwm.client = {}
 
----Kill the focused window
+---Close the focused window. Closing it again after it ignored the request
+---for the kill timeout kills it
 ---@return table Action table for keybinding
 function oxwm.client.kill() end
 
+---Kill the focused window without asking it to close, for hung applications
+---@return table Action table for keybinding
+function oxwm.client.force_kill() end
+
+---Set how long a window may ignore a close request before it can be killed
+---@param milliseconds integer Timeout (default 5000)
+---@param auto boolean? Kill such windows automatically instead of on the next close (default false)
+function oxwm.client.set_kill_timeout(milliseconds, auto) end
+
+---Set where new windows enter the tiling order. Window rules can override it with `attach`
+---@param position "master"|"aside"|"bottom"|"above_focused"|"after_focused" Position (default "aside", right after the first master)
+function oxwm.client.set_attach(position) end
+
+---Let applications activate their windows through _NET_ACTIVE_WINDOW, switching to
+---the window's tag and focusing it. When disabled such requests only mark the window
+---urgent; pagers and taskbars are always obeyed
+---@param allow boolean Allow activation (default false)
+function oxwm.client.set_allow_activation(allow) end
+
+---Ask the compositor to unredirect fullscreen windows by setting _NET_WM_BYPASS_COMPOSITOR
+---on them, which cuts latency in games. Windows that set the hint themselves are left alone
+---@param enabled boolean Bypass the compositor for fullscreen windows (default false)
+function oxwm.client.set_bypass_compositor(enabled) end
+
 ---Toggle fullscreen mode
 ---@return table Action table for keybinding
 function oxwm.client.toggle_fullscreen() end
 
+---Toggle fullscreen across all monitors combined, e.g. for video walls and presentations.
+---Toggling again restores the window's previous geometry
+---@return table Action table for keybinding
+function oxwm.client.toggle_fullscreen_span() end
+
 ---Toggle floating mode
 ---@return table Action table for keybinding
 function oxwm.client.toggle_floating() end
@@ -165,6 +343,66 @@ function oxwm.client.focus_stack(dir) end
 ---@return table Action table for keybinding
 function oxwm.client.move_stack(dir) end
 
+---Move the focused window into the master area, or swap the master with the
+---next window when it already is the master
+---@return table Action table for keybinding
+function oxwm.client.zoom() end
+
+---Open the window switcher, or move through it while it is open. Windows are
+---listed most recently focused first and the highlighted one is focused once
+---the modifier held with the binding is released. Built with the
+---`window-previews` feature the switcher shows live miniatures of the windows
+---@param dir integer? Direction to move the highlight (1 for forward, -1 for backward, default 1)
+---@return table Action table for keybinding
+function oxwm.client.switcher(dir) end
+
+---Adjust opacity of the focused window (requires a compositor such as picom).
+---Opacity stays between 10% and 100%
+---@param delta integer Percentage to adjust by (negative for more transparent, positive for more opaque)
+---@return table Action table for keybinding
+function oxwm.client.set_opacity(delta) end
+
+---Toggle maximize: grow the focused window to fill the monitor (keeping the bar and gaps), or restore it
+---@return table Action table for keybinding
+function oxwm.client.toggle_maximize() end
+
+---Center the focused window on its monitor. A tiled window is floated and stays floating; use toggle_floating to tile it again
+---@return table Action table for keybinding
+function oxwm.client.center() end
+
+---Float the focused window and snap it to half or a quarter of its monitor, keeping the bar and gaps
+---@param region "left-half"|"right-half"|"top-half"|"bottom-half"|"top-left"|"top-right"|"bottom-left"|"bottom-right"
+---@return table Action table for keybinding
+function oxwm.client.snap(region) end
+
+---Minimize (iconify) the focused window
+---@return table Action table for keybinding
+function oxwm.client.minimize() end
+
+---Restore the most recently minimized window
+---@return table Action table for keybinding
+function oxwm.client.restore_last() end
+
+---Move the focused window to the hidden stash tag
+---@return table Action table for keybinding
+function oxwm.client.stash() end
+
+---Bring the most recently stashed window back to the current tag
+---@return table Action table for keybinding
+function oxwm.client.unstash() end
+
+---Set where new floating windows and dialogs are placed
+---@param placement "requested"|"smart"|"cascade"|"center"|"under_pointer" "requested" keeps the position the window asks for, "smart" avoids overlapping other floating windows
+function oxwm.client.set_placement(placement) end
+
+---Set how close a dragged window has to get to snap to monitor edges and other floating windows
+---@param distance integer Snap distance in pixels (default: 32, 0 to disable)
+function oxwm.client.set_snap(distance) end
+
+---Keep dragged windows inside their monitor; hold Shift while dragging to move them past the edge
+---@param enabled boolean Constrain dragging (default: false)
+function oxwm.client.set_constrain_drag(enabled) end
+
 ---Monitor management module
 ---@class oxwm.monitor
 oxwm.monitor = {}
@@ -179,19 +417,117 @@ function oxwm.monitor.focus(dir) end
 ---@return table Action table for keybinding
 function oxwm.monitor.tag(dir) end
 
+---Send focused window to monitor and follow it there
+---@param dir integer Direction (-1 for previous, 1 for next)
+---@return table Action table for keybinding
+function oxwm.monitor.tag_follow(dir) end
+
+---Set monitor order by output name (monitors not listed keep their detected order after these)
+---@param outputs string[] Output names as reported by xrandr (e.g., {"DP-1", "HDMI-1"})
+function oxwm.monitor.set_order(outputs) end
+
+---Set the scale factor for a monitor (scales bar height, font size, borders and gaps; applied at startup)
+---@param index integer Monitor index (0-based, after oxwm.monitor.set_order)
+---@param scale number Scale factor (e.g., 2.0 for HiDPI)
+function oxwm.monitor.set_scale(index, scale) end
+
+---Keep space free along the edges of a monitor, e.g. for a conky sidebar. Added to what docks reserve
+---@param index integer Monitor index (0-based, after oxwm.monitor.set_order)
+---@param top integer Pixels
+---@param bottom integer Pixels
+---@param left integer Pixels
+---@param right integer Pixels
+function oxwm.monitor.set_padding(index, top, bottom, left, right) end
+
+---Keep a window's tags when sending it to another monitor
+---By default it takes over the tags currently shown on the target monitor
+---@param enabled boolean Preserve tags across monitors (default: false)
+function oxwm.monitor.set_keep_tags(enabled) end
+
 ---Layout management module
 ---@class oxwm.layout
 oxwm.layout = {}
 
----Cycle through layouts
+---Cycle through layouts, skipping those registered with cycle = false
 ---@return table Action table for keybinding
 function oxwm.layout.cycle() end
 
+---Handle to a built-in layout, returned by oxwm.layout.register
+---@class oxwm.LayoutHandle
+---@field name string Layout name the handle belongs to
+local LayoutHandle = {}
+
+---Switch to this layout
+---@return table Action table for keybinding
+function LayoutHandle.set() end
+
+---Change the symbol shown in the bar for this layout. Only takes effect
+---while the config loads, not when called later from an on_click handler
+---@param symbol string Symbol to display
+function LayoutHandle.set_symbol(symbol) end
+
+---Include this layout in oxwm.layout.cycle() and bar scrolling or leave it out.
+---Only takes effect while the config loads, not when called later from an
+---on_click handler
+---@param cycle boolean Whether cycling reaches this layout
+function LayoutHandle.set_cycle(cycle) end
+
+---Register a built-in layout, setting its symbol and whether cycling reaches it
+---in one call. Layouts can't be defined in Lua, so name must be a built-in one
+---@param name string Layout name (e.g., "tiling", "bstack", "normie", "tabbed", "grid", "monocle")
+---@param options {symbol: string?, cycle: boolean?}? Symbol shown in the bar and whether oxwm.layout.cycle() includes it (default: true)
+---@return oxwm.LayoutHandle handle Handle for binding and changing the layout later
+function oxwm.layout.register(name, options) end
+
 ---Set specific layout
----@param name string Layout name (e.g., "tiling", "normie", "tabbed", "grid", "monocle")
+---@param name string Layout name (e.g., "tiling", "bstack", "normie", "tabbed", "grid", "monocle")
 ---@return table Action table for keybinding
 function oxwm.layout.set(name) end
 
+---Set the default master area size as a fraction of the screen
+---@param factor number Master factor between 0.05 and 0.95 (default: 0.55)
+---@param monitor integer? Only apply to this monitor index
+function oxwm.layout.set_master_factor(factor, monitor) end
+
+---Set the default number of windows in the master area
+---@param count integer Number of master windows (default: 1)
+---@param monitor integer? Only apply to this monitor index
+function oxwm.layout.set_num_master(count, monitor) end
+
+---Keep tiled windows to their size hints (e.g. terminal cell sizes), centering them in their slot
+---Floating windows and the normie layout always respect size hints
+---@param enabled boolean Respect size hints (default: false)
+---@param layout string? Only apply to this layout (e.g., "tiling", "grid")
+function oxwm.layout.set_resize_hints(enabled, layout) end
+
+---Grow or shrink the master area of the focused monitor
+---@param delta integer? Percentage points to adjust by (default: 5, negative to shrink)
+---@return table Action table for keybinding
+function oxwm.layout.inc_master_factor(delta) end
+
+---Move the edge of the master area in a direction. Left/right resize the
+---master column in tiling, up/down the master row in bstack; directions
+---along the other axis do nothing, so both pairs can be bound at once
+---@param direction "left"|"right"|"up"|"down" Direction to move the edge in
+---@param amount integer? Percentage points to move by (default: 5)
+---@return table Action table for keybinding
+function oxwm.layout.resize_master(direction, amount) end
+
+---Increment/decrement the number of master windows on the focused monitor
+---@param delta integer? Amount to adjust by (default: 1, negative to decrease)
+---@return table Action table for keybinding
+function oxwm.layout.inc_num_master(delta) end
+
+---Raise and focus the next tab in the tabbed layout, wrapping around at the end.
+---In other layouts this focuses the next window like oxwm.client.focus_stack(1)
+---@return table Action table for keybinding
+function oxwm.layout.tab_next() end
+
+---Raise and focus the previous tab in the tabbed layout, wrapping around at the start.
+---In other layouts this focuses the previous window like oxwm.client.focus_stack(-1)
+---@return table Action table for keybinding
+function oxwm.layout.tab_prev() end
+
 ---Tag/workspace management module
 ---@class oxwm.tag
 oxwm.tag = {}
@@ -201,6 +537,19 @@ oxwm.tag = {}
 ---@return table Action table for keybinding
 function oxwm.tag.view(index) end
 
+---Override gaps and border width while a tag is viewed, e.g. no gaps or borders on a media tag.
+---With several tags viewed, the first one decides
+---@param index integer Tag index (0-based)
+---@param overrides {gaps: boolean?, border_width: integer?} Settings to override, others keep the defaults
+function oxwm.tag.set_overrides(index, overrides) end
+
+---Show an icon in place of a tag's name while a window of a class is on it, e.g.
+---a browser glyph while Firefox is open there. Classes match WM_CLASS class or
+---instance, ignoring case, and the most recently focused matching window wins.
+---Nerd Font glyphs need a font that has them, e.g. as a fallback in oxwm.bar.set_font
+---@param icons table<string, string> Icon for each window class
+function oxwm.tag.set_icons(icons) end
+
 ---Move focused window to tag
 ---@param index integer Tag index (0-based)
 ---@return table Action table for keybinding
@@ -216,13 +565,67 @@ function oxwm.tag.toggleview(index) end
 ---@return table Action table for keybinding
 function oxwm.tag.toggletag(index) end
 
+---Rename the current tag for this session. Without a name a prompt is shown
+---@param name string? New tag name
+---@return table Action table for keybinding
+function oxwm.tag.rename(name) end
+
+---Append a new tag and view it. Added tags disappear again once they are empty and not viewed
+---@param name string? Tag name (defaults to its number)
+---@return table Action table for keybinding
+function oxwm.tag.add(name) end
+
 ---Status bar configuration module
 ---@class oxwm.bar
 oxwm.bar = {}
 
+---Show/hide the bar on the focused monitor
+---@return table Action table for keybinding
+function oxwm.bar.toggle() end
+
 ---Set status bar font
+---Characters the font has no glyph for (icons, emoji, CJK) are drawn with
+---the first fallback font that has one
 ---@param font string Font string (e.g., "monospace:style=Bold:size=10")
-function oxwm.bar.set_font(font) end
+---@param fallbacks? string[] Fallback fonts in order (e.g., { "Noto Color Emoji", "Noto Sans CJK JP:size=10" })
+function oxwm.bar.set_font(font, fallbacks) end
+
+---Override the status bar height (defaults to 1.4x the font height)
+---@param height integer Bar height in pixels, greater than 0
+function oxwm.bar.set_height(height) end
+
+---Set the separator drawn between status blocks
+---@param separator string Separator text or glyph (e.g., " | ")
+function oxwm.bar.set_separator(separator) end
+
+---Set horizontal padding on each side of every status block
+---@param padding integer Padding in pixels
+function oxwm.bar.set_block_padding(padding) end
+
+---Set horizontal padding on each side of every tag cell (defaults to 0.4x the font height)
+---@param padding integer Padding in pixels
+function oxwm.bar.set_tag_padding(padding) end
+
+---Show how many windows are on each tag in its cell, e.g. "3 2" with "suffix" or "3²" with "superscript"
+---@param style "none"|"suffix"|"superscript" Defaults to "none"
+function oxwm.bar.set_tag_counts(style) end
+
+---Make the bar background translucent. Needs a compositor (e.g., picom)
+---running when oxwm starts; without one the bar stays opaque
+---@param alpha number Opacity from 0 (transparent) to 1 (opaque, the default)
+function oxwm.bar.set_alpha(alpha) end
+
+---Round the corners of the bar
+---@param radius integer Corner radius in pixels (0, the default, for square corners)
+function oxwm.bar.set_radius(radius) end
+
+---Set the thickness of tag and block underlines (defaults to 1/8 of the font height)
+---@param thickness integer Thickness in pixels
+function oxwm.bar.set_underline_thickness(thickness) end
+
+---Set whether scrolling over the bar tags skips tags without windows (default true)
+---@param skip boolean Skip empty tags when scrolling
+function oxwm.bar.set_scroll_skip_empty(skip) end
 
 ---DEPRECATED: Add a status bar block (use oxwm.bar.set_blocks with block constructors instead)
 ---@deprecated
@@ -234,39 +637,64 @@ function oxwm.bar.set_font(font) end
 ---@param underline boolean Whether to underline the block
 function oxwm.bar.add_block(format, command, arg, interval, color, underline) end
 
----Set status bar blocks using block constructors
+---Set status bar blocks using block constructors (set `monitor` on a block to show it only on that monitor, 0-based)
 ---@param blocks table[] Array of block configurations created with oxwm.bar.block.*
 function oxwm.bar.set_blocks(blocks) end
 
+---A click on a status block, passed to its `on_click` function
+---@class oxwm.BlockClick
+---@field name string The block's `name`, or "" if it has none
+---@field button "left"|"middle"|"right"|"scroll_up"|"scroll_down"
+---@field x integer Horizontal offset of the click within the block, in pixels
+
+---Either a function receiving the click and returning an action (or nil), or a table of
+---actions keyed by button, e.g. { left = oxwm.spawn("pavucontrol"), scroll_up = oxwm.spawn(...) }
+---@alias oxwm.BlockClickHandler (fun(click: oxwm.BlockClick): table?)|table<string, table>
+
 ---Block constructors module
 ---@class oxwm.bar.block
 oxwm.bar.block = {}
 
 ---Create a RAM usage block
----@param config {format: string, interval: integer, color: string|integer, underline: boolean} Block configuration
+---@param config {format: string, interval: integer, color: string|integer, underline: boolean, monitor: integer?, name: string?, on_click: oxwm.BlockClickHandler?} Block configuration
 ---@return table Block configuration
 function oxwm.bar.block.ram(config) end
 
 ---Create a date/time block
----@param config {format: string, date_format: string, interval: integer, color: string|integer, underline: boolean} Block configuration (format is display template with {}, date_format is strftime format)
+---@param config {format: string, date_format: string, interval: integer, color: string|integer, underline: boolean, monitor: integer?, name: string?, on_click: oxwm.BlockClickHandler?} Block configuration (format is display template with {}, date_format is strftime format)
 ---@return table Block configuration
 function oxwm.bar.block.datetime(config) end
 
----Create a shell command block
----@param config {format: string, command: string, interval: integer, color: string|integer, underline: boolean} Block configuration
+---Create a shell command block. Its output may color parts of itself with ^fg(#rrggbb)text^fg() and ^bg(#rrggbb)text^bg(); ^^ is a literal caret. The command runs in the background; while a run takes longer than the interval the block shows `busy` (default "…"), and after a run exits non-zero it is drawn in `error_color` (default red)
+---@param config {format: string, command: string, interval: integer, color: string|integer, underline: boolean, monitor: integer?, name: string?, on_click: oxwm.BlockClickHandler?, busy: string?, error_color: string|integer?} Block configuration
 ---@return table Block configuration
 function oxwm.bar.block.shell(config) end
 
+---Create a weather block showing the current weather from wttr.in, fetched in the background. Needs oxwm built with the `net-blocks` feature; otherwise the block is skipped
+---@param config {format: string, location: string, weather_format: string?, interval: integer, color: string|integer, underline: boolean, monitor: integer?, name: string?, on_click: oxwm.BlockClickHandler?} Block configuration (weather_format uses wttr.in's format codes, default "%c %t")
+---@return table Block configuration
+function oxwm.bar.block.weather(config) end
+
+---Create a block showing the artist and title of the active media player, updated as soon as the track changes. Needs oxwm built with the `mpris` feature; otherwise the block is skipped
+---@param config {format: string, color: string|integer, paused_color: string|integer?, interval: integer?, underline: boolean, monitor: integer?, name: string?, on_click: oxwm.BlockClickHandler?} Block configuration (paused_color defaults to color)
+---@return table Block configuration
+function oxwm.bar.block.now_playing(config) end
+
 ---Create a static text block
----@param config {format: string, text: string, interval: integer, color: string|integer, underline: boolean} Block configuration
+---@param config {format: string, text: string, interval: integer, color: string|integer, underline: boolean, monitor: integer?, name: string?, on_click: oxwm.BlockClickHandler?} Block configuration
 ---@return table Block configuration
 function oxwm.bar.block.static(config) end
 
 ---Create a battery status block
----@param config {format: string, charging: string, discharging: string, full: string, interval: integer, color: string|integer, underline: boolean} Block configuration
+---@param config {format: string, charging: string, discharging: string, full: string, interval: integer, color: string|integer, underline: boolean, monitor: integer?, name: string?, on_click: oxwm.BlockClickHandler?} Block configuration
 ---@return table Block configuration
 function oxwm.bar.block.battery(config) end
 
+---Create a temperature block reading /sys/class/hwmon ({celsius} and {fahrenheit} placeholders)
+---@param config {format: string, sensor: string?, critical: number?, critical_color: string|integer?, interval: integer, color: string|integer, underline: boolean, monitor: integer?, name: string?, on_click: oxwm.BlockClickHandler?} Block configuration (sensor is the hwmon name, e.g. "coretemp" or "k10temp"; color switches to critical_color at or above critical degrees celsius)
+---@return table Block configuration
+function oxwm.bar.block.temperature(config) end
+
 ---Set normal tag color scheme (unselected, no windows)
 ---@param foreground string|integer Foreground color
 ---@param background string|integer Background color
@@ -285,6 +713,128 @@ function oxwm.bar.set_scheme_occupied(foreground, background, underline) end
 ---@param underline string|integer Underline color
 function oxwm.bar.set_scheme_selected(foreground, background, underline) end
 
+---Screenshot module
+---@class oxwm.screenshot
+oxwm.screenshot = {}
+
+---Screenshot every monitor
+---@return table Action table for keybinding
+function oxwm.screenshot.full() end
+
+---Screenshot the focused window
+---@return table Action table for keybinding
+function oxwm.screenshot.window() end
+
+---Screenshot a rectangle dragged out with the mouse; any key cancels
+---@return table Action table for keybinding
+function oxwm.screenshot.selection() end
+
+---Set the directory screenshots are saved to
+---@param dir string Directory (default: XDG pictures directory)
+function oxwm.screenshot.set_dir(dir) end
+
+---Save screenshots to the screenshot directory
+---@param enabled boolean Save screenshots (default: true)
+function oxwm.screenshot.set_save(enabled) end
+
+---Copy screenshots to the clipboard as PNG, using xclip
+---@param enabled boolean Copy screenshots (default: false)
+function oxwm.screenshot.set_clipboard(enabled) end
+
+---Media module, controlling the active media player over MPRIS
+---Built with the `mpris` feature this talks to players directly, otherwise it runs playerctl
+---@class oxwm.media
+oxwm.media = {}
+
+---Play or pause the active media player
+---@return table Action table for keybinding
+function oxwm.media.play_pause() end
+
+---Skip to the next track
+---@return table Action table for keybinding
+function oxwm.media.next() end
+
+---Go back to the previous track
+---@return table Action table for keybinding
+function oxwm.media.previous() end
+
+---Theme module, for colors that follow a generated palette such as pywal
+---@class oxwm.theme
+oxwm.theme = {}
+
+---Read border and bar colors from a colors file, overriding the ones set above
+---A .json file is read like pywal's colors.json, anything else like an Xresources file
+---Roles: border_focused, border_unfocused, border_urgent and {normal,occupied,selected}_{fg,bg,underline}
+---@param path string Path to the colors file (e.g., "~/.cache/wal/colors.json")
+---@param roles table<string, string>? Color name per role (e.g., { border_focused = "color2" }); defaults follow pywal
+function oxwm.theme.set_file(path, roles) end
+
+---Re-read the theme file and repaint borders and bars without reloading the config
+---@return table Action table for keybinding
+function oxwm.theme.reload() end
+
 ---Add an autostart command
 ---@param cmd string Command to run at startup
 function oxwm.autostart(cmd) end
+
+---Add an autostart command that runs once per session, surviving restarts
+---Started processes are terminated when oxwm quits
+---@param cmd string Command to run at startup
+---@param options {restart: boolean}? Set restart = true to restart the command if it exits
+function oxwm.autostart_once(cmd, options) end
+
+---Read an environment variable
+---@param name string Variable name
+---@param default string? Value returned when the variable is not set
+---@return string? value
+function oxwm.env(name, default) end
+
+---Get the hostname of this machine, e.g. to share one config between a laptop and a desktop
+---@return string hostname
+function oxwm.hostname() end
+
+---Get the number of connected monitors
+---@return integer count
+function oxwm.monitor_count() end
+
+---Read an X resource loaded with xrdb (falls back to ~/.Xresources), e.g. to match pywal colors
+---Wildcard entries match too, so "color4" finds "*.color4" and "*color4"
+---@param name string Resource name (e.g., "color4", "background")
+---@param default string? Value returned when the resource is not set
+---@return string? value
+function oxwm.xresources(name, default) end
+
+---@class oxwm.ClientState
+---@field window integer X window id
+---@field class string WM_CLASS class
+---@field instance string WM_CLASS instance
+---@field title string Window title
+---@field tags integer[] Tag indices (0-based) the window is on
+---@field floating boolean Whether the window is floating
+---@field monitor integer Monitor index (0-based)
+
+---@class oxwm.MonitorState
+---@field index integer Monitor index (0-based)
+---@field x integer
+---@field y integer
+---@field width integer
+---@field height integer
+---@field name string? Output name, e.g. "HDMI-1"
+---@field tags integer[] Viewed tag indices (0-based)
+---@field focused boolean Whether this is the selected monitor
+
+---Read-only state of the running window manager, filled in when the config is reloaded
+---@class oxwm.state
+oxwm.state = {}
+
+---List managed windows (empty on first start)
+---@return oxwm.ClientState[]
+function oxwm.state.clients() end
+
+---List monitors (empty on first start)
+---@return oxwm.MonitorState[]
+function oxwm.state.monitors() end
+
+---Get the first viewed tag (0-based) on the selected monitor, or nil on first start
+---@return integer?
+function oxwm.state.current_tag() end